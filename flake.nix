@@ -80,6 +80,8 @@
             with pkgs; mkShell {
               buildInputs = [
                 rust-toolchain
+                # Type checks modules in ct-builder tests
+                typescript
               ] ++ lib.optionals stdenv.isLinux [
                 chromium
                 chromedriver
@@ -218,11 +220,14 @@
                   bash ./wit/wit-tools.sh deps
                   export CARGO_COMPONENT_CACHE_DIR=.cargo-component-cache
                   bash ./scripts/component-build ct-js-vm ./target
+                  bash ./scripts/component-build ct-py-vm ./target
                 '';
                 installPhase = ''
                   mkdir -p $out/wasm-components
                   cp ./target/wasm32-wasip1/release/virt_ct_js_vm.wasm \
                      $out/wasm-components/virt_ct_js_vm.wasm
+                  cp ./target/wasm32-wasip1/release/virt_ct_py_vm.wasm \
+                     $out/wasm-components/virt_ct_py_vm.wasm
                 '';
 
                 nativeBuildInputs = [ rust-toolchain ] ++ common-build-inputs;
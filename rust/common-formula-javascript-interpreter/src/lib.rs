@@ -48,8 +48,8 @@ mod guest {
     }
 
     impl VirtualModuleGuest for JavaScriptInterpreter {
-        fn set_source(source: String) -> Result<(), String> {
-            Module::load(Some(source))?;
+        fn set_source(source: String, dependencies: Vec<(String, String)>) -> Result<(), String> {
+            Module::load(Some(source), dependencies.into_iter().collect())?;
             Ok(())
         }
     }
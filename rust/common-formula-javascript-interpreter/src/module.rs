@@ -6,7 +6,7 @@ use boa_engine::property::Attribute;
 use boa_engine::{
     builtins::promise::PromiseState, js_string, Context, JsObject, Module as JsModule, Source,
 };
-use boa_engine::{JsResult, JsString, JsValue};
+use boa_engine::{JsNativeError, JsResult, JsString, JsValue};
 use boa_runtime::Console;
 use once_cell::unsync::OnceCell;
 use std::collections::BTreeMap;
@@ -17,59 +17,60 @@ thread_local! {
     pub static MODULE_STATE: RefCell<OnceCell<Rc<RwLock<Module>>>> = const { RefCell::new(OnceCell::new()) };
 }
 
+/// Resolves imports to the pre-bundled dependencies that were
+/// provided alongside the module source.
 pub struct CommonModuleLoader {
-    builtins: RefCell<BTreeMap<JsString, JsModule>>,
+    dependencies: BTreeMap<String, String>,
+    modules: RefCell<BTreeMap<JsString, JsModule>>,
 }
 
 impl CommonModuleLoader {
-    pub fn new() -> Self {
+    pub fn new(dependencies: BTreeMap<String, String>) -> Self {
         Self {
-            builtins: RefCell::new(BTreeMap::new()),
+            dependencies,
+            modules: RefCell::new(BTreeMap::new()),
         }
     }
+
+    fn resolve(&self, specifier: &str, context: &mut Context) -> JsResult<JsModule> {
+        let Some(source) = self.dependencies.get(specifier) else {
+            return Err(JsNativeError::typ()
+                .with_message(format!("Could not resolve module '{specifier}'"))
+                .into());
+        };
+
+        JsModule::parse(Source::from_bytes(source.as_bytes()), None, context)
+    }
 }
 
 impl ModuleLoader for CommonModuleLoader {
     fn load_imported_module(
         &self,
         _referrer: Referrer,
-        _specifier: JsString,
-        _finish_load: Box<dyn FnOnce(JsResult<JsModule>, &mut Context)>,
-        _context: &mut Context,
+        specifier: JsString,
+        finish_load: Box<dyn FnOnce(JsResult<JsModule>, &mut Context)>,
+        context: &mut Context,
     ) {
-        unimplemented!();
-        /*
-        println!("Loading imported module: {:?}", specifier);
-        let maybe_module = self.builtins.borrow().get(&specifier).cloned();
-        let module = match maybe_module {
-            None => {
-                let specifier_std = specifier.to_std_string().unwrap_or_default();
-                let module: boa_engine::Module = match specifier_std.as_str() {
-                    //"common:io/state@0.0.1" => create_io_state_module(context),
-                    _ => {
-                        finish_load(
-                            Err(JsError::from_opaque(js_string!("Invalid specifier").into())),
-                            context,
-                        );
-                        return;
-                    }
-                };
-
-                self.builtins
-                    .borrow_mut()
-                    .insert(specifier.clone(), module.clone());
-                module
-            }
-            Some(module) => module,
-        };
-        finish_load(Ok(module), context);
-        */
+        if let Some(module) = self.get_module(specifier.clone()) {
+            finish_load(Ok(module), context);
+            return;
+        }
+
+        let result = self.resolve(&specifier.to_std_string_escaped(), context);
+
+        if let Ok(module) = &result {
+            self.register_module(specifier, module.clone());
+        }
+
+        finish_load(result, context);
     }
 
-    fn register_module(&self, _specifier: boa_engine::JsString, _module: JsModule) {}
+    fn register_module(&self, specifier: JsString, module: JsModule) {
+        self.modules.borrow_mut().insert(specifier, module);
+    }
 
     fn get_module(&self, specifier: JsString) -> Option<JsModule> {
-        self.builtins.borrow().get(&specifier).cloned()
+        self.modules.borrow().get(&specifier).cloned()
     }
 
     fn init_import_meta(
@@ -131,20 +132,28 @@ impl Module {
         <Vec<Instruction>>::from_js(result, &mut self.context)
     }
 
-    pub fn load(maybe_script: Option<String>) -> Result<Rc<RwLock<Module>>, String> {
-        let script_id = Rc::new(blake3::hash(
-            maybe_script.clone().unwrap_or_default().as_bytes(),
+    pub fn load(
+        maybe_script: Option<String>,
+        dependencies: BTreeMap<String, String>,
+    ) -> Result<Rc<RwLock<Module>>, String> {
+        let script_id = Rc::new(script_id(
+            maybe_script.as_deref().unwrap_or_default(),
+            &dependencies,
         ));
         let maybe_script = maybe_script.map(Rc::new);
 
-        let state = Self::get_or_init(maybe_script.clone(), script_id.clone())?;
+        let state = Self::get_or_init(
+            maybe_script.clone(),
+            dependencies.clone(),
+            script_id.clone(),
+        )?;
         let read_state = state.read().map_err(|error| format!("{error}"))?;
 
         if read_state.script_id == script_id {
             Ok(state.clone())
         } else {
             Self::reset();
-            Self::get_or_init(maybe_script, script_id)
+            Self::get_or_init(maybe_script, dependencies, script_id)
         }
     }
 
@@ -160,6 +169,7 @@ impl Module {
 
     fn get_or_init(
         maybe_script: Option<Rc<String>>,
+        dependencies: BTreeMap<String, String>,
         script_id: Rc<Hash>,
     ) -> Result<Rc<RwLock<Module>>, String> {
         Ok(MODULE_STATE
@@ -173,7 +183,7 @@ impl Module {
                             return Err("Must provide a script to load!".to_owned());
                         };
 
-                        let loader = Rc::new(CommonModuleLoader::new());
+                        let loader = Rc::new(CommonModuleLoader::new(dependencies));
 
                         let mut context = Context::builder()
                             .module_loader(loader.clone())
@@ -205,7 +215,7 @@ impl Module {
                                 println!("Module didn't evaluate!")
                             }
                             PromiseState::Rejected(error) => {
-                                println!("Module error: {}", error.display())
+                                return Err(format!("Module error: {}", error.display()));
                             }
                         };
 
@@ -243,9 +253,40 @@ fn get_module_export(
         .ok_or_else(|| format!("No '{export_name}' function was exported!"))
 }
 
+/// The ID of `script` and its `dependencies`, with each field
+/// length-prefixed.
+fn script_id(script: &str, dependencies: &BTreeMap<String, String>) -> Hash {
+    let mut hasher = blake3::Hasher::new();
+    let fields = dependencies
+        .iter()
+        .flat_map(|(specifier, source)| [specifier.as_str(), source.as_str()]);
+    for field in std::iter::once(script).chain(fields) {
+        hasher.update(&(field.len() as u64).to_le_bytes());
+        hasher.update(field.as_bytes());
+    }
+    hasher.finalize()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Module;
+    use super::{script_id, Module};
+    use crate::types::{Comparison, Pattern, RangeQuery, Scalar, Term};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn it_does_not_share_script_ids_across_field_boundaries() {
+        let dependencies = |specifier: &str, source: &str| {
+            BTreeMap::from([(specifier.to_owned(), source.to_owned())])
+        };
+        assert_ne!(
+            script_id("", &dependencies("a", "bc")),
+            script_id("", &dependencies("ab", "c"))
+        );
+        assert_ne!(
+            script_id("a", &dependencies("b", "c")),
+            script_id("ab", &dependencies("", "c"))
+        );
+    }
 
     #[test]
     fn it_runs_a_common_function() -> Result<(), String> {
@@ -266,7 +307,7 @@ export const step = (total, datums) => {
 export const end = (total) => [total]"#
             .to_owned();
 
-        let module = Module::load(Some(script))?;
+        let module = Module::load(Some(script), Default::default())?;
         let mut module = module.write().map_err(|error| format!("{error}"))?;
 
         for _ in 0..3 {
@@ -277,4 +318,86 @@ export const end = (total) => [total]"#
 
         Ok(())
     }
+
+    #[test]
+    fn it_imports_provided_dependencies() -> Result<(), String> {
+        let script = r#"
+import { attribute } from "./attribute.js";
+
+export const init = (input) => [0, { "ByAttribute": { attribute } }];
+export const step = (total, datoms) => [total + datoms.length, []];
+export const end = (total) => [];"#
+            .to_owned();
+        let dependencies = [(
+            "./attribute.js".to_owned(),
+            r#"export const attribute = "myattr";"#.to_owned(),
+        )]
+        .into();
+
+        let module = Module::load(Some(script), dependencies)?;
+        let mut module = module.write().map_err(|error| format!("{error}"))?;
+        module.call_init(vec![])?;
+
+        Module::reset();
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_returns_a_query() -> Result<(), String> {
+        let script = r#"
+export const init = (input) => [0, { "Query": {
+  find: ["title"],
+  where: [
+    [{ variable: "book" }, "author", { variable: "author" }],
+    [{ variable: "author" }, "name", "Ursula"],
+    [{ variable: "book" }, "title", { variable: "title" }],
+  ],
+  predicates: [["!=", { variable: "title" }, ""]],
+}}];
+export const step = (total, datoms) => [total + datoms.length, []];
+export const end = (total) => [];"#
+            .to_owned();
+
+        let module = Module::load(Some(script), Default::default())?;
+        let mut module = module.write().map_err(|error| format!("{error}"))?;
+        let (_, range_query) = module.call_init(vec![])?;
+
+        let RangeQuery::Query(query) = range_query else {
+            return Err("Expected a query".into());
+        };
+        assert_eq!(query.find, vec!["title".to_owned()]);
+        assert_eq!(query.patterns.len(), 3);
+        assert!(matches!(
+            &query.patterns[1],
+            Pattern {
+                entity: Term::Variable(author),
+                attribute: Term::Constant(Scalar::String(name)),
+                value: Term::Constant(Scalar::String(value)),
+            } if author == "author" && name == "name" && value == "Ursula"
+        ));
+        assert!(matches!(
+            query.predicates[0].comparison,
+            Comparison::NotEqual
+        ));
+
+        Module::reset();
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_fails_to_import_unknown_modules() {
+        let script = r#"
+import { attribute } from "./attribute.js";
+
+export const init = (input) => [0, { "ByAttribute": { attribute } }];
+export const step = (total, datoms) => [total + datoms.length, []];
+export const end = (total) => [];"#
+            .to_owned();
+
+        assert!(Module::load(Some(script), Default::default()).is_err());
+
+        Module::reset();
+    }
 }
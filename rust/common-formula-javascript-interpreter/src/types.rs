@@ -3,8 +3,8 @@
 //! and conversions to/from their JS representations.
 
 pub use crate::bindings::exports::common::formula::module::{
-    AttributeRangeQuery, Datom, Entity, EntityRangeQuery, Instruction, RangeQuery, Scalar, State,
-    ValueRangeQuery,
+    AttributeRangeQuery, Comparison, Datom, Entity, EntityRangeQuery, Instruction, Pattern,
+    Predicate, Query, RangeQuery, Scalar, State, Term, ValueRangeQuery,
 };
 use crate::{bindings::exports::common::formula::module::Fact, util::format_error};
 use boa_engine::{
@@ -23,6 +23,8 @@ const OP_IMPORT: &str = "Import";
 const RANGE_QUERY_BY_ENTITY: &str = "ByEntity";
 const RANGE_QUERY_BY_ATTRIBUTE: &str = "ByAttribute";
 const RANGE_QUERY_BY_VALUE: &str = "ByValue";
+const RANGE_QUERY_QUERY: &str = "Query";
+const TERM_VARIABLE: &str = "variable";
 
 macro_rules! js_object {
     ($context:expr, { $($key:expr => $value:expr),+ }) => (
@@ -358,21 +360,115 @@ impl FromJs for RangeQuery {
                 .map_err(format_error)?,
             obj.get(js_string!(RANGE_QUERY_BY_VALUE), context)
                 .map_err(format_error)?,
+            obj.get(js_string!(RANGE_QUERY_QUERY), context)
+                .map_err(format_error)?,
         ) {
-            (JsValue::Object(obj), JsValue::Undefined, JsValue::Undefined) => Ok(
-                RangeQuery::Entity(EntityRangeQuery::from_js(JsValue::Object(obj), context)?),
-            ),
-            (JsValue::Undefined, JsValue::Object(obj), JsValue::Undefined) => Ok(
-                RangeQuery::Attribute(AttributeRangeQuery::from_js(JsValue::Object(obj), context)?),
-            ),
-            (JsValue::Undefined, JsValue::Undefined, JsValue::Object(obj)) => Ok(
-                RangeQuery::Value(ValueRangeQuery::from_js(JsValue::Object(obj), context)?),
-            ),
+            (JsValue::Object(obj), JsValue::Undefined, JsValue::Undefined, JsValue::Undefined) => {
+                Ok(RangeQuery::Entity(EntityRangeQuery::from_js(
+                    JsValue::Object(obj),
+                    context,
+                )?))
+            }
+            (JsValue::Undefined, JsValue::Object(obj), JsValue::Undefined, JsValue::Undefined) => {
+                Ok(RangeQuery::Attribute(AttributeRangeQuery::from_js(
+                    JsValue::Object(obj),
+                    context,
+                )?))
+            }
+            (JsValue::Undefined, JsValue::Undefined, JsValue::Object(obj), JsValue::Undefined) => {
+                Ok(RangeQuery::Value(ValueRangeQuery::from_js(
+                    JsValue::Object(obj),
+                    context,
+                )?))
+            }
+            (JsValue::Undefined, JsValue::Undefined, JsValue::Undefined, JsValue::Object(obj)) => {
+                Ok(RangeQuery::Query(Query::from_js(
+                    JsValue::Object(obj),
+                    context,
+                )?))
+            }
             _ => Err(INVALID_JS_TYPE.into()),
         }
     }
 }
 
+/// A query is represented in JS as
+/// `{ find: [...variables], where: [...patterns], predicates?: [...predicates] }`.
+impl FromJs for Query {
+    fn from_js(value: JsValue, context: &mut Context) -> Result<Self, String> {
+        let obj = value.as_object().ok_or(INVALID_JS_TYPE)?;
+        Ok(Query {
+            find: Vec::<String>::from_js(
+                obj.get(js_string!("find"), context).map_err(format_error)?,
+                context,
+            )?,
+            patterns: Vec::<Pattern>::from_js(
+                obj.get(js_string!("where"), context)
+                    .map_err(format_error)?,
+                context,
+            )?,
+            predicates: Option::<Vec<Predicate>>::from_js(
+                obj.get(js_string!("predicates"), context)
+                    .map_err(format_error)?,
+                context,
+            )?
+            .unwrap_or_default(),
+        })
+    }
+}
+
+/// A pattern is represented in JS as an `[entity, attribute, value]` array
+/// of terms.
+impl FromJs for Pattern {
+    fn from_js(value: JsValue, context: &mut Context) -> Result<Self, String> {
+        let array = js_value_to_js_array(value)?;
+        Ok(Pattern {
+            entity: Term::from_js(array.at(0, context).map_err(format_error)?, context)?,
+            attribute: Term::from_js(array.at(1, context).map_err(format_error)?, context)?,
+            value: Term::from_js(array.at(2, context).map_err(format_error)?, context)?,
+        })
+    }
+}
+
+/// A predicate is represented in JS as a `[comparison, left, right]` array,
+/// where `comparison` is one of `"="`, `"!="`, `"<"`, `"<="`, `">"` or `">="`.
+impl FromJs for Predicate {
+    fn from_js(value: JsValue, context: &mut Context) -> Result<Self, String> {
+        let array = js_value_to_js_array(value)?;
+        let comparison =
+            match String::from_js(array.at(0, context).map_err(format_error)?, context)?.as_str() {
+                "=" => Comparison::Equal,
+                "!=" => Comparison::NotEqual,
+                "<" => Comparison::LessThan,
+                "<=" => Comparison::LessThanOrEqual,
+                ">" => Comparison::GreaterThan,
+                ">=" => Comparison::GreaterThanOrEqual,
+                comparison => return Err(format!("Invalid comparison '{comparison}'.")),
+            };
+        Ok(Predicate {
+            comparison,
+            left: Term::from_js(array.at(1, context).map_err(format_error)?, context)?,
+            right: Term::from_js(array.at(2, context).map_err(format_error)?, context)?,
+        })
+    }
+}
+
+/// A term is represented in JS as `{ variable: name }`, or as any other
+/// scalar value for a constant.
+impl FromJs for Term {
+    fn from_js(value: JsValue, context: &mut Context) -> Result<Self, String> {
+        if let Some(obj) = value.as_object() {
+            let variable = obj
+                .get(js_string!(TERM_VARIABLE), context)
+                .map_err(format_error)?;
+            if !variable.is_undefined() {
+                return Ok(Term::Variable(String::from_js(variable, context)?));
+            }
+        }
+        Ok(Term::Constant(Scalar::from_js(value, context)?))
+    }
+}
+
 macro_rules! range_query_type_from_js {
     ($query_type:ty, $entity_type:ty, $attr_type:ty, $value_type:ty) => {
         impl FromJs for $query_type {
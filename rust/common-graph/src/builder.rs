@@ -1,4 +1,4 @@
-use super::{CommonGraphError, Edge, Graph, Result};
+use super::{CommonGraphError, Edge, Graph, GraphOptions, MergeStrategy, Result};
 use std::fmt::Debug;
 
 type BuilderNode<T> = (String, usize, T, (Vec<String>, Vec<String>));
@@ -11,6 +11,7 @@ pub struct GraphBuilder<T: Debug> {
     inputs: Option<Vec<String>>,
     outputs: Option<Vec<String>>,
     root_label: Option<String>,
+    options: GraphOptions,
 }
 
 impl<T> GraphBuilder<T>
@@ -23,6 +24,32 @@ where
         self
     }
 
+    /// Permit nodes to form cycles, building
+    /// the graph with [`Graph::new_cyclic`].
+    pub fn allow_cycles(mut self) -> Self {
+        self.options.allow_cycles = true;
+        self
+    }
+
+    /// Permit a node's input port to have multiple incoming
+    /// connections, merging their values with `strategy`.
+    pub fn set_merge_strategy<S1, S2>(
+        mut self,
+        port: (S1, S2),
+        strategy: MergeStrategy,
+    ) -> Result<Self>
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        let node_name = port.0.into();
+        let (_, node_index, _, _) = self.get_node(&node_name)?;
+
+        let merge = ((node_index.to_owned(), port.1.into()), strategy);
+        self.options.merges.push(merge);
+        Ok(self)
+    }
+
     /// Set graph inputs/root node outputs.
     pub fn set_graph_input<I, S>(mut self, inputs: I) -> Self
     where
@@ -151,7 +178,7 @@ where
         for (label, _, node, io) in self.nodes.into_iter() {
             nodes.push((Some(node), label, io));
         }
-        Graph::new(nodes, self.edges)
+        Graph::with_options(nodes, self.edges, self.options)
     }
 }
 
@@ -166,6 +193,7 @@ where
             inputs: None,
             outputs: None,
             root_label: None,
+            options: GraphOptions::default(),
         }
     }
 }
@@ -209,7 +237,7 @@ mod tests {
         assert_eq!(root.label(), "RootNode");
         assert_eq!(
             root.inputs(),
-            &[(s("out1"), Some((2, s("B-out")))), (s("out2"), None)]
+            &[(s("out1"), Some(vec![(2, s("B-out"))])), (s("out2"), None)]
         );
         assert_eq!(
             root.outputs(),
@@ -219,14 +247,14 @@ mod tests {
             ]
         );
 
-        assert_eq!(a.inputs(), &[(s("A-in"), Some((0, s("in1"))))],);
+        assert_eq!(a.inputs(), &[(s("A-in"), Some(vec![(0, s("in1"))]))],);
         assert_eq!(a.outputs(), &[(s("A-out"), Some(vec![(2, s("B-in2"))])),]);
 
         assert_eq!(
             b.inputs(),
             &[
-                (s("B-in1"), Some((0, s("in1")))),
-                (s("B-in2"), Some((1, s("A-out")))),
+                (s("B-in1"), Some(vec![(0, s("in1"))])),
+                (s("B-in2"), Some(vec![(1, s("A-out"))])),
             ],
         );
         assert_eq!(b.outputs(), &[(s("B-out"), Some(vec![(0, s("out1"))]))]);
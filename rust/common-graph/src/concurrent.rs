@@ -0,0 +1,504 @@
+use crate::{
+    storage::GraphData, CommonGraphError, Graph, GraphProcessor, Node, PortType, Processor, Result,
+};
+use futures_util::{stream::FuturesUnordered, StreamExt};
+use std::{collections::VecDeque, fmt::Debug, future::Future};
+
+/// A node yielded by [`ConcurrentGraphProcessor::ready`].
+///
+/// Unlike [`GraphProcessorItem`](crate::GraphProcessorItem), it owns
+/// copies of its port values, so that it can be processed alongside
+/// other ready nodes. Once its outputs are written, it is returned
+/// with [`ConcurrentGraphProcessor::complete`].
+#[derive(Debug)]
+pub struct ReadyNode<'g, T, V> {
+    node: &'g Node<T>,
+    inputs: Vec<(&'g str, Option<V>)>,
+    outputs: Vec<(&'g str, Option<V>)>,
+}
+
+impl<'g, T, V> ReadyNode<'g, T, V> {
+    /// Returns the [`Node`] to be processed.
+    pub fn node(&self) -> &'g Node<T> {
+        self.node
+    }
+
+    /// Returns the map of port name and value, representing
+    /// incoming data.
+    pub fn inputs(&self) -> &[(&'g str, Option<V>)] {
+        &self.inputs[..]
+    }
+
+    /// Returns the map of port name and value, representing
+    /// outgoing data. Ports retain their values from any
+    /// previous processing of the node.
+    pub fn outputs_mut(&mut self) -> &mut [(&'g str, Option<V>)] {
+        &mut self.outputs[..]
+    }
+}
+
+/// Progress of a sweep over a cyclic component.
+#[derive(Clone, Copy, Default)]
+struct Sweep {
+    /// Position within the component of the next node to consider.
+    position: usize,
+    /// Number of passes over the component that yielded a node.
+    iteration: usize,
+    /// Whether the current pass has yielded a node.
+    progressed: bool,
+}
+
+/// A processor for [`Graph`] that yields every node whose
+/// inputs are satisfied at once, so that they can be processed
+/// concurrently, propagating each node's outputs as it completes.
+///
+/// Created from a [`GraphProcessor`] with
+/// [`GraphProcessor::into_concurrent`], retaining its merge function,
+/// iteration limit and incremental [`ConcurrentGraphProcessor::update`]s.
+///
+/// A node is ready once none of the nodes upstream of it are still
+/// ready or being processed. Nodes within a cycle are yielded one at
+/// a time, in the same order as [`GraphProcessor::try_next`] would.
+///
+/// See [`Graph::process_async`] to drive a [`ConcurrentGraphProcessor`]
+/// with an async callback.
+pub struct ConcurrentGraphProcessor<'g, T, V>
+where
+    T: Debug + 'g,
+    V: Clone + 'g,
+{
+    processor: GraphProcessor<'g, T, V>,
+    /// Whether each node has been yielded and not yet completed.
+    in_flight: Vec<bool>,
+    /// Progress of each component, used by cyclic components.
+    sweeps: Vec<Sweep>,
+}
+
+impl<'g, T, V> ConcurrentGraphProcessor<'g, T, V>
+where
+    T: Debug + 'g,
+    V: Clone + 'g,
+{
+    /// Creates a new [`ConcurrentGraphProcessor`] from a
+    /// [`GraphProcessor`] that has not yielded any nodes.
+    pub(crate) fn new(mut processor: GraphProcessor<'g, T, V>) -> Result<Self> {
+        let graph = processor.graph;
+        processor.propagate(0)?;
+        Ok(Self {
+            processor,
+            in_flight: vec![false; graph.nodes().len()],
+            sweeps: vec![Sweep::default(); graph.components().len()],
+        })
+    }
+
+    /// Returns the nodes that became ready since the last call.
+    ///
+    /// An empty set while no nodes are being processed
+    /// means that the processor is exhausted.
+    pub fn ready(&mut self) -> Result<Vec<ReadyNode<'g, T, V>>> {
+        let graph = self.processor.graph;
+        // Whether each node may still have its outputs change.
+        let mut pending = vec![false; graph.nodes().len()];
+        let mut ready = vec![];
+
+        for (component_index, component) in graph.components().iter().enumerate() {
+            let mut blocked = component.nodes.iter().any(|index| self.in_flight[*index]);
+            for index in component.nodes.iter() {
+                for (_, sources) in graph.get_node(*index)?.inputs() {
+                    blocked |= sources.iter().flatten().any(|(source_index, _)| {
+                        pending[*source_index]
+                            && component.nodes.binary_search(source_index).is_err()
+                    });
+                }
+            }
+
+            let next = match blocked {
+                true => vec![],
+                false if component.cyclic => {
+                    let label = graph.get_node(component.nodes[0])?.label();
+                    let sweep = &mut self.sweeps[component_index];
+                    match self.processor.next_in_sweep(&component.nodes, sweep)? {
+                        Some(_) if sweep.iteration > self.processor.max_iterations => {
+                            return Err(CommonGraphError::FixpointNotReached(
+                                label.into(),
+                                self.processor.max_iterations,
+                            ));
+                        }
+                        Some(index) => vec![index],
+                        None => vec![],
+                    }
+                }
+                false => {
+                    let mut next = vec![];
+                    for index in component.nodes.iter() {
+                        if self.processor.dirty[*index] && self.processor.is_ready(*index)? {
+                            next.push(*index);
+                        }
+                    }
+                    next
+                }
+            };
+
+            let settled = !blocked && next.is_empty();
+            for index in component.nodes.iter() {
+                pending[*index] = !settled;
+            }
+            for index in next {
+                self.in_flight[index] = true;
+                self.processor.dirty[index] = false;
+                ready.push(self.ready_node(index)?);
+            }
+        }
+
+        if ready.is_empty() && !self.in_flight.contains(&true) {
+            // No more nodes to process -- collect output.
+            self.processor.current_index = None;
+        }
+        Ok(ready)
+    }
+
+    /// Writes the outputs of a node yielded by
+    /// [`ConcurrentGraphProcessor::ready`], propagating them
+    /// to the inputs they connect to.
+    pub fn complete(&mut self, ready: ReadyNode<'g, T, V>) -> Result<()> {
+        let index = ready.node.index();
+        if !self.in_flight.get(index).copied().unwrap_or_default() {
+            return Err(CommonGraphError::InternalError(format!(
+                "Node '{}' completed without being yielded.",
+                ready.node.label()
+            )));
+        }
+        for (port_name, value) in ready.outputs {
+            if let Some(value) = value {
+                self.processor
+                    .storage
+                    .set(index, port_name, value, PortType::Output)?;
+            }
+        }
+        self.in_flight[index] = false;
+        self.processor.propagate(index)
+    }
+
+    /// Returns the data of all ports if the processor
+    /// has been exhausted, without consuming it.
+    pub fn snapshot(&self) -> Option<GraphData<'g, V>> {
+        self.processor.snapshot()
+    }
+
+    /// Consumes the processor, returning the output of the
+    /// process if it has been exhausted.
+    pub fn output(self) -> Option<GraphData<'g, V>> {
+        self.processor.output()
+    }
+
+    fn ready_node(&self, index: usize) -> Result<ReadyNode<'g, T, V>> {
+        let node = self.processor.graph.get_node(index)?;
+        let (inputs, outputs) = &self.processor.storage.data().inner()[index];
+        Ok(ReadyNode {
+            node,
+            inputs: inputs.clone(),
+            outputs: outputs.clone(),
+        })
+    }
+}
+
+impl<'g, T, V> ConcurrentGraphProcessor<'g, T, V>
+where
+    T: Debug + 'g,
+    V: Clone + PartialEq + 'g,
+{
+    /// Writes changed graph `input` into an exhausted processor.
+    ///
+    /// See [`GraphProcessor::update`].
+    pub fn update<'ext, I>(&mut self, input: I) -> Result<()>
+    where
+        I: IntoIterator<Item = (&'ext str, V)>,
+    {
+        self.processor.update(input)?;
+        self.processor.propagate(0)
+    }
+}
+
+impl<'g, T, V> GraphProcessor<'g, T, V>
+where
+    T: Debug + 'g,
+    V: Clone + 'g,
+{
+    /// Advances `sweep` over the cyclic component of `nodes`,
+    /// returning the next node to process, if any. The sweep
+    /// is reset once the component reaches a fixpoint.
+    fn next_in_sweep(&self, nodes: &[usize], sweep: &mut Sweep) -> Result<Option<usize>> {
+        loop {
+            while let Some(&index) = nodes.get(sweep.position) {
+                sweep.position += 1;
+                if self.dirty[index] && self.is_ready(index)? {
+                    if !sweep.progressed {
+                        sweep.progressed = true;
+                        sweep.iteration += 1;
+                    }
+                    return Ok(Some(index));
+                }
+            }
+            if !sweep.progressed {
+                *sweep = Sweep::default();
+                return Ok(None);
+            }
+            if self.eq.is_none() {
+                let label = self.graph.get_node(nodes[0])?.label();
+                return Err(CommonGraphError::ChangesNotDetected(label.into()));
+            }
+            sweep.position = 0;
+            sweep.progressed = false;
+        }
+    }
+}
+
+impl<'g, T, V> GraphProcessor<'g, T, V>
+where
+    T: Debug + 'g,
+    V: Clone + 'g,
+{
+    /// Converts this processor into a [`ConcurrentGraphProcessor`],
+    /// which yields all ready nodes at once.
+    ///
+    /// Fails if this processor has already yielded nodes.
+    pub fn into_concurrent(self) -> Result<ConcurrentGraphProcessor<'g, T, V>> {
+        if self.current_index != Some(0) || self.cursor != (0, 0) {
+            return Err(CommonGraphError::InternalError(
+                "Only unstarted processors may be processed concurrently.".into(),
+            ));
+        }
+        ConcurrentGraphProcessor::new(self)
+    }
+}
+
+impl<T> Graph<T>
+where
+    T: Debug,
+{
+    /// Process this graph with an async callback.
+    ///
+    /// `func` is called for each node with its inputs in order
+    /// to write its outputs, processing up to `concurrency` nodes
+    /// (at least one) at a time. Each node's outputs propagate
+    /// as soon as its future completes.
+    pub async fn process_async<'a, 'ext, I, V, F, Fut, E>(
+        &'a self,
+        inputs: I,
+        concurrency: usize,
+        mut func: F,
+    ) -> Result<::std::result::Result<GraphData<'a, V>, E>>
+    where
+        F: FnMut(ReadyNode<'a, T, V>) -> Fut,
+        Fut: Future<Output = ::std::result::Result<ReadyNode<'a, T, V>, E>>,
+        I: IntoIterator<Item = (&'ext str, V)>,
+        V: Clone + 'a,
+        T: 'a,
+        'ext: 'a,
+    {
+        let mut processor = self.process_iter(inputs)?.into_concurrent()?;
+        let mut queue = VecDeque::new();
+        let mut running = FuturesUnordered::new();
+        loop {
+            queue.extend(processor.ready()?);
+            while running.len() < concurrency.max(1) {
+                let Some(ready) = queue.pop_front() else {
+                    break;
+                };
+                running.push(func(ready));
+            }
+            match running.next().await {
+                Some(Ok(ready)) => processor.complete(ready)?,
+                Some(Err(e)) => return Ok(Err(e)),
+                None => break,
+            }
+        }
+        let Some(output) = processor.output() else {
+            return Err(CommonGraphError::Unexpected(
+                "Graph does not have output.".into(),
+            ))?;
+        };
+        Ok(Ok(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        helpers::{Op, OpNum},
+        GraphBuilder,
+    };
+    use common_tracing::common_tracing;
+
+    /// (a * 2) + (b + 1)
+    fn build_graph() -> Result<Graph<Op>> {
+        GraphBuilder::default()
+            .set_graph_input(["a", "b"])
+            .set_graph_output(["out"])
+            .node("one", Op::Identity(1.0), Vec::<String>::new(), ["out"])
+            .node("two", Op::Identity(2.0), Vec::<String>::new(), ["out"])
+            .node("double", Op::Multiply, ["x", "y"], ["out"])
+            .node("increment", Op::Add, ["x", "y"], ["out"])
+            .node("sum", Op::Add, ["x", "y"], ["out"])
+            .connect_input("a", ("double", "x"))?
+            .connect_input("b", ("increment", "x"))?
+            .connect(("two", "out"), ("double", "y"))?
+            .connect(("one", "out"), ("increment", "y"))?
+            .connect(("double", "out"), ("sum", "x"))?
+            .connect(("increment", "out"), ("sum", "y"))?
+            .connect_output(("sum", "out"), "out")?
+            .build()
+    }
+
+    /// Processes every ready set in turn, returning the
+    /// sorted labels of the nodes in each.
+    fn drain(processor: &mut ConcurrentGraphProcessor<'_, Op, OpNum>) -> Result<Vec<Vec<String>>> {
+        let mut ready_sets = vec![];
+        loop {
+            let ready = processor.ready()?;
+            if ready.is_empty() {
+                return Ok(ready_sets);
+            }
+            let mut labels: Vec<String> = ready
+                .iter()
+                .map(|ready| ready.node().label().to_owned())
+                .collect();
+            labels.sort();
+            ready_sets.push(labels);
+            for ready in ready {
+                processor.complete(Op::run_ready(ready)?)?;
+            }
+        }
+    }
+
+    #[test]
+    #[common_tracing]
+    fn it_yields_ready_sets() -> Result<()> {
+        let graph = build_graph()?;
+        let mut processor = graph
+            .process_iter([("a", 10.0), ("b", -5.0)])?
+            .into_concurrent()?;
+        assert_eq!(
+            drain(&mut processor)?,
+            vec![vec!["one", "two"], vec!["double", "increment"], vec!["sum"]]
+        );
+        assert_eq!(
+            processor.snapshot().unwrap().inner()[0].0,
+            vec![("out", Some(16.0))]
+        );
+
+        // Only nodes downstream of `b` are processed again.
+        processor.update([("b", 5.0)])?;
+        assert_eq!(drain(&mut processor)?, vec![vec!["increment"], vec!["sum"]]);
+        let output = processor.output().unwrap();
+        assert_eq!(output.inner()[0].0, vec![("out", Some(26.0))]);
+        Ok(())
+    }
+
+    #[test]
+    #[common_tracing]
+    fn it_holds_nodes_until_upstream_nodes_complete() -> Result<()> {
+        let graph = build_graph()?;
+        let mut processor = graph
+            .process_iter([("a", 10.0), ("b", -5.0)])?
+            .into_concurrent()?;
+        let mut ready = processor.ready()?;
+        assert_eq!(ready.len(), 2);
+        // Nodes are not yielded twice while in flight.
+        assert!(processor.ready()?.is_empty());
+        assert!(processor.snapshot().is_none());
+
+        processor.complete(Op::run_ready(ready.pop().unwrap())?)?;
+        let next = processor.ready()?;
+        assert_eq!(next.len(), 1);
+        assert!(processor
+            .complete(Op::run_ready(ready.pop().unwrap())?)
+            .is_ok());
+        assert_eq!(processor.ready()?.len(), 1);
+
+        let mut started = graph.process_iter([("a", 10.0), ("b", -5.0)])?;
+        started.try_next()?;
+        assert!(started.into_concurrent().is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[common_tracing]
+    fn it_processes_cycles_concurrently() -> Result<()> {
+        let graph = GraphBuilder::default()
+            .set_graph_input(["x"])
+            .set_graph_output(["out"])
+            .node("lowest", Op::Min, ["x", "loop"], ["out"])
+            .node("echo", Op::Max, ["in"], ["out"])
+            .connect_input("x", ("lowest", "x"))?
+            .connect(("lowest", "out"), ("echo", "in"))?
+            .connect(("echo", "out"), ("lowest", "loop"))?
+            .connect_output(("lowest", "out"), "out")?
+            .allow_cycles()
+            .build()?;
+
+        let mut processor = graph
+            .process_iter([("x", 3.0)])?
+            .with_change_detection()
+            .into_concurrent()?;
+        assert_eq!(
+            drain(&mut processor)?,
+            vec![vec!["lowest"], vec!["echo"], vec!["lowest"]]
+        );
+        let output = processor.output().unwrap();
+        assert_eq!(output.inner()[0].0, vec![("out", Some(3.0))]);
+
+        let graph = GraphBuilder::default()
+            .set_graph_input(["x"])
+            .set_graph_output(["out"])
+            .node("max", Op::Max, ["x", "loop"], ["out"])
+            .node("one", Op::Identity(1.0), Vec::<String>::new(), ["out"])
+            .node("increment", Op::Add, ["in", "one"], ["out"])
+            .connect_input("x", ("max", "x"))?
+            .connect(("max", "out"), ("increment", "in"))?
+            .connect(("one", "out"), ("increment", "one"))?
+            .connect(("increment", "out"), ("max", "loop"))?
+            .connect_output(("max", "out"), "out")?
+            .allow_cycles()
+            .build()?;
+        let mut processor = graph
+            .process_iter([("x", 0.0)])?
+            .with_change_detection()
+            .with_max_iterations(10)
+            .into_concurrent()?;
+        assert_eq!(
+            drain(&mut processor),
+            Err(CommonGraphError::FixpointNotReached("max".into(), 10))
+        );
+        Ok(())
+    }
+
+    #[common_tracing]
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn it_processes_graph_async_with_concurrency_limit() -> Result<()> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let graph = build_graph()?;
+        for (concurrency, expected_peak) in [(1, 1), (2, 2), (8, 2)] {
+            let active = AtomicUsize::new(0);
+            let peak = AtomicUsize::new(0);
+            let output = graph
+                .process_async([("a", 10.0), ("b", -5.0)], concurrency, |ready| {
+                    let (active, peak) = (&active, &peak);
+                    async move {
+                        let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(now, Ordering::SeqCst);
+                        tokio::task::yield_now().await;
+                        active.fetch_sub(1, Ordering::SeqCst);
+                        Op::run_ready(ready)
+                    }
+                })
+                .await??;
+            assert_eq!(output.inner()[0].0, vec![("out", Some(16.0))]);
+            assert_eq!(peak.load(Ordering::SeqCst), expected_peak);
+        }
+        Ok(())
+    }
+}
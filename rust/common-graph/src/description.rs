@@ -0,0 +1,329 @@
+use crate::{CommonGraphError, Graph, GraphBuilder, MergeStrategy, Node, Result};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{collections::BTreeMap, fmt::Debug};
+
+/// A port in a [`GraphDescription`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PortDescription {
+    /// Label of the node, or `None` for the graph's
+    /// own inputs and outputs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    /// Name of the port.
+    pub port: String,
+}
+
+/// A connection in a [`GraphDescription`], from
+/// an output port to an input port.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EdgeDescription {
+    /// Output port of a node, or a graph input.
+    pub from: PortDescription,
+    /// Input port of a node, or a graph output.
+    pub to: PortDescription,
+}
+
+/// A node in a [`GraphDescription`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NodeDescription<T> {
+    /// Unique label of the node.
+    pub label: String,
+    /// The node's payload.
+    pub inner: T,
+    /// Names of the node's input ports.
+    #[serde(default)]
+    pub inputs: Vec<String>,
+    /// Names of the node's output ports.
+    #[serde(default)]
+    pub outputs: Vec<String>,
+    /// Input ports accepting multiple incoming connections.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub merges: BTreeMap<String, MergeStrategy>,
+}
+
+/// A serializable description of a [`Graph`].
+///
+/// Descriptions are loaded via [`GraphBuilder`], and are subject to
+/// the same validation. [`Graph`] itself serializes as its description.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GraphDescription<T> {
+    /// Label of the graph's root node.
+    #[serde(default = "default_label")]
+    pub label: String,
+    /// Names of the graph's inputs.
+    #[serde(default)]
+    pub inputs: Vec<String>,
+    /// Names of the graph's outputs.
+    #[serde(default)]
+    pub outputs: Vec<String>,
+    /// The graph's nodes, excluding its root node.
+    pub nodes: Vec<NodeDescription<T>>,
+    /// The graph's connections, in connection order.
+    #[serde(default)]
+    pub edges: Vec<EdgeDescription>,
+    /// Permit nodes to form cycles.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allow_cycles: bool,
+}
+
+fn default_label() -> String {
+    String::from("Root")
+}
+
+impl<T> GraphDescription<T>
+where
+    T: Debug,
+{
+    /// Returns a [`GraphBuilder`] populated with this description.
+    pub fn into_builder(self) -> Result<GraphBuilder<T>> {
+        let mut builder = GraphBuilder::default()
+            .set_label(self.label)
+            .set_graph_input(self.inputs)
+            .set_graph_output(self.outputs);
+        if self.allow_cycles {
+            builder = builder.allow_cycles();
+        }
+
+        let mut merges = vec![];
+        for node in self.nodes {
+            for (port, strategy) in node.merges {
+                merges.push((node.label.clone(), port, strategy));
+            }
+            builder = builder.node(node.label, node.inner, node.inputs, node.outputs);
+        }
+        for (node, port, strategy) in merges {
+            builder = builder.set_merge_strategy((node, port), strategy)?;
+        }
+
+        for EdgeDescription { from, to } in self.edges {
+            builder = match (from.node, to.node) {
+                (None, Some(to_node)) => builder.connect_input(from.port, (to_node, to.port))?,
+                (Some(from_node), None) => {
+                    builder.connect_output((from_node, from.port), to.port)?
+                }
+                (Some(from_node), Some(to_node)) => {
+                    builder.connect((from_node, from.port), (to_node, to.port))?
+                }
+                (None, None) => {
+                    return Err(CommonGraphError::GraphBuilderFailure(format!(
+                        "Graph input '{}' may not connect directly to graph output '{}'.",
+                        from.port, to.port
+                    )))
+                }
+            };
+        }
+        Ok(builder)
+    }
+}
+
+impl<T> TryFrom<GraphDescription<T>> for Graph<T>
+where
+    T: Debug,
+{
+    type Error = CommonGraphError;
+
+    fn try_from(value: GraphDescription<T>) -> Result<Self> {
+        value.into_builder()?.build()
+    }
+}
+
+impl<'a, T> From<&'a Graph<T>> for GraphDescription<&'a T>
+where
+    T: Debug,
+{
+    fn from(graph: &'a Graph<T>) -> Self {
+        let nodes = graph.nodes();
+        let root = &nodes[0];
+        let port = |node: &Node<T>, port: &str| PortDescription {
+            node: (!node.is_root()).then(|| node.label().to_owned()),
+            port: port.to_owned(),
+        };
+
+        let mut edges = vec![];
+        // Visit the root node last, so that graph outputs
+        // follow the connections leading up to them.
+        for node in nodes.iter().skip(1).chain(nodes.first()) {
+            for (input, sources) in node.inputs() {
+                for (source_index, source_port) in sources.iter().flatten() {
+                    edges.push(EdgeDescription {
+                        from: port(&nodes[*source_index], source_port),
+                        to: port(node, input),
+                    });
+                }
+            }
+        }
+
+        GraphDescription {
+            label: root.label().to_owned(),
+            // Graph inputs are root node outputs, and vice versa.
+            inputs: root
+                .outputs()
+                .iter()
+                .map(|(name, _)| name.to_owned())
+                .collect(),
+            outputs: root
+                .inputs()
+                .iter()
+                .map(|(name, _)| name.to_owned())
+                .collect(),
+            nodes: nodes
+                .iter()
+                .filter_map(|node| {
+                    Some(NodeDescription {
+                        label: node.label().to_owned(),
+                        inner: node.inner()?,
+                        inputs: node
+                            .inputs()
+                            .iter()
+                            .map(|(name, _)| name.to_owned())
+                            .collect(),
+                        outputs: node
+                            .outputs()
+                            .iter()
+                            .map(|(name, _)| name.to_owned())
+                            .collect(),
+                        merges: node
+                            .inputs()
+                            .iter()
+                            .filter_map(|(name, _)| {
+                                Some((name.to_owned(), node.merge_strategy(name)?))
+                            })
+                            .collect(),
+                    })
+                })
+                .collect(),
+            edges,
+            allow_cycles: graph.allows_cycles(),
+        }
+    }
+}
+
+impl<T> Serialize for Graph<T>
+where
+    T: Debug + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+        GraphDescription::from(self).serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Graph<T>
+where
+    T: Debug + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+        Graph::try_from(GraphDescription::<T>::deserialize(deserializer)?)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Nested, PortDetails, PortType};
+    use common_tracing::common_tracing;
+
+    const DESCRIPTION: &str = r#"{
+  "label": "Messages",
+  "inputs": ["inbox", "sent"],
+  "outputs": ["summary"],
+  "nodes": [
+    {
+      "label": "Digest",
+      "inner": "llm",
+      "inputs": ["messages"],
+      "outputs": ["summary"],
+      "merges": { "messages": "collect" }
+    }
+  ],
+  "edges": [
+    { "from": { "port": "inbox" }, "to": { "node": "Digest", "port": "messages" } },
+    { "from": { "port": "sent" }, "to": { "node": "Digest", "port": "messages" } },
+    { "from": { "node": "Digest", "port": "summary" }, "to": { "port": "summary" } }
+  ]
+}"#;
+
+    #[test]
+    #[common_tracing]
+    fn it_loads_and_saves_graph_descriptions() -> Result<()> {
+        let graph: Graph<String> = serde_json::from_str(DESCRIPTION).unwrap();
+        let digest = &graph.nodes()[1];
+        assert_eq!(digest.inner(), Some(&String::from("llm")));
+        assert_eq!(
+            digest.merge_strategy("messages"),
+            Some(MergeStrategy::Collect)
+        );
+        assert_eq!(
+            digest.inputs()[0].1,
+            Some(vec![(0, String::from("inbox")), (0, String::from("sent"))])
+        );
+
+        let saved = serde_json::to_string(&graph).unwrap();
+        assert_eq!(
+            serde_json::from_str::<GraphDescription<String>>(&saved).unwrap(),
+            serde_json::from_str::<GraphDescription<String>>(DESCRIPTION).unwrap()
+        );
+        let reloaded: Graph<String> = serde_json::from_str(&saved).unwrap();
+        assert_eq!(
+            GraphDescription::from(&reloaded),
+            GraphDescription::from(&graph)
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[common_tracing]
+    fn it_validates_graph_descriptions() -> Result<()> {
+        let mut description: GraphDescription<String> = serde_json::from_str(DESCRIPTION).unwrap();
+        description.nodes[0].merges.clear();
+        assert_eq!(
+            Graph::try_from(description).err(),
+            Some(CommonGraphError::MultipleInputs(
+                PortDetails {
+                    node: "Digest".into(),
+                    port: "messages".into(),
+                    port_type: PortType::Input,
+                }
+                .into()
+            ))
+        );
+
+        let missing_node = DESCRIPTION.replace(
+            r#""node": "Digest", "port": "summary""#,
+            r#""node": "Summarize", "port": "summary""#,
+        );
+        assert!(serde_json::from_str::<Graph<String>>(&missing_node).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[common_tracing]
+    fn it_serializes_nested_subgraphs() -> Result<()> {
+        let inner: Graph<Nested<String>> = serde_json::from_str(
+            &DESCRIPTION.replace(r#""inner": "llm""#, r#""inner": { "leaf": "llm" }"#),
+        )
+        .unwrap();
+        let graph = GraphBuilder::default()
+            .set_graph_input(["inbox", "sent"])
+            .set_graph_output(["summary"])
+            .subgraph("Messages", inner)
+            .connect_input("inbox", ("Messages", "inbox"))?
+            .connect_input("sent", ("Messages", "sent"))?
+            .connect_output(("Messages", "summary"), "summary")?
+            .build()?;
+
+        let saved = serde_json::to_value(&graph).unwrap();
+        assert_eq!(
+            saved["nodes"][0]["inner"]["subgraph"]["nodes"][0]["inner"]["leaf"],
+            "llm"
+        );
+        let reloaded: Graph<Nested<String>> = serde_json::from_value(saved).unwrap();
+        let flat = reloaded.flatten()?;
+        assert_eq!(flat.nodes()[1].label(), "Messages/Digest");
+        assert_eq!(
+            flat.nodes()[1].merge_strategy("messages"),
+            Some(MergeStrategy::Collect)
+        );
+        Ok(())
+    }
+}
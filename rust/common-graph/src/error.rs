@@ -72,6 +72,25 @@ pub enum CommonGraphError {
     #[error("Node '{}' input port '{}' has multiple incoming connections.", .0.node, .0.port)]
     MultipleInputs(Box<PortDetails>),
 
+    /// There was the same connection between two ports more than once.
+    #[error("Node '{}' output port '{}' connects to node '{}' input port '{}' more than once.", .0.source_node, .0.source_port, .0.target_node, .0.target_port)]
+    DuplicateEdge(Box<ConnectionDetails>),
+
+    /// An input port with multiple incoming connections was
+    /// processed without a function to merge their values.
+    #[error("Node '{}' input port '{}' has no function to merge its incoming connections.", .0.node, .0.port)]
+    MissingMerge(Box<PortDetails>),
+
+    /// A cycle in the graph did not reach a fixpoint
+    /// within the maximum number of iterations.
+    #[error("Cycle containing node '{0}' did not converge within {1} iterations.")]
+    FixpointNotReached(String, usize),
+
+    /// A cycle in the graph was processed without comparing
+    /// values, so that it could not reach a fixpoint.
+    #[error("Cycle containing node '{0}' was processed without change detection.")]
+    ChangesNotDetected(String),
+
     /// A catch-all failure.
     #[error("{0}")]
     InternalError(String),
@@ -97,6 +116,13 @@ impl From<std::string::FromUtf8Error> for CommonGraphError {
     }
 }
 
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for CommonGraphError {
+    fn from(value: serde_json::Error) -> Self {
+        value.to_string().into()
+    }
+}
+
 impl From<String> for CommonGraphError {
     fn from(value: String) -> Self {
         CommonGraphError::InternalError(value)
@@ -1,7 +1,7 @@
 use super::PortDetails;
 use super::{CommonGraphError, ConnectionDetails, Result};
 use crate::storage::GraphData;
-use crate::utils::{is_empty, non_unique_entries};
+use crate::utils::{is_empty, non_unique_entries, strongly_connected_components};
 use crate::{GraphProcessor, GraphProcessorItem, Processor};
 use std::collections::HashSet;
 use std::fmt::Debug;
@@ -22,14 +22,50 @@ pub enum PortType {
     Output,
 }
 
+/// How an input port with multiple incoming
+/// connections combines their values.
+///
+/// A [`GraphProcessor`] waits for every connection to a port
+/// before merging their values with the function provided to
+/// [`GraphProcessor::with_merge`].
+#[derive(strum::Display, Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum MergeStrategy {
+    /// Combine all values, in connection order, e.g. into a list.
+    #[strum(to_string = "collect")]
+    Collect,
+    /// Use the most recently written value.
+    #[strum(to_string = "last-writer-wins")]
+    LastWriterWins,
+    /// Fold all values, in connection order, with a reducer.
+    #[strum(to_string = "reduce")]
+    Reduce,
+}
+
+/// Options relaxing the constraints of a [`Graph`]
+/// constructed via [`Graph::with_options`].
+#[derive(Debug, Default, Clone)]
+pub struct GraphOptions {
+    /// Permit nodes to form cycles.
+    pub allow_cycles: bool,
+    /// Input ports that accept multiple incoming
+    /// connections, and how their values are merged.
+    pub merges: Vec<(PortRef, MergeStrategy)>,
+}
+
 /// The internal node of a [`Graph`].
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Node<T> {
     index: usize,
     inner: Option<T>,
     label: String,
-    inputs: Vec<(String, Option<PortRef>)>,
+    inputs: Vec<(String, Option<Vec<PortRef>>)>,
     outputs: Vec<(String, Option<Vec<PortRef>>)>,
+    merges: Vec<(String, MergeStrategy)>,
 }
 
 impl<T> Node<T> {
@@ -61,6 +97,7 @@ impl<T> Node<T> {
             label,
             inputs,
             outputs,
+            merges: vec![],
         })
     }
 
@@ -84,11 +121,22 @@ impl<T> Node<T> {
         &self.label
     }
 
-    /// Get incoming ports with their source [`PortRef`].
-    pub fn inputs(&self) -> &[(String, Option<PortRef>)] {
+    /// Get incoming ports with their source [`PortRef`]s.
+    ///
+    /// Ports have at most one source, unless they
+    /// have a [`MergeStrategy`].
+    pub fn inputs(&self) -> &[(String, Option<Vec<PortRef>>)] {
         &self.inputs[..]
     }
 
+    /// Returns the [`MergeStrategy`] of input port `port_name`, if any.
+    pub fn merge_strategy(&self, port_name: &str) -> Option<MergeStrategy> {
+        self.merges
+            .iter()
+            .find(|(name, _)| name == port_name)
+            .map(|(_, strategy)| *strategy)
+    }
+
     /// Get outgoing ports with their target [`PortRef`].
     pub fn outputs(&self) -> &[(String, Option<Vec<PortRef>>)] {
         &self.outputs[..]
@@ -117,7 +165,24 @@ impl<T> Node<T> {
         Ok(())
     }
 
+    fn set_merge_strategy(&mut self, port_name: &str, strategy: MergeStrategy) -> Result<()> {
+        if !self.inputs.iter().any(|(s, _)| s == port_name) {
+            return Err(CommonGraphError::MissingPort(
+                PortDetails {
+                    node: self.label().into(),
+                    port_type: PortType::Input,
+                    port: port_name.into(),
+                }
+                .into(),
+            ));
+        }
+        self.merges.retain(|(s, _)| s != port_name);
+        self.merges.push((port_name.into(), strategy));
+        Ok(())
+    }
+
     fn set_incoming(&mut self, port_name: &str, port_ref: PortRef) -> Result<()> {
+        let merges = self.merge_strategy(port_name).is_some();
         let Some(port) = self.inputs.iter_mut().find(|(s, _)| s == port_name) else {
             return Err(CommonGraphError::MissingPort(
                 PortDetails {
@@ -130,6 +195,11 @@ impl<T> Node<T> {
         };
 
         match &mut port.1 {
+            // Port merges multiple incoming connections
+            Some(ref mut vec) if merges => {
+                vec.push(port_ref);
+                Ok(())
+            }
             // Port has an existing incoming connection
             Some(_) => Err(CommonGraphError::MultipleInputs(
                 PortDetails {
@@ -141,13 +211,24 @@ impl<T> Node<T> {
             )),
             // Port exists and has no outgoing connection
             None => {
-                port.1 = Some(port_ref);
+                port.1 = Some(vec![port_ref]);
                 Ok(())
             }
         }
     }
 }
 
+/// A set of nodes that are processed together, in
+/// the topological order of a [`Graph`]'s components.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Component {
+    /// Indices of nodes in this component, sorted.
+    pub(crate) nodes: Vec<usize>,
+    /// Whether this component contains a cycle, and must
+    /// be iterated over until it reaches a fixpoint.
+    pub(crate) cyclic: bool,
+}
+
 /// A [directed acyclic graph] of nodes with ports.
 ///
 /// The [`Graph`] contains vertices that have named
@@ -168,12 +249,14 @@ impl<T> Node<T> {
 ///   but an input and output port can).
 /// * No cycles other than the implicit cycle created by graph
 ///   IO and the root node. Nodes can only connect to nodes of
-///   a higher index.
+///   a higher index. Graphs constructed via [`Graph::new_cyclic`]
+///   lift this restriction, and are processed by iterating over
+///   each cycle until it reaches a fixpoint.
 /// * Nodes must have at least one incoming or outgoing connection.
 /// * All connections described in the input must be valid.
-/// * Input ports may only have one incoming connection (no fan-in).
-/// * Edges must be unique across the graph. Implicitly
-///   enforced by not allowing fan-in.
+/// * Input ports may only have one incoming connection (no fan-in),
+///   unless given a [`MergeStrategy`] via [`Graph::with_options`].
+/// * Edges must be unique across the graph.
 /// * There must be a single root node, and it must be located at index `0`.
 /// * Graph must contain at least one root node and one other connected node.
 ///
@@ -216,9 +299,11 @@ impl<T> Node<T> {
 /// # }
 /// ```
 /// [directed acyclic graph]: https://en.wikipedia.org/wiki/Directed_acyclic_graph
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct Graph<T: Debug> {
     nodes: Vec<Node<T>>,
+    components: Vec<Component>,
+    allow_cycles: bool,
 }
 
 impl<T> Graph<T>
@@ -232,6 +317,45 @@ where
     ///
     /// See [`Graph`] for all constraints.
     pub fn new<NodeIter, EdgeIter, S>(nodes_iter: NodeIter, edges: EdgeIter) -> Result<Self>
+    where
+        NodeIter: IntoIterator<Item = (Option<T>, S, (Vec<String>, Vec<String>))>,
+        EdgeIter: IntoIterator<Item = Edge>,
+        S: Into<String>,
+    {
+        Graph::with_options(nodes_iter, edges, GraphOptions::default())
+    }
+
+    /// Like [`Graph::new`], but nodes may form cycles, including
+    /// connecting to themselves.
+    ///
+    /// Nodes within a cycle are yielded by [`GraphProcessor`]
+    /// once their inputs from outside of the cycle are available,
+    /// and then repeatedly while their inputs change, until the
+    /// cycle reaches a fixpoint.
+    pub fn new_cyclic<NodeIter, EdgeIter, S>(nodes_iter: NodeIter, edges: EdgeIter) -> Result<Self>
+    where
+        NodeIter: IntoIterator<Item = (Option<T>, S, (Vec<String>, Vec<String>))>,
+        EdgeIter: IntoIterator<Item = Edge>,
+        S: Into<String>,
+    {
+        Graph::with_options(
+            nodes_iter,
+            edges,
+            GraphOptions {
+                allow_cycles: true,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Like [`Graph::new`], with constraints relaxed by `options`:
+    /// permitting cycles as in [`Graph::new_cyclic`], and input ports
+    /// with a [`MergeStrategy`] to have multiple incoming connections.
+    pub fn with_options<NodeIter, EdgeIter, S>(
+        nodes_iter: NodeIter,
+        edges: EdgeIter,
+        options: GraphOptions,
+    ) -> Result<Self>
     where
         NodeIter: IntoIterator<Item = (Option<T>, S, (Vec<String>, Vec<String>))>,
         EdgeIter: IntoIterator<Item = Edge>,
@@ -260,7 +384,14 @@ where
             return Err(CommonGraphError::EmptyGraph);
         }
 
-        populate_connections(&mut nodes, edges)?;
+        for ((index, port_name), strategy) in options.merges {
+            let Some(node) = nodes.get_mut(index) else {
+                return Err(CommonGraphError::MissingNode(index));
+            };
+            node.set_merge_strategy(&port_name, strategy)?;
+        }
+
+        populate_connections(&mut nodes, edges, options.allow_cycles)?;
 
         for node in nodes.iter() {
             if is_empty(node.inputs()) && is_empty(node.outputs()) {
@@ -268,7 +399,12 @@ where
             }
         }
 
-        Ok(Graph { nodes })
+        let components = find_components(&nodes);
+        Ok(Graph {
+            nodes,
+            components,
+            allow_cycles: options.allow_cycles,
+        })
     }
 
     /// Returns a reference to nodes in this graph.
@@ -276,6 +412,32 @@ where
         &self.nodes
     }
 
+    /// Whether any nodes in this graph form a cycle.
+    pub fn is_cyclic(&self) -> bool {
+        self.components.iter().any(|component| component.cyclic)
+    }
+
+    /// Whether this graph was constructed permitting cycles,
+    /// regardless of whether its nodes form any.
+    pub fn allows_cycles(&self) -> bool {
+        self.allow_cycles
+    }
+
+    /// Returns the components of this graph, excluding
+    /// the root node, in topological order.
+    pub(crate) fn components(&self) -> &[Component] {
+        &self.components
+    }
+
+    /// Whether nodes `a` and `b` are both within the same cycle.
+    pub(crate) fn in_same_cycle(&self, a: usize, b: usize) -> bool {
+        self.components.iter().any(|component| {
+            component.cyclic
+                && component.nodes.binary_search(&a).is_ok()
+                && component.nodes.binary_search(&b).is_ok()
+        })
+    }
+
     /// Returns a reference to an internal [`Node`].
     pub fn get_node(&self, index: usize) -> Result<&Node<T>> {
         self.nodes
@@ -283,6 +445,32 @@ where
             .ok_or_else(|| CommonGraphError::MissingNode(index))
     }
 
+    /// Returns a graph with the same nodes and connections,
+    /// with each node's inner `T` replaced by `func(inner)`.
+    pub fn map<U, F>(&self, mut func: F) -> Graph<U>
+    where
+        U: Debug,
+        F: FnMut(&T) -> U,
+    {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| Node {
+                index: node.index,
+                inner: node.inner.as_ref().map(&mut func),
+                label: node.label.clone(),
+                inputs: node.inputs.clone(),
+                outputs: node.outputs.clone(),
+                merges: node.merges.clone(),
+            })
+            .collect();
+        Graph {
+            nodes,
+            components: self.components.clone(),
+            allow_cycles: self.allow_cycles,
+        }
+    }
+
     /// Returns a [`GraphProcessor`] that can be iterated over
     /// to propagate `input` throughout the graph network.
     pub fn process_iter<'a, 'ext, V, I>(&'a self, input: I) -> Result<GraphProcessor<'a, T, V>>
@@ -374,40 +562,36 @@ where
         };
         Ok(Ok(output))
     }
+}
 
-    /*
-    /// Iteratively process this graph with async callback.
-    ///
-    /// `func` is called for each node with its inputs
-    /// in order to write its outputs.
-    pub async fn process_async<'a, 'ext, I, V, F, Fut, E>(
-        &'a self,
-        inputs: I,
-        mut func: F,
-    ) -> Result<::std::result::Result<Vec<PortStore<'a, V>>, E>>
-    where
-        Fut: ::std::future::Future<Output = ::std::result::Result<(), E>>,
-        F: for<'b> FnMut(&'b mut GraphProcessorItem<'b, T, V>) -> Fut,
-        I: IntoIterator<Item = (&'ext str, V)>,
-        V: Clone + 'a,
-        T: Debug + 'a,
-        'ext: 'a,
-    {
-        // TODO
-        let mut processor = self.process_iter(inputs)?;
-        while let Some(mut item) = processor.try_next()? {
-            if let Err(e) = func(&mut item).await {
-                return Ok(Err(e));
-            };
-        }
-        let Some(output) = processor.output() else {
-            return Err(CommonGraphError::Unexpected(
-                "Graph does not have output.".into(),
-            ))?;
-        };
-        Ok(Ok(output))
-    }
-    */
+/// Groups the non-root nodes into strongly connected
+/// [`Component`]s, in topological order. Connections back
+/// to the root node are not considered cycles.
+fn find_components<T>(nodes: &[Node<T>]) -> Vec<Component> {
+    let successors: Vec<Vec<usize>> = nodes
+        .iter()
+        .map(|node| {
+            node.outputs()
+                .iter()
+                .filter_map(|(_, targets)| targets.as_ref())
+                .flatten()
+                .map(|(target, _)| *target)
+                .filter(|target| *target != 0)
+                .collect()
+        })
+        .collect();
+
+    strongly_connected_components(&successors)
+        .into_iter()
+        .filter(|component| component != &[0])
+        .map(|component| {
+            let cyclic = component.len() > 1 || successors[component[0]].contains(&component[0]);
+            Component {
+                nodes: component,
+                cyclic,
+            }
+        })
+        .collect()
 }
 
 /// Takes the definition of edges and populates the
@@ -415,7 +599,7 @@ where
 /// error occurs, the mutations are not reverted and the graph
 /// should be considered invalid.
 /// Called during construction.
-fn populate_connections<T, E>(nodes: &mut [Node<T>], edges: E) -> Result<()>
+fn populate_connections<T, E>(nodes: &mut [Node<T>], edges: E, allow_cycles: bool) -> Result<()>
 where
     E: IntoIterator<Item = Edge>,
 {
@@ -440,7 +624,7 @@ where
         // nodes to connect to nodes of a higher index.
         // If a node is connecting to itself, or to a node of a lower index,
         // consider it an invalid cycle.
-        if target_index != 0 && source_index >= target_index {
+        if !allow_cycles && target_index != 0 && source_index >= target_index {
             return Err(CommonGraphError::InvalidCycleDetected(
                 ConnectionDetails {
                     source_node: source_label.into(),
@@ -452,6 +636,24 @@ where
             ));
         }
 
+        let is_duplicate = nodes[target_index]
+            .inputs()
+            .iter()
+            .find(|(name, _)| *name == target_port)
+            .and_then(|(_, sources)| sources.as_ref())
+            .is_some_and(|sources| sources.contains(&source));
+        if is_duplicate {
+            return Err(CommonGraphError::DuplicateEdge(
+                ConnectionDetails {
+                    source_node: source_label.into(),
+                    source_port,
+                    target_node: target_label.into(),
+                    target_port,
+                }
+                .into(),
+            ));
+        }
+
         // Add connections
         for (index, port_name, port_ref, port_type) in [
             (source_index, source_port, target, PortType::Output),
@@ -528,6 +730,103 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[common_tracing]
+    fn it_allows_cycles_when_requested() -> Result<()> {
+        let builder = GraphBuilder::default()
+            .set_graph_input(["in"])
+            .set_graph_output(["out"])
+            .node("A", (), ["in1", "in2"], ["out"])
+            .node("B", (), ["in"], ["out"])
+            .node("C", (), ["in"], ["out"])
+            .connect_input("in", ("A", "in1"))?
+            .connect(("A", "out"), ("B", "in"))?
+            .connect(("B", "out"), ("A", "in2"))?
+            .connect(("B", "out"), ("C", "in"))?
+            .connect_output(("C", "out"), "out")?;
+
+        assert!(builder.clone().build().is_err());
+        let graph = builder.allow_cycles().build()?;
+        assert!(graph.is_cyclic());
+
+        let components: Vec<_> = graph
+            .components()
+            .iter()
+            .map(|component| (component.nodes.clone(), component.cyclic))
+            .collect();
+        assert_eq!(components, vec![(vec![1, 2], true), (vec![3], false)]);
+        Ok(())
+    }
+
+    #[test]
+    #[common_tracing]
+    fn it_allows_fan_in_with_merge_strategy() -> Result<()> {
+        let (mut nodes, mut edges) = gen_graph_components();
+        nodes.push((Some(()), "B".into(), (vec!["in".into()], vec![])));
+        edges.push(((0, "out".into()), (2, "in".into())));
+        edges.push(((1, "out".into()), (2, "in".into())));
+
+        let options = GraphOptions {
+            merges: vec![((2, "in".into()), MergeStrategy::Collect)],
+            ..Default::default()
+        };
+        let graph = Graph::with_options(nodes.clone(), edges.clone(), options.clone())?;
+        assert_eq!(
+            graph.get_node(2)?.inputs(),
+            &[(
+                String::from("in"),
+                Some(vec![(0, "out".into()), (1, "out".into())])
+            )]
+        );
+        assert_eq!(
+            graph.get_node(2)?.merge_strategy("in"),
+            Some(MergeStrategy::Collect)
+        );
+
+        edges.push(((1, "out".into()), (2, "in".into())));
+        let Err(e) = Graph::with_options(nodes.clone(), edges, options) else {
+            panic!("expected error");
+        };
+        assert_eq!(
+            e,
+            CommonGraphError::DuplicateEdge(
+                ConnectionDetails {
+                    source_node: "A".into(),
+                    source_port: "out".into(),
+                    target_node: "B".into(),
+                    target_port: "in".into(),
+                }
+                .into()
+            )
+        );
+
+        let options = GraphOptions {
+            merges: vec![((2, "missing".into()), MergeStrategy::Collect)],
+            ..Default::default()
+        };
+        assert!(Graph::with_options(nodes, vec![], options).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[common_tracing]
+    fn it_maps_inner_values() -> Result<()> {
+        let (mut nodes, mut edges) = gen_graph_components();
+        nodes.push((Some(()), "B".into(), (vec!["in".into()], vec![])));
+        edges.push(((1, "out".into()), (2, "in".into())));
+        let graph = Graph::new(nodes, edges)?;
+
+        let mapped = graph.map(|_| String::from("mapped"));
+        for (node, mapped_node) in graph.nodes().iter().zip(mapped.nodes()) {
+            assert_eq!(mapped_node.label(), node.label());
+            assert_eq!(mapped_node.inputs(), node.inputs());
+            assert_eq!(mapped_node.outputs(), node.outputs());
+            assert_eq!(mapped_node.is_root(), node.is_root());
+        }
+        assert_eq!(mapped.nodes()[2].inner(), Some(&String::from("mapped")));
+        Ok(())
+    }
+
     #[test]
     #[common_tracing]
     fn it_fails_on_invalid_root() -> Result<()> {
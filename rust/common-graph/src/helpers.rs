@@ -1,6 +1,6 @@
 //! Helpers used in tests.
 
-use crate::{CommonGraphError, GraphProcessorItem};
+use crate::{CommonGraphError, GraphProcessorItem, ReadyNode};
 use std::fmt::Debug;
 use thiserror::Error;
 
@@ -61,13 +61,38 @@ impl Op {
     pub fn run<'a>(
         item: &'a mut GraphProcessorItem<'a, Op, OpNum>,
     ) -> ::std::result::Result<(), OpError> {
-        let values = item.inputs();
-        let v: Vec<OpNum> = values
-            .iter()
-            .filter_map(|(_, value)| value.as_ref().cloned())
-            .collect();
         let inner = item.node().inner().ok_or(OpError::MissingInner)?;
-        let output = match inner {
+        let output = inner.apply(values(item.inputs()))?;
+
+        {
+            let out_value = item
+                .outputs_mut()
+                .iter_mut()
+                .find_map(|(key, value)| if *key == "out" { Some(value) } else { None })
+                .ok_or(OpError::MissingOutput)?;
+            **out_value = Some(output);
+        }
+        Ok(())
+    }
+
+    /// Processes a [`ReadyNode`] for [`Op`] nodes.
+    pub fn run_ready(
+        mut ready: ReadyNode<'_, Op, OpNum>,
+    ) -> ::std::result::Result<ReadyNode<'_, Op, OpNum>, OpError> {
+        let inner = ready.node().inner().ok_or(OpError::MissingInner)?;
+        let output = inner.apply(values(ready.inputs()))?;
+        let out_value = ready
+            .outputs_mut()
+            .iter_mut()
+            .find_map(|(key, value)| if *key == "out" { Some(value) } else { None })
+            .ok_or(OpError::MissingOutput)?;
+        *out_value = Some(output);
+        Ok(ready)
+    }
+
+    /// Applies this operator to input values `v`.
+    fn apply(&self, v: Vec<OpNum>) -> ::std::result::Result<OpNum, OpError> {
+        Ok(match self {
             Op::Add => v[0] + v[1],
             Op::Subtract => v[0] - v[1],
             Op::Multiply => v[0] * v[1],
@@ -103,20 +128,18 @@ impl Op {
                 }
             }
             Op::Modulo => v[0] % v[1],
-        };
-
-        {
-            let out_value = item
-                .outputs_mut()
-                .iter_mut()
-                .find_map(|(key, value)| if *key == "out" { Some(value) } else { None })
-                .ok_or(OpError::MissingOutput)?;
-            **out_value = Some(output);
-        }
-        Ok(())
+        })
     }
 }
 
+/// Returns the values of all written ports in `ports`.
+fn values(ports: &[(&str, Option<OpNum>)]) -> Vec<OpNum> {
+    ports
+        .iter()
+        .filter_map(|(_, value)| value.as_ref().cloned())
+        .collect()
+}
+
 #[cfg(feature = "render")]
 impl crate::RenderableValue for OpNum {
     fn render_value(&self) -> String {
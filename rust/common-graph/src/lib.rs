@@ -9,6 +9,19 @@
 //! a [`Graph::process`] function that takes input data,
 //! and propagates data through the network, generating output.
 //!
+//! Graphs constructed via [`Graph::new_cyclic`] may also contain
+//! feedback loops, and a [`GraphProcessor`] with
+//! [change detection](GraphProcessor::with_change_detection)
+//! processes them until they reach a fixpoint. A [`GraphProcessor`]
+//! can also incrementally re-process a graph as its inputs change.
+//!
+//! [`Graph::process_async`] processes independent nodes concurrently,
+//! with an async callback, via a [`ConcurrentGraphProcessor`].
+//!
+//! Graphs of [`Nested`] nodes may contain other graphs as nodes,
+//! and can be [flattened](Graph::flatten) for processing. With the
+//! `serde` feature, graphs serialize as a [`GraphDescription`].
+//!
 //! # Example
 //!
 //! This example uses `Op` nodes which represent some math operation,
@@ -68,6 +81,9 @@
 //! [directed acyclic graph]: https://en.wikipedia.org/wiki/Directed_acyclic_graph
 
 mod builder;
+mod concurrent;
+#[cfg(feature = "serde")]
+mod description;
 mod error;
 mod graph;
 #[cfg(feature = "helpers")]
@@ -76,12 +92,17 @@ mod processor;
 #[cfg(feature = "render")]
 mod render;
 mod storage;
+mod subgraph;
 mod utils;
 
 pub use builder::*;
+pub use concurrent::*;
+#[cfg(feature = "serde")]
+pub use description::*;
 pub use error::*;
 pub use graph::*;
 pub use processor::*;
 #[cfg(feature = "render")]
 pub use render::*;
 pub use storage::*;
+pub use subgraph::*;
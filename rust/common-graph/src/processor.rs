@@ -1,8 +1,8 @@
 use crate::{
     storage::{GraphData, GraphStorage},
-    CommonGraphError, Graph, Node, PortType, Result,
+    CommonGraphError, Graph, MergeStrategy, Node, PortDetails, PortRef, PortType, Result,
 };
-use std::{collections::VecDeque, fmt::Debug};
+use std::{collections::HashMap, fmt::Debug};
 
 /// The item yielded by [`GraphProcessor`]'s iteration.
 ///
@@ -54,6 +54,28 @@ where
     }
 }
 
+/// Default maximum number of passes over a cycle
+/// before a [`GraphProcessor`] fails with
+/// [`CommonGraphError::FixpointNotReached`].
+pub const DEFAULT_MAX_ITERATIONS: usize = 100;
+
+/// Merges the values of an input port with multiple incoming
+/// connections. See [`GraphProcessor::with_merge`].
+type MergeFn<'g, V> = Box<dyn Fn(MergeStrategy, Vec<V>) -> V + 'g>;
+
+/// Compares a value written to a port with its previous value.
+/// See [`GraphProcessor::with_change_detection`].
+type EqFn<V> = fn(&V, &V) -> bool;
+
+/// The values written to an input port
+/// with multiple incoming connections.
+struct FanIn<V> {
+    /// Value of each incoming connection, in connection order.
+    values: Vec<Option<V>>,
+    /// Index of the most recently written connection.
+    last: usize,
+}
+
 /// An iterating processor for [`Graph`].
 ///
 /// A [`GraphProcessor`] takes inputs, and feeds them into an immutable [`Graph`]
@@ -63,6 +85,22 @@ where
 /// through the network, and the next node with complete input yields,
 /// and so forth, until all nodes have been processed.
 ///
+/// Nodes are yielded in the topological order of the graph's
+/// strongly connected components. Nodes within a cycle only
+/// require their inputs from outside of the cycle, and are
+/// yielded again while their inputs change, until the cycle
+/// reaches a fixpoint or exceeds [`DEFAULT_MAX_ITERATIONS`]
+/// (see [`GraphProcessor::with_max_iterations`]). Detecting
+/// a fixpoint requires [`GraphProcessor::with_change_detection`].
+///
+/// Input ports with a [`MergeStrategy`] are filled once all of
+/// their incoming connections have values, merging them with
+/// the function given to [`GraphProcessor::with_merge`].
+///
+/// Once exhausted, [`GraphProcessor::update`] feeds changed graph
+/// inputs into the network again, after which only the nodes downstream
+/// of those inputs, whose inputs changed in value, are yielded.
+///
 /// A [`GraphProcessor`] is similar to an [`Iterator`],
 /// using the [lending iterator] pattern instead to "lend"
 /// mutable references with a lifetime valid until the
@@ -75,10 +113,26 @@ where
     V: Clone + 'g,
     Self: 'g,
 {
-    graph: &'g Graph<T>,
-    current_index: Option<usize>,
-    storage: GraphStorage<'g, V>,
-    queue: VecDeque<usize>,
+    pub(crate) graph: &'g Graph<T>,
+    pub(crate) current_index: Option<usize>,
+    pub(crate) storage: GraphStorage<'g, V>,
+    /// Whether each node has inputs that changed
+    /// since it was last yielded.
+    pub(crate) dirty: Vec<bool>,
+    /// Index of the current component, and the
+    /// position within it of the next node to consider.
+    pub(crate) cursor: (usize, usize),
+    /// Number of passes over the current component
+    /// that yielded a node.
+    iteration: usize,
+    /// Whether the current pass has yielded a node.
+    progressed: bool,
+    pub(crate) max_iterations: usize,
+    /// Values of input ports with multiple incoming connections.
+    fan_in: HashMap<(usize, &'g str), FanIn<V>>,
+    merge: Option<MergeFn<'g, V>>,
+    /// Compares written values with previous ones, if enabled.
+    pub(crate) eq: Option<EqFn<V>>,
 }
 
 impl<'g, T, V> GraphProcessor<'g, T, V>
@@ -92,14 +146,8 @@ where
     where
         I: IntoIterator<Item = (&'ext str, V)>,
     {
-        let mut queue = VecDeque::default();
-
-        // Seed the storage with port keys and queue up any
-        // input-less nodes first after the root node.
+        // Seed the storage with port keys.
         let mut storage = GraphStorage::from_iter(graph.nodes().iter().map(|node| {
-            if !node.is_root() && node.inputs().is_empty() {
-                queue.push_back(node.index())
-            }
             (
                 node.inputs().iter().map(|(key, _)| key.as_str()),
                 node.outputs().iter().map(|(key, _)| key.as_str()),
@@ -115,9 +163,304 @@ where
             graph,
             storage,
             current_index: Some(0),
-            queue,
+            // Every node is processed at least once.
+            dirty: vec![true; graph.nodes().len()],
+            cursor: (0, 0),
+            iteration: 0,
+            progressed: false,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            fan_in: HashMap::default(),
+            merge: None,
+            eq: None,
         })
     }
+
+    /// Set the maximum number of passes over a cycle
+    /// before failing with [`CommonGraphError::FixpointNotReached`].
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Set the function that merges the values of input ports
+    /// with a [`MergeStrategy`], given the port's strategy and
+    /// its values in connection order. For
+    /// [`MergeStrategy::LastWriterWins`], the most recently
+    /// written value is moved last.
+    ///
+    /// Without a merge function, [`MergeStrategy::LastWriterWins`]
+    /// ports take the most recently written value, and other
+    /// strategies fail with [`CommonGraphError::MissingMerge`].
+    pub fn with_merge<F>(mut self, merge: F) -> Self
+    where
+        F: Fn(MergeStrategy, Vec<V>) -> V + 'g,
+    {
+        self.merge = Some(Box::new(merge));
+        self
+    }
+
+    /// Whether `value` is known to be equal to the `previous`
+    /// value of a port. Without change detection, every value
+    /// written is considered a change.
+    fn unchanged(eq: Option<EqFn<V>>, previous: Option<&V>, value: &V) -> bool {
+        match (eq, previous) {
+            (Some(eq), Some(previous)) => eq(previous, value),
+            _ => false,
+        }
+    }
+
+    /// Returns the data of all ports if the processor
+    /// has been exhausted, without consuming it.
+    pub fn snapshot(&self) -> Option<GraphData<'g, V>> {
+        match self.current_index {
+            None => Some(self.storage.data().clone()),
+            Some(_) => None,
+        }
+    }
+
+    /// Whether node `index` can be processed: all of its
+    /// inputs are set, apart from those fed back from
+    /// within a cycle it belongs to.
+    pub(crate) fn is_ready(&self, index: usize) -> Result<bool> {
+        for (port_name, sources) in self.graph.get_node(index)?.inputs() {
+            if self
+                .storage
+                .get(index, port_name, PortType::Input)?
+                .is_some()
+            {
+                continue;
+            }
+            match sources {
+                Some(sources)
+                    if sources.iter().all(|(source_index, _)| {
+                        self.graph.in_same_cycle(index, *source_index)
+                    }) => {}
+                _ => return Ok(false),
+            }
+        }
+        Ok(true)
+    }
+
+    /// Returns the index of the next node to process, if any,
+    /// advancing through components and passes over cycles.
+    fn next_index(&mut self) -> Result<Option<usize>> {
+        let components = self.graph.components();
+        while let Some(component) = components.get(self.cursor.0) {
+            while let Some(&index) = component.nodes.get(self.cursor.1) {
+                self.cursor.1 += 1;
+                if !self.dirty[index] || !self.is_ready(index)? {
+                    continue;
+                }
+                if !self.progressed {
+                    self.progressed = true;
+                    self.iteration += 1;
+                    if self.iteration > self.max_iterations {
+                        let label = self.graph.get_node(component.nodes[0])?.label();
+                        return Err(CommonGraphError::FixpointNotReached(
+                            label.into(),
+                            self.max_iterations,
+                        ));
+                    }
+                }
+                return Ok(Some(index));
+            }
+
+            // Sweep over a cycle again if any of its nodes were
+            // processed, until its nodes' inputs stop changing.
+            if component.cyclic && self.progressed {
+                if self.eq.is_none() {
+                    let label = self.graph.get_node(component.nodes[0])?.label();
+                    return Err(CommonGraphError::ChangesNotDetected(label.into()));
+                }
+                self.cursor.1 = 0;
+            } else {
+                self.cursor = (self.cursor.0 + 1, 0);
+                self.iteration = 0;
+            }
+            self.progressed = false;
+        }
+        Ok(None)
+    }
+}
+
+impl<'g, T, V> GraphProcessor<'g, T, V>
+where
+    T: Debug + 'g,
+    V: Clone + PartialEq + 'g,
+    Self: 'g,
+{
+    /// Compare values written to ports with their previous values,
+    /// so that nodes are only yielded again when their inputs change.
+    ///
+    /// Required for cycles to reach a fixpoint; without it, processing
+    /// a cycle fails with [`CommonGraphError::ChangesNotDetected`].
+    pub fn with_change_detection(mut self) -> Self {
+        self.eq = Some(V::eq);
+        self
+    }
+
+    /// Writes changed graph `input` into an exhausted processor,
+    /// enabling [`GraphProcessor::with_change_detection`].
+    ///
+    /// Subsequent [`GraphProcessor::try_next`] calls only yield nodes
+    /// downstream of the changed inputs, as their input values change,
+    /// while all other ports retain their values from previous processing.
+    pub fn update<'ext, I>(&mut self, input: I) -> Result<()>
+    where
+        I: IntoIterator<Item = (&'ext str, V)>,
+    {
+        if self.current_index.is_some() {
+            return Err(CommonGraphError::InternalError(
+                "Graph inputs may only be updated once processing is complete.".into(),
+            ));
+        }
+        self.eq = Some(V::eq);
+        for (port, value) in input {
+            self.storage.set(0, port, value, PortType::Output)?;
+        }
+        self.current_index = Some(0);
+        self.cursor = (0, 0);
+        self.iteration = 0;
+        self.progressed = false;
+        Ok(())
+    }
+}
+
+impl<'g, T, V> GraphProcessor<'g, T, V>
+where
+    T: Debug + 'g,
+    V: Clone + 'g,
+    Self: 'g,
+{
+    /// Propagates the outputs of the node at `index` to
+    /// the inputs they connect to, marking nodes whose
+    /// input values changed to be processed.
+    pub(crate) fn propagate(&mut self, index: usize) -> Result<()> {
+        let graph = self.graph;
+        let node = graph.get_node(index)?;
+        let cyclic = graph.in_same_cycle(index, index);
+
+        for (port_name, outgoing) in node.outputs().iter() {
+            let Some(outgoing) = outgoing else {
+                continue;
+            };
+
+            let value = match self.storage.get(index, port_name, PortType::Output) {
+                Ok(Some(value)) => value.to_owned(),
+                // Nodes within a cycle may not have output
+                // until values have been fed back to them.
+                Ok(None) if cyclic => continue,
+                Ok(None) => {
+                    return Err(CommonGraphError::Unexpected(
+                        "Expected output port to have value.".into(),
+                    ))
+                }
+                Err(e) => return Err(e),
+            };
+
+            for (outgoing_index, outgoing_port) in outgoing {
+                let outgoing_index = outgoing_index.to_owned();
+                let target = graph.get_node(outgoing_index)?;
+                let value = match target.merge_strategy(outgoing_port) {
+                    Some(strategy) => {
+                        let source = (index, port_name.to_owned());
+                        match self.merge_into(target, outgoing_port, source, &value, strategy)? {
+                            Some(value) => value,
+                            // Wait for the remaining incoming connections.
+                            None => continue,
+                        }
+                    }
+                    None => value.to_owned(),
+                };
+                let previous = self
+                    .storage
+                    .get(outgoing_index, outgoing_port, PortType::Input)?;
+                if Self::unchanged(self.eq, previous, &value) {
+                    continue;
+                }
+                self.storage.set(
+                    outgoing_index,
+                    outgoing_port,
+                    value.to_owned(),
+                    PortType::Input,
+                )?;
+                self.dirty[outgoing_index] = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// Records `value` written from `source` to the input port
+    /// `port_name` of `target`, returning the port's merged value
+    /// if it changed, once every incoming connection, apart from
+    /// those fed back from within a cycle, has a value.
+    fn merge_into(
+        &mut self,
+        target: &'g Node<T>,
+        port_name: &'g str,
+        source: PortRef,
+        value: &V,
+        strategy: MergeStrategy,
+    ) -> Result<Option<V>> {
+        let sources = target
+            .inputs()
+            .iter()
+            .find(|(name, _)| name == port_name)
+            .and_then(|(_, sources)| sources.as_ref())
+            .ok_or_else(|| CommonGraphError::Unexpected("Expected input port sources.".into()))?;
+        let slot = sources
+            .iter()
+            .position(|port_ref| *port_ref == source)
+            .ok_or_else(|| CommonGraphError::Unexpected("Expected input port source.".into()))?;
+        let optional: Vec<bool> = sources
+            .iter()
+            .map(|(source_index, _)| self.graph.in_same_cycle(target.index(), *source_index))
+            .collect();
+
+        let eq = self.eq;
+        let fan_in = self
+            .fan_in
+            .entry((target.index(), port_name))
+            .or_insert_with(|| FanIn {
+                values: vec![None; sources.len()],
+                last: slot,
+            });
+        if Self::unchanged(eq, fan_in.values[slot].as_ref(), value) {
+            return Ok(None);
+        }
+        fan_in.values[slot] = Some(value.to_owned());
+        fan_in.last = slot;
+
+        if fan_in
+            .values
+            .iter()
+            .zip(optional)
+            .any(|(value, optional)| value.is_none() && !optional)
+        {
+            return Ok(None);
+        }
+
+        let mut values: Vec<V> = fan_in.values.iter().flatten().cloned().collect();
+        match (&self.merge, strategy) {
+            (Some(merge), strategy) => {
+                if strategy == MergeStrategy::LastWriterWins {
+                    let last = fan_in.values[..fan_in.last].iter().flatten().count();
+                    let value = values.remove(last);
+                    values.push(value);
+                }
+                Ok(Some(merge(strategy, values)))
+            }
+            (None, MergeStrategy::LastWriterWins) => Ok(fan_in.values[fan_in.last].clone()),
+            (None, _) => Err(CommonGraphError::MissingMerge(
+                PortDetails {
+                    node: target.label().into(),
+                    port: port_name.into(),
+                    port_type: PortType::Input,
+                }
+                .into(),
+            )),
+        }
+    }
 }
 
 /// The lending iterator type trait implemented by [`GraphProcessor`].
@@ -160,52 +503,17 @@ where
             // We are done iterating.
             return Ok(None);
         };
-        let previous_node = self.graph.get_node(previous_index)?;
-
-        // Pump outgoing data to inputs
-        for (port_name, outgoing) in previous_node.outputs().iter() {
-            let Some(outgoing) = outgoing else {
-                continue;
-            };
-
-            let value = match self
-                .storage
-                .get(previous_index, port_name, PortType::Output)
-            {
-                Ok(Some(value)) => Ok(value.to_owned()),
-                Ok(None) => Err(CommonGraphError::Unexpected(
-                    "Expected output port to have value.".into(),
-                )),
-                Err(e) => Err(e),
-            }?;
-
-            for (outgoing_index, outgoing_port) in outgoing {
-                let outgoing_index = outgoing_index.to_owned();
-                self.storage.set(
-                    outgoing_index,
-                    outgoing_port,
-                    value.to_owned(),
-                    PortType::Input,
-                )?;
-
-                // If this node's output provides the final input
-                // needed to the outgoing node, queue it up.
-                // Do not queue up the root again once its inputs
-                // are full -- the graph has been fully processed.
-                if outgoing_index != 0 && self.storage.is_full(outgoing_index, PortType::Input) {
-                    self.queue.push_back(outgoing_index);
-                }
-            }
-        }
+        self.propagate(previous_index)?;
 
-        // Queue up the next node to process.
-        let Some(next_index) = self.queue.pop_front() else {
-            // No more nodes to queue -- collect output.
+        // Find the next node to process.
+        let Some(next_index) = self.next_index()? else {
+            // No more nodes to process -- collect output.
             self.current_index = None;
             return Ok(None);
         };
 
         self.current_index = Some(next_index);
+        self.dirty[next_index] = false;
         let next_node = self.graph.get_node(next_index)?;
 
         if next_node.is_root() {
@@ -214,12 +522,6 @@ where
             ));
         }
 
-        if !self.storage.is_full(next_index, PortType::Input) {
-            return Err(CommonGraphError::Unexpected(
-                "Node queued without full inputs.".into(),
-            ));
-        }
-
         let (inputs, outputs) = self.storage.get_io_mut(next_index)?;
 
         Ok(Some(GraphProcessorItem::new(next_node, inputs, outputs)))
@@ -239,7 +541,7 @@ mod tests {
     use super::*;
     use crate::{
         helpers::{Op, OpNum},
-        GraphBuilder,
+        GraphBuilder, MergeStrategy, PortDetails, PortType,
     };
     use common_tracing::common_tracing;
 
@@ -376,6 +678,205 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[common_tracing]
+    fn it_processes_cycles_until_fixpoint() -> Result<()> {
+        // Feeds the minimum seen so far back into itself.
+        let graph = GraphBuilder::default()
+            .set_graph_input(["x"])
+            .set_graph_output(["out"])
+            .node("lowest", Op::Min, ["x", "loop"], ["out"])
+            .node("echo", Op::Max, ["in"], ["out"])
+            .connect_input("x", ("lowest", "x"))?
+            .connect(("lowest", "out"), ("echo", "in"))?
+            .connect(("echo", "out"), ("lowest", "loop"))?
+            .connect_output(("lowest", "out"), "out")?
+            .allow_cycles()
+            .build()?;
+
+        let mut processor = graph.process_iter([("x", 3.0)])?.with_change_detection();
+        let mut yielded = vec![];
+        while let Some(mut item) = processor.try_next()? {
+            yielded.push(item.node().label().to_owned());
+            Op::run(&mut item)?;
+        }
+        assert_eq!(yielded, vec!["lowest", "echo", "lowest"]);
+        let output = processor.output().unwrap();
+        assert_eq!(output.inner()[0].0, vec![("out", Some(3.0))]);
+
+        // Without comparing values, the cycle cannot settle.
+        assert_eq!(
+            graph.process([("x", 3.0)], Op::run).map(|_| ()),
+            Err(CommonGraphError::ChangesNotDetected("lowest".into()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[common_tracing]
+    fn it_fails_when_cycles_do_not_converge() -> Result<()> {
+        // Increments a value forever.
+        let graph = GraphBuilder::default()
+            .set_graph_input(["x"])
+            .set_graph_output(["out"])
+            .node("max", Op::Max, ["x", "loop"], ["out"])
+            .node("one", Op::Identity(1.0), Vec::<String>::new(), ["out"])
+            .node("increment", Op::Add, ["in", "one"], ["out"])
+            .connect_input("x", ("max", "x"))?
+            .connect(("max", "out"), ("increment", "in"))?
+            .connect(("one", "out"), ("increment", "one"))?
+            .connect(("increment", "out"), ("max", "loop"))?
+            .connect_output(("max", "out"), "out")?
+            .allow_cycles()
+            .build()?;
+
+        let mut processor = graph
+            .process_iter([("x", 0.0)])?
+            .with_change_detection()
+            .with_max_iterations(10);
+        let result = loop {
+            match processor.try_next() {
+                Ok(Some(mut item)) => Op::run(&mut item)?,
+                Ok(None) => break Ok(()),
+                Err(e) => break Err(e),
+            }
+        };
+        assert_eq!(
+            result,
+            Err(CommonGraphError::FixpointNotReached("max".into(), 10))
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[common_tracing]
+    fn it_processes_changed_inputs_incrementally() -> Result<()> {
+        // ax + b
+        let graph = GraphBuilder::default()
+            .set_graph_input(["a", "b"])
+            .set_graph_output(["out"])
+            .node(
+                "identity",
+                Op::Identity(-1.0),
+                Vec::<String>::new(),
+                ["out"],
+            )
+            .node("flip", Op::Multiply, ["x", "y"], ["out"])
+            .node("divide", Op::Divide, ["x", "y"], ["out"])
+            .connect_input("a", ("divide", "y"))?
+            .connect_input("b", ("flip", "x"))?
+            .connect(("identity", "out"), ("flip", "y"))?
+            .connect(("flip", "out"), ("divide", "x"))?
+            .connect_output(("divide", "out"), "out")?
+            .build()?;
+
+        fn drain(processor: &mut GraphProcessor<'_, Op, OpNum>) -> Result<Vec<String>> {
+            let mut yielded = vec![];
+            while let Some(mut item) = processor.try_next()? {
+                yielded.push(item.node().label().to_owned());
+                Op::run(&mut item)?;
+            }
+            Ok(yielded)
+        }
+
+        let mut processor = graph.process_iter([("a", 10.0), ("b", -5.0)])?;
+        assert_eq!(drain(&mut processor)?, vec!["identity", "flip", "divide"]);
+        assert_eq!(
+            processor.snapshot().unwrap().inner()[0].0,
+            vec![("out", Some(0.5))]
+        );
+
+        // Only nodes downstream of `b` are processed again.
+        processor.update([("b", 5.0)])?;
+        assert_eq!(drain(&mut processor)?, vec!["flip", "divide"]);
+        assert_eq!(
+            processor.snapshot().unwrap().inner()[0].0,
+            vec![("out", Some(-0.5))]
+        );
+
+        // Unchanged values do not propagate.
+        processor.update([("b", 5.0)])?;
+        assert!(drain(&mut processor)?.is_empty());
+
+        processor.update([("a", -2.0)])?;
+        assert_eq!(drain(&mut processor)?, vec!["divide"]);
+        let output = processor.output().unwrap();
+        assert_eq!(output.inner()[0].0, vec![("out", Some(2.5))]);
+        Ok(())
+    }
+
+    #[test]
+    #[common_tracing]
+    fn it_merges_fan_in_ports() -> Result<()> {
+        let builder = GraphBuilder::default()
+            .set_graph_input(["a", "b", "c"])
+            .set_graph_output(["out"])
+            .node("merged", Op::Max, ["in"], ["out"])
+            .connect_input("a", ("merged", "in"))?
+            .connect_input("b", ("merged", "in"))?
+            .connect_input("c", ("merged", "in"))?
+            .connect_output(("merged", "out"), "out")?;
+        let input = [("a", 1.0), ("b", 2.0), ("c", -3.0)];
+
+        fn process(mut processor: GraphProcessor<'_, Op, OpNum>) -> Result<Option<OpNum>> {
+            while let Some(mut item) = processor.try_next()? {
+                Op::run(&mut item)?;
+            }
+            Ok(processor.output().unwrap().inner()[0].0[0].1)
+        }
+        let sum = |strategy: MergeStrategy, values: Vec<OpNum>| match strategy {
+            MergeStrategy::Reduce => values.into_iter().sum(),
+            MergeStrategy::Collect => values.len() as OpNum,
+            MergeStrategy::LastWriterWins => *values.last().unwrap(),
+        };
+
+        let graph = builder
+            .clone()
+            .set_merge_strategy(("merged", "in"), MergeStrategy::Reduce)?
+            .build()?;
+        assert_eq!(
+            process(graph.process_iter(input)?.with_merge(sum))?,
+            Some(0.0)
+        );
+        assert_eq!(
+            process(graph.process_iter(input)?),
+            Err(CommonGraphError::MissingMerge(
+                PortDetails {
+                    node: "merged".into(),
+                    port: "in".into(),
+                    port_type: PortType::Input,
+                }
+                .into()
+            ))
+        );
+
+        let graph = builder
+            .clone()
+            .set_merge_strategy(("merged", "in"), MergeStrategy::Collect)?
+            .build()?;
+        assert_eq!(
+            process(graph.process_iter(input)?.with_merge(sum))?,
+            Some(3.0)
+        );
+
+        // The most recently written value wins,
+        // even without a merge function.
+        let graph = builder
+            .set_merge_strategy(("merged", "in"), MergeStrategy::LastWriterWins)?
+            .build()?;
+        let mut processor = graph.process_iter(input)?;
+        while let Some(mut item) = processor.try_next()? {
+            Op::run(&mut item)?;
+        }
+        assert_eq!(processor.snapshot().unwrap().inner()[0].0[0].1, Some(-3.0));
+        processor.update([("a", 5.0)])?;
+        while let Some(mut item) = processor.try_next()? {
+            Op::run(&mut item)?;
+        }
+        assert_eq!(processor.snapshot().unwrap().inner()[0].0[0].1, Some(5.0));
+        Ok(())
+    }
+
     #[common_tracing]
     #[cfg(not(target_arch = "wasm32"))]
     #[tokio::test]
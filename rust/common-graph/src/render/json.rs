@@ -0,0 +1,223 @@
+use super::{RenderOpts, RenderableValue};
+use crate::{Graph, PortDescription, PortRef, Result};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Debug, io::Write};
+
+/// A port of a [`JsonNode`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JsonPort {
+    /// Name of the port.
+    pub name: String,
+    /// The rendered value of the port, if
+    /// graph data was provided and the port is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// The ports this port is connected to: sources
+    /// for input ports, and targets for output ports.
+    #[serde(default)]
+    pub connections: Vec<PortDescription>,
+}
+
+/// A node of a [`JsonGraph`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JsonNode {
+    /// Index of the node in its graph.
+    pub index: usize,
+    /// Label of the node.
+    pub label: String,
+    /// Whether this is the root node, representing graph input/output.
+    pub root: bool,
+    /// The node's input ports.
+    pub inputs: Vec<JsonPort>,
+    /// The node's output ports.
+    pub outputs: Vec<JsonPort>,
+}
+
+/// A structured dump of a [`Graph`], written by [`render_json`].
+///
+/// Unlike [`GraphDescription`](crate::GraphDescription), it lists
+/// connections from both ends, and rendered port values, for
+/// consumption by tooling rather than for loading graphs.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JsonGraph {
+    /// Graph name to display when rendering.
+    pub name: String,
+    /// Whether any nodes in the graph form a cycle.
+    pub cyclic: bool,
+    /// The graph's nodes, starting with its root node.
+    pub nodes: Vec<JsonNode>,
+}
+
+impl JsonGraph {
+    /// Returns the [`JsonGraph`] of `graph`, with `options`'
+    /// `graph_name` and `graph_data`.
+    pub fn new<T, V>(graph: &Graph<T>, options: &RenderOpts<V>) -> Result<Self>
+    where
+        T: Debug,
+        V: RenderableValue + Clone,
+    {
+        let data = options.graph_data.as_ref().map(|data| data.inner());
+        let mut nodes = vec![];
+        for (index, node) in graph.nodes().iter().enumerate() {
+            let (input_data, output_data) = match data.and_then(|data| data.get(index)) {
+                Some((inputs, outputs)) => (Some(inputs), Some(outputs)),
+                None => (None, None),
+            };
+            nodes.push(JsonNode {
+                index,
+                label: node.label().to_owned(),
+                root: node.is_root(),
+                inputs: json_ports(graph, node.inputs(), input_data)?,
+                outputs: json_ports(graph, node.outputs(), output_data)?,
+            });
+        }
+        Ok(JsonGraph {
+            name: options.graph_name.clone(),
+            cyclic: graph.is_cyclic(),
+            nodes,
+        })
+    }
+}
+
+/// Render [Graph] as JSON to provided writer
+/// with default rendering options.
+/// See [render_json_with_options] for more.
+pub fn render_json<T: Debug, W: Write>(graph: &Graph<T>, w: W) -> Result<()> {
+    render_json_with_options::<_, _, ()>(graph, w, &RenderOpts::default())
+}
+
+/// Writes the [`JsonGraph`] of the provided [Graph] into the provided writer.
+///
+/// Of [RenderOpts], only `graph_name` and `graph_data` apply.
+pub fn render_json_with_options<T, W, V>(
+    graph: &Graph<T>,
+    w: W,
+    options: &RenderOpts<V>,
+) -> Result<()>
+where
+    T: Debug,
+    W: Write,
+    V: RenderableValue + Clone,
+{
+    serde_json::to_writer_pretty(w, &JsonGraph::new(graph, options)?)?;
+    Ok(())
+}
+
+/// Returns `ports` with their connections, and their
+/// rendered values from `data`, if any.
+fn json_ports<T, V>(
+    graph: &Graph<T>,
+    ports: &[(String, Option<Vec<PortRef>>)],
+    data: Option<&Vec<(String, Option<V>)>>,
+) -> Result<Vec<JsonPort>>
+where
+    T: Debug,
+    V: RenderableValue,
+{
+    let mut json_ports = vec![];
+    for (name, connections) in ports {
+        let value = data
+            .and_then(|data| data.iter().find(|(key, _)| key == name))
+            .and_then(|(_, value)| value.as_ref())
+            .map(|value| value.render_value());
+        let connections = connections
+            .iter()
+            .flatten()
+            .map(|(index, port)| {
+                let node = graph.get_node(*index)?;
+                Ok(PortDescription {
+                    node: (!node.is_root()).then(|| node.label().to_owned()),
+                    port: port.to_owned(),
+                })
+            })
+            .collect::<Result<_>>()?;
+        json_ports.push(JsonPort {
+            name: name.to_owned(),
+            value,
+            connections,
+        });
+    }
+    Ok(json_ports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GraphBuilder, OwnedGraphData, RenderOptsBuilder};
+
+    #[derive(Clone)]
+    struct Count(u8);
+
+    impl RenderableValue for Count {
+        fn render_value(&self) -> String {
+            format!("{} items", self.0)
+        }
+    }
+
+    #[test]
+    fn it_renders_to_json() -> Result<()> {
+        let graph = GraphBuilder::default()
+            .set_graph_input(vec!["todos"])
+            .set_graph_output(vec!["out"])
+            .node("Count", (), vec!["in"], vec!["out"])
+            .connect_input("todos", ("Count", "in"))?
+            .connect_output(("Count", "out"), "out")?
+            .build()?;
+
+        let graph_data = OwnedGraphData::from(vec![
+            (
+                vec![(String::from("out"), None)],
+                vec![(String::from("todos"), Some(Count(3)))],
+            ),
+            (
+                vec![(String::from("in"), Some(Count(3)))],
+                vec![(String::from("out"), None)],
+            ),
+        ]);
+        let mut out = std::io::Cursor::new(vec![]);
+        RenderOptsBuilder::default()
+            .graph_name("Todos")
+            .graph_data(graph_data)
+            .render_json(&graph, &mut out)?;
+        let json: serde_json::Value = serde_json::from_slice(&out.into_inner())?;
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "name": "Todos",
+                "cyclic": false,
+                "nodes": [
+                    {
+                        "index": 0,
+                        "label": "Root",
+                        "root": true,
+                        "inputs": [{
+                            "name": "out",
+                            "connections": [{ "node": "Count", "port": "out" }]
+                        }],
+                        "outputs": [{
+                            "name": "todos",
+                            "value": "3 items",
+                            "connections": [{ "node": "Count", "port": "in" }]
+                        }]
+                    },
+                    {
+                        "index": 1,
+                        "label": "Count",
+                        "root": false,
+                        "inputs": [{
+                            "name": "in",
+                            "value": "3 items",
+                            "connections": [{ "port": "todos" }]
+                        }],
+                        "outputs": [{
+                            "name": "out",
+                            "connections": [{ "port": "out" }]
+                        }]
+                    }
+                ]
+            })
+        );
+        Ok(())
+    }
+}
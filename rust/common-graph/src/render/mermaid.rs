@@ -0,0 +1,150 @@
+use super::{get_render_data, slugify_port, slugify_str, RenderOpts, RenderableValue};
+use crate::{Graph, PortType, Result};
+use std::{fmt::Debug, io::Write};
+
+/// Render [Graph] as a Mermaid flowchart to provided writer
+/// with default rendering options.
+/// See [render_mermaid_with_options] for more.
+pub fn render_mermaid<T: Debug, W: Write>(graph: &Graph<T>, w: W) -> Result<()> {
+    render_mermaid_with_options::<_, _, ()>(graph, w, &RenderOpts::default())
+}
+
+/// Generates output as a [Mermaid] flowchart.
+///
+/// Traverses the provided [Graph] and writes a flowchart into the
+/// provided writer, with each node as a subgraph containing its ports.
+/// Unlike DOT, flowcharts can be embedded in Markdown documents
+/// in a `mermaid` code block, and rendered without Graphviz.
+///
+/// Of [RenderOpts], only `graph_name` and `graph_data` apply.
+///
+/// [Mermaid]: https://mermaid.js.org/syntax/flowchart.html
+pub fn render_mermaid_with_options<T, W, V>(
+    graph: &Graph<T>,
+    mut w: W,
+    options: &RenderOpts<V>,
+) -> Result<()>
+where
+    T: Debug,
+    W: Write,
+    V: RenderableValue + Clone,
+{
+    if !options.graph_name.is_empty() {
+        writeln!(w, "---\ntitle: {}\n---", escape_label(&options.graph_name))?;
+    }
+    writeln!(w, "flowchart TB")?;
+
+    for (index, node) in graph.nodes().iter().enumerate() {
+        // Node IDs may not collide with port IDs, nor with keywords
+        // such as `end`, so are suffixed with a port-like separator.
+        writeln!(
+            w,
+            r#"  subgraph {}__node ["{}"]"#,
+            slugify_str(node.label())?,
+            escape_label(node.label())
+        )?;
+        writeln!(w, "    direction TB")?;
+        for (port_type, ports) in [
+            (PortType::Input, node.inputs()),
+            (PortType::Output, node.outputs()),
+        ] {
+            for (port, _) in ports {
+                let label = match get_render_data(index, port, options, &port_type) {
+                    Some(render_data) => format!("{} ({})", port, render_data),
+                    None => port.to_owned(),
+                };
+                writeln!(
+                    w,
+                    r#"    {}["{}"]"#,
+                    slugify_port(node.label(), port, &port_type)?,
+                    escape_label(&label)
+                )?;
+            }
+        }
+        writeln!(w, "  end")?;
+    }
+
+    for node in graph.nodes() {
+        for (port, targets) in node.outputs() {
+            for (target_index, target_port) in targets.iter().flatten() {
+                let target = graph.get_node(*target_index)?;
+                writeln!(
+                    w,
+                    "  {} --> {}",
+                    slugify_port(node.label(), port, &PortType::Output)?,
+                    slugify_port(target.label(), target_port, &PortType::Input)?
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Escapes characters that would end or be interpreted
+/// within a quoted Mermaid label, as entity codes.
+fn escape_label(label: &str) -> String {
+    let mut out = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '"' => out.push_str("#quot;"),
+            '<' => out.push_str("#lt;"),
+            '>' => out.push_str("#gt;"),
+            '#' => out.push_str("#35;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GraphBuilder, OwnedGraphData, RenderOptsBuilder};
+
+    #[derive(Clone)]
+    struct Count(u8);
+
+    impl RenderableValue for Count {
+        fn render_value(&self) -> String {
+            format!("{} items", self.0)
+        }
+    }
+
+    #[test]
+    fn it_renders_to_mermaid_format() -> Result<()> {
+        let graph = GraphBuilder::default()
+            .set_graph_input(vec!["$todos"])
+            .set_graph_output(vec!["out"])
+            .node("end", (), vec!["in"], vec!["out"])
+            .connect_input("$todos", ("end", "in"))?
+            .connect_output(("end", "out"), "out")?
+            .build()?;
+
+        let graph_data = OwnedGraphData::from(vec![
+            (
+                vec![(String::from("out"), None)],
+                vec![(String::from("$todos"), Some(Count(3)))],
+            ),
+            (
+                vec![(String::from("in"), Some(Count(3)))],
+                vec![(String::from("out"), None)],
+            ),
+        ]);
+        let mut out = std::io::Cursor::new(vec![]);
+        RenderOptsBuilder::default()
+            .graph_name(r#"The "todo" list"#)
+            .graph_data(graph_data)
+            .render_mermaid(&graph, &mut out)?;
+        let mermaid = String::from_utf8(out.into_inner())?;
+
+        assert!(mermaid.starts_with("---\ntitle: The #quot;todo#quot; list\n---\nflowchart TB\n"));
+        assert!(mermaid.contains(r#"  subgraph end__node ["end"]"#));
+        // "$todos" => "B36todos"
+        assert!(mermaid.contains(r#"    Root__output__B36todos["$todos (3 items)"]"#));
+        assert!(mermaid.contains(r#"    end__output__out["out (None)"]"#));
+        assert!(mermaid.contains("  Root__output__B36todos --> end__input__in"));
+        assert!(mermaid.contains("  end__output__out --> Root__input__out"));
+        Ok(())
+    }
+}
@@ -1,6 +1,14 @@
-use crate::{storage::OwnedGraphData, CommonGraphError, Graph, Node, PortType, Result};
+use crate::{storage::OwnedGraphData, CommonGraphError, Graph, Nested, Node, PortType, Result};
 use std::{fmt::Debug, io::Write};
 
+#[cfg(feature = "serde")]
+mod json;
+mod mermaid;
+
+#[cfg(feature = "serde")]
+pub use json::*;
+pub use mermaid::*;
+
 /// A value `V` from a [`OwnedGraphData<V>`] that
 /// can be rendered for a port.
 pub trait RenderableValue {
@@ -154,8 +162,27 @@ where
     pub fn render<T: Debug, W: Write>(self, graph: &Graph<T>, w: W) -> Result<()> {
         render_with_options(graph, w, &self.opts)
     }
+
+    /// Call [render_nested_with_options] with the [RenderOpts] from this builder.
+    pub fn render_nested<T: Debug, W: Write>(self, graph: &Graph<Nested<T>>, w: W) -> Result<()> {
+        render_nested_with_options(graph, w, &self.opts)
+    }
+
+    /// Call [render_mermaid_with_options] with the [RenderOpts] from this builder.
+    pub fn render_mermaid<T: Debug, W: Write>(self, graph: &Graph<T>, w: W) -> Result<()> {
+        render_mermaid_with_options(graph, w, &self.opts)
+    }
+
+    /// Call [render_json_with_options] with the [RenderOpts] from this builder.
+    #[cfg(feature = "serde")]
+    pub fn render_json<T: Debug, W: Write>(self, graph: &Graph<T>, w: W) -> Result<()> {
+        render_json_with_options(graph, w, &self.opts)
+    }
 }
 
+/// Returns the graph nested within a node's inner value, if any.
+type Subgraphs<T> = dyn Fn(&T) -> Option<&Graph<T>>;
+
 /// Render [Graph] as a DOT file to provided writer
 /// with default rendering options.
 /// See [render_with_options] for more.
@@ -174,10 +201,41 @@ pub fn render<T: Debug, W: Write>(graph: &Graph<T>, w: W) -> Result<()> {
 ///
 /// [Graphviz]: https://www.graphviz.org
 /// [DOT]: https://www.graphviz.org/doc/info/lang.html
-pub fn render_with_options<T, W, V>(
+pub fn render_with_options<T, W, V>(graph: &Graph<T>, w: W, options: &RenderOpts<V>) -> Result<()>
+where
+    T: Debug,
+    W: Write,
+    V: RenderableValue + Clone,
+{
+    render_document(graph, w, options, &|_| None)
+}
+
+/// Like [render_with_options], rendering each [`Nested::Subgraph`]
+/// node as a cluster containing the nodes of its graph, whose
+/// connections to the subgraph's inputs and outputs are drawn to
+/// and from the node's ports.
+///
+/// [`RenderOpts`]' `graph_data` only applies to the outermost graph.
+pub fn render_nested_with_options<T, W, V>(
+    graph: &Graph<Nested<T>>,
+    w: W,
+    options: &RenderOpts<V>,
+) -> Result<()>
+where
+    T: Debug,
+    W: Write,
+    V: RenderableValue + Clone,
+{
+    render_document(graph, w, options, &Nested::subgraph)
+}
+
+/// Renders `graph` as a DOT document, with nested graphs
+/// from `subgraphs` rendered as clusters.
+fn render_document<T, W, V>(
     graph: &Graph<T>,
     mut w: W,
     options: &RenderOpts<V>,
+    subgraphs: &Subgraphs<T>,
 ) -> Result<()>
 where
     T: Debug,
@@ -199,29 +257,56 @@ rankdir = "TB";
 "#
     )?;
 
-    for (index, node) in graph.nodes().iter().enumerate() {
-        render_node::<T, W, V>(&mut w, node, index, options)?;
-        render_outgoing_edges(&mut w, graph, node)?;
-    }
+    render_graph(&mut w, graph, None, options, subgraphs)?;
 
     writeln!(w, "}}")?;
 
     Ok(())
 }
 
+/// Renders the nodes of `graph` and their connections.
+///
+/// When `graph` is nested within the node labelled `parent`, its
+/// root node is not rendered, as the parent node's ports stand for
+/// the root node's ports, and its nodes are labelled with their path.
+fn render_graph<T, W, V>(
+    w: &mut W,
+    graph: &Graph<T>,
+    parent: Option<&str>,
+    options: &RenderOpts<V>,
+    subgraphs: &Subgraphs<T>,
+) -> Result<()>
+where
+    T: Debug,
+    W: Write,
+    V: RenderableValue + Clone,
+{
+    for (index, node) in graph.nodes().iter().enumerate() {
+        if parent.is_none() || !node.is_root() {
+            // Graph data only applies to the outermost graph.
+            let index = parent.is_none().then_some(index);
+            render_node::<T, W, V>(w, node, parent, index, options, subgraphs)?;
+        }
+        render_outgoing_edges(w, graph, node, parent)?;
+    }
+    Ok(())
+}
+
 /// Render a [`Node`] and its ports as a `subgraph`.
 fn render_node<T, W, V>(
     w: &mut W,
     node: &Node<T>,
-    index: usize,
+    parent: Option<&str>,
+    index: Option<usize>,
     options: &RenderOpts<V>,
+    subgraphs: &Subgraphs<T>,
 ) -> Result<()>
 where
     T: Debug,
     W: Write,
     V: RenderableValue + Clone,
 {
-    let node_id = node.label().to_string();
+    let node_id = node_path(parent, node.label());
     let slug_id = slugify_str(&node_id)?;
 
     writeln!(
@@ -233,15 +318,16 @@ subgraph {slug_id} {{
   color = {};
   cluster = true;
 "#,
-        if index == 0 {
+        if index == Some(0) {
             &options.root_node_background_color
         } else {
             &options.node_background_color
         }
     )?;
 
-    let input_slugs = render_ports::<T, W, V>(w, node, index, options, PortType::Input)?;
-    let output_slugs = render_ports::<T, W, V>(w, node, index, options, PortType::Output)?;
+    let input_slugs = render_ports::<T, W, V>(w, node, &node_id, index, options, PortType::Input)?;
+    let output_slugs =
+        render_ports::<T, W, V>(w, node, &node_id, index, options, PortType::Output)?;
 
     // Render invisible connections between an input
     // and an output port to create a hierarchy.
@@ -253,6 +339,10 @@ subgraph {slug_id} {{
         )?;
     }
 
+    if let Some(graph) = node.inner().and_then(subgraphs) {
+        render_graph(w, graph, Some(&node_id), options, subgraphs)?;
+    }
+
     writeln!(w, "}}")?;
 
     Ok(())
@@ -264,8 +354,8 @@ fn render_outgoing_edges<T: Debug, W: Write>(
     w: &mut W,
     graph: &Graph<T>,
     node: &Node<T>,
+    parent: Option<&str>,
 ) -> Result<()> {
-    let node_id = node.label();
     for (port, outgoing_ports) in node.outputs() {
         let Some(outgoing_ports) = outgoing_ports else {
             continue;
@@ -275,19 +365,29 @@ fn render_outgoing_edges<T: Debug, W: Write>(
             writeln!(
                 w,
                 r#""{}" -> "{}";"#,
-                slugify_port(node_id, port, &PortType::Output)?,
-                slugify_port(out_node.label(), out_port, &PortType::Input)?
+                slugify_node_port(parent, node, port, PortType::Output)?,
+                slugify_node_port(parent, out_node, out_port, PortType::Input)?
             )?;
         }
     }
     Ok(())
 }
 
+/// Returns the label of a node labelled `label`,
+/// prefixed with the path of its `parent` node, if any.
+fn node_path(parent: Option<&str>, label: &str) -> String {
+    match parent {
+        Some(parent) => format!("{parent}/{label}"),
+        None => label.to_owned(),
+    }
+}
+
 /// Renders a group of `node`'s ports as a `subgraph`.
 fn render_ports<T, W, V>(
     w: &mut W,
     node: &Node<T>,
-    index: usize,
+    node_id: &str,
+    index: Option<usize>,
     options: &RenderOpts<V>,
     port_type: PortType,
 ) -> Result<Vec<String>>
@@ -296,7 +396,6 @@ where
     W: Write,
     V: RenderableValue + Clone,
 {
-    let node_id = node.label().to_string();
     let (ports, rank) = match port_type {
         PortType::Input => (
             node.inputs()
@@ -327,7 +426,7 @@ where
 "#,
         rank,
         port_type,
-        if index == 0 {
+        if index == Some(0) {
             &options.root_port_group_background_color
         } else {
             &options.port_group_background_color
@@ -336,9 +435,11 @@ where
 
     let mut port_slugs = vec![];
     for port in ports {
-        let port_slug = slugify_port(&node_id, &port, &port_type)?;
+        let port_slug = slugify_port(node_id, &port, &port_type)?;
 
-        let label = if let Some(render_data) = get_render_data(index, &port, options, &port_type) {
+        let render_data =
+            index.and_then(|index| get_render_data(index, &port, options, &port_type));
+        let label = if let Some(render_data) = render_data {
             format!("{} ({})", port, render_data)
         } else {
             port
@@ -383,6 +484,26 @@ where
     })
 }
 
+/// Slugify a port of `node`. The root node of a graph nested
+/// within a `parent` node is represented by the parent's ports,
+/// where its outputs are the parent's inputs, and vice versa.
+fn slugify_node_port<T>(
+    parent: Option<&str>,
+    node: &Node<T>,
+    port: &str,
+    port_type: PortType,
+) -> Result<String> {
+    match (parent, port_type) {
+        (Some(parent), PortType::Input) if node.is_root() => {
+            slugify_port(parent, port, &PortType::Output)
+        }
+        (Some(parent), PortType::Output) if node.is_root() => {
+            slugify_port(parent, port, &PortType::Input)
+        }
+        (parent, port_type) => slugify_port(&node_path(parent, node.label()), port, &port_type),
+    }
+}
+
 fn slugify_port(node_id: &str, port: &str, port_type: &PortType) -> Result<String> {
     slugify_str(&format!("{}__{}__{}", node_id, port_type, port,))
 }
@@ -530,4 +651,35 @@ mod tests {
         assert!(dot.contains("subgraph Storage"));
         Ok(())
     }
+
+    #[test]
+    fn it_renders_nested_subgraphs_as_clusters() -> Result<()> {
+        let inner = GraphBuilder::default()
+            .set_graph_input(vec!["in"])
+            .set_graph_output(vec!["out"])
+            .node("Leaf", Nested::Leaf(()), vec!["in"], vec!["out"])
+            .connect_input("in", ("Leaf", "in"))?
+            .connect_output(("Leaf", "out"), "out")?
+            .build()?;
+        let graph = GraphBuilder::default()
+            .set_graph_input(vec!["in"])
+            .set_graph_output(vec!["out"])
+            .subgraph("Inner", inner)
+            .connect_input("in", ("Inner", "in"))?
+            .connect_output(("Inner", "out"), "out")?
+            .build()?;
+
+        let mut out = std::io::Cursor::new(vec![]);
+        RenderOptsBuilder::<()>::default().render_nested(&graph, &mut out)?;
+        let dot = String::from_utf8(out.into_inner())?;
+        // "Inner/Leaf" => "InnerB47Leaf"
+        let cluster = dot.find("subgraph InnerB47Leaf").unwrap();
+        assert!(dot.find("subgraph Inner ").unwrap() < cluster);
+        // The nested root node is represented by the subgraph's ports.
+        assert!(!dot.contains("InnerB47Root"));
+        assert!(dot.contains(r#""Inner__input__in" -> "InnerB47Leaf__input__in";"#));
+        assert!(dot.contains(r#""InnerB47Leaf__output__out" -> "Inner__output__out";"#));
+        assert!(dot.contains(r#""Root__output__in" -> "Inner__input__in";"#));
+        Ok(())
+    }
 }
@@ -76,6 +76,11 @@ impl<'a, V> GraphStorage<'a, V> {
         GraphStorage(store.into())
     }
 
+    /// Returns the currently stored data.
+    pub(crate) fn data(&self) -> &GraphData<'a, V> {
+        &self.0
+    }
+
     /// Sets the value of node `index`'s `port_type` port with name
     /// `port_name` to `value`.
     pub(crate) fn set(
@@ -0,0 +1,374 @@
+use crate::{
+    CommonGraphError, Edge, Graph, GraphBuilder, GraphOptions, MergeStrategy, Node, PortRef,
+    PortType, Result,
+};
+use std::fmt::Debug;
+
+/// The inner value of a [`Node`] in a [`Graph`] whose
+/// nodes may themselves be whole graphs.
+///
+/// A [`Nested::Subgraph`] node's input and output ports are the
+/// inputs and outputs of its graph, i.e. its root node's output
+/// and input ports. See [`GraphBuilder::subgraph`] and
+/// [`Graph::flatten`].
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Nested<T: Debug> {
+    /// A node containing a `T`.
+    Leaf(T),
+    /// A node containing a graph.
+    Subgraph(Graph<Nested<T>>),
+}
+
+impl<T> Nested<T>
+where
+    T: Debug,
+{
+    /// Returns the graph of a [`Nested::Subgraph`] node.
+    pub fn subgraph(&self) -> Option<&Graph<Nested<T>>> {
+        match self {
+            Nested::Leaf(_) => None,
+            Nested::Subgraph(graph) => Some(graph),
+        }
+    }
+}
+
+impl<T> GraphBuilder<Nested<T>>
+where
+    T: Debug,
+{
+    /// Add `graph` as a new node, with the graph's
+    /// inputs and outputs as the node's input and output ports.
+    pub fn subgraph<S: Into<String>>(self, id: S, graph: Graph<Nested<T>>) -> Self {
+        let root = &graph.nodes()[0];
+        let inputs = port_names(root.outputs());
+        let outputs = port_names(root.inputs());
+        self.node(id, Nested::Subgraph(graph), inputs, outputs)
+    }
+}
+
+/// Where the nodes of a [`Graph<Nested<T>>`] are placed
+/// in its flattened [`Graph<T>`].
+enum Placement<T: Debug> {
+    /// A root or leaf node, at the given index.
+    Node(usize),
+    /// A flattened subgraph, whose non-root
+    /// nodes start at the given index.
+    Subgraph(usize, Graph<T>),
+}
+
+impl<T> Placement<T>
+where
+    T: Debug,
+{
+    /// Returns the ports of the flattened graph that `port` of
+    /// the placed node stands for. The input ports of a subgraph
+    /// stand for every port connected to its root's outputs,
+    /// and its output ports for those connected to its root's inputs.
+    fn resolve(&self, label: &str, port: &str, port_type: PortType) -> Result<Vec<PortRef>> {
+        let (offset, graph) = match self {
+            Placement::Node(index) => return Ok(vec![(*index, port.to_owned())]),
+            Placement::Subgraph(offset, graph) => (offset, graph),
+        };
+        let root = &graph.nodes()[0];
+        let ports = match port_type {
+            PortType::Input => root.outputs(),
+            PortType::Output => root.inputs(),
+        };
+        ports
+            .iter()
+            .find(|(name, _)| name == port)
+            .and_then(|(_, refs)| refs.as_ref())
+            .into_iter()
+            .flatten()
+            .map(|(index, port)| match index {
+                0 => Err(CommonGraphError::GraphBuilderFailure(format!(
+                    "Subgraph '{label}' connects an input directly to an output."
+                ))),
+                index => Ok((offset + index - 1, port.to_owned())),
+            })
+            .collect()
+    }
+}
+
+impl<T> Graph<Nested<T>>
+where
+    T: Debug + Clone,
+{
+    /// Returns an equivalent [`Graph`] with every [`Nested::Subgraph`]
+    /// node replaced by the nodes of its graph, and connections to
+    /// its ports rewired to the nodes its graph connects them to.
+    ///
+    /// Nodes from a subgraph are labelled with their path,
+    /// e.g. `"Subgraph/Node"`, and merge strategies on a subgraph's
+    /// input ports apply to each input port they are connected to.
+    pub fn flatten(&self) -> Result<Graph<T>> {
+        let mut nodes = vec![];
+        let mut merges = vec![];
+        let mut placements = vec![];
+
+        for node in self.nodes() {
+            let io = (port_names(node.inputs()), port_names(node.outputs()));
+            match node.inner() {
+                None => {
+                    placements.push(Placement::Node(nodes.len()));
+                    nodes.push((None, node.label().to_owned(), io));
+                }
+                Some(Nested::Leaf(inner)) => {
+                    merges.extend(port_merges(nodes.len(), node));
+                    placements.push(Placement::Node(nodes.len()));
+                    nodes.push((Some(inner.clone()), node.label().to_owned(), io));
+                }
+                Some(Nested::Subgraph(graph)) => {
+                    let graph = graph.flatten()?;
+                    let offset = nodes.len();
+                    for inner_node in graph.nodes().iter().skip(1) {
+                        merges.extend(port_merges(nodes.len(), inner_node));
+                        nodes.push((
+                            inner_node.inner().cloned(),
+                            format!("{}/{}", node.label(), inner_node.label()),
+                            (
+                                port_names(inner_node.inputs()),
+                                port_names(inner_node.outputs()),
+                            ),
+                        ));
+                    }
+                    placements.push(Placement::Subgraph(offset, graph));
+                }
+            }
+        }
+
+        let mut edges: Vec<Edge> = vec![];
+        for (node, placement) in self.nodes().iter().zip(placements.iter()) {
+            // Connections within a subgraph.
+            if let Placement::Subgraph(offset, graph) = placement {
+                for inner_node in graph.nodes().iter().skip(1) {
+                    for (port, sources) in inner_node.inputs() {
+                        for (source_index, source_port) in sources.iter().flatten() {
+                            if *source_index != 0 {
+                                edges.push((
+                                    (offset + source_index - 1, source_port.to_owned()),
+                                    (offset + inner_node.index() - 1, port.to_owned()),
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Incoming connections, in connection order.
+            for (port, sources) in node.inputs() {
+                let targets = placement.resolve(node.label(), port, PortType::Input)?;
+                if let (Placement::Subgraph(..), Some(strategy)) =
+                    (placement, node.merge_strategy(port))
+                {
+                    merges.extend(targets.iter().map(|target| (target.to_owned(), strategy)));
+                }
+                for (source_index, source_port) in sources.iter().flatten() {
+                    let source_node = self.get_node(*source_index)?;
+                    for source in placements[*source_index].resolve(
+                        source_node.label(),
+                        source_port,
+                        PortType::Output,
+                    )? {
+                        for target in targets.iter() {
+                            edges.push((source.to_owned(), target.to_owned()));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Cycles are permitted if this graph, or any of
+        // its subgraphs, was built permitting them.
+        let allow_cycles = self.allows_cycles()
+            || placements.iter().any(|placement| match placement {
+                Placement::Subgraph(_, graph) => graph.allows_cycles(),
+                Placement::Node(_) => false,
+            });
+        Graph::with_options(
+            nodes,
+            edges,
+            GraphOptions {
+                allow_cycles,
+                merges,
+            },
+        )
+    }
+}
+
+/// Returns the names of `ports`.
+fn port_names(ports: &[(String, Option<Vec<PortRef>>)]) -> Vec<String> {
+    ports.iter().map(|(name, _)| name.to_owned()).collect()
+}
+
+/// Returns the merge strategies of `node`'s input ports,
+/// for the node placed at `index`.
+fn port_merges<T>(
+    index: usize,
+    node: &Node<T>,
+) -> impl Iterator<Item = (PortRef, MergeStrategy)> + '_ {
+    node.inputs().iter().filter_map(move |(port, _)| {
+        node.merge_strategy(port)
+            .map(|strategy| ((index, port.to_owned()), strategy))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::Op;
+    use common_tracing::common_tracing;
+
+    /// 2x + 1
+    fn build_subgraph() -> Result<Graph<Nested<Op>>> {
+        GraphBuilder::default()
+            .set_label("Affine")
+            .set_graph_input(["x"])
+            .set_graph_output(["y"])
+            .node(
+                "two",
+                Nested::Leaf(Op::Identity(2.0)),
+                Vec::<String>::new(),
+                ["out"],
+            )
+            .node(
+                "one",
+                Nested::Leaf(Op::Identity(1.0)),
+                Vec::<String>::new(),
+                ["out"],
+            )
+            .node("double", Nested::Leaf(Op::Multiply), ["x", "y"], ["out"])
+            .node("increment", Nested::Leaf(Op::Add), ["x", "y"], ["out"])
+            .connect_input("x", ("double", "x"))?
+            .connect(("two", "out"), ("double", "y"))?
+            .connect(("double", "out"), ("increment", "x"))?
+            .connect(("one", "out"), ("increment", "y"))?
+            .connect_output(("increment", "out"), "y")?
+            .build()
+    }
+
+    #[test]
+    #[common_tracing]
+    fn it_flattens_nested_subgraphs() -> Result<()> {
+        // 2(2a + 1) + 1
+        let graph = GraphBuilder::default()
+            .set_graph_input(["a"])
+            .set_graph_output(["out"])
+            .subgraph("first", build_subgraph()?)
+            .subgraph("second", build_subgraph()?)
+            .connect_input("a", ("first", "x"))?
+            .connect(("first", "y"), ("second", "x"))?
+            .connect_output(("second", "y"), "out")?
+            .build()?;
+        assert_eq!(
+            graph.nodes()[1].inputs(),
+            &[(String::from("x"), Some(vec![(0, String::from("a"))]))]
+        );
+
+        let flat = graph.flatten()?;
+        let labels: Vec<&str> = flat.nodes().iter().map(|node| node.label()).collect();
+        assert_eq!(
+            labels,
+            vec![
+                "Root",
+                "first/two",
+                "first/one",
+                "first/double",
+                "first/increment",
+                "second/two",
+                "second/one",
+                "second/double",
+                "second/increment"
+            ]
+        );
+        assert_eq!(
+            flat.nodes()[7].inputs()[0],
+            (String::from("x"), Some(vec![(4, String::from("out"))]))
+        );
+
+        let output = flat.process([("a", 3.0)], Op::run)??;
+        assert_eq!(output.inner()[0].0, vec![("out", Some(15.0))]);
+
+        // Subgraphs nest arbitrarily deep.
+        let graph = GraphBuilder::default()
+            .set_graph_input(["a"])
+            .set_graph_output(["out"])
+            .subgraph("outer", graph)
+            .connect_input("a", ("outer", "a"))?
+            .connect_output(("outer", "out"), "out")?
+            .build()?;
+        let flat = graph.flatten()?;
+        assert_eq!(flat.nodes()[1].label(), "outer/first/two");
+        let output = flat.process([("a", 3.0)], Op::run)??;
+        assert_eq!(output.inner()[0].0, vec![("out", Some(15.0))]);
+        Ok(())
+    }
+
+    #[test]
+    #[common_tracing]
+    fn it_flattens_subgraphs_permitting_cycles() -> Result<()> {
+        let build_graph = |subgraph: Graph<Nested<Op>>| -> Result<Graph<Nested<Op>>> {
+            GraphBuilder::default()
+                .set_graph_input(["a"])
+                .set_graph_output(["out"])
+                .subgraph("inner", subgraph)
+                .connect_input("a", ("inner", "x"))?
+                .connect_output(("inner", "y"), "out")?
+                .build()
+        };
+        assert!(!build_graph(build_subgraph()?)?.flatten()?.allows_cycles());
+
+        // Permitted cycles carry over, even where no nodes form one.
+        let cyclic = GraphBuilder::default()
+            .set_graph_input(["x"])
+            .set_graph_output(["y"])
+            .node("double", Nested::Leaf(Op::Multiply), ["x", "y"], ["out"])
+            .connect_input("x", ("double", "x"))?
+            .connect_input("x", ("double", "y"))?
+            .connect_output(("double", "out"), "y")?
+            .allow_cycles()
+            .build()?;
+        let flat = build_graph(cyclic)?.flatten()?;
+        assert!(flat.allows_cycles());
+        assert!(!flat.is_cyclic());
+        Ok(())
+    }
+
+    #[test]
+    #[common_tracing]
+    fn it_rejects_subgraphs_passing_inputs_through() -> Result<()> {
+        // Graph input `x` connects directly to graph output `y`.
+        let passthrough = Graph::new(
+            [
+                (
+                    None,
+                    "Passthrough",
+                    (vec!["y".into(), "z".into()], vec!["x".into()]),
+                ),
+                (
+                    Some(Nested::Leaf(Op::Identity(1.0))),
+                    "one",
+                    (vec![], vec!["out".into()]),
+                ),
+            ],
+            [
+                ((0, "x".into()), (0, "y".into())),
+                ((1, "out".into()), (0, "z".into())),
+            ],
+        )?;
+        let graph = GraphBuilder::default()
+            .set_graph_input(["a"])
+            .set_graph_output(["out"])
+            .subgraph("inner", passthrough)
+            .connect_input("a", ("inner", "x"))?
+            .connect_output(("inner", "y"), "out")?
+            .build()?;
+        assert!(graph.flatten().is_err());
+        Ok(())
+    }
+}
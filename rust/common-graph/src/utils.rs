@@ -32,3 +32,103 @@ where
     let mut set: HashSet<V> = HashSet::default();
     list.into_iter().find(|&item| !set.insert(item))
 }
+
+/// Returns the strongly connected components of the directed
+/// graph where node `n` has edges to each of `successors[n]`,
+/// in topological order. Nodes within a component are sorted.
+///
+/// Uses [Tarjan's algorithm], which finds components in
+/// reverse topological order.
+///
+/// [Tarjan's algorithm]: https://en.wikipedia.org/wiki/Tarjan%27s_strongly_connected_components_algorithm
+pub fn strongly_connected_components(successors: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    struct Tarjan<'a> {
+        successors: &'a [Vec<usize>],
+        index: Vec<Option<usize>>,
+        low_link: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        next_index: usize,
+        components: Vec<Vec<usize>>,
+    }
+
+    impl Tarjan<'_> {
+        fn visit(&mut self, node: usize) {
+            self.index[node] = Some(self.next_index);
+            self.low_link[node] = self.next_index;
+            self.next_index += 1;
+            self.stack.push(node);
+            self.on_stack[node] = true;
+
+            let successors = self.successors;
+            for &successor in &successors[node] {
+                match self.index[successor] {
+                    None => {
+                        self.visit(successor);
+                        self.low_link[node] = self.low_link[node].min(self.low_link[successor]);
+                    }
+                    Some(index) if self.on_stack[successor] => {
+                        self.low_link[node] = self.low_link[node].min(index);
+                    }
+                    _ => {}
+                }
+            }
+
+            if self.index[node] == Some(self.low_link[node]) {
+                let mut component = vec![];
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                component.sort_unstable();
+                self.components.push(component);
+            }
+        }
+    }
+
+    let len = successors.len();
+    let mut tarjan = Tarjan {
+        successors,
+        index: vec![None; len],
+        low_link: vec![0; len],
+        on_stack: vec![false; len],
+        stack: vec![],
+        next_index: 0,
+        components: vec![],
+    };
+    for node in 0..len {
+        if tarjan.index[node].is_none() {
+            tarjan.visit(node);
+        }
+    }
+    tarjan.components.reverse();
+    tarjan.components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_finds_strongly_connected_components() {
+        // 0 -> 1 <-> 2 -> 3, 3 -> 3, 4 -> 1
+        let successors = vec![vec![1], vec![2], vec![1, 3], vec![3], vec![1]];
+        let components = strongly_connected_components(&successors);
+        let position = |node| {
+            components
+                .iter()
+                .position(|component| component.contains(&node))
+                .unwrap()
+        };
+
+        assert_eq!(components.len(), 4);
+        assert!(components.contains(&vec![1, 2]));
+        assert!(components.contains(&vec![3]));
+        assert!(position(0) < position(1));
+        assert!(position(4) < position(1));
+        assert!(position(1) < position(3));
+    }
+}
@@ -0,0 +1,153 @@
+use crate::{Confidentiality, Integrity, Label, PrincipalFormula};
+use std::fmt::Display;
+
+#[cfg(doc)]
+use crate::{validate_graph, Context, Policy};
+
+/// A sanctioned way for a trusted module to lower the [`Label`]
+/// of the data it outputs, below what [`Label::constrain`]
+/// would otherwise assign.
+///
+/// Capabilities are attached to a module's [`Context`], and must
+/// be authorized by the [`Policy`] the module is validated against.
+/// Every use in [`validate_graph`] is recorded in an [`AuditTrail`].
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Capability {
+    /// Lower [`Confidentiality`] down to `to`, and drop every
+    /// secrecy clause that `privilege` satisfies.
+    Declassify {
+        /// Lowest confidentiality output may be declassified to.
+        to: Confidentiality,
+        /// Principals whose authority is exercised to declassify.
+        privilege: PrincipalFormula,
+    },
+    /// Raise [`Integrity`] up to `to`, and endorse
+    /// output on behalf of `privilege`.
+    Endorse {
+        /// Highest integrity output may be endorsed to.
+        to: Integrity,
+        /// Principals whose authority is exercised to endorse.
+        privilege: PrincipalFormula,
+    },
+}
+
+impl Capability {
+    /// Lower `label` as permitted by this capability.
+    pub fn apply(&self, label: &Label) -> Label {
+        let mut label = label.clone();
+        match self {
+            Capability::Declassify { to, privilege } => {
+                label.confidentiality = std::cmp::min(&label.confidentiality, to).clone();
+                label.secrecy = label.secrecy.without_clauses_implied_by(privilege);
+            }
+            Capability::Endorse { to, privilege } => {
+                label.integrity = std::cmp::max(&label.integrity, to).clone();
+                label.endorsement = label.endorsement.and(privilege);
+            }
+        }
+        label
+    }
+
+    /// Whether this capability permits at least
+    /// everything that `other` permits.
+    pub fn covers(&self, other: &Capability) -> bool {
+        match (self, other) {
+            (
+                Capability::Declassify { to, privilege },
+                Capability::Declassify {
+                    to: other_to,
+                    privilege: other_privilege,
+                },
+            ) => to <= other_to && privilege.implies(other_privilege),
+            (
+                Capability::Endorse { to, privilege },
+                Capability::Endorse {
+                    to: other_to,
+                    privilege: other_privilege,
+                },
+            ) => to >= other_to && privilege.implies(other_privilege),
+            _ => false,
+        }
+    }
+}
+
+impl Display for Capability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Capability::Declassify { to, privilege } => {
+                write!(f, "Declassify({to}, {privilege})")
+            }
+            Capability::Endorse { to, privilege } => write!(f, "Endorse({to}, {privilege})"),
+        }
+    }
+}
+
+/// A record of a [`Capability`] lowering a [`Label`]
+/// on the outputs of a graph node.
+#[derive(PartialEq, Clone, Debug)]
+pub struct AuditEntry {
+    /// Label of the node that used the capability.
+    pub node: String,
+    /// The capability that was used.
+    pub capability: Capability,
+    /// The label before the capability was applied.
+    pub before: Label,
+    /// The label after the capability was applied.
+    pub after: Label,
+}
+
+/// Every [`Capability`] use during a [`validate_graph`] call,
+/// in evaluation order.
+pub type AuditTrail = Vec<AuditEntry>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Confidentiality::*, Integrity::*, Result};
+
+    #[test]
+    fn it_applies_capabilities() -> Result<()> {
+        let label = Label::from((Private, Low))
+            .with_secrecy("alice & (bob | carol)".parse::<PrincipalFormula>()?);
+
+        let declassify = Capability::Declassify {
+            to: Public,
+            privilege: "alice".parse()?,
+        };
+        assert_eq!(
+            declassify.apply(&label),
+            Label::from((Public, Low)).with_secrecy("(bob | carol)".parse::<PrincipalFormula>()?)
+        );
+
+        let endorse = Capability::Endorse {
+            to: High,
+            privilege: "mail".parse()?,
+        };
+        assert_eq!(
+            endorse.apply(&label),
+            Label::from((Private, High))
+                .with_secrecy("alice & (bob | carol)".parse::<PrincipalFormula>()?)
+                .with_endorsement("mail".parse::<PrincipalFormula>()?)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn it_compares_capabilities() -> Result<()> {
+        let broad = Capability::Declassify {
+            to: Public,
+            privilege: "alice & bob".parse()?,
+        };
+        let narrow = Capability::Declassify {
+            to: Private,
+            privilege: "alice".parse()?,
+        };
+        assert!(broad.covers(&narrow));
+        assert!(!narrow.covers(&broad));
+        assert!(!broad.covers(&Capability::Endorse {
+            to: Low,
+            privilege: PrincipalFormula::truth(),
+        }));
+        Ok(())
+    }
+}
@@ -1,16 +1,146 @@
-use crate::{CommonIfcError, Result};
+use crate::{Capability, CommonIfcError, Result};
+use std::{cmp::Ordering, collections::BTreeSet};
 
 #[cfg(doc)]
 use crate::Policy;
 
 /// Environment a module is being evaluated in,
-/// ordered from least to most "private".
-#[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Debug)]
+/// partially ordered from least to most "private".
+///
+/// [`ModuleEnvironment::Server`] is the least private, and
+/// a [`ModuleEnvironment::WebBrowser`] is more private than a
+/// [`ModuleEnvironment::BrowserExtension`] running alongside it.
+/// A [`ModuleEnvironment::Enclave`] is neither more nor less
+/// private than an on-device environment.
+#[derive(Eq, PartialEq, Clone, Debug, strum::Display, strum::EnumString)]
 pub enum ModuleEnvironment {
     /// Confidential compute environment.
     Server,
     /// On a web browser client.
     WebBrowser,
+    /// On device, but in an untrusted browser extension.
+    BrowserExtension,
+    /// In a trusted execution environment on a server.
+    Enclave,
+}
+
+impl ModuleEnvironment {
+    /// Rank of this environment within its chain of
+    /// comparable environments.
+    fn rank(&self) -> usize {
+        match self {
+            ModuleEnvironment::Server => 0,
+            ModuleEnvironment::BrowserExtension | ModuleEnvironment::Enclave => 1,
+            ModuleEnvironment::WebBrowser => 2,
+        }
+    }
+}
+
+impl PartialOrd for ModuleEnvironment {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        use ModuleEnvironment::*;
+        match (self, other) {
+            (Enclave, BrowserExtension | WebBrowser) | (BrowserExtension | WebBrowser, Enclave) => {
+                None
+            }
+            _ => Some(self.rank().cmp(&other.rank())),
+        }
+    }
+}
+
+/// How strongly the integrity of a module's
+/// environment has been attested, ordered
+/// from weakest to strongest.
+#[derive(
+    Ord, PartialOrd, Eq, PartialEq, Clone, Debug, Default, strum::Display, strum::EnumString,
+)]
+pub enum Attestation {
+    /// No attestation.
+    #[default]
+    None,
+    /// Attested by the software running the module.
+    Software,
+    /// Attested by hardware, e.g. a TEE quote.
+    Hardware,
+}
+
+/// Where a module may execute, or is executing.
+///
+/// As a requirement, [`Jurisdiction::Within`] lists the regions
+/// a module may execute in; as an actual [`Context`], the regions
+/// it may be executing in. A narrower set of regions
+/// satisfies a broader one.
+#[derive(Eq, PartialEq, Clone, Debug, Default)]
+pub enum Jurisdiction {
+    /// No restriction, or an unknown location.
+    #[default]
+    Anywhere,
+    /// Within one of the named regions, e.g. `"EU"`.
+    Within(BTreeSet<String>),
+}
+
+impl Jurisdiction {
+    /// A [`Jurisdiction`] within any of `regions`.
+    pub fn within<I, S>(regions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Jurisdiction::Within(regions.into_iter().map(|region| region.into()).collect())
+    }
+}
+
+impl PartialOrd for Jurisdiction {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Jurisdiction::Anywhere, Jurisdiction::Anywhere) => Some(Ordering::Equal),
+            (Jurisdiction::Anywhere, _) => Some(Ordering::Less),
+            (_, Jurisdiction::Anywhere) => Some(Ordering::Greater),
+            (Jurisdiction::Within(ours), Jurisdiction::Within(theirs)) => {
+                if ours == theirs {
+                    Some(Ordering::Equal)
+                } else if theirs.is_subset(ours) {
+                    Some(Ordering::Less)
+                } else if ours.is_subset(theirs) {
+                    Some(Ordering::Greater)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// Whether a user is present while a module executes,
+/// ordered from least to most assurance.
+#[derive(
+    Ord, PartialOrd, Eq, PartialEq, Clone, Debug, Default, strum::Display, strum::EnumString,
+)]
+pub enum UserPresence {
+    /// No user is known to be present.
+    #[default]
+    Absent,
+    /// A user is interacting with the module.
+    Present,
+    /// A present user has been verified, e.g. by re-authenticating.
+    Verified,
+}
+
+/// A requirement dimension of a [`Context`].
+#[derive(Eq, PartialEq, Clone, Copy, Debug, strum::Display)]
+pub enum ContextDimension {
+    /// [`Context::environment`].
+    #[strum(to_string = "environment")]
+    Environment,
+    /// [`Context::attestation`].
+    #[strum(to_string = "attestation")]
+    Attestation,
+    /// [`Context::jurisdiction`].
+    #[strum(to_string = "jurisdiction")]
+    Jurisdiction,
+    /// [`Context::user_presence`].
+    #[strum(to_string = "user presence")]
+    UserPresence,
 }
 
 /// Represents an execution environment of a module.
@@ -19,18 +149,76 @@ pub enum ModuleEnvironment {
 /// the minimum level needed to execute a module,
 /// validating against the actual [`Context`] during
 /// execution.
+///
+/// Each requirement dimension has its own partial order,
+/// and the actual [`Context`] must satisfy all of them.
+///
+/// A module's [`Context`] may also carry [`Capability`]s
+/// permitting it to lower the labels of its outputs,
+/// which the [`Policy`] must authorize.
 #[derive(Debug, Clone)]
 pub struct Context {
     /// Minimum allowed module environment.
     pub environment: ModuleEnvironment,
+    /// Minimum attestation of the environment.
+    pub attestation: Attestation,
+    /// Regions the module may execute in.
+    pub jurisdiction: Jurisdiction,
+    /// Minimum assurance that a user is present.
+    pub user_presence: UserPresence,
+    /// Capabilities held by the module.
+    pub capabilities: Vec<Capability>,
 }
 
 impl Context {
+    /// Set the attestation of this [`Context`].
+    pub fn with_attestation(mut self, attestation: Attestation) -> Self {
+        self.attestation = attestation;
+        self
+    }
+
+    /// Set the jurisdiction of this [`Context`].
+    pub fn with_jurisdiction(mut self, jurisdiction: Jurisdiction) -> Self {
+        self.jurisdiction = jurisdiction;
+        self
+    }
+
+    /// Set the user presence of this [`Context`].
+    pub fn with_user_presence(mut self, user_presence: UserPresence) -> Self {
+        self.user_presence = user_presence;
+        self
+    }
+
+    /// Grant `capability` to the module running in this [`Context`].
+    pub fn with_capability(mut self, capability: Capability) -> Self {
+        self.capabilities.push(capability);
+        self
+    }
+
     /// Ensures the provided [`Context`] surpasses
     /// the threshold for all of this context's requirements.
     pub fn validate(&self, ctx: &Context) -> Result<()> {
-        if self.environment > ctx.environment {
-            return Err(CommonIfcError::InvalidEnvironment);
+        for (satisfied, dimension) in [
+            (
+                self.environment <= ctx.environment,
+                ContextDimension::Environment,
+            ),
+            (
+                self.attestation <= ctx.attestation,
+                ContextDimension::Attestation,
+            ),
+            (
+                self.jurisdiction <= ctx.jurisdiction,
+                ContextDimension::Jurisdiction,
+            ),
+            (
+                self.user_presence <= ctx.user_presence,
+                ContextDimension::UserPresence,
+            ),
+        ] {
+            if !satisfied {
+                return Err(CommonIfcError::InvalidContext(dimension));
+            }
         }
         Ok(())
     }
@@ -40,6 +228,22 @@ impl From<(ModuleEnvironment,)> for Context {
     fn from(value: (ModuleEnvironment,)) -> Self {
         Context {
             environment: value.0,
+            attestation: Attestation::default(),
+            jurisdiction: Jurisdiction::default(),
+            user_presence: UserPresence::default(),
+            capabilities: vec![],
+        }
+    }
+}
+
+impl From<(ModuleEnvironment, Attestation, Jurisdiction, UserPresence)> for Context {
+    fn from(value: (ModuleEnvironment, Attestation, Jurisdiction, UserPresence)) -> Self {
+        Context {
+            environment: value.0,
+            attestation: value.1,
+            jurisdiction: value.2,
+            user_presence: value.3,
+            capabilities: vec![],
         }
     }
 }
@@ -62,4 +266,69 @@ mod tests {
         assert!(browser.validate(&server).is_err());
         Ok(())
     }
+
+    #[test]
+    #[common_tracing]
+    fn it_partially_orders_environments() -> Result<()> {
+        let extension = Context::from((BrowserExtension,));
+        let browser = Context::from((WebBrowser,));
+        let enclave = Context::from((Enclave,));
+
+        extension.validate(&browser)?;
+        assert_eq!(
+            browser.validate(&extension),
+            Err(CommonIfcError::InvalidContext(
+                ContextDimension::Environment
+            ))
+        );
+        assert!(enclave.validate(&browser).is_err());
+        assert!(browser.validate(&enclave).is_err());
+        Context::from((Server,)).validate(&enclave)?;
+        Ok(())
+    }
+
+    #[test]
+    #[common_tracing]
+    fn it_validates_every_dimension() -> Result<()> {
+        let requirement = Context::from((
+            Server,
+            Attestation::Software,
+            Jurisdiction::within(["EU", "UK"]),
+            UserPresence::Present,
+        ));
+        let actual = Context::from((
+            Enclave,
+            Attestation::Hardware,
+            Jurisdiction::within(["EU"]),
+            UserPresence::Verified,
+        ));
+        requirement.validate(&actual)?;
+
+        for (actual, dimension) in [
+            (
+                actual.clone().with_attestation(Attestation::None),
+                ContextDimension::Attestation,
+            ),
+            (
+                actual.clone().with_jurisdiction(Jurisdiction::Anywhere),
+                ContextDimension::Jurisdiction,
+            ),
+            (
+                actual
+                    .clone()
+                    .with_jurisdiction(Jurisdiction::within(["EU", "US"])),
+                ContextDimension::Jurisdiction,
+            ),
+            (
+                actual.clone().with_user_presence(UserPresence::Absent),
+                ContextDimension::UserPresence,
+            ),
+        ] {
+            assert_eq!(
+                requirement.validate(&actual),
+                Err(CommonIfcError::InvalidContext(dimension))
+            );
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,460 @@
+//! Declarative descriptions of [`Policy`]s and graphs of
+//! [`Context`]s, loadable from JSON or TOML.
+//!
+//! A policy maps every [`Confidentiality`] and [`Integrity`]
+//! level to the [`Context`] required to handle data at that level:
+//!
+//! ```toml
+//! clearance = "alice & bob"
+//!
+//! [confidentiality]
+//! Public = { environment = "Server" }
+//! Private = { environment = "WebBrowser", jurisdiction = ["EU"] }
+//!
+//! [integrity]
+//! LowIntegrity = { environment = "Server" }
+//! HighIntegrity = { environment = "Server", attestation = "Hardware" }
+//!
+//! [[capabilities]]
+//! declassify = { to = "Public", privilege = "alice" }
+//! ```
+
+use crate::{
+    Attestation, Capability, CommonIfcError, Confidentiality, Context, Integrity, Jurisdiction,
+    Label, ModuleEnvironment, Policy, Result, UserPresence,
+};
+use common_graph::{Graph, GraphBuilder};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path, str::FromStr};
+
+/// Formats a [`PolicyDocument`] or [`GraphDocument`] may be written in.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum DocumentFormat {
+    /// JSON.
+    Json,
+    /// TOML.
+    Toml,
+}
+
+impl DocumentFormat {
+    /// Determine the format of the file at `path` from its extension.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Ok(DocumentFormat::Json),
+            Some("toml") => Ok(DocumentFormat::Toml),
+            _ => Err(CommonIfcError::InvalidDocument(format!(
+                "Unknown document format for '{}'; expected .json or .toml",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Parse `source` written in this format.
+    pub fn parse<T: DeserializeOwned>(&self, source: &str) -> Result<T> {
+        match self {
+            DocumentFormat::Json => serde_json::from_str(source)
+                .map_err(|error| CommonIfcError::InvalidDocument(error.to_string())),
+            DocumentFormat::Toml => toml::from_str(source)
+                .map_err(|error| CommonIfcError::InvalidDocument(error.to_string())),
+        }
+    }
+}
+
+/// Read and parse the document at `path`, in the format
+/// indicated by its extension.
+pub fn load_document<T, P>(path: P) -> Result<T>
+where
+    T: DeserializeOwned,
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let source = std::fs::read_to_string(path)?;
+    DocumentFormat::from_path(path)?
+        .parse(&source)
+        .map_err(|error| match error {
+            CommonIfcError::InvalidDocument(message) => {
+                CommonIfcError::InvalidDocument(format!("{}: {message}", path.display()))
+            }
+            error => error,
+        })
+}
+
+/// Declarative form of a [`Capability`].
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum CapabilityDocument {
+    /// A [`Capability::Declassify`].
+    Declassify {
+        /// A [`Confidentiality`] level.
+        to: String,
+        /// A [`PrincipalFormula`](crate::PrincipalFormula).
+        privilege: String,
+    },
+    /// A [`Capability::Endorse`].
+    Endorse {
+        /// An [`Integrity`] level.
+        to: String,
+        /// A [`PrincipalFormula`](crate::PrincipalFormula).
+        privilege: String,
+    },
+}
+
+impl CapabilityDocument {
+    /// Convert into a [`Capability`], describing errors
+    /// relative to the entry at `path`.
+    pub fn into_capability(self, path: &str) -> Result<Capability> {
+        Ok(match self {
+            CapabilityDocument::Declassify { to, privilege } => Capability::Declassify {
+                to: parse_entry(&format!("{path}.declassify.to"), &to)?,
+                privilege: parse_entry(&format!("{path}.declassify.privilege"), &privilege)?,
+            },
+            CapabilityDocument::Endorse { to, privilege } => Capability::Endorse {
+                to: parse_entry(&format!("{path}.endorse.to"), &to)?,
+                privilege: parse_entry(&format!("{path}.endorse.privilege"), &privilege)?,
+            },
+        })
+    }
+}
+
+/// Declarative form of a [`Context`].
+///
+/// Only `environment` is required; other dimensions
+/// default to their least restrictive value.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ContextDocument {
+    /// A [`ModuleEnvironment`].
+    pub environment: String,
+    /// An [`Attestation`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attestation: Option<String>,
+    /// The regions of a [`Jurisdiction::Within`], or
+    /// [`Jurisdiction::Anywhere`] if omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jurisdiction: Option<Vec<String>>,
+    /// A [`UserPresence`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_presence: Option<String>,
+    /// Capabilities held in this [`Context`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub capabilities: Vec<CapabilityDocument>,
+}
+
+impl ContextDocument {
+    /// Convert into a [`Context`], describing errors
+    /// relative to the entry at `path`.
+    pub fn into_context(self, path: &str) -> Result<Context> {
+        let environment: ModuleEnvironment =
+            parse_entry(&format!("{path}.environment"), &self.environment)?;
+        let mut context = Context::from((environment,));
+        if let Some(attestation) = self.attestation {
+            context = context.with_attestation(parse_entry::<Attestation>(
+                &format!("{path}.attestation"),
+                &attestation,
+            )?);
+        }
+        if let Some(jurisdiction) = self.jurisdiction {
+            context = context.with_jurisdiction(Jurisdiction::within(jurisdiction));
+        }
+        if let Some(user_presence) = self.user_presence {
+            context = context.with_user_presence(parse_entry::<UserPresence>(
+                &format!("{path}.user_presence"),
+                &user_presence,
+            )?);
+        }
+        for (index, capability) in self.capabilities.into_iter().enumerate() {
+            context = context.with_capability(
+                capability.into_capability(&format!("{path}.capabilities[{index}]"))?,
+            );
+        }
+        Ok(context)
+    }
+}
+
+/// Declarative form of a [`Policy`].
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyDocument {
+    /// A [`PrincipalFormula`](crate::PrincipalFormula) passed
+    /// to [`Policy::with_clearance`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clearance: Option<String>,
+    /// Map of [`Confidentiality`] levels to required [`Context`]s.
+    pub confidentiality: BTreeMap<String, ContextDocument>,
+    /// Map of [`Integrity`] levels to required [`Context`]s.
+    pub integrity: BTreeMap<String, ContextDocument>,
+    /// Capabilities authorized by the [`Policy`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub capabilities: Vec<CapabilityDocument>,
+}
+
+impl TryFrom<PolicyDocument> for Policy {
+    type Error = CommonIfcError;
+
+    fn try_from(document: PolicyDocument) -> Result<Self> {
+        let confidentiality_map = document
+            .confidentiality
+            .into_iter()
+            .map(|(level, context)| {
+                let path = format!("confidentiality.{level}");
+                Ok((
+                    parse_entry::<Confidentiality>(&path, &level)?,
+                    context.into_context(&path)?,
+                ))
+            })
+            .collect::<Result<BTreeMap<_, _>>>()?;
+        let integrity_map = document
+            .integrity
+            .into_iter()
+            .map(|(level, context)| {
+                let path = format!("integrity.{level}");
+                Ok((
+                    parse_entry::<Integrity>(&path, &level)?,
+                    context.into_context(&path)?,
+                ))
+            })
+            .collect::<Result<BTreeMap<_, _>>>()?;
+
+        let mut policy = Policy::new(confidentiality_map, integrity_map)?;
+        if let Some(clearance) = document.clearance {
+            policy = policy.with_clearance(parse_entry::<crate::PrincipalFormula>(
+                "clearance",
+                &clearance,
+            )?);
+        }
+        for (index, capability) in document.capabilities.into_iter().enumerate() {
+            policy = policy
+                .with_capability(capability.into_capability(&format!("capabilities[{index}]"))?);
+        }
+        Ok(policy)
+    }
+}
+
+impl Policy {
+    /// Load a [`Policy`] from the JSON or TOML [`PolicyDocument`] at `path`.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        load_document::<PolicyDocument, _>(path)?.try_into()
+    }
+}
+
+/// Declarative form of a [`Label`], using the same strings
+/// as `LabeledData`, e.g. `Private[alice]` and `LowIntegrity`.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct LabelDocument {
+    /// The [`Confidentiality`] and secrecy of the label.
+    pub confidentiality: String,
+    /// The [`Integrity`] and endorsement of the label.
+    pub integrity: String,
+}
+
+/// Declarative form of a node in a [`GraphDocument`].
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct NodeDocument {
+    /// Unique label of the node.
+    pub label: String,
+    /// The [`Context`] the node executes in.
+    pub context: ContextDocument,
+    /// Names of the node's input ports.
+    #[serde(default)]
+    pub inputs: Vec<String>,
+    /// Names of the node's output ports.
+    #[serde(default)]
+    pub outputs: Vec<String>,
+}
+
+/// Declarative form of an edge in a [`GraphDocument`].
+///
+/// Ports are written `node.port`, or just `port` for
+/// the inputs (as `from`) and outputs (as `to`) of the graph.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct EdgeDocument {
+    /// The source port.
+    pub from: String,
+    /// The target port.
+    pub to: String,
+}
+
+/// Declarative form of a [`Graph`] of [`Context`]s, along with the
+/// [`Label`]s of its inputs, as validated by [`validate_graph`](crate::validate_graph).
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct GraphDocument {
+    /// Label of the graph.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Graph inputs and their labels.
+    pub inputs: BTreeMap<String, LabelDocument>,
+    /// Names of the graph outputs.
+    #[serde(default)]
+    pub outputs: Vec<String>,
+    /// Nodes of the graph.
+    pub nodes: Vec<NodeDocument>,
+    /// Edges of the graph.
+    pub edges: Vec<EdgeDocument>,
+}
+
+impl GraphDocument {
+    /// Build the described [`Graph`], returning it along
+    /// with the [`Label`] of each graph input.
+    pub fn into_graph(self) -> Result<(Graph<Context>, Vec<(String, Label)>)> {
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|(name, label)| {
+                let label =
+                    Label::try_from((label.confidentiality.as_str(), label.integrity.as_str()))
+                        .map_err(|error| {
+                            CommonIfcError::InvalidGraph(format!("inputs.{name}: {error}"))
+                        })?;
+                Ok((name.to_owned(), label))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut builder = GraphBuilder::default()
+            .set_graph_input(self.inputs.keys())
+            .set_graph_output(self.outputs);
+        if let Some(label) = self.label {
+            builder = builder.set_label(label);
+        }
+
+        for (index, node) in self.nodes.into_iter().enumerate() {
+            let context = node
+                .context
+                .into_context(&format!("nodes[{index}].context"))
+                .map_err(|error| CommonIfcError::InvalidGraph(error.to_string()))?;
+            builder = builder.node(node.label, context, node.inputs, node.outputs);
+        }
+
+        for (index, edge) in self.edges.into_iter().enumerate() {
+            let in_edge = |error: common_graph::CommonGraphError| {
+                CommonIfcError::InvalidGraph(format!("edges[{index}]: {error}"))
+            };
+            builder = match (edge.from.split_once('.'), edge.to.split_once('.')) {
+                (Some(from), Some(to)) => builder.connect(from, to).map_err(in_edge)?,
+                (None, Some(to)) => builder
+                    .connect_input(edge.from.as_str(), to)
+                    .map_err(in_edge)?,
+                (Some(from), None) => builder
+                    .connect_output(from, edge.to.as_str())
+                    .map_err(in_edge)?,
+                (None, None) => {
+                    return Err(CommonIfcError::InvalidGraph(format!(
+                        "edges[{index}]: Graph inputs may not connect directly to graph outputs"
+                    )))
+                }
+            };
+        }
+
+        Ok((builder.build()?, inputs))
+    }
+
+    /// Load a [`GraphDocument`] from the JSON or TOML file at `path`.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        load_document(path)
+    }
+}
+
+/// Parse the value of the entry at `path`.
+fn parse_entry<T: FromStr>(path: &str, value: &str) -> Result<T> {
+    T::from_str(value)
+        .map_err(|_| CommonIfcError::InvalidPolicy(format!("{path}: Invalid value '{value}'")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{validate_graph, ContextDimension, PolicyViolationSource};
+
+    const POLICY: &str = r#"
+clearance = "alice"
+
+[confidentiality]
+Public = { environment = "Server" }
+Private = { environment = "WebBrowser", jurisdiction = ["EU"] }
+
+[integrity]
+LowIntegrity = { environment = "Server" }
+HighIntegrity = { environment = "Server", attestation = "Hardware" }
+"#;
+
+    const GRAPH: &str = r#"{
+  "label": "Journal",
+  "inputs": {
+    "entries": { "confidentiality": "Private[alice]", "integrity": "LowIntegrity" }
+  },
+  "outputs": ["summary"],
+  "nodes": [
+    {
+      "label": "Summarize",
+      "context": { "environment": "Server" },
+      "inputs": ["entries"],
+      "outputs": ["summary"]
+    }
+  ],
+  "edges": [
+    { "from": "entries", "to": "Summarize.entries" },
+    { "from": "Summarize.summary", "to": "summary" }
+  ]
+}"#;
+
+    #[test]
+    fn it_validates_a_graph_document_against_a_policy_document() -> Result<()> {
+        let policy: Policy = DocumentFormat::Toml
+            .parse::<PolicyDocument>(POLICY)?
+            .try_into()?;
+        let (graph, inputs) = DocumentFormat::Json
+            .parse::<GraphDocument>(GRAPH)?
+            .into_graph()?;
+
+        let Err(CommonIfcError::PolicyViolation(source)) = validate_graph(
+            &graph,
+            &policy,
+            inputs
+                .iter()
+                .map(|(name, label)| (name.as_str(), label.clone())),
+        ) else {
+            panic!("Expected policy violation.");
+        };
+        assert_eq!(
+            *source,
+            PolicyViolationSource {
+                cause: CommonIfcError::InvalidContext(ContextDimension::Environment),
+                input: String::from("entries"),
+                label_type: crate::LabelType::Confidentiality,
+                node: Some(String::from("Summarize")),
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn it_points_at_invalid_entries() -> Result<()> {
+        let document: PolicyDocument =
+            DocumentFormat::Toml.parse(&POLICY.replace("\"Hardware\"", "\"Firmware\""))?;
+        assert_eq!(
+            Policy::try_from(document).err(),
+            Some(CommonIfcError::InvalidPolicy(String::from(
+                "integrity.HighIntegrity.attestation: Invalid value 'Firmware'"
+            )))
+        );
+
+        let document: PolicyDocument =
+            DocumentFormat::Toml.parse(&POLICY.replace("Public =", "Secret ="))?;
+        assert_eq!(
+            Policy::try_from(document).err(),
+            Some(CommonIfcError::InvalidPolicy(String::from(
+                "confidentiality.Secret: Invalid value 'Secret'"
+            )))
+        );
+
+        assert!(matches!(
+            DocumentFormat::Toml.parse::<PolicyDocument>("[confidentiality]\nPublic = {}"),
+            Err(CommonIfcError::InvalidDocument(_))
+        ));
+        Ok(())
+    }
+}
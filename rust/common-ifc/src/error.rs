@@ -1,4 +1,4 @@
-use crate::LabelType;
+use crate::{Capability, ContextDimension, LabelType};
 use common_graph::CommonGraphError;
 use thiserror::Error;
 
@@ -18,6 +18,17 @@ pub struct PolicyViolationSource {
     pub node: Option<String>,
 }
 
+impl PolicyViolationSource {
+    /// The dimension of the [`Context`](crate::Context) that
+    /// failed to meet the policy, if any.
+    pub fn dimension(&self) -> Option<ContextDimension> {
+        match self.cause {
+            CommonIfcError::InvalidContext(dimension) => Some(dimension),
+            _ => None,
+        }
+    }
+}
+
 /// Errors for policy validation and other errors.
 #[derive(PartialEq, Error, Debug)]
 pub enum CommonIfcError {
@@ -28,9 +39,28 @@ pub enum CommonIfcError {
     /// The policy is malformed or illegal.
     #[error("{0}")]
     InvalidPolicy(String),
-    /// The environment is insufficient based on the policy.
-    #[error("Insufficient permission to execute in this environment")]
-    InvalidEnvironment,
+    /// A policy or graph document could not be parsed.
+    #[error("Invalid document: {0}")]
+    InvalidDocument(String),
+    /// A label or principal could not be parsed.
+    #[error("{0}")]
+    InvalidLabel(String),
+    /// A dimension of the context is insufficient based on the policy.
+    #[error("Insufficient {0} to execute in this context")]
+    InvalidContext(ContextDimension),
+    /// The principals cleared by the policy do not
+    /// satisfy the secrecy of the data.
+    #[error("Insufficient clearance to observe this data")]
+    InsufficientClearance,
+    /// A module holds a [`Capability`] that the policy
+    /// does not authorize.
+    #[error("Capability '{capability}' is not authorized by the policy")]
+    UnauthorizedCapability {
+        /// The unauthorized capability.
+        capability: Capability,
+        /// Optional source node in a graph of violation.
+        node: Option<String>,
+    },
     /// A policy violation occurred.
     /// [`CommonIfcError`] with additional graph context.
     #[error("Policy violation: {}", .0.cause)]
@@ -1,61 +1,111 @@
-use crate::{CommonIfcError, Context, Label, Policy, Result};
-use common_graph::{Graph, GraphProcessorItem, OwnedGraphData};
+use crate::{AuditEntry, AuditTrail, CommonIfcError, Context, Label, Policy, Result};
+use common_graph::{Graph, GraphProcessorItem, OwnedGraphData, Processor};
 
 /// Evaluates a [`Policy`] against given `inputs` containing
 /// [`Label`]s as it propagates through a [`Graph`].
+///
+/// Output labels of each node are lowered by the
+/// [`Capability`](crate::Capability)s in its [`Context`], each use
+/// of which is recorded in the returned [`AuditTrail`].
+///
+/// Input ports with multiple incoming connections receive the
+/// [`Label::join`] of every contributing label, regardless
+/// of their [`MergeStrategy`](common_graph::MergeStrategy).
 pub fn validate_graph<'ext, I>(
     graph: &Graph<Context>,
     policy: &Policy,
     inputs: I,
-) -> Result<OwnedGraphData<Label>>
+) -> Result<(OwnedGraphData<Label>, AuditTrail)>
 where
     I: IntoIterator<Item = (&'ext str, Label)>,
 {
-    let output = graph.process(inputs, move |item: &mut GraphProcessorItem<'_, _, _>| {
-        let context = item.node().inner().ok_or_else(|| {
-            CommonIfcError::Unexpected("Missing context in graph evaluation.".into())
-        })?;
+    let mut audit_trail = AuditTrail::new();
+    let mut processor = graph
+        .process_iter(inputs)?
+        .with_merge(|_, labels: Vec<Label>| {
+            labels
+                .iter()
+                .skip(1)
+                .fold(labels[0].clone(), |joined, label| joined.join(label))
+        });
+    while let Some(mut item) = processor.try_next()? {
+        validate_node(&mut item, policy, &mut audit_trail)?;
+    }
+    let output = processor
+        .output()
+        .ok_or_else(|| CommonIfcError::Unexpected("Graph does not have output.".into()))?;
+    Ok((output.into_owned(), audit_trail))
+}
 
-        // All inputs must be written to at this point.
-        // We may introduce "optional" inputs in the future,
-        // which could be ignored here, or change how
-        // ports are referenced in the graph, but for now, verify
-        // that claim. Collect instead of passing an iterator
-        // to support this.
-        let inputs: Vec<_> = item
-            .inputs()
-            .iter()
-            .map(|(k, v)| {
-                if let Some(label) = v {
-                    Ok((*k, label))
-                } else {
-                    Err(CommonIfcError::Unexpected(
-                        "Context missing label information.".into(),
-                    ))
-                }
-            })
-            .collect::<Result<Vec<_>>>()?;
-
-        policy
-            .validate(inputs.clone(), context)
-            .map_err(|e| match e {
-                CommonIfcError::PolicyViolation(mut inner) => {
-                    let node = item.node().label().to_string();
-                    inner.node = Some(node);
-                    inner.into()
+/// Validates the input labels of the node in `item` against `policy`,
+/// writing the constrained label to all of its outputs.
+fn validate_node<'a>(
+    item: &'a mut GraphProcessorItem<'a, Context, Label>,
+    policy: &Policy,
+    audit_trail: &mut AuditTrail,
+) -> Result<()> {
+    let context = item
+        .node()
+        .inner()
+        .ok_or_else(|| CommonIfcError::Unexpected("Missing context in graph evaluation.".into()))?;
+
+    // All inputs must be written to at this point.
+    // We may introduce "optional" inputs in the future,
+    // which could be ignored here, or change how
+    // ports are referenced in the graph, but for now, verify
+    // that claim. Collect instead of passing an iterator
+    // to support this.
+    let inputs: Vec<_> = item
+        .inputs()
+        .iter()
+        .map(|(k, v)| {
+            if let Some(label) = v {
+                Ok((*k, label))
+            } else {
+                Err(CommonIfcError::Unexpected(
+                    "Context missing label information.".into(),
+                ))
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    policy
+        .validate(inputs.clone(), context)
+        .map_err(|e| match e {
+            CommonIfcError::PolicyViolation(mut inner) => {
+                let node = item.node().label().to_string();
+                inner.node = Some(node);
+                inner.into()
+            }
+            CommonIfcError::UnauthorizedCapability { capability, .. } => {
+                CommonIfcError::UnauthorizedCapability {
+                    capability,
+                    node: Some(item.node().label().to_string()),
                 }
-                e => e,
-            })?;
+            }
+            e => e,
+        })?;
 
-        let constrained = Label::constrain(inputs.iter().map(|(_, v)| *v));
+    let mut constrained = Label::constrain(inputs.iter().map(|(_, v)| *v));
 
-        for (_, out_value) in item.outputs_mut() {
-            **out_value = Some(constrained.clone());
+    for capability in &context.capabilities {
+        let lowered = capability.apply(&constrained);
+        if lowered != constrained {
+            audit_trail.push(AuditEntry {
+                node: item.node().label().to_string(),
+                capability: capability.clone(),
+                before: constrained,
+                after: lowered.clone(),
+            });
+            constrained = lowered;
         }
+    }
 
-        Ok::<(), CommonIfcError>(())
-    })??;
-    Ok(output.into_owned())
+    for (_, out_value) in item.outputs_mut() {
+        **out_value = Some(constrained.clone());
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -63,21 +113,18 @@ mod tests {
     use std::collections::BTreeMap;
 
     use crate::{
-        error::PolicyViolationSource, validate_graph, CommonIfcError, Confidentiality, Context,
-        Integrity, Label, LabelType, ModuleEnvironment, Policy, Result,
+        error::PolicyViolationSource, validate_graph, AuditEntry, Capability, CommonIfcError,
+        Confidentiality, Context, ContextDimension, Integrity, Label, LabelType, ModuleEnvironment,
+        Policy, PrincipalFormula, Result,
     };
-    use common_graph::GraphBuilder;
+    use common_graph::{GraphBuilder, MergeStrategy};
 
     #[test]
     fn it_validates_policy_graph() -> Result<()> {
         pub use {Confidentiality::*, Integrity::*, ModuleEnvironment::*};
 
-        let srv = Context {
-            environment: ModuleEnvironment::Server,
-        };
-        let brw = Context {
-            environment: ModuleEnvironment::WebBrowser,
-        };
+        let srv = Context::from((ModuleEnvironment::Server,));
+        let brw = Context::from((ModuleEnvironment::WebBrowser,));
 
         let builder = GraphBuilder::default();
         let graph = builder
@@ -151,7 +198,7 @@ mod tests {
         assert_eq!(
             e,
             CommonIfcError::from(PolicyViolationSource {
-                cause: CommonIfcError::InvalidEnvironment,
+                cause: CommonIfcError::InvalidContext(ContextDimension::Environment),
                 input: String::from("prompt"),
                 label_type: LabelType::Confidentiality,
                 node: Some(String::from("LLM")),
@@ -159,4 +206,104 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn it_joins_labels_across_fan_in() -> Result<()> {
+        use {Confidentiality::*, Integrity::*, ModuleEnvironment::*};
+
+        let graph = GraphBuilder::default()
+            .set_label("Feed")
+            .set_graph_input(vec!["news", "messages"])
+            .set_graph_output(vec!["summary"])
+            .node(
+                "Summarize",
+                Context::from((Server,)),
+                vec!["items"],
+                vec!["summary"],
+            )
+            .connect_input("news", ("Summarize", "items"))?
+            .connect_input("messages", ("Summarize", "items"))?
+            .set_merge_strategy(("Summarize", "items"), MergeStrategy::Collect)?
+            .connect_output(("Summarize", "summary"), "summary")?
+            .build()?;
+
+        let inputs = [
+            ("news", Label::from((Public, Low))),
+            ("messages", Label::from((Private, Low))),
+        ];
+        validate_graph(&graph, &Policy::with_defaults()?, inputs.clone())?;
+
+        let strict_policy = Policy::new(
+            BTreeMap::from([(Public, (Server,).into()), (Private, (WebBrowser,).into())]),
+            BTreeMap::from([(Low, (Server,).into()), (High, (Server,).into())]),
+        )?;
+        let Err(e) = validate_graph(&graph, &strict_policy, inputs) else {
+            panic!("Expected the private input to taint the merged port.");
+        };
+        assert_eq!(
+            e,
+            CommonIfcError::from(PolicyViolationSource {
+                cause: CommonIfcError::InvalidContext(ContextDimension::Environment),
+                input: String::from("items"),
+                label_type: LabelType::Confidentiality,
+                node: Some(String::from("Summarize")),
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn it_records_capability_use() -> Result<()> {
+        use {Confidentiality::*, Integrity::*, ModuleEnvironment::*};
+
+        let declassify = Capability::Declassify {
+            to: Public,
+            privilege: "alice".parse()?,
+        };
+        let graph = GraphBuilder::default()
+            .set_label("Aggregate")
+            .set_graph_input(vec!["records"])
+            .set_graph_output(vec!["count"])
+            .node(
+                "Count",
+                Context::from((Server,)).with_capability(declassify.clone()),
+                vec!["records"],
+                vec!["count"],
+            )
+            .connect_input("records", ("Count", "records"))?
+            .connect_output(("Count", "count"), "count")?
+            .build()?;
+
+        let records =
+            Label::from((Private, High)).with_secrecy("alice".parse::<PrincipalFormula>()?);
+
+        let Err(e) = validate_graph(
+            &graph,
+            &Policy::with_defaults()?,
+            [("records", records.clone())],
+        ) else {
+            panic!("Expected unauthorized capability.");
+        };
+        assert_eq!(
+            e,
+            CommonIfcError::UnauthorizedCapability {
+                capability: declassify.clone(),
+                node: Some(String::from("Count")),
+            }
+        );
+
+        let policy = Policy::with_defaults()?.with_capability(declassify.clone());
+        let (_, audit_trail) = validate_graph(&graph, &policy, [("records", records)])?;
+        assert_eq!(
+            audit_trail,
+            vec![AuditEntry {
+                node: String::from("Count"),
+                capability: declassify,
+                before: Label::from((Private, Low))
+                    .with_secrecy("alice".parse::<PrincipalFormula>()?),
+                after: Label::from((Public, Low)),
+            }]
+        );
+        Ok(())
+    }
 }
@@ -1,7 +1,9 @@
+use crate::{CommonIfcError, PrincipalFormula, Result};
 use common_macros::Lattice;
 use std::{
     fmt::{Debug, Display},
     slice::Iter,
+    str::FromStr,
 };
 
 /// Enum representing either the [`Confidentiality`] or [`Integrity`] lattices.
@@ -27,28 +29,86 @@ pub trait Lattice: Display + Debug + Ord + PartialOrd + Eq + PartialEq + Sized {
 /// Contains the [`Confidentiality`] and
 /// [`Integrity`] describing the confidentiality
 /// and integrity of data `T` associated with data.
+///
+/// Alongside the fixed levels, a [`Label`] carries DCLabel-style
+/// [`PrincipalFormula`]s naming the user-defined principals
+/// that data is restricted to (`secrecy`) and endorsed by
+/// (`endorsement`). Both default to [`PrincipalFormula::truth`],
+/// which places no restriction and carries no endorsement.
 #[derive(PartialEq, Debug, Clone, Default)]
 pub struct Label {
     /// Confidentiality component of [`Label`].
     pub confidentiality: Confidentiality,
     /// Integrity component of [`Label`].
     pub integrity: Integrity,
+    /// Principals whose authority is required to observe the data.
+    pub secrecy: PrincipalFormula,
+    /// Principals whose authority vouches for the data.
+    pub endorsement: PrincipalFormula,
 }
 
 impl Label {
     /// Create a [`Label`] that sets its [`Confidentiality`]
     /// and [`Integrity`] to the highest confidentiality
     /// found in `input`, and the lowest integrity.
+    ///
+    /// The secrecy of the result is the conjunction of all
+    /// `input` secrecy, and it carries no endorsement.
     pub fn constrain<'a, I>(input: I) -> Self
     where
         I: IntoIterator<Item = &'a Label>,
     {
         let mut max_conf = Confidentiality::bottom();
+        let mut secrecy = PrincipalFormula::truth();
         for label in input {
-            let (conf, _) = label.into();
-            max_conf = std::cmp::max(max_conf, conf);
+            max_conf = std::cmp::max(max_conf, label.confidentiality.clone());
+            secrecy = secrecy.and(&label.secrecy);
+        }
+        Label::from((max_conf, Integrity::bottom())).with_secrecy(secrecy)
+    }
+
+    /// Set the principals whose authority is required to observe the data.
+    pub fn with_secrecy<P: Into<PrincipalFormula>>(mut self, secrecy: P) -> Self {
+        self.secrecy = secrecy.into();
+        self
+    }
+
+    /// Set the principals whose authority vouches for the data.
+    pub fn with_endorsement<P: Into<PrincipalFormula>>(mut self, endorsement: P) -> Self {
+        self.endorsement = endorsement.into();
+        self
+    }
+
+    /// Whether data labeled with this [`Label`] may flow to
+    /// a destination labeled `other`: `other` is at least as
+    /// confidential, and at most as trusted.
+    pub fn can_flow_to(&self, other: &Label) -> bool {
+        self.confidentiality <= other.confidentiality
+            && self.integrity >= other.integrity
+            && other.secrecy.implies(&self.secrecy)
+            && self.endorsement.implies(&other.endorsement)
+    }
+
+    /// The least upper bound of this [`Label`] and `other`: the
+    /// least restrictive [`Label`] both may flow to.
+    pub fn join(&self, other: &Label) -> Self {
+        Label {
+            confidentiality: std::cmp::max(&self.confidentiality, &other.confidentiality).clone(),
+            integrity: std::cmp::min(&self.integrity, &other.integrity).clone(),
+            secrecy: self.secrecy.and(&other.secrecy),
+            endorsement: self.endorsement.or(&other.endorsement),
+        }
+    }
+
+    /// The greatest lower bound of this [`Label`] and `other`: the
+    /// most restrictive [`Label`] that may flow to both.
+    pub fn meet(&self, other: &Label) -> Self {
+        Label {
+            confidentiality: std::cmp::min(&self.confidentiality, &other.confidentiality).clone(),
+            integrity: std::cmp::max(&self.integrity, &other.integrity).clone(),
+            secrecy: self.secrecy.or(&other.secrecy),
+            endorsement: self.endorsement.and(&other.endorsement),
         }
-        (max_conf, Integrity::bottom()).into()
     }
 }
 
@@ -57,10 +117,65 @@ impl From<(Confidentiality, Integrity)> for Label {
         Label {
             confidentiality: value.0,
             integrity: value.1,
+            secrecy: PrincipalFormula::truth(),
+            endorsement: PrincipalFormula::truth(),
         }
     }
 }
 
+/// Formats a level followed by its [`PrincipalFormula`], if any,
+/// e.g. `Private` or `Private[alice & (bob | carol)]`.
+fn format_component<T: Display>(level: &T, principals: &PrincipalFormula) -> String {
+    if principals.is_truth() {
+        level.to_string()
+    } else {
+        format!("{level}[{principals}]")
+    }
+}
+
+/// Parses the output of [`format_component`].
+fn parse_component<T: FromStr>(value: &str) -> Result<(T, PrincipalFormula)> {
+    let (level, principals) = match value.split_once('[') {
+        Some((level, principals)) => (
+            level,
+            principals
+                .strip_suffix(']')
+                .ok_or_else(|| CommonIfcError::InvalidLabel(format!("Invalid label '{value}'")))?
+                .parse()?,
+        ),
+        None => (value, PrincipalFormula::truth()),
+    };
+    let level = T::from_str(level)
+        .map_err(|_| CommonIfcError::InvalidLabel(format!("Invalid label level '{level}'")))?;
+    Ok((level, principals))
+}
+
+impl TryFrom<(&str, &str)> for Label {
+    type Error = CommonIfcError;
+
+    /// Parse a [`Label`] from its confidentiality and integrity
+    /// strings, as produced by converting a `&Label` into `(String, String)`.
+    fn try_from((confidentiality, integrity): (&str, &str)) -> Result<Self> {
+        let (confidentiality, secrecy) = parse_component(confidentiality)?;
+        let (integrity, endorsement) = parse_component(integrity)?;
+        Ok(Label {
+            confidentiality,
+            integrity,
+            secrecy,
+            endorsement,
+        })
+    }
+}
+
+impl From<&Label> for (String, String) {
+    fn from(value: &Label) -> (String, String) {
+        (
+            format_component(&value.confidentiality, &value.secrecy),
+            format_component(&value.integrity, &value.endorsement),
+        )
+    }
+}
+
 impl From<Label> for (Confidentiality, Integrity) {
     fn from(value: Label) -> (Confidentiality, Integrity) {
         (value.confidentiality, value.integrity)
@@ -124,7 +239,7 @@ pub enum Confidentiality {
 #[cfg(feature = "render")]
 impl common_graph::RenderableValue for Label {
     fn render_value(&self) -> String {
-        self.confidentiality.to_string()
+        format_component(&self.confidentiality, &self.secrecy)
     }
 }
 
@@ -187,4 +302,78 @@ mod tests {
             (Private, Low).into(),
         );
     }
+
+    #[test]
+    fn it_constrains_principals_from_input() -> Result<()> {
+        let alice = Label::from((Public, High))
+            .with_secrecy("alice".parse::<PrincipalFormula>()?)
+            .with_endorsement("mail".parse::<PrincipalFormula>()?);
+        let bob = Label::from((Public, High)).with_secrecy("bob".parse::<PrincipalFormula>()?);
+
+        assert_eq!(
+            Label::constrain([&alice, &bob]),
+            Label::from((Public, Low)).with_secrecy("alice & bob".parse::<PrincipalFormula>()?),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn it_joins_and_meets_labels() -> Result<()> {
+        let alice = Label::from((Public, High))
+            .with_secrecy("alice".parse::<PrincipalFormula>()?)
+            .with_endorsement("mail & calendar".parse::<PrincipalFormula>()?);
+        let bob = Label::from((Private, Low))
+            .with_secrecy("bob".parse::<PrincipalFormula>()?)
+            .with_endorsement("mail".parse::<PrincipalFormula>()?);
+
+        let join = alice.join(&bob);
+        assert_eq!(
+            join,
+            Label::from((Private, Low))
+                .with_secrecy("alice & bob".parse::<PrincipalFormula>()?)
+                .with_endorsement("mail".parse::<PrincipalFormula>()?),
+        );
+        let meet = alice.meet(&bob);
+        assert_eq!(
+            meet,
+            Label::from((Public, High))
+                .with_secrecy("(alice | bob)".parse::<PrincipalFormula>()?)
+                .with_endorsement("mail & calendar".parse::<PrincipalFormula>()?),
+        );
+
+        for label in [&alice, &bob] {
+            assert!(label.can_flow_to(&join));
+            assert!(meet.can_flow_to(label));
+        }
+        assert!(!alice.can_flow_to(&bob));
+        assert!(!bob.can_flow_to(&alice));
+        Ok(())
+    }
+
+    #[test]
+    fn it_round_trips_labels_as_strings() -> Result<()> {
+        let plain = Label::from((Private, Low));
+        assert_eq!(
+            <(String, String)>::from(&plain),
+            ("Private".into(), "LowIntegrity".into())
+        );
+
+        let principals = Label::from((Private, High))
+            .with_secrecy("alice & (bob | carol)".parse::<PrincipalFormula>()?)
+            .with_endorsement("mail".parse::<PrincipalFormula>()?);
+        let (confidentiality, integrity) = <(String, String)>::from(&principals);
+        assert_eq!(confidentiality, "Private[alice & (bob | carol)]");
+        assert_eq!(integrity, "HighIntegrity[mail]");
+
+        for label in [plain, principals] {
+            let (confidentiality, integrity) = <(String, String)>::from(&label);
+            assert_eq!(
+                Label::try_from((confidentiality.as_str(), integrity.as_str()))?,
+                label
+            );
+        }
+        assert!(Label::try_from(("Secret", "LowIntegrity")).is_err());
+        assert!(Label::try_from(("Private[alice", "LowIntegrity")).is_err());
+        Ok(())
+    }
 }
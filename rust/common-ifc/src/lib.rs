@@ -4,21 +4,44 @@
 //!
 //! Data in the system is wrapped by a [`Label`], representing
 //! its [`Confidentiality`] and [`Integrity`] levels.
+//! Labels may additionally restrict data to, or mark it
+//! as endorsed by, user-defined [`Principal`]s via a
+//! [`PrincipalFormula`].
 //! A [`Policy`] contains a map of these labels and
 //! their [`Context`] requirements, describing conditions
 //! that must be met in order to permit data flow.
+//! Trusted modules may lower labels with a [`Capability`]
+//! the [`Policy`] authorizes, with every use recorded
+//! in an [`AuditTrail`].
+//!
+//! With the `serde` feature, policies and graphs may be
+//! loaded from declarative JSON or TOML documents; see
+//! `PolicyDocument` and `GraphDocument`.
 //!
 //! <https://en.wikipedia.org/wiki/Information_flow_(information_theory)#Information_flow_control>
 
+mod capability;
 mod context;
+#[cfg(feature = "serde")]
+mod document;
 mod error;
 mod graph;
 mod labels;
 mod policy;
+mod principals;
 
+pub use capability::{AuditEntry, AuditTrail, Capability};
 pub use common_macros::Lattice;
-pub use context::{Context, ModuleEnvironment};
+pub use context::{
+    Attestation, Context, ContextDimension, Jurisdiction, ModuleEnvironment, UserPresence,
+};
+#[cfg(feature = "serde")]
+pub use document::{
+    load_document, CapabilityDocument, ContextDocument, DocumentFormat, EdgeDocument,
+    GraphDocument, LabelDocument, NodeDocument, PolicyDocument,
+};
 pub use error::{CommonIfcError, PolicyViolationSource, Result};
 pub use graph::validate_graph;
 pub use labels::{Confidentiality, Integrity, Label, LabelType, Lattice};
 pub use policy::Policy;
+pub use principals::{Principal, PrincipalFormula};
@@ -1,6 +1,6 @@
 use crate::{
-    error::PolicyViolationSource, CommonIfcError, Confidentiality, Context, Integrity, Label,
-    LabelType, Lattice, ModuleEnvironment, Result,
+    error::PolicyViolationSource, Capability, CommonIfcError, Confidentiality, Context, Integrity,
+    Label, LabelType, Lattice, ModuleEnvironment, PrincipalFormula, Result,
 };
 use std::collections::BTreeMap;
 
@@ -57,6 +57,12 @@ pub struct Policy {
     /// Map of integrity principals to the minimum
     /// required [Context] components.
     integrity_map: PolicyMap<Integrity>,
+    /// Principals the invoker acts for, if the
+    /// secrecy of input data should be enforced.
+    clearance: Option<PrincipalFormula>,
+    /// Capabilities that modules may hold to
+    /// lower the labels of their outputs.
+    capabilities: Vec<Capability>,
 }
 
 impl Policy {
@@ -72,19 +78,69 @@ impl Policy {
         Ok(Self {
             confidentiality_map,
             integrity_map,
+            clearance: None,
+            capabilities: vec![],
         })
     }
 
+    /// Require that the secrecy of all input data be satisfied
+    /// by `clearance`, the principals the invoker acts for.
+    ///
+    /// Without a clearance, the secrecy of input data is
+    /// propagated but not enforced.
+    pub fn with_clearance<P: Into<PrincipalFormula>>(mut self, clearance: P) -> Self {
+        self.clearance = Some(clearance.into());
+        self
+    }
+
+    /// Permit modules to hold `capability`, or any
+    /// [Capability] it covers.
+    pub fn with_capability(mut self, capability: Capability) -> Self {
+        self.capabilities.push(capability);
+        self
+    }
+
+    /// Ensure every [Capability] held in `ctx` is
+    /// covered by one authorized in this policy.
+    pub fn authorize(&self, ctx: &Context) -> Result<()> {
+        for capability in &ctx.capabilities {
+            if !self
+                .capabilities
+                .iter()
+                .any(|authorized| authorized.covers(capability))
+            {
+                return Err(CommonIfcError::UnauthorizedCapability {
+                    capability: capability.clone(),
+                    node: None,
+                });
+            }
+        }
+        Ok(())
+    }
+
     /// Validate input against this policy, given a [Context].
     pub fn validate<'a, I, S>(&'a self, input: I, ctx: &Context) -> Result<()>
     where
         I: IntoIterator<Item = (S, &'a Label)>,
         S: AsRef<str>,
     {
+        self.authorize(ctx)?;
+
         for (name, label) in input {
             let name = name.as_ref();
             let (conf_reqs, int_reqs) = self.get_requirements(label)?;
 
+            if let Some(clearance) = &self.clearance {
+                if !clearance.implies(&label.secrecy) {
+                    return Err(CommonIfcError::from(PolicyViolationSource {
+                        cause: CommonIfcError::InsufficientClearance,
+                        input: name.into(),
+                        label_type: LabelType::Confidentiality,
+                        node: None,
+                    }));
+                }
+            }
+
             for (reqs, label_type) in [
                 (conf_reqs, LabelType::Confidentiality),
                 (int_reqs, LabelType::Integrity),
@@ -140,7 +196,9 @@ impl Policy {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Confidentiality::*, Integrity::*, ModuleEnvironment::*};
+    use crate::{
+        Confidentiality::*, ContextDimension, Integrity::*, Jurisdiction, ModuleEnvironment::*,
+    };
     use common_tracing::common_tracing;
 
     #[test]
@@ -160,7 +218,7 @@ mod tests {
         assert_eq!(
             policy.validate(&input, &(Server,).into()),
             Err(CommonIfcError::from(PolicyViolationSource {
-                cause: CommonIfcError::InvalidEnvironment,
+                cause: CommonIfcError::InvalidContext(ContextDimension::Environment),
                 input: String::from("in"),
                 label_type: LabelType::Confidentiality,
                 node: None,
@@ -170,4 +228,85 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[common_tracing]
+    fn it_reports_the_failed_dimension() -> Result<()> {
+        let input = BTreeMap::from([(String::from("in"), (Private, High).into())]);
+
+        // Private data only within the EU
+        let eu_only = Context::from((Server,)).with_jurisdiction(Jurisdiction::within(["EU"]));
+        let policy = Policy::new(
+            BTreeMap::from([(Public, (Server,).into()), (Private, eu_only.clone())]),
+            BTreeMap::from([(Low, (Server,).into()), (High, (Server,).into())]),
+        )?;
+
+        let Err(CommonIfcError::PolicyViolation(source)) =
+            policy.validate(&input, &(WebBrowser,).into())
+        else {
+            panic!("Expected policy violation.");
+        };
+        assert_eq!(source.dimension(), Some(ContextDimension::Jurisdiction));
+        assert!(policy.validate(&input, &eu_only).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    #[common_tracing]
+    fn it_authorizes_capabilities() -> Result<()> {
+        let input = BTreeMap::from([(String::from("in"), (Private, Low).into())]);
+        let declassify = Capability::Declassify {
+            to: Public,
+            privilege: "alice".parse()?,
+        };
+        let ctx = Context::from((Server,)).with_capability(declassify.clone());
+
+        let policy = Policy::with_defaults()?;
+        assert_eq!(
+            policy.validate(&input, &ctx),
+            Err(CommonIfcError::UnauthorizedCapability {
+                capability: declassify.clone(),
+                node: None,
+            })
+        );
+
+        let policy = Policy::with_defaults()?.with_capability(Capability::Declassify {
+            to: Public,
+            privilege: "alice & bob".parse()?,
+        });
+        assert!(policy.validate(&input, &ctx).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    #[common_tracing]
+    fn it_validates_clearance() -> Result<()> {
+        let input = BTreeMap::from([(
+            String::from("in"),
+            Label::from((Private, High)).with_secrecy("alice & bob".parse::<PrincipalFormula>()?),
+        )]);
+
+        // Secrecy is not enforced without a clearance
+        let policy = Policy::with_defaults()?;
+        assert!(policy.validate(&input, &(Server,).into()).is_ok());
+
+        let policy = Policy::with_defaults()?.with_clearance("alice".parse::<PrincipalFormula>()?);
+        assert_eq!(
+            policy.validate(&input, &(Server,).into()),
+            Err(CommonIfcError::from(PolicyViolationSource {
+                cause: CommonIfcError::InsufficientClearance,
+                input: String::from("in"),
+                label_type: LabelType::Confidentiality,
+                node: None,
+            }))
+        );
+
+        let policy =
+            Policy::with_defaults()?.with_clearance("alice & bob".parse::<PrincipalFormula>()?);
+        assert!(policy.validate(&input, &(Server,).into()).is_ok());
+
+        Ok(())
+    }
 }
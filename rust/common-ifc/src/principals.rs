@@ -0,0 +1,312 @@
+use crate::{CommonIfcError, Result};
+use std::{collections::BTreeSet, fmt::Display, str::FromStr};
+
+/// A user-defined authority, such as a user, a team or a service,
+/// that data can be restricted to or endorsed by.
+#[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Debug, Hash)]
+pub struct Principal(String);
+
+impl Principal {
+    /// Returns whether `c` may appear in a [`Principal`] name.
+    fn is_valid_char(c: char) -> bool {
+        c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ':' | '@' | '/')
+    }
+}
+
+impl AsRef<str> for Principal {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for Principal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Principal {
+    type Err = CommonIfcError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s.is_empty()
+            || s == PrincipalFormula::TRUE
+            || s == PrincipalFormula::FALSE
+            || !s.chars().all(Principal::is_valid_char)
+        {
+            return Err(CommonIfcError::InvalidLabel(format!(
+                "Invalid principal '{s}'"
+            )));
+        }
+        Ok(Principal(s.to_owned()))
+    }
+}
+
+impl TryFrom<&str> for Principal {
+    type Error = CommonIfcError;
+
+    fn try_from(value: &str) -> Result<Self> {
+        value.parse()
+    }
+}
+
+/// A disjunction of [`Principal`]s: satisfied by any one of them.
+type Clause = BTreeSet<Principal>;
+
+/// A DCLabel-style formula over [`Principal`]s, in conjunctive
+/// normal form: a conjunction of disjunctions, such as
+/// `alice & (bob | carol)`.
+///
+/// Formulas are partially ordered by implication. As the secrecy
+/// component of a [`Label`](crate::Label), a formula lists whose
+/// authority is required to observe data, so a stronger formula is more
+/// secret. As the endorsement component, it lists whose authority vouches
+/// for data, so a stronger formula has more integrity.
+///
+/// [`PrincipalFormula::truth`] (no clauses) is satisfied by anyone, and
+/// [`PrincipalFormula::falsity`] (a single empty clause) by no one.
+#[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Debug, Default)]
+pub struct PrincipalFormula(BTreeSet<Clause>);
+
+impl PrincipalFormula {
+    const TRUE: &'static str = "True";
+    const FALSE: &'static str = "False";
+
+    /// The formula satisfied by anyone.
+    pub fn truth() -> Self {
+        PrincipalFormula(BTreeSet::new())
+    }
+
+    /// The formula satisfied by no one.
+    pub fn falsity() -> Self {
+        PrincipalFormula(BTreeSet::from([Clause::new()]))
+    }
+
+    /// A formula satisfied only by `principal`.
+    pub fn principal(principal: Principal) -> Self {
+        PrincipalFormula(BTreeSet::from([Clause::from([principal])]))
+    }
+
+    /// A formula satisfied by any of `principals`.
+    pub fn any<I>(principals: I) -> Self
+    where
+        I: IntoIterator<Item = Principal>,
+    {
+        PrincipalFormula::normalize([principals.into_iter().collect()])
+    }
+
+    /// A formula satisfied only by all of `principals` together.
+    pub fn all<I>(principals: I) -> Self
+    where
+        I: IntoIterator<Item = Principal>,
+    {
+        PrincipalFormula::normalize(principals.into_iter().map(|p| Clause::from([p])))
+    }
+
+    /// Whether this formula is satisfied by anyone.
+    pub fn is_truth(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether this formula is satisfied by no one.
+    pub fn is_falsity(&self) -> bool {
+        self.0.contains(&Clause::new())
+    }
+
+    /// Whether this formula logically implies `other`: every
+    /// clause of `other` is subsumed by some clause of this formula.
+    pub fn implies(&self, other: &PrincipalFormula) -> bool {
+        other
+            .0
+            .iter()
+            .all(|theirs| self.0.iter().any(|ours| ours.is_subset(theirs)))
+    }
+
+    /// The conjunction of this formula and `other`.
+    pub fn and(&self, other: &PrincipalFormula) -> Self {
+        PrincipalFormula::normalize(self.0.iter().chain(other.0.iter()).cloned())
+    }
+
+    /// The disjunction of this formula and `other`.
+    pub fn or(&self, other: &PrincipalFormula) -> Self {
+        PrincipalFormula::normalize(self.0.iter().flat_map(|ours| {
+            other
+                .0
+                .iter()
+                .map(move |theirs| ours.union(theirs).cloned().collect())
+        }))
+    }
+
+    /// This formula without the clauses that `privilege` satisfies:
+    /// the weakest formula that, together with `privilege`,
+    /// still implies this one.
+    pub fn without_clauses_implied_by(&self, privilege: &PrincipalFormula) -> Self {
+        PrincipalFormula(
+            self.0
+                .iter()
+                .filter(|clause| {
+                    !privilege.implies(&PrincipalFormula(BTreeSet::from([(*clause).clone()])))
+                })
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// Build a formula from `clauses`, dropping every clause
+    /// subsumed by another so that equivalent formulas compare equal.
+    fn normalize<I>(clauses: I) -> Self
+    where
+        I: IntoIterator<Item = Clause>,
+    {
+        let clauses: BTreeSet<Clause> = clauses.into_iter().collect();
+        if clauses.contains(&Clause::new()) {
+            return PrincipalFormula::falsity();
+        }
+        PrincipalFormula(
+            clauses
+                .iter()
+                .filter(|clause| {
+                    !clauses
+                        .iter()
+                        .any(|other| other != *clause && other.is_subset(clause))
+                })
+                .cloned()
+                .collect(),
+        )
+    }
+}
+
+impl From<Principal> for PrincipalFormula {
+    fn from(value: Principal) -> Self {
+        PrincipalFormula::principal(value)
+    }
+}
+
+impl Display for PrincipalFormula {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_truth() {
+            return write!(f, "{}", PrincipalFormula::TRUE);
+        }
+        if self.is_falsity() {
+            return write!(f, "{}", PrincipalFormula::FALSE);
+        }
+        let clauses: Vec<String> = self
+            .0
+            .iter()
+            .map(|clause| {
+                let principals: Vec<&str> = clause.iter().map(|p| p.as_ref()).collect();
+                if principals.len() == 1 {
+                    principals[0].to_owned()
+                } else {
+                    format!("({})", principals.join(" | "))
+                }
+            })
+            .collect();
+        write!(f, "{}", clauses.join(" & "))
+    }
+}
+
+impl FromStr for PrincipalFormula {
+    type Err = CommonIfcError;
+
+    /// Parses the format produced by [`Display`], e.g.
+    /// `alice & (bob | carol)`, `True` or `False`.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            PrincipalFormula::TRUE => return Ok(PrincipalFormula::truth()),
+            PrincipalFormula::FALSE => return Ok(PrincipalFormula::falsity()),
+            _ => (),
+        };
+        let clauses = s
+            .split('&')
+            .map(|clause| {
+                let clause = clause.trim();
+                let clause = clause
+                    .strip_prefix('(')
+                    .and_then(|clause| clause.strip_suffix(')'))
+                    .unwrap_or(clause);
+                clause
+                    .split('|')
+                    .map(|principal| principal.trim().parse())
+                    .collect::<Result<Clause>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(PrincipalFormula::normalize(clauses))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn p(name: &str) -> Principal {
+        name.parse().unwrap()
+    }
+
+    #[test]
+    fn it_orders_formulas_by_implication() -> Result<()> {
+        let alice = PrincipalFormula::from(p("alice"));
+        let alice_and_bob = PrincipalFormula::all([p("alice"), p("bob")]);
+        let alice_or_bob = PrincipalFormula::any([p("alice"), p("bob")]);
+
+        assert!(alice_and_bob.implies(&alice));
+        assert!(alice.implies(&alice_or_bob));
+        assert!(!alice_or_bob.implies(&alice));
+        assert!(PrincipalFormula::falsity().implies(&alice_and_bob));
+        assert!(alice.implies(&PrincipalFormula::truth()));
+        assert!(!PrincipalFormula::truth().implies(&alice));
+        Ok(())
+    }
+
+    #[test]
+    fn it_combines_formulas() -> Result<()> {
+        let alice = PrincipalFormula::from(p("alice"));
+        let bob = PrincipalFormula::from(p("bob"));
+
+        assert_eq!(
+            alice.and(&bob),
+            PrincipalFormula::all([p("alice"), p("bob")])
+        );
+        assert_eq!(
+            alice.or(&bob),
+            PrincipalFormula::any([p("alice"), p("bob")])
+        );
+        // Subsumed clauses are dropped
+        assert_eq!(alice.and(&alice.or(&bob)), alice);
+        assert_eq!(
+            alice.or(&PrincipalFormula::truth()),
+            PrincipalFormula::truth()
+        );
+        assert_eq!(
+            alice.and(&PrincipalFormula::falsity()),
+            PrincipalFormula::falsity()
+        );
+        assert_eq!(alice.or(&PrincipalFormula::falsity()), alice);
+        Ok(())
+    }
+
+    #[test]
+    fn it_round_trips_formulas_as_strings() -> Result<()> {
+        for formula in [
+            "True",
+            "False",
+            "alice",
+            "alice & bob",
+            "(bob | carol) & alice",
+            "(service:mail | team/eng@example.com)",
+        ] {
+            let parsed: PrincipalFormula = formula.parse()?;
+            assert_eq!(parsed.to_string().parse::<PrincipalFormula>()?, parsed);
+        }
+        assert_eq!(
+            "alice & (carol | bob)"
+                .parse::<PrincipalFormula>()?
+                .to_string(),
+            "alice & (bob | carol)"
+        );
+        assert!("alice & ".parse::<PrincipalFormula>().is_err());
+        assert!("alice bob".parse::<PrincipalFormula>().is_err());
+        assert!("True | alice".parse::<PrincipalFormula>().is_err());
+        Ok(())
+    }
+}
@@ -91,19 +91,17 @@ async fn it_runs_a_precompiled_module() -> Result<()> {
     let mut builder_client =
         builder::builder_client::BuilderClient::connect(builder_address_str).await?;
 
-    let builder::BuildComponentResponse { id: module_id } = builder_client
+    let builder::BuildComponentResponse {
+        component_id: module_id,
+        ..
+    } = builder_client
         .build_component(builder::BuildComponentRequest {
-            target: common::Target::CommonFunction.into(),
-            module_source: Some(common::ModuleSource {
-                source_code: [(
-                    "module".to_owned(),
-                    common::SourceCode {
-                        content_type: common::ContentType::JavaScript.into(),
-                        body: BASIC_MODULE_JS.into(),
-                    },
-                )]
-                .into(),
+            module_definition: Some(common::ModuleDefinition {
+                content_type: common::ContentType::JavaScript.into(),
+                source: BASIC_MODULE_JS.into(),
+                ..Default::default()
             }),
+            ..Default::default()
         })
         .await?
         .into_inner();
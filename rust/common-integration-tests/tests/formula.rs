@@ -82,6 +82,102 @@ export const end = (total) => [{ "Assert": {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[common_tracing]
+async fn it_streams_datoms_into_a_formula_with_retained_state() -> Result<()> {
+    let VirtualEnvironment {
+        mut runtime_client, ..
+    } = start_runtime().await?;
+
+    let source = r#"
+export const init = (input) => [0, { "ByAttribute": { attribute: "price" } }];
+
+export const step = (total, datoms) => {
+  total = datoms.reduce((total, datom) => total + datom.value, total);
+  return [total, [{ "Assert": {
+    entity: { id: "cart" },
+    attribute: "subtotal",
+    value: total,
+  }}]];
+}
+
+export const end = (total) => [{ "Assert": {
+  entity: { id: "cart" },
+  attribute: "total",
+  value: total,
+}}]
+"#;
+
+    let instance_id = instantiate(&mut runtime_client, source).await?;
+    let _ = init(&mut runtime_client, &instance_id, ScalarMap::default()).await?;
+
+    let price = |id: &str, value: i32| -> formula::Datom {
+        Datom {
+            entity: Entity { id: id.into() },
+            attribute: "price".into(),
+            value: Scalar::Integer(value),
+            cause: Entity { id: "test".into() },
+        }
+        .into()
+    };
+
+    let requests = vec![
+        formula::StreamStepFormulaRequest {
+            instance_id: instance_id.clone(),
+            state: None,
+            datoms: vec![price("apple", 1), price("banana", 2)],
+        },
+        formula::StreamStepFormulaRequest {
+            instance_id: String::new(),
+            state: None,
+            datoms: vec![price("cherry", 3)],
+        },
+    ];
+
+    let mut responses = runtime_client
+        .stream_step_formula(futures_util::stream::iter(requests))
+        .await?
+        .into_inner();
+
+    let mut subtotals = vec![];
+    while let Some(formula::StreamStepFormulaResponse { instructions }) =
+        responses.message().await?
+    {
+        for instruction in instructions {
+            subtotals.push(Instruction::try_from(instruction)?);
+        }
+    }
+
+    let subtotal = |value: i32| {
+        Instruction::Assert(Fact {
+            entity: Entity { id: "cart".into() },
+            attribute: "subtotal".into(),
+            value: Scalar::Integer(value),
+        })
+    };
+    assert_eq!(subtotals, vec![subtotal(3), subtotal(6)]);
+
+    // `RunEndFormula` picks up the state retained by the stream
+    let formula::RunEndFormulaResponse { instructions, .. } = runtime_client
+        .run_end_formula(formula::RunEndFormulaRequest {
+            instance_id: instance_id.clone(),
+            state: vec![],
+            use_retained_state: true,
+        })
+        .await?
+        .into_inner();
+    assert_eq!(
+        Instruction::try_from(instructions[0].clone())?,
+        Instruction::Assert(Fact {
+            entity: Entity { id: "cart".into() },
+            attribute: "total".into(),
+            value: Scalar::Integer(6)
+        })
+    );
+
+    Ok(())
+}
+
 async fn instantiate(
     runtime_client: &mut RuntimeClient<tonic::transport::channel::Channel>,
     source: &str,
@@ -141,6 +237,7 @@ async fn step(
             instance_id: instance_id.to_string(),
             state: state.into(),
             datoms: datoms.into_iter().map(|d| d.into()).collect(),
+            use_retained_state: false,
         })
         .await?
         .into_inner();
@@ -162,6 +259,7 @@ async fn end(
         .run_end_formula(formula::RunEndFormulaRequest {
             instance_id: instance_id.to_string(),
             state: state.into(),
+            use_retained_state: false,
         })
         .await?
         .into_inner();
@@ -0,0 +1,466 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use anyhow::Result;
+use common_builder::{serve as serve_builder, BuilderError};
+use common_runtime::{
+    target::formula_vm::{
+        find, AttributeRangeQuery, Comparison, Datom, DatomStore, Entity, Fact, FormulaDriver,
+        FormulaRun, FormulaSubscriptions, IndexedDatomStore, Instruction, NativeFormulaVm,
+        NativeFormulaVmContext, Pattern, Predicate, Query, RangeQuery, Scalar, Term, Transaction,
+        ValueRangeQuery,
+    },
+    Affinity, ArtifactResolver, BasicIo, ContentType, FormulaVmDefinition, ModuleBody,
+    ModuleDefinition, ModuleDriver, ModuleFactory, NativeRuntime, SourceCode,
+};
+use common_tracing::common_tracing;
+use common_wit::Target;
+use http::Uri;
+use std::sync::Arc;
+use tokio::{net::TcpListener, sync::Mutex, task::JoinHandle};
+
+/// Start a build server, returning its address and a task handler.
+async fn init_build_server() -> Result<(Uri, JoinHandle<Result<(), BuilderError>>)> {
+    let builder_listener = TcpListener::bind("127.0.0.1:0").await?;
+    let builder_url = format!("http://{}", builder_listener.local_addr()?);
+    let builder_task = tokio::task::spawn(serve_builder(builder_listener));
+
+    Ok((builder_url.parse()?, builder_task))
+}
+
+async fn instantiate_formula(runtime: &NativeRuntime, source: &str) -> Result<NativeFormulaVm> {
+    let factory = runtime
+        .prepare(FormulaVmDefinition::try_from(ModuleDefinition {
+            target: Target::CommonFormulaVm,
+            affinity: Affinity::LocalOnly,
+            inputs: Default::default(),
+            outputs: Default::default(),
+            body: ModuleBody::SourceCode(
+                [(
+                    "module".to_owned(),
+                    SourceCode {
+                        content_type: ContentType::JavaScript,
+                        body: source.to_owned().into(),
+                    },
+                )]
+                .into(),
+            ),
+        })?)
+        .await?;
+
+    Ok(factory
+        .instantiate(NativeFormulaVmContext::new(
+            BasicIo::default(),
+            common_ifc::Context::from((common_ifc::ModuleEnvironment::Server,)),
+        ))
+        .await?)
+}
+
+fn entity(id: &str) -> Entity {
+    Entity { id: id.into() }
+}
+
+fn assert_fact(entity_id: &str, attribute: &str, value: Scalar) -> Instruction {
+    Instruction::Assert(Fact {
+        entity: entity(entity_id),
+        attribute: attribute.into(),
+        value,
+    })
+}
+
+fn retract_fact(entity_id: &str, attribute: &str, value: Scalar) -> Instruction {
+    Instruction::Retract(Fact {
+        entity: entity(entity_id),
+        attribute: attribute.into(),
+        value,
+    })
+}
+
+async fn collect(store: &impl DatomStore, query: RangeQuery) -> Result<Vec<Datom>> {
+    use futures_util::StreamExt;
+    let mut datoms = vec![];
+    let mut stream = store.query(&query);
+    while let Some(datom) = stream.next().await {
+        datoms.push(datom?);
+    }
+    Ok(datoms)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[common_tracing]
+async fn it_drives_a_formula_against_a_datom_store() -> Result<()> {
+    let (builder_address, _) = init_build_server().await?;
+    let runtime = NativeRuntime::new(ArtifactResolver::new(Some(builder_address))?)?;
+
+    let source = r#"
+export const init = (input) => [0, { "ByAttribute": { attribute: "price" } }];
+export const step = (total, datoms) => [
+  datoms.reduce((total, datom) => total + datom.value, total),
+  datoms.map((datom) => ({ "Retract": {
+    entity: datom.entity,
+    attribute: "price",
+    value: datom.value,
+  }})),
+];
+export const end = (total) => [{ "Assert": {
+  entity: { id: "cart" },
+  attribute: "total",
+  value: total,
+}}];
+"#;
+
+    let mut store = IndexedDatomStore::open_memory()?;
+    store
+        .commit(Transaction::new(
+            entity("seed"),
+            vec![
+                assert_fact("apple", "price", Scalar::Integer(1)),
+                assert_fact("banana", "price", Scalar::Integer(2)),
+                assert_fact("cherry", "price", Scalar::Integer(3)),
+                assert_fact("cherry", "color", Scalar::String("red".into())),
+            ],
+        ))
+        .await?;
+
+    let mut formula = instantiate_formula(&runtime, source).await?;
+    let mut driver = FormulaDriver::new(store).with_batch_size(2);
+
+    let FormulaRun {
+        range_query,
+        transaction,
+        ..
+    } = driver.run(&mut formula, &[], entity("checkout")).await?;
+
+    assert_eq!(
+        range_query,
+        RangeQuery::Attribute(AttributeRangeQuery {
+            entity: None,
+            attribute: "price".into(),
+            value: None,
+        })
+    );
+    assert_eq!(transaction.instructions.len(), 4);
+
+    let store = driver.into_inner();
+
+    let prices = collect(
+        &store,
+        RangeQuery::Attribute(AttributeRangeQuery {
+            entity: None,
+            attribute: "price".into(),
+            value: None,
+        }),
+    )
+    .await?;
+    assert!(prices.is_empty());
+
+    let totals = collect(
+        &store,
+        RangeQuery::Value(ValueRangeQuery {
+            entity: Some(entity("cart")),
+            attribute: Some("total".into()),
+            value: Scalar::Integer(6),
+        }),
+    )
+    .await?;
+    assert_eq!(totals.len(), 1);
+    assert_eq!(totals[0].cause, entity("checkout"));
+
+    let colors = collect(
+        &store,
+        RangeQuery::Attribute(AttributeRangeQuery {
+            entity: Some(entity("cherry")),
+            attribute: "color".into(),
+            value: None,
+        }),
+    )
+    .await?;
+    assert_eq!(colors.len(), 1);
+    assert_eq!(colors[0].cause, entity("seed"));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[common_tracing]
+async fn it_re_evaluates_subscribed_formulas_as_facts_change() -> Result<()> {
+    let (builder_address, _) = init_build_server().await?;
+    let runtime = NativeRuntime::new(ArtifactResolver::new(Some(builder_address))?)?;
+
+    let source = r#"
+export const init = (input) => [0, { "ByAttribute": { attribute: "price" } }];
+export const step = (total, datoms) => [
+  datoms.reduce((total, datom) => total + datom.value, total),
+  [],
+];
+export const end = (total) => [{ "Assert": {
+  entity: { id: "cart" },
+  attribute: "total",
+  value: total,
+}}];
+"#;
+
+    let total = |value: i32| {
+        RangeQuery::Value(ValueRangeQuery {
+            entity: Some(entity("cart")),
+            attribute: Some("total".into()),
+            value: Scalar::Integer(value),
+        })
+    };
+
+    let mut store = IndexedDatomStore::open_memory()?;
+    store
+        .commit(Transaction::new(
+            entity("seed"),
+            vec![
+                assert_fact("apple", "price", Scalar::Integer(1)),
+                assert_fact("banana", "price", Scalar::Integer(2)),
+            ],
+        ))
+        .await?;
+
+    let formula = Arc::new(Mutex::new(instantiate_formula(&runtime, source).await?));
+    let mut subscriptions = FormulaSubscriptions::new(FormulaDriver::new(store));
+
+    let (run, reactions) = subscriptions
+        .subscribe(formula, vec![], entity("checkout"))
+        .await?;
+    assert!(reactions.is_empty());
+    assert_eq!(
+        run.transaction.instructions,
+        vec![assert_fact("cart", "total", Scalar::Integer(3))]
+    );
+
+    // Facts outside of the formula's range do not re-evaluate it
+    let reactions = subscriptions
+        .commit(Transaction::new(
+            entity("user"),
+            vec![assert_fact("cherry", "color", Scalar::String("red".into()))],
+        ))
+        .await?;
+    assert!(reactions.is_empty());
+
+    // Newly asserted facts are folded into the retained state
+    let reactions = subscriptions
+        .commit(Transaction::new(
+            entity("user"),
+            vec![assert_fact("cherry", "price", Scalar::Integer(4))],
+        ))
+        .await?;
+    assert_eq!(reactions.len(), 1);
+    assert_eq!(reactions[0].cause, entity("checkout"));
+    assert_eq!(
+        reactions[0].instructions,
+        vec![
+            assert_fact("cart", "total", Scalar::Integer(7)),
+            retract_fact("cart", "total", Scalar::Integer(3)),
+        ]
+    );
+    assert_eq!(
+        collect(subscriptions.driver().store(), total(7))
+            .await?
+            .len(),
+        1
+    );
+    assert!(collect(subscriptions.driver().store(), total(3))
+        .await?
+        .is_empty());
+
+    // Retractions run the formula again from scratch
+    let reactions = subscriptions
+        .commit(Transaction::new(
+            entity("user"),
+            vec![retract_fact("apple", "price", Scalar::Integer(1))],
+        ))
+        .await?;
+    assert_eq!(reactions.len(), 1);
+    assert_eq!(
+        reactions[0].instructions,
+        vec![
+            assert_fact("cart", "total", Scalar::Integer(6)),
+            retract_fact("cart", "total", Scalar::Integer(7)),
+        ]
+    );
+    assert_eq!(
+        collect(subscriptions.driver().store(), total(6))
+            .await?
+            .len(),
+        1
+    );
+    assert!(collect(subscriptions.driver().store(), total(7))
+        .await?
+        .is_empty());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[common_tracing]
+async fn it_retracts_facts_derived_from_retracted_facts() -> Result<()> {
+    let (builder_address, _) = init_build_server().await?;
+    let runtime = NativeRuntime::new(ArtifactResolver::new(Some(builder_address))?)?;
+
+    let source = r#"
+export const init = (input) => [0, { "ByAttribute": { attribute: "price" } }];
+export const step = (state, datoms) => [
+  state,
+  datoms
+    .filter((datom) => datom.value >= 2)
+    .map((datom) => ({ "Assert": {
+      entity: datom.entity,
+      attribute: "expensive",
+      value: true,
+    }})),
+];
+export const end = (state) => [];
+"#;
+
+    let expensive = || {
+        RangeQuery::Attribute(AttributeRangeQuery {
+            entity: None,
+            attribute: "expensive".into(),
+            value: None,
+        })
+    };
+
+    let mut store = IndexedDatomStore::open_memory()?;
+    store
+        .commit(Transaction::new(
+            entity("seed"),
+            vec![
+                assert_fact("apple", "price", Scalar::Integer(1)),
+                assert_fact("banana", "price", Scalar::Integer(2)),
+                assert_fact("cherry", "price", Scalar::Integer(4)),
+            ],
+        ))
+        .await?;
+
+    let formula = Arc::new(Mutex::new(instantiate_formula(&runtime, source).await?));
+    let mut subscriptions = FormulaSubscriptions::new(FormulaDriver::new(store));
+    subscriptions
+        .subscribe(formula, vec![], entity("pricing"))
+        .await?;
+    assert_eq!(
+        collect(subscriptions.driver().store(), expensive())
+            .await?
+            .len(),
+        2
+    );
+
+    let reactions = subscriptions
+        .commit(Transaction::new(
+            entity("user"),
+            vec![retract_fact("banana", "price", Scalar::Integer(2))],
+        ))
+        .await?;
+    assert_eq!(reactions.len(), 1);
+    assert_eq!(
+        reactions[0].instructions,
+        vec![
+            assert_fact("cherry", "expensive", Scalar::Boolean(true)),
+            retract_fact("banana", "expensive", Scalar::Boolean(true)),
+        ]
+    );
+
+    let derived = collect(subscriptions.driver().store(), expensive()).await?;
+    assert_eq!(derived.len(), 1);
+    assert_eq!(derived[0].entity, entity("cherry"));
+
+    Ok(())
+}
+
+fn variable(name: &str) -> Term {
+    Term::Variable(name.into())
+}
+
+fn constant(value: impl Into<Scalar>) -> Term {
+    Term::Constant(value.into())
+}
+
+async fn library() -> Result<impl DatomStore> {
+    let mut store = IndexedDatomStore::open_memory()?;
+    store
+        .commit(Transaction::new(
+            entity("seed"),
+            vec![
+                assert_fact("le-guin", "name", Scalar::String("Ursula".into())),
+                assert_fact("herbert", "name", Scalar::String("Frank".into())),
+                assert_fact("earthsea", "author", Scalar::Entity(entity("le-guin"))),
+                assert_fact("earthsea", "title", Scalar::String("Earthsea".into())),
+                assert_fact("earthsea", "year", Scalar::Integer(1968)),
+                assert_fact("dispossessed", "author", Scalar::Entity(entity("le-guin"))),
+                assert_fact(
+                    "dispossessed",
+                    "title",
+                    Scalar::String("The Dispossessed".into()),
+                ),
+                assert_fact("dispossessed", "year", Scalar::Integer(1974)),
+                assert_fact("dune", "author", Scalar::Entity(entity("herbert"))),
+                assert_fact("dune", "title", Scalar::String("Dune".into())),
+                assert_fact("dune", "year", Scalar::Integer(1965)),
+            ],
+        ))
+        .await?;
+    Ok(store)
+}
+
+#[tokio::test]
+async fn it_joins_facts_with_a_query() -> Result<()> {
+    let store = library().await?;
+
+    let query = Query {
+        find: vec!["title".into(), "year".into()],
+        patterns: vec![
+            Pattern {
+                entity: variable("book"),
+                attribute: constant(String::from("title")),
+                value: variable("title"),
+            },
+            Pattern {
+                entity: variable("book"),
+                attribute: constant(String::from("author")),
+                value: variable("author"),
+            },
+            Pattern {
+                entity: variable("author"),
+                attribute: constant(String::from("name")),
+                value: constant(String::from("Ursula")),
+            },
+            Pattern {
+                entity: variable("book"),
+                attribute: constant(String::from("year")),
+                value: variable("year"),
+            },
+        ],
+        predicates: vec![Predicate {
+            comparison: Comparison::GreaterThan,
+            left: variable("year"),
+            right: constant(1970),
+        }],
+    };
+
+    let rows = find(&store, &query).await?;
+    assert_eq!(
+        rows,
+        vec![vec![
+            Scalar::String("The Dispossessed".into()),
+            Scalar::Integer(1974)
+        ]]
+    );
+
+    // Formulas receive the datoms that support each solution
+    let datoms = collect(&store, RangeQuery::Query(query)).await?;
+    assert_eq!(datoms.len(), 4);
+
+    let unconstrained = Query {
+        find: vec!["value".into()],
+        patterns: vec![Pattern {
+            entity: variable("entity"),
+            attribute: variable("attribute"),
+            value: variable("value"),
+        }],
+        predicates: vec![],
+    };
+    assert!(find(&store, &unconstrained).await.is_err());
+
+    Ok(())
+}
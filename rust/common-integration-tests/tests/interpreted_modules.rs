@@ -171,3 +171,129 @@ export const run = () => {
     assert!(run(&mut runtime_client, &instance_id, true).await.is_err());
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[common_tracing]
+async fn it_reads_and_writes_composite_values() -> Result<()> {
+    let VirtualEnvironment {
+        mut runtime_client, ..
+    } = start_runtime().await?;
+
+    let string = |value: &str| common::Value {
+        variant: Some(common::value::Variant::String(value.into())),
+    };
+    let number = |value: f64| common::Value {
+        variant: Some(common::value::Variant::Number(value)),
+    };
+    let null = || common::Value {
+        variant: Some(common::value::Variant::Null(true)),
+    };
+
+    let runtime::InstantiateModuleResponse { instance_id, .. } = runtime_client
+        .instantiate_module(runtime::InstantiateModuleRequest {
+            output_shape: [
+                ("list-out".into(), common::ValueKind::List.into()),
+                ("map-out".into(), common::ValueKind::Map.into()),
+                ("null-out".into(), common::ValueKind::Null.into()),
+            ]
+            .into(),
+            default_input: [(
+                "list".into(),
+                common::Value {
+                    variant: Some(common::value::Variant::List(common::ValueList {
+                        values: vec![],
+                    })),
+                },
+            )]
+            .into(),
+            target: common::Target::CommonFunctionVm.into(),
+            module_reference: Some(common::ModuleBody {
+                variant: Some(common::module_body::Variant::ModuleSource(
+                    common::ModuleSource {
+                        source_code: [(
+                            "module".into(),
+                            common::SourceCode {
+                                content_type: common::ContentType::JavaScript.into(),
+                                body: r#"import { read, write } from "common:io/state@0.0.1";
+export const run = () => {
+  const list = read("list")?.deref()?.val;
+
+  write("list-out", {
+    tag: "list",
+    val: [...list, { tag: "number", val: list.length }],
+  });
+  write("map-out", {
+    tag: "map",
+    val: {
+      first: list[0],
+      nested: { tag: "list", val: [{ tag: "null", val: null }] },
+    },
+  });
+  write("null-out", { tag: "null", val: null });
+};
+"#
+                                .into(),
+                            },
+                        )]
+                        .into(),
+                    },
+                )),
+            }),
+        })
+        .await?
+        .into_inner();
+
+    let runtime::RunModuleResponse { output } = runtime_client
+        .run_module(runtime::RunModuleRequest {
+            instance_id,
+            keep_alive: false,
+            input: [(
+                "list".into(),
+                common::LabeledData {
+                    value: Some(common::Value {
+                        variant: Some(common::value::Variant::List(common::ValueList {
+                            values: vec![string("foo"), null()],
+                        })),
+                    }),
+                    confidentiality: "Public".into(),
+                    integrity: "LowIntegrity".into(),
+                },
+            )]
+            .into(),
+        })
+        .await?
+        .into_inner();
+
+    let value = |key: &str| output.get(key).and_then(|data| data.value.clone());
+
+    assert_eq!(
+        value("list-out"),
+        Some(common::Value {
+            variant: Some(common::value::Variant::List(common::ValueList {
+                values: vec![string("foo"), null(), number(2.0)],
+            })),
+        })
+    );
+    assert_eq!(
+        value("map-out"),
+        Some(common::Value {
+            variant: Some(common::value::Variant::Map(common::ValueMap {
+                entries: [
+                    ("first".into(), string("foo")),
+                    (
+                        "nested".into(),
+                        common::Value {
+                            variant: Some(common::value::Variant::List(common::ValueList {
+                                values: vec![null()],
+                            })),
+                        },
+                    ),
+                ]
+                .into(),
+            })),
+        })
+    );
+    assert_eq!(value("null-out"), Some(null()));
+
+    Ok(())
+}
@@ -0,0 +1,179 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use anyhow::Result;
+use common_ifc::{Confidentiality, Integrity, ModuleEnvironment, Policy};
+use common_protos::{common, runtime};
+use common_runtime::{
+    helpers::{start_runtime, VirtualEnvironment},
+    serve_server, serve_with_policy, Server,
+};
+use common_test_fixtures::sources::common::BASIC_MODULE_JS;
+use common_tracing::common_tracing;
+use tokio::net::TcpListener;
+
+fn string_data(value: &str, confidentiality: &str) -> common::LabeledData {
+    common::LabeledData {
+        value: Some(common::Value {
+            variant: Some(common::value::Variant::String(value.into())),
+        }),
+        confidentiality: confidentiality.into(),
+        integrity: "LowIntegrity".into(),
+    }
+}
+
+fn port(node: &str, port: &str) -> Option<runtime::ModuleGraphPort> {
+    Some(runtime::ModuleGraphPort {
+        node: node.into(),
+        port: port.into(),
+    })
+}
+
+/// Two chained basic modules, each appending ":bar" to its input.
+fn basic_module_graph(
+    target: common::Target,
+    affinity: runtime::Affinity,
+    confidentiality: &str,
+) -> runtime::RunModuleGraphRequest {
+    let node = |label: &str| runtime::ModuleGraphNode {
+        label: label.into(),
+        module_reference: Some(common::ModuleBody {
+            variant: Some(common::module_body::Variant::ModuleSource(
+                common::ModuleSource {
+                    source_code: [(
+                        "module".into(),
+                        common::SourceCode {
+                            content_type: common::ContentType::JavaScript.into(),
+                            body: BASIC_MODULE_JS.into(),
+                        },
+                    )]
+                    .into(),
+                },
+            )),
+        }),
+        target: target.into(),
+        affinity: affinity.into(),
+        input_shape: [("foo".into(), common::ValueKind::String.into())].into(),
+        output_shape: [("bar".into(), common::ValueKind::String.into())].into(),
+    };
+
+    runtime::RunModuleGraphRequest {
+        nodes: vec![node("first"), node("second")],
+        edges: vec![
+            runtime::ModuleGraphEdge {
+                from: port("", "foo"),
+                to: port("first", "foo"),
+            },
+            runtime::ModuleGraphEdge {
+                from: port("first", "bar"),
+                to: port("second", "foo"),
+            },
+            runtime::ModuleGraphEdge {
+                from: port("second", "bar"),
+                to: port("", "bar"),
+            },
+        ],
+        input: [("foo".into(), string_data("foo", confidentiality))].into(),
+        outputs: vec!["bar".into()],
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[common_tracing]
+async fn it_runs_a_graph_of_modules() -> Result<()> {
+    let VirtualEnvironment {
+        mut runtime_client, ..
+    } = start_runtime().await?;
+
+    let runtime::RunModuleGraphResponse { output } = runtime_client
+        .run_module_graph(basic_module_graph(
+            common::Target::CommonFunctionVm,
+            runtime::Affinity::LocalOnly,
+            "Public",
+        ))
+        .await?
+        .into_inner();
+
+    assert_eq!(
+        output.get("bar"),
+        Some(&string_data("foo:bar:bar", "Public"))
+    );
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[common_tracing]
+async fn it_rejects_graphs_that_violate_the_policy() -> Result<()> {
+    // Private data only on BrowserClient
+    let policy = Policy::new(
+        [
+            (Confidentiality::Public, (ModuleEnvironment::Server,).into()),
+            (
+                Confidentiality::Private,
+                (ModuleEnvironment::WebBrowser,).into(),
+            ),
+        ],
+        [
+            (Integrity::Low, (ModuleEnvironment::Server,).into()),
+            (Integrity::High, (ModuleEnvironment::Server,).into()),
+        ],
+    )?;
+    let runtime_listener = TcpListener::bind("127.0.0.1:0").await?;
+    let runtime_address = runtime_listener.local_addr()?;
+    let _runtime_task = tokio::task::spawn(serve_with_policy(runtime_listener, None, policy));
+
+    let mut runtime_client =
+        runtime::runtime_client::RuntimeClient::connect(format!("http://{}", runtime_address))
+            .await?;
+
+    let status = runtime_client
+        .run_module_graph(basic_module_graph(
+            common::Target::CommonFunctionVm,
+            runtime::Affinity::LocalOnly,
+            "Private",
+        ))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[common_tracing]
+async fn it_runs_remote_only_modules_on_the_remote_runtime() -> Result<()> {
+    let VirtualEnvironment { runtime_port, .. } = start_runtime().await?;
+
+    // Without a build server, this runtime cannot
+    // compile modules to run them locally.
+    let runtime_listener = TcpListener::bind("127.0.0.1:0").await?;
+    let runtime_address = runtime_listener.local_addr()?;
+    let server =
+        Server::new(None)?.with_remote_runtime(format!("http://127.0.0.1:{runtime_port}").parse()?);
+    let _runtime_task = tokio::task::spawn(serve_server(runtime_listener, server));
+
+    let mut runtime_client =
+        runtime::runtime_client::RuntimeClient::connect(format!("http://{}", runtime_address))
+            .await?;
+
+    let runtime::RunModuleGraphResponse { output } = runtime_client
+        .run_module_graph(basic_module_graph(
+            common::Target::CommonFunction,
+            runtime::Affinity::RemoteOnly,
+            "Public",
+        ))
+        .await?
+        .into_inner();
+    assert_eq!(
+        output.get("bar"),
+        Some(&string_data("foo:bar:bar", "Public"))
+    );
+
+    assert!(runtime_client
+        .run_module_graph(basic_module_graph(
+            common::Target::CommonFunction,
+            runtime::Affinity::LocalOnly,
+            "Public",
+        ))
+        .await
+        .is_err());
+    Ok(())
+}
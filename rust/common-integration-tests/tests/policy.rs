@@ -29,7 +29,17 @@ async fn init_build_server() -> Result<(Uri, JoinHandle<Result<(), BuilderError>
 async fn get_basic_js_module(
     runtime: &NativeRuntime,
 ) -> Result<(NativeFunctionVmFactory, IoShape)> {
-    let input_shape = IoShape::from(BTreeMap::from([("foo".into(), ValueKind::String)]));
+    get_js_module(runtime, BASIC_MODULE_JS).await
+}
+
+async fn get_js_module(
+    runtime: &NativeRuntime,
+    source: &str,
+) -> Result<(NativeFunctionVmFactory, IoShape)> {
+    let input_shape = IoShape::from(BTreeMap::from([
+        ("foo".into(), ValueKind::String),
+        ("secret".into(), ValueKind::String),
+    ]));
     let output_shape = IoShape::from(BTreeMap::from([("bar".into(), ValueKind::String)]));
 
     let factory = runtime
@@ -43,7 +53,7 @@ async fn get_basic_js_module(
                     "module".to_owned(),
                     SourceCode {
                         content_type: ContentType::JavaScript,
-                        body: BASIC_MODULE_JS.into(),
+                        body: source.into(),
                     },
                 )]
                 .into(),
@@ -103,9 +113,7 @@ async fn it_propagates_labels() -> Result<()> {
     let mut instance = factory
         .instantiate(NativeFunctionVmContext::new(
             BasicIo::new(IoData::default(), output_shape),
-            IfcContext {
-                environment: ModuleEnvironment::Server,
-            },
+            IfcContext::from((ModuleEnvironment::Server,)),
         ))
         .await?;
 
@@ -136,6 +144,105 @@ async fn it_propagates_labels() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[common_tracing]
+async fn it_labels_outputs_with_only_the_inputs_that_were_read() -> Result<()> {
+    let (builder_address, _) = init_build_server().await?;
+    let artifact_resolver = ArtifactResolver::new(Some(builder_address))?;
+    let runtime = NativeRuntime::new(artifact_resolver)?;
+
+    let (factory, output_shape) = get_basic_js_module(&runtime).await?;
+
+    let mut instance = factory
+        .instantiate(NativeFunctionVmContext::new(
+            BasicIo::new(IoData::default(), output_shape),
+            IfcContext::from((ModuleEnvironment::Server,)),
+        ))
+        .await?;
+
+    // The module never reads "secret", so its label does not
+    // constrain the output
+    assert_io!(
+        &mut instance,
+        &Policy::with_defaults()?,
+        {
+            "foo" => ("foo", Confidentiality::Public, Integrity::Low),
+            "secret" => ("secret", Confidentiality::Private, Integrity::High)
+        },
+        {
+           "bar" => ("foo:bar", Confidentiality::Public, Integrity::Low)
+        }
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[common_tracing]
+async fn it_labels_outputs_with_inputs_read_in_earlier_runs() -> Result<()> {
+    let (builder_address, _) = init_build_server().await?;
+    let artifact_resolver = ArtifactResolver::new(Some(builder_address))?;
+    let runtime = NativeRuntime::new(artifact_resolver)?;
+
+    // Stashes "secret" whenever it is provided, and
+    // writes the stashed value in subsequent runs
+    let (factory, output_shape) = get_js_module(
+        &runtime,
+        r#"
+import { read, write } from "common:io/state@0.0.1";
+
+let stash;
+
+export const run = () => {
+  const secret = read("secret");
+  if (secret) {
+    stash = secret.deref();
+  }
+
+  write("bar", {
+    tag: "string",
+    val: `${stash?.val}:bar`,
+  });
+};
+"#,
+    )
+    .await?;
+
+    let mut instance = factory
+        .instantiate(NativeFunctionVmContext::new(
+            BasicIo::new(IoData::default(), output_shape),
+            IfcContext::from((ModuleEnvironment::Server,)),
+        ))
+        .await?;
+
+    assert_io!(
+        &mut instance,
+        &Policy::with_defaults()?,
+        {
+            "foo" => ("foo", Confidentiality::Public, Integrity::Low),
+            "secret" => ("secret", Confidentiality::Private, Integrity::High)
+        },
+        {
+           "bar" => ("secret:bar", Confidentiality::Private, Integrity::Low)
+        }
+    );
+
+    // The secret read in the previous run still
+    // constrains the output
+    assert_io!(
+        &mut instance,
+        &Policy::with_defaults()?,
+        {
+            "foo" => ("foo", Confidentiality::Public, Integrity::Low)
+        },
+        {
+           "bar" => ("secret:bar", Confidentiality::Private, Integrity::Low)
+        }
+    );
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 #[common_tracing]
 async fn it_rejects_based_on_env() -> Result<()> {
@@ -148,9 +255,7 @@ async fn it_rejects_based_on_env() -> Result<()> {
     let instance = factory
         .instantiate(NativeFunctionVmContext::new(
             BasicIo::new(IoData::default(), output_shape),
-            IfcContext {
-                environment: ModuleEnvironment::Server,
-            },
+            IfcContext::from((ModuleEnvironment::Server,)),
         ))
         .await?;
 
@@ -0,0 +1,290 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use common_protos::{
+    common::{self, LabeledData},
+    runtime::{self, runtime_client::RuntimeClient},
+};
+use common_runtime::helpers::{start_runtime, VirtualEnvironment};
+use common_tracing::common_tracing;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[common_tracing]
+async fn it_interprets_and_runs_a_common_python_script() -> Result<()> {
+    let VirtualEnvironment {
+        mut runtime_client, ..
+    } = start_runtime().await?;
+
+    let runtime::InstantiateModuleResponse { instance_id, .. } = runtime_client
+        .instantiate_module(runtime::InstantiateModuleRequest {
+            output_shape: [
+                ("str-out".into(), common::ValueKind::String.into()),
+                ("num-out".into(), common::ValueKind::Number.into()),
+                ("bool-out".into(), common::ValueKind::Boolean.into()),
+            ]
+            .into(),
+            default_input: [
+                (
+                    "str".into(),
+                    common::Value {
+                        variant: Some(common::value::Variant::String("initial foo".into())),
+                    },
+                ),
+                (
+                    "num".into(),
+                    common::Value {
+                        variant: Some(common::value::Variant::Number(0.0)),
+                    },
+                ),
+                (
+                    "bool".into(),
+                    common::Value {
+                        variant: Some(common::value::Variant::Boolean(false)),
+                    },
+                ),
+            ]
+            .into(),
+            target: common::Target::CommonFunctionVm.into(),
+            module_reference: Some(common::ModuleBody {
+                variant: Some(common::module_body::Variant::ModuleSource(
+                    common::ModuleSource {
+                        source_code: [(
+                            "module".into(),
+                            common::SourceCode {
+                                content_type: common::ContentType::Python.into(),
+                                body: r#"from common_io_state import read, write
+
+def run():
+    string = read("str").deref()["val"]
+    num = read("num").deref()["val"]
+    boolean = read("bool").deref()["val"]
+
+    write("str-out", {"tag": "string", "val": f"{string}:new"})
+    write("num-out", {"tag": "number", "val": num + 1})
+    write("bool-out", {"tag": "boolean", "val": not boolean})
+"#
+                                .into(),
+                            },
+                        )]
+                        .into(),
+                    },
+                )),
+            }),
+        })
+        .await?
+        .into_inner();
+
+    async fn run(
+        runtime_client: &mut RuntimeClient<tonic::transport::channel::Channel>,
+        instance_id: &str,
+        keep_alive: bool,
+    ) -> Result<HashMap<String, LabeledData>> {
+        let runtime::RunModuleResponse { output } = runtime_client
+            .run_module(runtime::RunModuleRequest {
+                instance_id: instance_id.to_string(),
+                keep_alive,
+                input: [
+                    (
+                        "str".into(),
+                        common::LabeledData {
+                            value: Some(common::Value {
+                                variant: Some(common::value::Variant::String("foo".into())),
+                            }),
+                            confidentiality: "Public".into(),
+                            integrity: "LowIntegrity".into(),
+                        },
+                    ),
+                    (
+                        "num".into(),
+                        common::LabeledData {
+                            value: Some(common::Value {
+                                variant: Some(common::value::Variant::Number(10.0.into())),
+                            }),
+                            confidentiality: "Public".into(),
+                            integrity: "LowIntegrity".into(),
+                        },
+                    ),
+                    (
+                        "bool".into(),
+                        common::LabeledData {
+                            value: Some(common::Value {
+                                variant: Some(common::value::Variant::Boolean(false)),
+                            }),
+                            confidentiality: "Public".into(),
+                            integrity: "LowIntegrity".into(),
+                        },
+                    ),
+                ]
+                .into(),
+            })
+            .await?
+            .into_inner();
+        Ok(output)
+    }
+
+    for keep_alive in [true, false] {
+        let output = run(&mut runtime_client, &instance_id, keep_alive).await?;
+        assert_eq!(
+            output.get("str-out"),
+            Some(&common::LabeledData {
+                value: Some(common::Value {
+                    variant: Some(common::value::Variant::String("foo:new".into()))
+                }),
+                confidentiality: "Public".into(),
+                integrity: "LowIntegrity".into(),
+            })
+        );
+        assert_eq!(
+            output.get("num-out"),
+            Some(&common::LabeledData {
+                value: Some(common::Value {
+                    variant: Some(common::value::Variant::Number(11.0)),
+                }),
+                confidentiality: "Public".into(),
+                integrity: "LowIntegrity".into(),
+            })
+        );
+        assert_eq!(
+            output.get("bool-out"),
+            Some(&common::LabeledData {
+                value: Some(common::Value {
+                    variant: Some(common::value::Variant::Boolean(true)),
+                }),
+                confidentiality: "Public".into(),
+                integrity: "LowIntegrity".into(),
+            })
+        );
+    }
+    // The second run does not keep the module alive.
+    // This third run should fail.
+    assert!(run(&mut runtime_client, &instance_id, true).await.is_err());
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[common_tracing]
+async fn it_reads_and_writes_composite_python_values() -> Result<()> {
+    let VirtualEnvironment {
+        mut runtime_client, ..
+    } = start_runtime().await?;
+
+    let string = |value: &str| common::Value {
+        variant: Some(common::value::Variant::String(value.into())),
+    };
+    let number = |value: f64| common::Value {
+        variant: Some(common::value::Variant::Number(value)),
+    };
+    let null = || common::Value {
+        variant: Some(common::value::Variant::Null(true)),
+    };
+
+    let runtime::InstantiateModuleResponse { instance_id, .. } = runtime_client
+        .instantiate_module(runtime::InstantiateModuleRequest {
+            output_shape: [
+                ("list-out".into(), common::ValueKind::List.into()),
+                ("map-out".into(), common::ValueKind::Map.into()),
+                ("null-out".into(), common::ValueKind::Null.into()),
+            ]
+            .into(),
+            default_input: [(
+                "list".into(),
+                common::Value {
+                    variant: Some(common::value::Variant::List(common::ValueList {
+                        values: vec![],
+                    })),
+                },
+            )]
+            .into(),
+            target: common::Target::CommonFunctionVm.into(),
+            module_reference: Some(common::ModuleBody {
+                variant: Some(common::module_body::Variant::ModuleSource(
+                    common::ModuleSource {
+                        source_code: [(
+                            "module".into(),
+                            common::SourceCode {
+                                content_type: common::ContentType::Python.into(),
+                                body: r#"from common_io_state import read, write
+
+def run():
+    items = read("list").deref()["val"]
+
+    write("list-out", {
+        "tag": "list",
+        "val": items + [{"tag": "number", "val": len(items)}],
+    })
+    write("map-out", {
+        "tag": "map",
+        "val": {
+            "first": items[0],
+            "nested": {"tag": "list", "val": [{"tag": "null", "val": None}]},
+        },
+    })
+    write("null-out", {"tag": "null", "val": None})
+"#
+                                .into(),
+                            },
+                        )]
+                        .into(),
+                    },
+                )),
+            }),
+        })
+        .await?
+        .into_inner();
+
+    let runtime::RunModuleResponse { output } = runtime_client
+        .run_module(runtime::RunModuleRequest {
+            instance_id,
+            keep_alive: false,
+            input: [(
+                "list".into(),
+                common::LabeledData {
+                    value: Some(common::Value {
+                        variant: Some(common::value::Variant::List(common::ValueList {
+                            values: vec![string("foo"), null()],
+                        })),
+                    }),
+                    confidentiality: "Public".into(),
+                    integrity: "LowIntegrity".into(),
+                },
+            )]
+            .into(),
+        })
+        .await?
+        .into_inner();
+
+    let value = |key: &str| output.get(key).and_then(|data| data.value.clone());
+
+    assert_eq!(
+        value("list-out"),
+        Some(common::Value {
+            variant: Some(common::value::Variant::List(common::ValueList {
+                values: vec![string("foo"), null(), number(2.0)],
+            })),
+        })
+    );
+    assert_eq!(
+        value("map-out"),
+        Some(common::Value {
+            variant: Some(common::value::Variant::Map(common::ValueMap {
+                entries: [
+                    ("first".into(), string("foo")),
+                    (
+                        "nested".into(),
+                        common::Value {
+                            variant: Some(common::value::Variant::List(common::ValueList {
+                                values: vec![null()],
+                            })),
+                        },
+                    ),
+                ]
+                .into(),
+            })),
+        })
+    );
+    assert_eq!(value("null-out"), Some(null()));
+
+    Ok(())
+}
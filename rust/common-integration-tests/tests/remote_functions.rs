@@ -57,9 +57,7 @@ async fn it_runs_a_remote_common_function_vm() -> Result<()> {
     let mut instance = factory
         .instantiate(WebRemoteFunctionContext::new(
             initial_io,
-            common_ifc::Context {
-                environment: ModuleEnvironment::Server,
-            },
+            common_ifc::Context::from((ModuleEnvironment::Server,)),
         ))
         .await?;
 
@@ -123,9 +121,7 @@ async fn it_runs_a_remote_common_function() -> Result<()> {
     let mut instance = factory
         .instantiate(WebRemoteFunctionContext::new(
             initial_io,
-            common_ifc::Context {
-                environment: ModuleEnvironment::Server,
-            },
+            common_ifc::Context::from((ModuleEnvironment::Server,)),
         ))
         .await?;
 
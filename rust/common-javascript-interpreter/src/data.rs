@@ -1,13 +1,14 @@
 use boa_engine::{
     class::{Class, ClassBuilder},
-    object::builtins::JsUint8Array,
+    object::builtins::{JsArray, JsUint8Array},
     property::PropertyDescriptor,
     Context, Finalize, JsArgs, JsData, JsError, JsObject, JsResult, JsString, JsValue,
     NativeFunction, Trace,
 };
 
 use crate::bindings::common::{
-    data::types::Reference as HostReference, io::state::Value as HostValue,
+    data::types::{Reference as HostReference, ValueNode as HostValueNode},
+    io::state::Value as HostValue,
 };
 
 #[repr(transparent)]
@@ -58,39 +59,7 @@ impl Reference {
                     return Ok(JsValue::Undefined);
                 };
 
-                let (tag, val) = match value {
-                    HostValue::String(string) => ("string", JsValue::String(string.into())),
-                    HostValue::Number(number) => ("number", JsValue::Rational(number)),
-                    HostValue::Boolean(boolean) => ("boolean", JsValue::Boolean(boolean)),
-                    HostValue::Buffer(buffer) => (
-                        "buffer",
-                        JsValue::Object(
-                            JsUint8Array::from_iter(buffer.into_iter(), context)?.into(),
-                        ),
-                    ),
-                };
-
-                let object = JsObject::default();
-                object.insert_property(
-                    JsString::from("tag"),
-                    PropertyDescriptor::builder()
-                        .configurable(false)
-                        .enumerable(true)
-                        .writable(false)
-                        .value(JsValue::String(tag.into()))
-                        .build(),
-                );
-                object.insert_property(
-                    JsString::from("val"),
-                    PropertyDescriptor::builder()
-                        .configurable(false)
-                        .enumerable(true)
-                        .writable(false)
-                        .value(val)
-                        .build(),
-                );
-
-                return Ok(JsValue::Object(object));
+                return host_value_to_js(value, context);
             }
         }
 
@@ -129,3 +98,97 @@ impl Class for Reference {
         )))
     }
 }
+
+/// Convert a [`HostValue`] into its tagged (`{ tag, val }`) JavaScript
+/// representation. Members of lists and maps are tagged in the same way.
+pub fn host_value_to_js(value: HostValue, context: &mut Context) -> JsResult<JsValue> {
+    match value {
+        HostValue::String(string) => Ok(tagged("string", JsValue::String(string.into()))),
+        HostValue::Number(number) => Ok(tagged("number", JsValue::Rational(number))),
+        HostValue::Boolean(boolean) => Ok(tagged("boolean", JsValue::Boolean(boolean))),
+        HostValue::Buffer(buffer) => Ok(tagged(
+            "buffer",
+            JsValue::Object(JsUint8Array::from_iter(buffer, context)?.into()),
+        )),
+        HostValue::Null => Ok(tagged("null", JsValue::Null)),
+        HostValue::List(tree) | HostValue::Map(tree) => value_node_to_js(&tree, 0, context),
+    }
+}
+
+/// Convert the node at `index` of a flattened value tree into its tagged
+/// JavaScript representation. Members must appear after their parent in the
+/// tree, which rules out cycles.
+fn value_node_to_js(
+    tree: &[HostValueNode],
+    index: usize,
+    context: &mut Context,
+) -> JsResult<JsValue> {
+    let member_index = |member_index: u32| {
+        let member_index = member_index as usize;
+        if member_index > index && member_index < tree.len() {
+            Ok(member_index)
+        } else {
+            Err(JsError::from_opaque(JsValue::String(
+                format!("Invalid value tree member index {member_index}").into(),
+            )))
+        }
+    };
+
+    let Some(node) = tree.get(index) else {
+        return Ok(tagged("null", JsValue::Null));
+    };
+
+    Ok(match node {
+        HostValueNode::String(string) => tagged("string", JsValue::String(string.as_str().into())),
+        HostValueNode::Number(number) => tagged("number", JsValue::Rational(*number)),
+        HostValueNode::Boolean(boolean) => tagged("boolean", JsValue::Boolean(*boolean)),
+        HostValueNode::Buffer(buffer) => tagged(
+            "buffer",
+            JsValue::Object(JsUint8Array::from_iter(buffer.iter().copied(), context)?.into()),
+        ),
+        HostValueNode::Null => tagged("null", JsValue::Null),
+        HostValueNode::List(members) => {
+            let array = JsArray::new(context);
+            for member in members {
+                let member = value_node_to_js(tree, member_index(*member)?, context)?;
+                array.push(member, context)?;
+            }
+            tagged("list", JsValue::Object(array.into()))
+        }
+        HostValueNode::Map(members) => {
+            let object = JsObject::default();
+            for (key, member) in members {
+                let member = value_node_to_js(tree, member_index(*member)?, context)?;
+                object.create_data_property_or_throw(
+                    JsString::from(key.as_str()),
+                    member,
+                    context,
+                )?;
+            }
+            tagged("map", JsValue::Object(object))
+        }
+    })
+}
+
+fn tagged(tag: &str, val: JsValue) -> JsValue {
+    let object = JsObject::default();
+    object.insert_property(
+        JsString::from("tag"),
+        PropertyDescriptor::builder()
+            .configurable(false)
+            .enumerable(true)
+            .writable(false)
+            .value(JsValue::String(tag.into()))
+            .build(),
+    );
+    object.insert_property(
+        JsString::from("val"),
+        PropertyDescriptor::builder()
+            .configurable(false)
+            .enumerable(true)
+            .writable(false)
+            .value(val)
+            .build(),
+    );
+    JsValue::Object(object)
+}
@@ -1,11 +1,20 @@
 use crate::{
-    bindings::common::io::state::{self, Value as HostValue},
+    bindings::common::{
+        data::types::ValueNode as HostValueNode,
+        io::state::{self, Value as HostValue},
+    },
     data::Reference,
     util::js_error,
 };
 use boa_engine::{
-    class::Class, js_string, module::SyntheticModuleInitializer, object::FunctionObjectBuilder,
-    Context, JsArgs, JsError, JsResult, JsValue, Module, NativeFunction,
+    class::Class,
+    js_string,
+    module::SyntheticModuleInitializer,
+    object::{
+        builtins::{JsArray, JsUint8Array},
+        FunctionObjectBuilder,
+    },
+    Context, JsArgs, JsError, JsObject, JsResult, JsValue, Module, NativeFunction,
 };
 
 pub fn create_io_state_module(context: &mut Context) -> Module {
@@ -72,6 +81,29 @@ pub fn create_io_state_module(context: &mut Context) -> Module {
 }
 
 fn js_to_host_value(context: &mut Context, obj: &JsValue) -> JsResult<HostValue> {
+    let mut tree = vec![];
+    js_to_value_node(context, obj, &mut tree)?;
+
+    Ok(match tree.first() {
+        Some(HostValueNode::List(_)) => HostValue::List(tree),
+        Some(HostValueNode::Map(_)) => HostValue::Map(tree),
+        _ => match tree.swap_remove(0) {
+            HostValueNode::String(value) => HostValue::String(value),
+            HostValueNode::Number(value) => HostValue::Number(value),
+            HostValueNode::Boolean(value) => HostValue::Boolean(value),
+            HostValueNode::Buffer(value) => HostValue::Buffer(value),
+            _ => HostValue::Null,
+        },
+    })
+}
+
+/// Flatten a tagged (`{ tag, val }`) JavaScript value into `tree` in
+/// pre-order, returning the index of its node.
+fn js_to_value_node(
+    context: &mut Context,
+    obj: &JsValue,
+    tree: &mut Vec<HostValueNode>,
+) -> JsResult<u32> {
     const VAL_TYPE_MISMATCH: &str = "'val' type does not match 'tag' type";
 
     let JsValue::Object(object) = obj else {
@@ -87,28 +119,102 @@ fn js_to_host_value(context: &mut Context, obj: &JsValue) -> JsResult<HostValue>
 
     let val = object.get(js_string!("val"), context)?;
 
-    match tag.as_str() {
+    let index = tree.len();
+    tree.push(HostValueNode::Null);
+
+    let node = match tag.as_str() {
         "string" => {
             let value = val
                 .as_string()
                 .ok_or_else(|| js_error(VAL_TYPE_MISMATCH))?
                 .to_std_string()
                 .map_err(|error| js_error(format!("{error}")))?;
-            Ok(HostValue::String(value))
+            HostValueNode::String(value)
         }
         "number" => {
             let value = val.as_number().ok_or_else(|| js_error(VAL_TYPE_MISMATCH))?;
-            Ok(HostValue::Number(value))
+            HostValueNode::Number(value)
         }
         "boolean" => {
             let value = val
                 .as_boolean()
                 .ok_or_else(|| js_error(VAL_TYPE_MISMATCH))?;
-            Ok(HostValue::Boolean(value))
+            HostValueNode::Boolean(value)
         }
         "buffer" => {
-            todo!();
+            let array = val
+                .as_object()
+                .cloned()
+                .ok_or_else(|| js_error(VAL_TYPE_MISMATCH))
+                .and_then(JsUint8Array::from_object)?;
+            let length = array.length(context)?;
+            let mut buffer = Vec::with_capacity(length);
+            for i in 0..length {
+                buffer.push(array.at(i as i64, context)?.to_uint8(context)?);
+            }
+            HostValueNode::Buffer(buffer)
+        }
+        "null" => HostValueNode::Null,
+        "list" => {
+            let array = val
+                .as_object()
+                .cloned()
+                .ok_or_else(|| js_error(VAL_TYPE_MISMATCH))
+                .and_then(JsArray::from_object)?;
+            let length = array.length(context)?;
+            let mut members = Vec::with_capacity(length as usize);
+            for i in 0..length {
+                let member = array.at(i as i64, context)?;
+                members.push(js_to_value_node(context, &member, tree)?);
+            }
+            HostValueNode::List(members)
+        }
+        "map" => {
+            let map = val
+                .as_object()
+                .cloned()
+                .ok_or_else(|| js_error(VAL_TYPE_MISMATCH))?;
+            let mut members = vec![];
+            for key in object_keys(context, &map)? {
+                let member = map.get(js_string!(key.clone()), context)?;
+                members.push((key, js_to_value_node(context, &member, tree)?));
+            }
+            HostValueNode::Map(members)
         }
-        t => Err(js_error(format!("Unknown 'tag' type '{t}'."))),
+        t => return Err(js_error(format!("Unknown 'tag' type '{t}'."))),
+    };
+
+    tree[index] = node;
+    Ok(index as u32)
+}
+
+/// Enumerate the own, enumerable string keys of an object (`Object.keys`).
+fn object_keys(context: &mut Context, object: &JsObject) -> JsResult<Vec<String>> {
+    let constructor = context.intrinsics().constructors().object().constructor();
+    let keys = constructor
+        .get(js_string!("keys"), context)?
+        .as_callable()
+        .ok_or_else(|| js_error("Object.keys is not callable"))?
+        .call(
+            &JsValue::from(constructor),
+            &[JsValue::from(object.clone())],
+            context,
+        )?;
+    let keys = keys
+        .as_object()
+        .cloned()
+        .ok_or_else(|| js_error("Object.keys did not return an array"))
+        .and_then(JsArray::from_object)?;
+
+    let length = keys.length(context)?;
+    let mut out = Vec::with_capacity(length as usize);
+    for i in 0..length {
+        let key = keys
+            .at(i as i64, context)?
+            .to_string(context)?
+            .to_std_string()
+            .map_err(|error| js_error(format!("{error}")))?;
+        out.push(key);
     }
+    Ok(out)
 }
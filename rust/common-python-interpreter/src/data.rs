@@ -0,0 +1,113 @@
+use rustpython_vm::{pyclass, PyObjectRef, PyPayload, PyResult, VirtualMachine};
+
+use crate::{
+    bindings::common::{
+        data::types::{Reference as HostReference, ValueNode as HostValueNode},
+        io::state::Value as HostValue,
+    },
+    util::py_error,
+};
+
+#[pyclass(module = "common_io_state", name = "Reference")]
+#[derive(Debug, PyPayload)]
+pub struct Reference {
+    pub inner: HostReference,
+}
+
+#[pyclass]
+impl Reference {
+    #[pymethod]
+    fn read(&self, name: String, vm: &VirtualMachine) -> PyResult<PyObjectRef> {
+        let Some(reference) = self.inner.read(&name) else {
+            return Ok(vm.ctx.none());
+        };
+
+        Ok(Reference { inner: reference }.into_ref(&vm.ctx).into())
+    }
+
+    #[pymethod]
+    fn deref(&self, vm: &VirtualMachine) -> PyResult<PyObjectRef> {
+        let Some(value) = self
+            .inner
+            .deref()
+            .map_err(|error| py_error(error.to_string(), vm))?
+        else {
+            return Ok(vm.ctx.none());
+        };
+
+        host_value_to_py(value, vm)
+    }
+}
+
+/// Convert a [`HostValue`] into its tagged (`{"tag": ..., "val": ...}`)
+/// Python representation. Members of lists and maps are tagged in the
+/// same way.
+pub fn host_value_to_py(value: HostValue, vm: &VirtualMachine) -> PyResult<PyObjectRef> {
+    match value {
+        HostValue::String(string) => tagged("string", vm.ctx.new_str(string).into(), vm),
+        HostValue::Number(number) => tagged("number", vm.ctx.new_float(number).into(), vm),
+        HostValue::Boolean(boolean) => tagged("boolean", vm.ctx.new_bool(boolean).into(), vm),
+        HostValue::Buffer(buffer) => tagged("buffer", vm.ctx.new_bytes(buffer).into(), vm),
+        HostValue::Null => tagged("null", vm.ctx.none(), vm),
+        HostValue::List(tree) | HostValue::Map(tree) => value_node_to_py(&tree, 0, vm),
+    }
+}
+
+/// Convert the node at `index` of a flattened value tree into its tagged
+/// Python representation. Members must appear after their parent in the
+/// tree, which rules out cycles.
+fn value_node_to_py(
+    tree: &[HostValueNode],
+    index: usize,
+    vm: &VirtualMachine,
+) -> PyResult<PyObjectRef> {
+    let member_index = |member_index: u32| {
+        let member_index = member_index as usize;
+        if member_index > index && member_index < tree.len() {
+            Ok(member_index)
+        } else {
+            Err(py_error(
+                format!("Invalid value tree member index {member_index}"),
+                vm,
+            ))
+        }
+    };
+
+    let Some(node) = tree.get(index) else {
+        return tagged("null", vm.ctx.none(), vm);
+    };
+
+    match node {
+        HostValueNode::String(string) => {
+            tagged("string", vm.ctx.new_str(string.as_str()).into(), vm)
+        }
+        HostValueNode::Number(number) => tagged("number", vm.ctx.new_float(*number).into(), vm),
+        HostValueNode::Boolean(boolean) => tagged("boolean", vm.ctx.new_bool(*boolean).into(), vm),
+        HostValueNode::Buffer(buffer) => {
+            tagged("buffer", vm.ctx.new_bytes(buffer.clone()).into(), vm)
+        }
+        HostValueNode::Null => tagged("null", vm.ctx.none(), vm),
+        HostValueNode::List(members) => {
+            let members = members
+                .iter()
+                .map(|member| value_node_to_py(tree, member_index(*member)?, vm))
+                .collect::<PyResult<Vec<_>>>()?;
+            tagged("list", vm.ctx.new_list(members).into(), vm)
+        }
+        HostValueNode::Map(members) => {
+            let dict = vm.ctx.new_dict();
+            for (key, member) in members {
+                let member = value_node_to_py(tree, member_index(*member)?, vm)?;
+                dict.set_item(key.as_str(), member, vm)?;
+            }
+            tagged("map", dict.into(), vm)
+        }
+    }
+}
+
+fn tagged(tag: &str, val: PyObjectRef, vm: &VirtualMachine) -> PyResult<PyObjectRef> {
+    let dict = vm.ctx.new_dict();
+    dict.set_item("tag", vm.ctx.new_str(tag).into(), vm)?;
+    dict.set_item("val", val, vm)?;
+    Ok(dict.into())
+}
@@ -0,0 +1,153 @@
+use rustpython_vm::pymodule;
+
+/// Native module mirroring `common:io/state`. Usage from Python:
+///
+/// ```python
+/// from common_io_state import read, write
+/// ```
+#[pymodule]
+pub mod common_io_state {
+    use crate::{
+        bindings::common::{
+            data::types::ValueNode as HostValueNode,
+            io::state::{self, Value as HostValue},
+        },
+        data::Reference,
+        util::py_error,
+    };
+    use rustpython_vm::{
+        builtins::{PyBytes, PyDict, PyList, PyStr, PyTypeRef},
+        class::PyClassImpl,
+        PyObjectRef, PyPayload, PyResult, VirtualMachine,
+    };
+
+    #[pyattr(name = "Reference")]
+    fn reference_class(vm: &VirtualMachine) -> PyTypeRef {
+        Reference::make_class(&vm.ctx)
+    }
+
+    #[pyfunction]
+    fn read(name: String, vm: &VirtualMachine) -> PyResult<PyObjectRef> {
+        let Some(reference) = state::read(&name) else {
+            return Ok(vm.ctx.none());
+        };
+
+        Ok(Reference { inner: reference }.into_ref(&vm.ctx).into())
+    }
+
+    #[pyfunction]
+    fn write(name: String, value: PyObjectRef, vm: &VirtualMachine) -> PyResult<()> {
+        let value = py_to_host_value(&value, vm)?;
+
+        state::write(&name, &value);
+
+        Ok(())
+    }
+
+    fn py_to_host_value(value: &PyObjectRef, vm: &VirtualMachine) -> PyResult<HostValue> {
+        let mut tree = vec![];
+        py_to_value_node(value, &mut tree, vm)?;
+
+        Ok(match tree.first() {
+            Some(HostValueNode::List(_)) => HostValue::List(tree),
+            Some(HostValueNode::Map(_)) => HostValue::Map(tree),
+            _ => match tree.swap_remove(0) {
+                HostValueNode::String(value) => HostValue::String(value),
+                HostValueNode::Number(value) => HostValue::Number(value),
+                HostValueNode::Boolean(value) => HostValue::Boolean(value),
+                HostValueNode::Buffer(value) => HostValue::Buffer(value),
+                _ => HostValue::Null,
+            },
+        })
+    }
+
+    /// Flatten a tagged (`{"tag": ..., "val": ...}`) Python value into
+    /// `tree` in pre-order, returning the index of its node.
+    fn py_to_value_node(
+        value: &PyObjectRef,
+        tree: &mut Vec<HostValueNode>,
+        vm: &VirtualMachine,
+    ) -> PyResult<u32> {
+        const VAL_TYPE_MISMATCH: &str = "'val' type does not match 'tag' type";
+
+        let dict = value
+            .downcast_ref::<PyDict>()
+            .ok_or_else(|| py_error("Write received an unsupported type", vm))?;
+
+        let tag = dict
+            .get_item_opt("tag", vm)?
+            .and_then(|tag| {
+                tag.downcast_ref::<PyStr>()
+                    .map(|tag| tag.as_str().to_owned())
+            })
+            .ok_or_else(|| py_error("Unexpected type for 'tag' property", vm))?;
+
+        let val = dict
+            .get_item_opt("val", vm)?
+            .unwrap_or_else(|| vm.ctx.none());
+
+        let index = tree.len();
+        tree.push(HostValueNode::Null);
+
+        let node = match tag.as_str() {
+            "string" => {
+                let value = val
+                    .downcast_ref::<PyStr>()
+                    .ok_or_else(|| py_error(VAL_TYPE_MISMATCH, vm))?;
+                HostValueNode::String(value.as_str().to_owned())
+            }
+            "number" => {
+                if val.class().is(vm.ctx.types.bool_type) {
+                    return Err(py_error(VAL_TYPE_MISMATCH, vm));
+                }
+                let value: f64 = val
+                    .try_into_value(vm)
+                    .map_err(|_| py_error(VAL_TYPE_MISMATCH, vm))?;
+                HostValueNode::Number(value)
+            }
+            "boolean" => {
+                if !val.class().is(vm.ctx.types.bool_type) {
+                    return Err(py_error(VAL_TYPE_MISMATCH, vm));
+                }
+                HostValueNode::Boolean(val.try_to_bool(vm)?)
+            }
+            "buffer" => {
+                let value = val
+                    .downcast_ref::<PyBytes>()
+                    .ok_or_else(|| py_error(VAL_TYPE_MISMATCH, vm))?;
+                HostValueNode::Buffer(value.as_bytes().to_vec())
+            }
+            "null" => HostValueNode::Null,
+            "list" => {
+                let list = val
+                    .downcast_ref::<PyList>()
+                    .ok_or_else(|| py_error(VAL_TYPE_MISMATCH, vm))?;
+                let members = list.borrow_vec().to_vec();
+                let mut indices = Vec::with_capacity(members.len());
+                for member in members.iter() {
+                    indices.push(py_to_value_node(member, tree, vm)?);
+                }
+                HostValueNode::List(indices)
+            }
+            "map" => {
+                let map = val
+                    .downcast_ref::<PyDict>()
+                    .ok_or_else(|| py_error(VAL_TYPE_MISMATCH, vm))?;
+                let mut members = vec![];
+                for (key, member) in map {
+                    let key = key
+                        .downcast_ref::<PyStr>()
+                        .ok_or_else(|| py_error("Map keys must be strings", vm))?
+                        .as_str()
+                        .to_owned();
+                    members.push((key, py_to_value_node(&member, tree, vm)?));
+                }
+                HostValueNode::Map(members)
+            }
+            t => return Err(py_error(format!("Unknown 'tag' type '{t}'."), vm)),
+        };
+
+        tree[index] = node;
+        Ok(index as u32)
+    }
+}
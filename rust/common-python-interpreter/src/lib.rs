@@ -0,0 +1,61 @@
+#![warn(missing_docs)]
+
+//! This package implements a basic Python VM for interpreting
+//! `common:function`-compatible Python sources. The environment supports
+//! importing `common:*` APIs as native Python modules (e.g.
+//! `common_io_state` for `common:io/state`). Notably: this package is
+//! designed to be compiled as a Wasm Component exporting the
+//! `common:function/virtual-module` target. This enables us to evaluate
+//! Python within a Wasm sandbox.
+#[cfg(all(target_arch = "wasm32", target_os = "wasi"))]
+#[allow(warnings)]
+#[rustfmt::skip]
+mod bindings;
+
+#[cfg(all(target_arch = "wasm32", target_os = "wasi"))]
+mod data;
+#[cfg(all(target_arch = "wasm32", target_os = "wasi"))]
+mod io;
+#[cfg(all(target_arch = "wasm32", target_os = "wasi"))]
+mod module;
+#[cfg(all(target_arch = "wasm32", target_os = "wasi"))]
+mod util;
+
+#[cfg(all(target_arch = "wasm32", target_os = "wasi"))]
+mod guest {
+    use crate::bindings::Guest;
+    use crate::module::Module;
+
+    pub struct PythonInterpreter;
+
+    impl Guest for PythonInterpreter {
+        fn run() -> Result<(), String> {
+            let module = Module::get().ok_or("No script source has been set!")?;
+            let module = module.read().map_err(|error| format!("{error}"))?;
+
+            module.run()?;
+
+            Ok(())
+        }
+
+        fn set_source(source: String, dependencies: Vec<(String, String)>) -> Result<(), String> {
+            if !dependencies.is_empty() {
+                return Err("Python modules do not support dependencies".to_owned());
+            }
+            Module::load(Some(source))?;
+            Ok(())
+        }
+    }
+
+    impl Drop for PythonInterpreter {
+        fn drop(&mut self) {
+            Module::reset();
+        }
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_os = "wasi"))]
+use guest::*;
+
+#[cfg(all(target_arch = "wasm32", target_os = "wasi"))]
+bindings::export!(PythonInterpreter with_types_in bindings);
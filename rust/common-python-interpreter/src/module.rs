@@ -0,0 +1,111 @@
+use crate::{io::common_io_state, util};
+use blake3::Hash;
+use once_cell::unsync::OnceCell;
+use rustpython_vm::{compiler::Mode, Interpreter, PyObjectRef};
+use std::{cell::RefCell, thread_local};
+use std::{rc::Rc, sync::RwLock};
+
+thread_local! {
+    pub static MODULE_STATE: RefCell<OnceCell<Rc<RwLock<Module>>>> = const { RefCell::new(OnceCell::new()) };
+}
+
+pub struct Module {
+    run: PyObjectRef,
+    interpreter: Interpreter,
+    script_id: Rc<Hash>,
+}
+
+impl Module {
+    pub fn run(&self) -> Result<(), String> {
+        self.interpreter.enter(|vm| {
+            self.run
+                .call((), vm)
+                .map_err(|error| util::format_exception(vm, error))
+        })?;
+        Ok(())
+    }
+
+    pub fn load(maybe_script: Option<String>) -> Result<Rc<RwLock<Module>>, String> {
+        let script_id = Rc::new(blake3::hash(
+            maybe_script.clone().unwrap_or_default().as_bytes(),
+        ));
+        let maybe_script = maybe_script.map(Rc::new);
+
+        let state = Self::get_or_init(maybe_script.clone(), script_id.clone())?;
+        let read_state = state.read().map_err(|error| format!("{error}"))?;
+
+        if read_state.script_id == script_id {
+            Ok(state.clone())
+        } else {
+            Self::reset();
+            Self::get_or_init(maybe_script, script_id)
+        }
+    }
+
+    pub fn get() -> Option<Rc<RwLock<Module>>> {
+        MODULE_STATE.with_borrow(|state| state.get().cloned())
+    }
+
+    pub fn reset() {
+        MODULE_STATE.with_borrow_mut(|state| {
+            state.take();
+        });
+    }
+
+    fn get_or_init(
+        maybe_script: Option<Rc<String>>,
+        script_id: Rc<Hash>,
+    ) -> Result<Rc<RwLock<Module>>, String> {
+        Ok(MODULE_STATE
+            .try_with(move |state| {
+                let state = state.borrow_mut();
+                let result = state
+                    .get_or_try_init(move || {
+                        let script = if let Some(script) = maybe_script {
+                            script
+                        } else {
+                            return Err("Must provide a script to load!".to_owned());
+                        };
+
+                        let interpreter = Interpreter::with_init(Default::default(), |vm| {
+                            vm.add_native_module(
+                                "common_io_state".to_owned(),
+                                Box::new(common_io_state::make_module),
+                            );
+                        });
+
+                        let run = interpreter.enter(|vm| {
+                            let scope = vm.new_scope_with_builtins();
+                            let code = vm
+                                .compile(&script, Mode::Exec, "<module>".to_owned())
+                                .map_err(|error| format!("Module error: {error}"))?;
+                            vm.run_code_obj(code, scope.clone()).map_err(|error| {
+                                format!("Module error: {}", util::format_exception(vm, error))
+                            })?;
+
+                            scope
+                                .globals
+                                .get_item_opt("run", vm)
+                                .map_err(|error| {
+                                    format!(
+                                        "Failed to get 'run' export: {}",
+                                        util::format_exception(vm, error)
+                                    )
+                                })?
+                                .filter(|run| run.is_callable())
+                                .ok_or_else(|| "No 'run' function was exported!".to_owned())
+                        })?;
+
+                        Ok(Rc::new(RwLock::new(Module {
+                            run,
+                            interpreter,
+                            script_id,
+                        }))) as Result<_, String>
+                    })?
+                    .clone();
+                Ok(result) as Result<Rc<RwLock<Module>>, String>
+            })
+            .map_err(|error| format!("{error}"))??
+            .clone())
+    }
+}
@@ -0,0 +1,15 @@
+use rustpython_vm::{builtins::PyBaseExceptionRef, VirtualMachine};
+
+/// Converts a Python exception to a string, including its traceback.
+pub fn format_exception(vm: &VirtualMachine, exception: PyBaseExceptionRef) -> String {
+    let mut message = String::new();
+    match vm.write_exception(&mut message, &exception) {
+        Ok(_) => message.trim_end().to_owned(),
+        Err(_) => "Unknown Python exception".to_owned(),
+    }
+}
+
+/// Create a Python exception from a string.
+pub fn py_error<S: Into<String>>(message: S, vm: &VirtualMachine) -> PyBaseExceptionRef {
+    vm.new_runtime_error(message.into())
+}
@@ -16,6 +16,7 @@ use common_runtime::{
 use common_test_fixtures::sources::common::BASIC_MODULE_JS;
 use common_wit::Target;
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use ct_runtime::{Runtime, RuntimeConfig};
 use http::Uri;
 use std::{collections::BTreeMap, sync::Arc};
 use tokio::{net::TcpListener, sync::Mutex, task::JoinHandle};
@@ -79,9 +80,7 @@ fn run_benchmark(criterion: &mut Criterion) {
             let function = function_factory
                 .instantiate(NativeFunctionContext::new(
                     io.clone(),
-                    common_ifc::Context {
-                        environment: common_ifc::ModuleEnvironment::Server,
-                    },
+                    common_ifc::Context::from((common_ifc::ModuleEnvironment::Server,)),
                 ))
                 .await?;
 
@@ -93,9 +92,7 @@ fn run_benchmark(criterion: &mut Criterion) {
             let function_vm = function_vm_factory
                 .instantiate(NativeFunctionVmContext::new(
                     io,
-                    common_ifc::Context {
-                        environment: common_ifc::ModuleEnvironment::Server,
-                    },
+                    common_ifc::Context::from((common_ifc::ModuleEnvironment::Server,)),
                 ))
                 .await?;
 
@@ -162,5 +159,50 @@ fn run_benchmark(criterion: &mut Criterion) {
     );
 }
 
-criterion_group!(benches, run_benchmark);
+const INSTANTIATE_SOURCE: &str = r#"
+export const run = (input) => {
+  input.foo = input.foo + 1;
+  return input;
+}
+"#;
+
+/// Measures a cold start of a [`ct_runtime::Runtime`] module
+/// (instantiation followed by a single run), with and without
+/// instantiating from a snapshot.
+fn instantiate_benchmark(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("instantiate_benchmark");
+
+    for snapshots in [false, true] {
+        let host_callback = |input: String| -> Result<String, String> { Ok(input) };
+        let runtime = Runtime::with_config(
+            host_callback,
+            RuntimeConfig {
+                snapshots,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        // Any snapshot is created here, outside of the measurement.
+        let mut module = runtime
+            .module(ct_common::ModuleDefinition {
+                content_type: ct_common::ContentType::JavaScript,
+                source: INSTANTIATE_SOURCE.into(),
+                dependencies: Default::default(),
+                source_map: None,
+            })
+            .unwrap();
+
+        group.bench_function(
+            BenchmarkId::new("js_vm", if snapshots { "snapshot" } else { "source" }),
+            |bencher| {
+                bencher.iter(|| {
+                    let mut instance = module.instantiate().unwrap();
+                    instance.run(r#"{"foo":9}"#.into()).unwrap()
+                })
+            },
+        );
+    }
+}
+
+criterion_group!(benches, run_benchmark, instantiate_benchmark);
 criterion_main!(benches);
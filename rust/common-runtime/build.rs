@@ -4,9 +4,9 @@ use std::{
     process::Command,
 };
 
-/// Builds `common-javascript-interpreter` as a
-/// wasm32-wasip1 component for inclusion via
-/// `include_bytes!(env!("COMMON_JAVASCRIPT_INTERPRETER_WASM_PATH"));`
+/// Builds the virtual module interpreters as wasm32-wasip1
+/// components for inclusion via e.g.
+/// `include_bytes!(env!("JAVASCRIPT_COMMON_FUNCTION_INTERPRETER_WASM_PATH"));`
 fn main() {
     let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
     let project_root_dir = manifest_dir.parent().unwrap().parent().unwrap();
@@ -37,6 +37,13 @@ fn main() {
         "common-formula-javascript-interpreter",
         "JAVASCRIPT_COMMON_FORMULA_INTERPRETER",
     );
+
+    build_component(
+        project_root_dir,
+        out_dir.as_path(),
+        "common-python-interpreter",
+        "PYTHON_COMMON_FUNCTION_INTERPRETER",
+    );
 }
 
 fn build_component(project_root_dir: &Path, out_dir: &Path, crate_name: &str, env_name: &str) {
@@ -11,7 +11,8 @@ pub fn main() {
 #[tokio::main]
 pub async fn main() -> Result<(), common_runtime::CommonRuntimeError> {
     use clap::Parser;
-    use common_runtime::serve;
+    use common_ifc::Policy;
+    use common_runtime::{serve_server, Server};
     use std::net::SocketAddr;
     use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
@@ -25,6 +26,16 @@ pub async fn main() -> Result<(), common_runtime::CommonRuntimeError> {
         /// URL to the build server.
         #[arg(short, long)]
         builder_address: Option<http::Uri>,
+
+        /// Path to a JSON or TOML policy document to validate
+        /// module runs against, instead of the default policy.
+        #[arg(long)]
+        policy: Option<std::path::PathBuf>,
+
+        /// URL to a runtime server to run the remote-only
+        /// modules of module graphs on.
+        #[arg(long)]
+        remote_runtime_address: Option<http::Uri>,
     }
 
     let subscriber = FmtSubscriber::builder()
@@ -33,6 +44,10 @@ pub async fn main() -> Result<(), common_runtime::CommonRuntimeError> {
     tracing::subscriber::set_global_default(subscriber).expect("Failed to configure tracing");
 
     let cli = Cli::parse();
+    let policy = match &cli.policy {
+        Some(path) => Policy::from_path(path)?,
+        None => Policy::with_defaults()?,
+    };
     let port = cli.port;
     let socket_address: SocketAddr = format!("0.0.0.0:{port}")
         .parse()
@@ -57,7 +72,12 @@ pub async fn main() -> Result<(), common_runtime::CommonRuntimeError> {
         }
     });
 
-    serve(listener, builder_address).await?;
+    let mut server = Server::new(builder_address)?.with_policy(policy);
+    if let Some(remote_runtime_address) = cli.remote_runtime_address {
+        server = server.with_remote_runtime(remote_runtime_address);
+    }
+
+    serve_server(listener, server).await?;
 
     Ok(())
 }
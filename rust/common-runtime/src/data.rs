@@ -1,7 +1,6 @@
 use crate::CommonRuntimeError;
 use common_ifc::{Confidentiality, Integrity, Label};
 use common_protos::common as proto;
-use std::str::FromStr;
 
 /// The data that gets passed between runtime modules,
 /// containing the underlying `T` and its confidentiality
@@ -39,13 +38,8 @@ where
                 .ok_or(CommonRuntimeError::InvalidValue)?
                 .try_into()
                 .map_err(|_| CommonRuntimeError::InvalidValue)?,
-            label: (
-                Confidentiality::from_str(&data.confidentiality)
-                    .map_err(|_| CommonRuntimeError::InvalidValue)?,
-                Integrity::from_str(&data.integrity)
-                    .map_err(|_| CommonRuntimeError::InvalidValue)?,
-            )
-                .into(),
+            label: Label::try_from((data.confidentiality.as_str(), data.integrity.as_str()))
+                .map_err(|_| CommonRuntimeError::InvalidValue)?,
         })
     }
 }
@@ -55,10 +49,11 @@ where
     T: Into<proto::Value>,
 {
     fn from(data: Data<T>) -> Self {
+        let (confidentiality, integrity): (String, String) = (&data.label).into();
         proto::LabeledData {
             value: Some(data.value.into()),
-            confidentiality: data.label.confidentiality.to_string(),
-            integrity: data.label.integrity.to_string(),
+            confidentiality,
+            integrity,
         }
     }
 }
@@ -1,5 +1,7 @@
 use crate::ModuleInstanceId;
+use common_graph::CommonGraphError;
 use common_ifc::CommonIfcError;
+use ct_common::{ModuleError, SourceMapper};
 use std::fmt::Debug;
 use thiserror::Error;
 
@@ -30,6 +32,10 @@ pub enum CommonRuntimeError {
     #[error("Failed to run a Common Module: {0}")]
     ModuleRunFailed(String),
 
+    /// The code of a Common Module threw an error when it was run
+    #[error("Failed to run a Common Module: {0}")]
+    ModuleFailure(ModuleError),
+
     /// An unexpected internal error occurred
     #[error("Internal error")]
     InternalError(String),
@@ -59,11 +65,43 @@ pub enum CommonRuntimeError {
     #[error("Invalid instantiation parameters: {0}")]
     InvalidInstantiationParameters(String),
 
+    /// A formula query could not be evaluated
+    #[error("Invalid query: {0}")]
+    InvalidQuery(String),
+
+    /// A graph of Common Modules was malformed
+    #[error("Invalid module graph: {0}")]
+    InvalidModuleGraph(String),
+
     /// There was a policy failure.
     #[error("Policy rejected invocation: {0}")]
     PolicyRejection(CommonIfcError),
 }
 
+impl CommonRuntimeError {
+    /// A [CommonRuntimeError::ModuleFailure] for an `error` reported by the
+    /// code of a Common Module, with its stack frames remapped to the
+    /// Module's original source by `source_mapper`, if any
+    pub fn module_failure(error: &str, source_mapper: Option<&SourceMapper>) -> Self {
+        let error = ModuleError::parse(error);
+        CommonRuntimeError::ModuleFailure(match source_mapper {
+            Some(source_mapper) => source_mapper.remap(error),
+            None => error,
+        })
+    }
+
+    /// The [CommonRuntimeError] for a `status` returned when running a
+    /// Common Module remotely: a [CommonRuntimeError::ModuleFailure] if
+    /// `status` carries a [ModuleError], otherwise a
+    /// [CommonRuntimeError::ModuleRunFailed]
+    pub fn from_run_status(status: tonic::Status) -> Self {
+        match ct_protos::common::ModuleError::from_status(&status) {
+            Some(error) => CommonRuntimeError::ModuleFailure(error.into()),
+            None => CommonRuntimeError::ModuleRunFailed(format!("{status}")),
+        }
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 impl From<tonic::transport::Error> for CommonRuntimeError {
     fn from(value: tonic::transport::Error) -> Self {
@@ -76,3 +114,9 @@ impl From<CommonIfcError> for CommonRuntimeError {
         CommonRuntimeError::PolicyRejection(value)
     }
 }
+
+impl From<CommonGraphError> for CommonRuntimeError {
+    fn from(value: CommonGraphError) -> Self {
+        CommonRuntimeError::InvalidModuleGraph(format!("{value}"))
+    }
+}
@@ -81,9 +81,7 @@ impl CommonRuntime {
                     .map_err(to_string)?;
                 let context = WebRemoteFunctionContext::new(
                     io,
-                    common_ifc::Context {
-                        environment: ModuleEnvironment::Server,
-                    },
+                    common_ifc::Context::from((ModuleEnvironment::Server,)),
                 );
 
                 let instance = FunctionVariant::RemoteModule(Rc::new(RefCell::new(
@@ -1,6 +1,6 @@
 use crate::Value as RuntimeValue;
 use wasm_bindgen::prelude::*;
-use web_sys::js_sys::{JsString, Number, Reflect, Uint8Array};
+use web_sys::js_sys::{Array, JsString, Number, Object, Reflect, Uint8Array};
 
 /// An intermediate representation of a Runtime-legible value, used
 /// as fulcrum for transformation between plain JavaScript objects and
@@ -18,6 +18,7 @@ impl Value {
     /// Construct a new [`Value`] from a raw JavaScript value. A plain,
     /// un-tagged JavaScript value will be inferred, so this can be constructed
     /// with "just a string" or "just a number" or "just a Uint8Array" etc.
+    /// Arrays and plain objects are inferred as lists and maps respectively.
     #[wasm_bindgen(constructor)]
     pub fn new(inner: JsValue) -> Self {
         match inner.js_typeof().as_string().unwrap_or_default().as_str() {
@@ -40,6 +41,13 @@ impl Value {
                 };
             }
             "object" => {
+                if inner.is_null() {
+                    return Self {
+                        tag: "null".into(),
+                        val: inner,
+                    };
+                }
+
                 if inner.is_instance_of::<Uint8Array>() {
                     return Self {
                         tag: "buffer".into(),
@@ -55,6 +63,18 @@ impl Value {
                         return Self { tag, val };
                     };
                 };
+
+                if Array::is_array(&inner) {
+                    return Self {
+                        tag: "list".into(),
+                        val: inner,
+                    };
+                }
+
+                return Self {
+                    tag: "map".into(),
+                    val: inner,
+                };
             }
             _ => (),
         };
@@ -90,10 +110,47 @@ impl From<RuntimeValue> for Value {
                 tag: "buffer".into(),
                 val: buffer.into(),
             },
+            RuntimeValue::Null => Value {
+                tag: "null".into(),
+                val: JsValue::null(),
+            },
+            RuntimeValue::List(list) => Value {
+                tag: "list".into(),
+                val: list
+                    .into_iter()
+                    .map(|member| JsValue::from(Value::from(member).into_tagged_object()))
+                    .collect::<Array>()
+                    .into(),
+            },
+            RuntimeValue::Map(map) => {
+                let object = Object::new();
+                for (key, member) in map {
+                    let _ = Reflect::set(
+                        &object,
+                        &JsValue::from(key),
+                        &Value::from(member).into_tagged_object(),
+                    );
+                }
+                Value {
+                    tag: "map".into(),
+                    val: object.into(),
+                }
+            }
         }
     }
 }
 
+impl Value {
+    /// Convert into a plain `{ tag, val }` JavaScript object, which is how
+    /// members of lists and maps are represented.
+    fn into_tagged_object(self) -> Object {
+        let object = Object::new();
+        let _ = Reflect::set(&object, &JsString::from("tag"), &JsValue::from(self.tag));
+        let _ = Reflect::set(&object, &JsString::from("val"), &self.val);
+        object
+    }
+}
+
 impl TryFrom<Value> for RuntimeValue {
     type Error = String;
 
@@ -129,6 +186,33 @@ impl TryFrom<Value> for RuntimeValue {
                 })?;
                 crate::Value::Buffer(array.to_vec())
             }
+            "null" => crate::Value::Null,
+            "list" => {
+                let array = JsValue::dyn_into::<Array>(value.val).map_err(|val| {
+                    format!("Expected a list (Array), but could not cast given value {val:?}")
+                })?;
+                crate::Value::List(
+                    array
+                        .iter()
+                        .map(|member| crate::Value::try_from(Value::new(member)))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            "map" => {
+                let object = JsValue::dyn_into::<Object>(value.val).map_err(|val| {
+                    format!("Expected a map (Object), but could not cast given value {val:?}")
+                })?;
+                let mut map = std::collections::BTreeMap::new();
+                for key in Object::keys(&object).iter() {
+                    let member = Reflect::get(&object, &key)
+                        .map_err(|error| format!("Could not read map entry: {error:?}"))?;
+                    let key = key
+                        .as_string()
+                        .ok_or_else(|| format!("Expected a string map key, got {key:?}"))?;
+                    map.insert(key, crate::Value::try_from(Value::new(member))?);
+                }
+                crate::Value::Map(map)
+            }
             _ => return Err(format!("Unrecognized value kind '{}'", value.tag)),
         })
     }
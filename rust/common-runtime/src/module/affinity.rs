@@ -1,6 +1,8 @@
+use common_protos::runtime;
+
 /// Defines the intended proximity of Module instantiation relative to the host device
 /// that is currently running a Runtime instance.
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub enum Affinity {
     /// Only instantiate *on* the local device
     LocalOnly,
@@ -13,3 +15,14 @@ pub enum Affinity {
     /// if possible
     PrefersRemote,
 }
+
+impl From<runtime::Affinity> for Affinity {
+    fn from(value: runtime::Affinity) -> Self {
+        match value {
+            runtime::Affinity::LocalOnly => Affinity::LocalOnly,
+            runtime::Affinity::RemoteOnly => Affinity::RemoteOnly,
+            runtime::Affinity::PrefersLocal => Affinity::PrefersLocal,
+            runtime::Affinity::PrefersRemote => Affinity::PrefersRemote,
+        }
+    }
+}
@@ -4,7 +4,7 @@ use common_protos::common as protos;
 
 /// The variants that are accepted as the body field of a
 /// [crate::ModuleDefinition].
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub enum ModuleBody {
     /// A signature body is a [`ModuleId`] that can be used to look up a pre-built
     /// artifact
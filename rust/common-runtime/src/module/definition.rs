@@ -23,7 +23,7 @@ use crate::{Module, ModuleDriver};
 ///
 /// A [`ModuleDefinition`] can be instantiated as a live Module by a Runtime that
 /// implements an appropriate [`ModuleDriver`].
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct ModuleDefinition {
     /// The [`Target`] of the Module, which is always dereferencable to a WIT
     /// definition
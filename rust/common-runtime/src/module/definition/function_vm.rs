@@ -1,7 +1,10 @@
 use common_macros::NewType;
 use common_wit::Target;
 
-use crate::{Affinity, CommonRuntimeError, ContentType, ModuleBody};
+use crate::{
+    dependency_source_code, entry_source_code, Affinity, CommonRuntimeError, ContentType,
+    ModuleBody,
+};
 
 use super::ModuleDefinition;
 
@@ -19,14 +22,25 @@ impl FunctionVmDefinition {
     /// [`ModuleDefinition`]
     pub fn content_type(&self) -> Result<ContentType, CommonRuntimeError> {
         if let ModuleBody::SourceCode(source_code_collection) = &self.0.body {
-            if let Some((_, source_code)) = source_code_collection.iter().next() {
-                return Ok(source_code.content_type);
+            if !source_code_collection.is_empty() {
+                return Ok(entry_source_code(source_code_collection)?.content_type);
             };
         };
         Err(CommonRuntimeError::InvalidModuleSource(
             "Source code content-type was required but not specified".to_string(),
         ))
     }
+
+    /// Get the dependencies of the entry source of this [`ModuleDefinition`],
+    /// as pairs of specifier and source code
+    pub fn dependencies(&self) -> Result<Vec<(String, String)>, CommonRuntimeError> {
+        match &self.0.body {
+            ModuleBody::SourceCode(source_code_collection) => {
+                dependency_source_code(source_code_collection)
+            }
+            ModuleBody::Signature(_) => Ok(vec![]),
+        }
+    }
 }
 
 impl TryFrom<ModuleDefinition> for FunctionVmDefinition {
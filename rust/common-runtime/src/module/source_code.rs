@@ -1,4 +1,4 @@
-use crate::ContentType;
+use crate::{CommonRuntimeError, ContentType};
 use bytes::Bytes;
 use common_protos::common;
 use std::collections::BTreeMap;
@@ -15,6 +15,48 @@ pub struct SourceCode {
 /// A mapping of human-readable module specifiers to source code files
 pub type SourceCodeCollection = BTreeMap<String, SourceCode>;
 
+/// The specifier of the entry source in a [`SourceCodeCollection`] with more
+/// than one source; the other sources are dependencies that it may import
+pub const ENTRY_SOURCE_SPECIFIER: &str = "module";
+
+/// Get the entry [`SourceCode`] of a [`SourceCodeCollection`]: its only
+/// source, or else the one keyed by [`ENTRY_SOURCE_SPECIFIER`]
+pub fn entry_source_code(
+    source_code_collection: &SourceCodeCollection,
+) -> Result<&SourceCode, CommonRuntimeError> {
+    let mut sources = source_code_collection.values();
+    match (sources.next(), sources.next()) {
+        (Some(source_code), None) => Ok(source_code),
+        _ => source_code_collection
+            .get(ENTRY_SOURCE_SPECIFIER)
+            .ok_or_else(|| {
+                CommonRuntimeError::InvalidModuleSource(format!(
+                    "Expected a single source, or an entry source named '{ENTRY_SOURCE_SPECIFIER}'"
+                ))
+            }),
+    }
+}
+
+/// Get the sources of a [`SourceCodeCollection`] other than its entry (see
+/// [`entry_source_code`]), as pairs of specifier and UTF-8 source code
+pub fn dependency_source_code(
+    source_code_collection: &SourceCodeCollection,
+) -> Result<Vec<(String, String)>, CommonRuntimeError> {
+    if source_code_collection.len() < 2 {
+        return Ok(vec![]);
+    }
+
+    source_code_collection
+        .iter()
+        .filter(|(specifier, _)| specifier.as_str() != ENTRY_SOURCE_SPECIFIER)
+        .map(|(specifier, source_code)| {
+            String::from_utf8(source_code.body.to_vec())
+                .map(|source| (specifier.to_owned(), source))
+                .map_err(|error| CommonRuntimeError::InvalidModuleSource(format!("{error}")))
+        })
+        .collect()
+}
+
 impl From<common::SourceCode> for SourceCode {
     fn from(value: common::SourceCode) -> Self {
         SourceCode {
@@ -50,3 +92,35 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{dependency_source_code, entry_source_code, SourceCodeCollection};
+    use crate::ContentType;
+
+    #[test]
+    fn it_splits_entry_and_dependency_sources() {
+        let single: SourceCodeCollection =
+            [("main.js".into(), (ContentType::JavaScript, "main").into())].into();
+        assert_eq!(entry_source_code(&single).unwrap().body, "main");
+        assert!(dependency_source_code(&single).unwrap().is_empty());
+
+        let multiple: SourceCodeCollection = [
+            ("module".into(), (ContentType::JavaScript, "main").into()),
+            ("./dep.js".into(), (ContentType::JavaScript, "dep").into()),
+        ]
+        .into();
+        assert_eq!(entry_source_code(&multiple).unwrap().body, "main");
+        assert_eq!(
+            dependency_source_code(&multiple).unwrap(),
+            vec![("./dep.js".to_owned(), "dep".to_owned())]
+        );
+
+        let missing_entry: SourceCodeCollection = [
+            ("a.js".into(), (ContentType::JavaScript, "a").into()),
+            ("b.js".into(), (ContentType::JavaScript, "b").into()),
+        ]
+        .into();
+        assert!(entry_source_code(&missing_entry).is_err());
+    }
+}
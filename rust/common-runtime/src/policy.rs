@@ -63,9 +63,7 @@ mod tests {
             Value::String("bar".into()),
         )]));
         let io = BasicIo::from_initial_state(input_values, Default::default());
-        let context = IfcContext {
-            environment: ModuleEnvironment::Server,
-        };
+        let context = IfcContext::from((ModuleEnvironment::Server,));
 
         let _ = Validated::try_from((policy, &context, io))?;
 
@@ -96,9 +94,7 @@ mod tests {
             )),
         )]));
         let io = BasicIo::new(input_data, Default::default());
-        let context = IfcContext {
-            environment: ModuleEnvironment::Server,
-        };
+        let context = IfcContext::from((ModuleEnvironment::Server,));
 
         assert!(Validated::try_from((policy, &context, io)).is_err());
 
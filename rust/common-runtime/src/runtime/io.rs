@@ -3,6 +3,7 @@ use common_ifc::Label;
 use common_macros::NewType;
 use common_protos::common;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 
 #[cfg(doc)]
@@ -150,6 +151,17 @@ pub trait InputOutput: Clone + Default + ConditionalSync + std::fmt::Debug {
     /// whether or not it was considered to be successful.
     fn write(&mut self, key: &str, value: Value);
 
+    /// Record that the module has observed the [`Value`] assigned to input
+    /// `key` (e.g. by dereferencing a reference to it). Subsequent writes are
+    /// labeled according to only the inputs that have been observed.
+    fn observe(&mut self, key: &str);
+
+    /// Carry over the inputs observed through `previous`, the [`InputOutput`]
+    /// of an earlier run of the same module instance. An instance may retain
+    /// what it observed in one run and write it in a later one, so writes are
+    /// labeled according to every input observed over the instance's lifetime.
+    fn retain_observations(&mut self, previous: &Self);
+
     /// Get a mapping of the input keys to their set [Data].
     fn input(&self) -> &IoData;
 
@@ -178,6 +190,14 @@ where
         self.as_mut().write(key, value)
     }
 
+    fn observe(&mut self, key: &str) {
+        self.as_mut().observe(key)
+    }
+
+    fn retain_observations(&mut self, previous: &Self) {
+        self.as_mut().retain_observations(previous.as_ref())
+    }
+
     fn input(&self) -> &IoData {
         self.as_ref().input()
     }
@@ -197,27 +217,46 @@ where
 
 /// An implementation of [`InputOutput`] that is suitable for use with many kinds
 /// of [`ModuleDriver`].
-#[derive(Debug, Default, Clone)]
+///
+/// Each write is labeled with the [`Label`]s of only the inputs that were
+/// [observed](InputOutput::observe) before it, so that an output does not
+/// inherit the label of an input the module never read. Labels observed in
+/// earlier runs of the same instance are [retained](InputOutput::retain_observations).
+#[derive(Debug, Clone)]
 pub struct BasicIo {
     input: IoData,
     output: IoData,
     output_shape: IoShape,
+    observed: BTreeSet<String>,
+    retained_constraints: Label,
     label_constraints: Label,
 }
 
+impl Default for BasicIo {
+    fn default() -> Self {
+        BasicIo::new(IoData::default(), IoShape::default())
+    }
+}
+
 impl BasicIo {
     /// Instantiate a [`BasicIo`], providing initial input state, and the
     /// expected shape of output state.
     pub fn new(input: IoData, output_shape: IoShape) -> Self {
-        let label_constraints = Label::constrain(input.iter().map(|(_, v)| &v.label));
         Self {
             input,
             output_shape,
             output: IoData::default(),
-            label_constraints,
+            observed: BTreeSet::new(),
+            retained_constraints: Label::constrain([]),
+            label_constraints: Label::constrain([]),
         }
     }
 
+    /// The input keys observed by the module so far.
+    pub fn observed(&self) -> &BTreeSet<String> {
+        &self.observed
+    }
+
     /// Takes input values [`IoValues`] and an output shape [`IoShape`], and converts
     /// the values into [`Data`] with strictest labels. Used for
     /// specifying initial state.
@@ -228,6 +267,18 @@ impl BasicIo {
         }
         BasicIo::new(IoData::from(map), output_shape)
     }
+
+    /// Constrain writes to the labels of the observed inputs,
+    /// along with those retained from earlier runs.
+    fn constrain_labels(&mut self) {
+        self.label_constraints = Label::constrain(
+            self.observed
+                .iter()
+                .filter_map(|key| self.input.get(key))
+                .map(|data| &data.label)
+                .chain([&self.retained_constraints]),
+        );
+    }
 }
 
 impl InputOutput for BasicIo {
@@ -248,6 +299,18 @@ impl InputOutput for BasicIo {
         }
     }
 
+    fn observe(&mut self, key: &str) {
+        if !self.input.contains_key(key) || !self.observed.insert(key.into()) {
+            return;
+        }
+        self.constrain_labels();
+    }
+
+    fn retain_observations(&mut self, previous: &Self) {
+        self.retained_constraints = previous.label_constraints.clone();
+        self.constrain_labels();
+    }
+
     fn input(&self) -> &IoData {
         &self.input
     }
@@ -264,3 +327,66 @@ impl InputOutput for BasicIo {
         &self.output_shape
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common_ifc::{Confidentiality, Integrity};
+
+    fn basic_io(secret: &str) -> BasicIo {
+        BasicIo::new(
+            IoData::from(BTreeMap::from([
+                (
+                    "secret".to_owned(),
+                    Data::from((
+                        Value::String(secret.into()),
+                        Confidentiality::Private,
+                        Integrity::Low,
+                    )),
+                ),
+                (
+                    "public".to_owned(),
+                    Data::from((
+                        Value::String("public".into()),
+                        Confidentiality::Public,
+                        Integrity::Low,
+                    )),
+                ),
+            ])),
+            IoShape::from(BTreeMap::from([("out".to_owned(), ValueKind::String)])),
+        )
+    }
+
+    #[test]
+    fn it_constrains_writes_to_observations_retained_from_earlier_runs() {
+        let mut first = basic_io("first");
+        first.observe("secret");
+
+        let mut second = basic_io("second");
+        second.retain_observations(&first);
+        second.observe("public");
+        second.write("out", Value::String("first".into()));
+
+        assert_eq!(
+            second
+                .output()
+                .get("out")
+                .map(|data| &data.label.confidentiality),
+            Some(&Confidentiality::Private)
+        );
+    }
+
+    #[test]
+    fn it_constrains_writes_to_observed_inputs_only() {
+        let mut io = basic_io("secret");
+        io.observe("public");
+        io.write("out", Value::String("public".into()));
+
+        assert_eq!(
+            io.output()
+                .get("out")
+                .map(|data| &data.label.confidentiality),
+            Some(&Confidentiality::Public)
+        );
+    }
+}
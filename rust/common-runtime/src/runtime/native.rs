@@ -26,6 +26,9 @@ pub mod target;
 /// reference to the native architecture of the local machine, and is used to
 /// distinguish the Runtime from one that may run in a virtual machine or web
 /// browser.
+///
+/// Clones share their caches of prepared modules.
+#[derive(Clone)]
 pub struct NativeRuntime {
     artifact_resolver: ArtifactResolver,
     wasmtime_engine: WasmtimeEngine,
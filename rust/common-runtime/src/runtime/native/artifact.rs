@@ -1,14 +1,20 @@
 use std::sync::Arc;
 
 use bytes::Bytes;
-use common_protos::builder::{
-    builder_client::BuilderClient, BuildComponentRequest, BuildComponentResponse,
-    BundleSourceCodeRequest, BundleSourceCodeResponse, ReadComponentRequest, ReadComponentResponse,
+use common_protos::{
+    builder::{
+        builder_client::BuilderClient, BuildComponentRequest, BuildComponentResponse,
+        BundleSourceCodeRequest, BundleSourceCodeResponse, ReadComponentRequest,
+        ReadComponentResponse,
+    },
+    common,
 };
-use common_wit::Target;
 use http::Uri;
 
-use crate::{cache::Cache, module::ModuleDefinition, CommonRuntimeError, ModuleBody, ModuleId};
+use crate::{
+    cache::Cache, entry_source_code, module::ModuleDefinition, CommonRuntimeError, ContentType,
+    ModuleBody, ModuleId,
+};
 
 // NOTE: Theoretical memory ceiling is WASM_CACHE_CAPACITY * WASM_MAX_BYTE_SIZE
 // + BUNDLED_SOURCE_CODE_MAX_BYTE_SIZE * BUNDLED_SOURCE_CODE_CACHE_CAPACITY
@@ -27,6 +33,10 @@ static JAVASCRIPT_COMMON_FORMULA_INTERPRETER: Bytes = Bytes::from_static(include
     "JAVASCRIPT_COMMON_FORMULA_INTERPRETER_WASM_PATH"
 )));
 
+static PYTHON_COMMON_FUNCTION_INTERPRETER: Bytes = Bytes::from_static(include_bytes!(env!(
+    "PYTHON_COMMON_FUNCTION_INTERPRETER_WASM_PATH"
+)));
+
 /// Well-known virtual module interpreters that may be requested from an [`ArtifactResolver`]
 #[derive(Eq, PartialEq, Hash, Clone)]
 pub enum VirtualModuleInterpreter {
@@ -34,6 +44,18 @@ pub enum VirtualModuleInterpreter {
     JavaScriptFunction,
     /// A JavaScript interpreter that emulates a `common:formula/module`
     JavaScriptFormula,
+    /// A Python interpreter that emulates a `common:function/module`
+    PythonFunction,
+}
+
+/// The source code of a Module, bundled into a single source
+#[derive(Clone, Debug)]
+pub struct BundledSourceCode {
+    /// The bundled source code
+    pub source_code: String,
+    /// A source map relating the bundled source code to the
+    /// Module's original source, if it was transformed
+    pub source_map: Option<String>,
 }
 
 /// An [`ArtifactResolver`] is a one-stop shop for accessing
@@ -41,7 +63,7 @@ pub enum VirtualModuleInterpreter {
 pub struct ArtifactResolver {
     builder_address: Option<Uri>,
     wasm_cache: Cache<ModuleId, Bytes>,
-    bundled_source_code_cache: Cache<ModuleId, Arc<String>>,
+    bundled_source_code_cache: Cache<ModuleId, Arc<BundledSourceCode>>,
 }
 
 impl ArtifactResolver {
@@ -67,6 +89,9 @@ impl ArtifactResolver {
             VirtualModuleInterpreter::JavaScriptFormula => {
                 Ok(JAVASCRIPT_COMMON_FORMULA_INTERPRETER.clone())
             }
+            VirtualModuleInterpreter::PythonFunction => {
+                Ok(PYTHON_COMMON_FUNCTION_INTERPRETER.clone())
+            }
         }
     }
 
@@ -87,28 +112,25 @@ impl ArtifactResolver {
                 .max_decoding_message_size(WASM_MAX_BYTE_SIZE);
 
             // TODO: Align IDs as derived by builder with IDs as derived by runtime
-            let id = if let ModuleBody::SourceCode(source_code_collection) = &definition.body {
-                let BuildComponentResponse { id } = builder_client
+            let id = if let ModuleBody::SourceCode(_) = &definition.body {
+                let BuildComponentResponse { component_id, .. } = builder_client
                     .build_component(tonic::Request::new(BuildComponentRequest {
-                        target: common_protos::common::Target::CommonFunction.into(),
-                        module_source: Some(common_protos::common::ModuleSource {
-                            source_code: source_code_collection
-                                .iter()
-                                .map(|(key, value)| (key.to_owned(), value.into()))
-                                .collect(),
-                        }),
+                        module_definition: Some(builder_module_definition(definition)?),
+                        ..Default::default()
                     }))
                     .await
                     .map_err(|error| CommonRuntimeError::PreparationFailed(format!("{error}")))?
                     .into_inner();
-                ModuleId::Base64(id)
+                blake3::Hash::from_hex(&component_id)
+                    .map(ModuleId::Hash)
+                    .map_err(|error| CommonRuntimeError::PreparationFailed(format!("{error}")))?
             } else {
                 id
             };
 
-            let ReadComponentResponse { component } = builder_client
+            let ReadComponentResponse { component, .. } = builder_client
                 .read_component(tonic::Request::new(ReadComponentRequest {
-                    id: id.to_string(),
+                    component_id: id.to_string(),
                 }))
                 .await
                 .map_err(|error| CommonRuntimeError::PreparationFailed(format!("{error}")))?
@@ -128,53 +150,51 @@ impl ArtifactResolver {
     }
 
     /// Given a [`ModuleDefinition`] with a [ModuleBody::SourceCode] body, resolve a bundled
-    /// artifact that combines all of its source code inputs
+    /// artifact of its entry source, along with a source map for the bundle if there is one.
+    /// The entry source's dependencies are not bundled, and are instead provided to the VM
+    /// that hosts it.
     pub async fn get_bundled_source_code(
         &self,
         definition: &ModuleDefinition,
-    ) -> Result<Arc<String>, CommonRuntimeError> {
+    ) -> Result<Arc<BundledSourceCode>, CommonRuntimeError> {
         let id = ModuleId::from(definition);
 
         if let Some(item) = self.bundled_source_code_cache.get(&id).await {
             Ok(item)
+        } else if let Some(source_code) = unbundled_source_code(definition)? {
+            let bundled_source_code = Arc::new(BundledSourceCode {
+                source_code,
+                source_map: None,
+            });
+
+            self.bundled_source_code_cache
+                .insert(id, bundled_source_code.clone())
+                .await;
+
+            Ok(bundled_source_code)
         } else if let Some(address) = &self.builder_address {
             let mut builder_client = BuilderClient::connect(address.to_string())
                 .await?
                 .max_encoding_message_size(BUNDLED_SOURCE_CODE_MAX_BYTE_SIZE)
                 .max_decoding_message_size(BUNDLED_SOURCE_CODE_MAX_BYTE_SIZE);
 
-            let target = match definition.target {
-                Target::CommonFunction | Target::CommonFunctionVm => {
-                    common_protos::common::Target::CommonFunction
-                }
-                Target::CommonFormulaVm => common_protos::common::Target::CommonFormulaVm,
-            };
-
-            let source_code: std::collections::HashMap<String, common_protos::common::SourceCode> =
-                match &definition.body {
-                    crate::ModuleBody::Signature(_) => {
-                        return Err(CommonRuntimeError::PreparationFailed(
-                            "Cannot bundle source code when module body is 'signature'".into(),
-                        ));
-                    }
-                    crate::ModuleBody::SourceCode(source_code_collection) => source_code_collection
-                        .iter()
-                        .map(|(name, source_code)| (name.to_owned(), source_code.into()))
-                        .collect(),
-                };
-
             let BundleSourceCodeResponse {
                 bundled_source_code,
+                source_map,
+                ..
             } = builder_client
                 .bundle_source_code(tonic::Request::new(BundleSourceCodeRequest {
-                    target: target.into(),
-                    module_source: Some(common_protos::common::ModuleSource { source_code }),
+                    module_definition: Some(builder_module_definition(definition)?),
+                    ..Default::default()
                 }))
                 .await
                 .map_err(|error| CommonRuntimeError::PreparationFailed(format!("{error}")))?
                 .into_inner();
 
-            let bundled_source_code = Arc::new(bundled_source_code);
+            let bundled_source_code = Arc::new(BundledSourceCode {
+                source_code: bundled_source_code,
+                source_map,
+            });
 
             self.bundled_source_code_cache
                 .insert(id, bundled_source_code.clone())
@@ -188,3 +208,107 @@ impl ArtifactResolver {
         }
     }
 }
+
+/// The definition of a Module sent to the Builder: only the entry source of its
+/// [ModuleBody::SourceCode] body, as the VM hosting the Module provides the other
+/// sources as dependencies.
+fn builder_module_definition(
+    definition: &ModuleDefinition,
+) -> Result<common::ModuleDefinition, CommonRuntimeError> {
+    let ModuleBody::SourceCode(source_code_collection) = &definition.body else {
+        return Err(CommonRuntimeError::PreparationFailed(
+            "Cannot build a module when its body is 'signature'".into(),
+        ));
+    };
+    let source_code = entry_source_code(source_code_collection)?;
+
+    Ok(common::ModuleDefinition {
+        content_type: common::ContentType::from(source_code.content_type).into(),
+        source: String::from_utf8(source_code.body.to_vec())
+            .map_err(|error| CommonRuntimeError::InvalidModuleSource(format!("{error}")))?,
+        dependencies: Default::default(),
+        source_map: None,
+    })
+}
+
+/// Python sources are interpreted as-is, so they are never sent to the
+/// Builder for bundling. Returns the entry source when that is the case.
+/// Python modules cannot import other sources, so a Python body must
+/// consist of a single source.
+fn unbundled_source_code(
+    definition: &ModuleDefinition,
+) -> Result<Option<String>, CommonRuntimeError> {
+    let ModuleBody::SourceCode(source_code_collection) = &definition.body else {
+        return Ok(None);
+    };
+    if source_code_collection.is_empty() {
+        return Ok(None);
+    }
+
+    let source_code = entry_source_code(source_code_collection)?;
+    if source_code.content_type != ContentType::Python {
+        return Ok(None);
+    }
+    if source_code_collection.len() > 1 {
+        return Err(CommonRuntimeError::InvalidModuleSource(format!(
+            "Python modules must consist of a single source, got {}",
+            source_code_collection.len()
+        )));
+    }
+
+    String::from_utf8(source_code.body.to_vec())
+        .map(Some)
+        .map_err(|error| CommonRuntimeError::InvalidModuleSource(format!("{error}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unbundled_source_code;
+    use crate::{Affinity, ContentType, ModuleBody, ModuleDefinition};
+    use common_wit::Target;
+
+    fn definition(sources: &[(&str, ContentType, &str)]) -> ModuleDefinition {
+        ModuleDefinition {
+            target: Target::CommonFunctionVm,
+            affinity: Affinity::LocalOnly,
+            inputs: Default::default(),
+            outputs: Default::default(),
+            body: ModuleBody::SourceCode(
+                sources
+                    .iter()
+                    .map(|(name, content_type, body)| {
+                        (name.to_string(), (*content_type, body.to_string()).into())
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    #[test]
+    fn it_passes_through_a_single_python_source() {
+        let definition = definition(&[("module", ContentType::Python, "def run(): pass")]);
+        assert_eq!(
+            unbundled_source_code(&definition).unwrap(),
+            Some("def run(): pass".to_owned())
+        );
+    }
+
+    #[test]
+    fn it_rejects_multiple_python_sources() {
+        let definition = definition(&[
+            ("module", ContentType::Python, "import helper"),
+            ("helper", ContentType::Python, "def help(): pass"),
+        ]);
+        assert!(unbundled_source_code(&definition).is_err());
+    }
+
+    #[test]
+    fn it_leaves_javascript_sources_to_the_builder() {
+        let definition = definition(&[(
+            "module",
+            ContentType::JavaScript,
+            "export const run = () => {}",
+        )]);
+        assert_eq!(unbundled_source_code(&definition).unwrap(), None);
+    }
+}
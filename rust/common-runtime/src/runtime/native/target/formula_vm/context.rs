@@ -0,0 +1,62 @@
+use crate::{module::ModuleContext, runtime::BasicIo, ModuleContextMut};
+use common_ifc::Context as IfcContext;
+use wasmtime::component::ResourceTable;
+use wasmtime_wasi::{WasiCtx, WasiView};
+
+/// The backing [ModuleContext] for a
+/// [crate::target::formula_vm::NativeFormulaVm] Module.
+pub struct NativeFormulaVmContext {
+    io: BasicIo,
+    ifc: IfcContext,
+
+    wasi_resources: ResourceTable,
+    wasi_ctx: WasiCtx,
+}
+
+impl NativeFormulaVmContext {
+    /// Instantiate a new [NativeFormulaVmContext] with a [BasicIo] and an
+    /// [IfcContext]
+    pub fn new(io: BasicIo, ifc: IfcContext) -> Self {
+        Self {
+            io,
+            ifc,
+
+            wasi_resources: ResourceTable::new(),
+            wasi_ctx: WasiCtx::builder()
+                .allow_tcp(false)
+                .allow_udp(false)
+                .allow_ip_name_lookup(false)
+                .allow_blocking_current_thread(false)
+                .inherit_stdout()
+                .build(),
+        }
+    }
+}
+
+impl ModuleContext for NativeFormulaVmContext {
+    type Io = BasicIo;
+
+    fn io(&self) -> &Self::Io {
+        &self.io
+    }
+
+    fn ifc(&self) -> &common_ifc::Context {
+        &self.ifc
+    }
+}
+
+impl ModuleContextMut for NativeFormulaVmContext {
+    fn io_mut(&mut self) -> &mut Self::Io {
+        &mut self.io
+    }
+}
+
+impl WasiView for NativeFormulaVmContext {
+    fn table(&mut self) -> &mut ResourceTable {
+        &mut self.wasi_resources
+    }
+
+    fn ctx(&mut self) -> &mut WasiCtx {
+        &mut self.wasi_ctx
+    }
+}
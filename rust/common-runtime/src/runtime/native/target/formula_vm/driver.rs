@@ -0,0 +1,214 @@
+use crate::CommonRuntimeError;
+use futures_util::StreamExt;
+
+use super::{
+    Datom, DatomStore, DatomStream, Entity, Fact, Instruction, NativeFormulaVm, RangeQuery, Scalar,
+    State, Transaction,
+};
+
+/// The number of [Datom]s passed to each `step` of a formula by default.
+pub const DEFAULT_BATCH_SIZE: usize = 64;
+
+/// The outcome of running a formula with a [FormulaDriver].
+#[derive(Debug)]
+pub struct FormulaRun {
+    /// The [State] returned by the formula's final `step` (or `init`,
+    /// if no [Datom]s matched its query)
+    pub state: State,
+    /// The query the formula returned from `init`
+    pub range_query: RangeQuery,
+    /// The [Transaction] that was committed to the [DatomStore]
+    pub transaction: Transaction,
+}
+
+/// Runs `common:formula/virtual-module` modules end-to-end against a
+/// [DatomStore].
+///
+/// The driver resolves the [RangeQuery] a formula returns from `init`,
+/// streams the matching [Datom]s into `step` in batches, and commits the
+/// [Instruction]s emitted by `step` and `end` as one [Transaction].
+pub struct FormulaDriver<S> {
+    store: S,
+    batch_size: usize,
+}
+
+impl<S> FormulaDriver<S>
+where
+    S: DatomStore + Send + Sync,
+{
+    /// Create a [FormulaDriver] over `store`
+    pub fn new(store: S) -> Self {
+        FormulaDriver {
+            store,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// Set the maximum number of [Datom]s passed to each `step`
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// The underlying [DatomStore]
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// The underlying [DatomStore], mutably
+    pub fn store_mut(&mut self) -> &mut S {
+        &mut self.store
+    }
+
+    /// Consume the driver, returning the underlying [DatomStore]
+    pub fn into_inner(self) -> S {
+        self.store
+    }
+
+    /// Run `formula` with `input`, committing the facts it asserts and
+    /// retracts with `cause` as their provenance.
+    ///
+    /// Nothing is committed if any part of the formula fails.
+    pub async fn run(
+        &mut self,
+        formula: &mut NativeFormulaVm,
+        input: &[(String, Scalar)],
+        cause: Entity,
+    ) -> Result<FormulaRun, CommonRuntimeError> {
+        let (run, _) = self.rerun(formula, input, &[], cause).await?;
+        Ok(run)
+    }
+
+    /// Run `formula` with `input` like [FormulaDriver::run], additionally
+    /// retracting each of the `superseded` facts (e.g. those derived by an
+    /// earlier run) that the formula does not assert again.
+    ///
+    /// Returns the run, along with the facts asserted by `end`.
+    pub async fn rerun(
+        &mut self,
+        formula: &mut NativeFormulaVm,
+        input: &[(String, Scalar)],
+        superseded: &[Fact],
+        cause: Entity,
+    ) -> Result<(FormulaRun, Vec<Fact>), CommonRuntimeError> {
+        let (state, range_query) = formula.init(input).await?;
+        let datoms = self.store.query(&range_query);
+        let (state, instructions) = Self::step(formula, state, datoms, self.batch_size).await?;
+        let (transaction, ended) = self
+            .end(formula, &state, instructions, superseded, cause)
+            .await?;
+
+        Ok((
+            FormulaRun {
+                state,
+                range_query,
+                transaction,
+            },
+            ended,
+        ))
+    }
+
+    /// Resume a `formula` previously run by this driver from its retained
+    /// `state`, feeding it only `datoms` (e.g. newly asserted facts that
+    /// match its query) and committing the facts it asserts and retracts
+    /// with `cause` as their provenance.
+    ///
+    /// `end` describes the formula's whole state, so the `superseded`
+    /// facts asserted by its previous `end` are retracted unless they are
+    /// asserted again. Returns the new [State], the committed
+    /// [Transaction] and the facts asserted by `end`.
+    pub async fn update(
+        &mut self,
+        formula: &mut NativeFormulaVm,
+        state: State,
+        datoms: Vec<Datom>,
+        superseded: &[Fact],
+        cause: Entity,
+    ) -> Result<(State, Transaction, Vec<Fact>), CommonRuntimeError> {
+        let datoms: DatomStream = Box::pin(futures_util::stream::iter(datoms.into_iter().map(Ok)));
+        let (state, instructions) = Self::step(formula, state, datoms, self.batch_size).await?;
+        let (transaction, ended) = self
+            .end(formula, &state, instructions, superseded, cause)
+            .await?;
+
+        Ok((state, transaction, ended))
+    }
+
+    /// Call `end` on `formula` and commit everything it emitted, along
+    /// with the retraction of every `superseded` fact that was not
+    /// asserted again. Returns the committed [Transaction] and the facts
+    /// asserted by `end`.
+    async fn end(
+        &mut self,
+        formula: &mut NativeFormulaVm,
+        state: &State,
+        mut instructions: Vec<Instruction>,
+        superseded: &[Fact],
+        cause: Entity,
+    ) -> Result<(Transaction, Vec<Fact>), CommonRuntimeError> {
+        let ended = formula.end(state).await?;
+        let ended_facts = ended
+            .iter()
+            .filter_map(|instruction| match instruction {
+                Instruction::Assert(fact) => Some(fact.clone()),
+                _ => None,
+            })
+            .collect();
+        instructions.extend(ended);
+
+        let stale = superseded
+            .iter()
+            .filter(|fact| !asserts(&instructions, fact))
+            .cloned()
+            .map(Instruction::Retract)
+            .collect::<Vec<_>>();
+        instructions.extend(stale);
+
+        let transaction = Transaction::new(cause, instructions);
+        if !transaction.is_empty() {
+            self.store.commit(transaction.clone()).await?;
+        }
+
+        Ok((transaction, ended_facts))
+    }
+
+    /// Feed `datoms` into `formula` in batches of at most `batch_size`,
+    /// returning its final [State] and the [Instruction]s emitted along
+    /// the way
+    async fn step(
+        formula: &mut NativeFormulaVm,
+        mut state: State,
+        mut datoms: DatomStream<'_>,
+        batch_size: usize,
+    ) -> Result<(State, Vec<Instruction>), CommonRuntimeError> {
+        let mut instructions = vec![];
+        let mut batch = Vec::with_capacity(batch_size);
+
+        loop {
+            let next = datoms.next().await.transpose()?;
+            let exhausted = next.is_none();
+
+            batch.extend(next);
+
+            if batch.len() == batch_size || (exhausted && !batch.is_empty()) {
+                let (next_state, emitted) =
+                    formula.step(&state, std::mem::take(&mut batch)).await?;
+                state = next_state;
+                instructions.extend(emitted);
+            }
+
+            if exhausted {
+                break;
+            }
+        }
+
+        Ok((state, instructions))
+    }
+}
+
+/// Whether `instructions` assert `fact`
+fn asserts(instructions: &[Instruction], fact: &Fact) -> bool {
+    instructions
+        .iter()
+        .any(|instruction| matches!(instruction, Instruction::Assert(asserted) if asserted == fact))
+}
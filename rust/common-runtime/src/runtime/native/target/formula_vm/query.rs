@@ -0,0 +1,382 @@
+use crate::CommonRuntimeError;
+use common_protos::formula as proto;
+use futures_util::StreamExt;
+use prost::Message;
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+};
+
+use super::{
+    AttributeRangeQuery, Comparison, Datom, DatomStore, EntityRangeQuery, Pattern, Predicate,
+    Query, RangeQuery, Scalar, Term, ValueRangeQuery,
+};
+
+/// Values bound to the variables of a [Query].
+pub type Bindings = BTreeMap<String, Scalar>;
+
+/// One way of satisfying every pattern and predicate of a [Query].
+#[derive(Clone, Debug, Default)]
+pub struct Solution {
+    /// The value bound to each variable
+    pub bindings: Bindings,
+    /// The [Datom] matched by each pattern, in evaluation order
+    pub datoms: Vec<Datom>,
+}
+
+impl Solution {
+    /// The values bound to `variables`, in order
+    pub fn project(&self, variables: &[String]) -> Result<Vec<Scalar>, CommonRuntimeError> {
+        variables
+            .iter()
+            .map(|variable| {
+                self.bindings.get(variable).cloned().ok_or_else(|| {
+                    CommonRuntimeError::InvalidQuery(format!("Variable '{variable}' is not bound"))
+                })
+            })
+            .collect()
+    }
+}
+
+/// Find every [Solution] to `query` among the facts in `store`.
+///
+/// Patterns are joined with nested loops: each pattern is resolved as a
+/// [RangeQuery] constrained by its constants and by the variables bound
+/// by the patterns before it. Patterns are evaluated in the order that
+/// keeps every one of them constrained, and predicates are checked as
+/// soon as their variables are bound.
+pub async fn solve<S>(store: &S, query: &Query) -> Result<Vec<Solution>, CommonRuntimeError>
+where
+    S: DatomStore + ?Sized,
+{
+    let plan = Plan::new(query)?;
+
+    if !holds_all(query, &plan.predicates, &Bindings::new()) {
+        return Ok(vec![]);
+    }
+
+    let mut solutions = vec![Solution::default()];
+
+    for step in plan.steps {
+        let pattern = &query.patterns[step.pattern];
+        let mut next = vec![];
+
+        for solution in solutions {
+            let Some(range_query) = range_query(pattern, &solution.bindings)? else {
+                continue;
+            };
+            let mut datoms = store.query(&range_query);
+
+            while let Some(datom) = datoms.next().await {
+                let datom = datom?;
+                let Some(bindings) = unify(pattern, &datom, &solution.bindings) else {
+                    continue;
+                };
+                if !holds_all(query, &step.predicates, &bindings) {
+                    continue;
+                }
+
+                let mut datoms = solution.datoms.clone();
+                datoms.push(datom);
+                next.push(Solution { bindings, datoms });
+            }
+        }
+
+        if next.is_empty() {
+            return Ok(next);
+        }
+        solutions = next;
+    }
+
+    Ok(solutions)
+}
+
+/// The distinct values of the `find` variables of `query` over every
+/// [Solution] in `store`.
+pub async fn find<S>(store: &S, query: &Query) -> Result<Vec<Vec<Scalar>>, CommonRuntimeError>
+where
+    S: DatomStore + ?Sized,
+{
+    let mut seen = BTreeSet::new();
+    let mut rows = vec![];
+
+    for solution in solve(store, query).await? {
+        let row = solution.project(&query.find)?;
+        let key = row
+            .iter()
+            .map(|scalar| proto::Scalar::from(scalar.clone()).encode_to_vec())
+            .collect::<Vec<_>>();
+        if seen.insert(key) {
+            rows.push(row);
+        }
+    }
+
+    Ok(rows)
+}
+
+/// The distinct [Datom]s that support any [Solution] to `query` in
+/// `store`; these are what a formula querying with
+/// [RangeQuery::Query] receives in `step`.
+pub async fn supporting_datoms<S>(
+    store: &S,
+    query: &Query,
+) -> Result<Vec<Datom>, CommonRuntimeError>
+where
+    S: DatomStore + ?Sized,
+{
+    let mut seen = BTreeSet::new();
+    let mut datoms = vec![];
+
+    for solution in solve(store, query).await? {
+        for datom in solution.datoms {
+            if seen.insert(proto::Datom::from(datom.clone()).encode_to_vec()) {
+                datoms.push(datom);
+            }
+        }
+    }
+
+    Ok(datoms)
+}
+
+/// Whether `datom` could match any pattern of `query`, treating every
+/// variable as a wildcard.
+pub fn may_match_query(query: &Query, datom: &Datom) -> bool {
+    let entity = Scalar::Entity(datom.entity.clone());
+    let attribute = Scalar::String(datom.attribute.clone());
+
+    query.patterns.iter().any(|pattern| {
+        [
+            (&pattern.entity, &entity),
+            (&pattern.attribute, &attribute),
+            (&pattern.value, &datom.value),
+        ]
+        .into_iter()
+        .all(|(term, value)| match term {
+            Term::Constant(constant) => constant == value,
+            Term::Variable(_) => true,
+        })
+    })
+}
+
+/// A pattern to evaluate, along with the predicates that can be checked
+/// once it has been matched.
+struct Step {
+    pattern: usize,
+    predicates: Vec<usize>,
+}
+
+/// The order in which the patterns of a [Query] are evaluated.
+struct Plan {
+    /// Predicates without variables, checked before any pattern
+    predicates: Vec<usize>,
+    steps: Vec<Step>,
+}
+
+impl Plan {
+    fn new(query: &Query) -> Result<Self, CommonRuntimeError> {
+        if query.patterns.is_empty() {
+            return Err(CommonRuntimeError::InvalidQuery(
+                "A query must have at least one pattern".into(),
+            ));
+        }
+
+        let mut bound = BTreeSet::new();
+        let mut remaining = (0..query.patterns.len()).collect::<Vec<_>>();
+        let mut pending = (0..query.predicates.len()).collect::<Vec<_>>();
+
+        let predicates = take_satisfied(query, &mut pending, &bound);
+        let mut steps = vec![];
+
+        while !remaining.is_empty() {
+            let (position, constrained) = remaining
+                .iter()
+                .enumerate()
+                .map(|(position, pattern)| {
+                    (position, constraints(&query.patterns[*pattern], &bound))
+                })
+                .max_by_key(|(_, constrained)| *constrained)
+                .expect("remaining patterns are not empty");
+
+            let pattern = remaining.remove(position);
+            if constrained == 0 {
+                return Err(CommonRuntimeError::InvalidQuery(format!(
+                    "Pattern {pattern} is not constrained by a constant or a variable bound by another pattern"
+                )));
+            }
+
+            for term in terms(&query.patterns[pattern]) {
+                if let Term::Variable(name) = term {
+                    bound.insert(name.as_str());
+                }
+            }
+
+            steps.push(Step {
+                pattern,
+                predicates: take_satisfied(query, &mut pending, &bound),
+            });
+        }
+
+        if !pending.is_empty() {
+            return Err(CommonRuntimeError::InvalidQuery(
+                "Predicates may only refer to variables bound by a pattern".into(),
+            ));
+        }
+
+        if let Some(variable) = query
+            .find
+            .iter()
+            .find(|variable| !bound.contains(variable.as_str()))
+        {
+            return Err(CommonRuntimeError::InvalidQuery(format!(
+                "Variable '{variable}' is not bound by any pattern"
+            )));
+        }
+
+        Ok(Plan { predicates, steps })
+    }
+}
+
+fn terms(pattern: &Pattern) -> [&Term; 3] {
+    [&pattern.entity, &pattern.attribute, &pattern.value]
+}
+
+/// The number of terms of `pattern` that are known before it is matched.
+fn constraints(pattern: &Pattern, bound: &BTreeSet<&str>) -> usize {
+    terms(pattern)
+        .into_iter()
+        .filter(|term| match term {
+            Term::Constant(_) => true,
+            Term::Variable(name) => bound.contains(name.as_str()),
+        })
+        .count()
+}
+
+/// Remove and return the `pending` predicates whose variables are all `bound`.
+fn take_satisfied(query: &Query, pending: &mut Vec<usize>, bound: &BTreeSet<&str>) -> Vec<usize> {
+    let is_known = |term: &Term| match term {
+        Term::Constant(_) => true,
+        Term::Variable(name) => bound.contains(name.as_str()),
+    };
+    let (satisfied, unsatisfied) = pending.iter().partition(|index| {
+        let predicate = &query.predicates[**index];
+        is_known(&predicate.left) && is_known(&predicate.right)
+    });
+    *pending = unsatisfied;
+    satisfied
+}
+
+fn resolve<'a>(term: &'a Term, bindings: &'a Bindings) -> Option<&'a Scalar> {
+    match term {
+        Term::Constant(constant) => Some(constant),
+        Term::Variable(name) => bindings.get(name),
+    }
+}
+
+/// The [RangeQuery] selecting candidate [Datom]s for `pattern`, or [None]
+/// if its known terms are of a type no [Datom] can match.
+fn range_query(
+    pattern: &Pattern,
+    bindings: &Bindings,
+) -> Result<Option<RangeQuery>, CommonRuntimeError> {
+    let entity = match resolve(&pattern.entity, bindings) {
+        Some(Scalar::Entity(entity)) => Some(entity.clone()),
+        Some(_) => return Ok(None),
+        None => None,
+    };
+    let attribute = match resolve(&pattern.attribute, bindings) {
+        Some(Scalar::String(attribute)) => Some(attribute.clone()),
+        Some(_) => return Ok(None),
+        None => None,
+    };
+    let value = resolve(&pattern.value, bindings).cloned();
+
+    Ok(Some(match (entity, attribute, value) {
+        (Some(entity), attribute, value) => RangeQuery::Entity(EntityRangeQuery {
+            entity,
+            attribute,
+            value,
+        }),
+        (None, Some(attribute), value) => RangeQuery::Attribute(AttributeRangeQuery {
+            entity: None,
+            attribute,
+            value,
+        }),
+        (None, None, Some(value)) => RangeQuery::Value(ValueRangeQuery {
+            entity: None,
+            attribute: None,
+            value,
+        }),
+        (None, None, None) => {
+            return Err(CommonRuntimeError::InvalidQuery(
+                "Pattern is not constrained".into(),
+            ))
+        }
+    }))
+}
+
+/// Extend `bindings` such that `pattern` matches `datom`, if possible.
+fn unify(pattern: &Pattern, datom: &Datom, bindings: &Bindings) -> Option<Bindings> {
+    let mut bindings = bindings.clone();
+    let values = [
+        Scalar::Entity(datom.entity.clone()),
+        Scalar::String(datom.attribute.clone()),
+        datom.value.clone(),
+    ];
+
+    for (term, value) in terms(pattern).into_iter().zip(values) {
+        match term {
+            Term::Constant(constant) if *constant != value => return None,
+            Term::Constant(_) => (),
+            Term::Variable(name) => match bindings.get(name) {
+                Some(bound) if *bound != value => return None,
+                Some(_) => (),
+                None => {
+                    bindings.insert(name.clone(), value);
+                }
+            },
+        }
+    }
+
+    Some(bindings)
+}
+
+fn holds_all(query: &Query, predicates: &[usize], bindings: &Bindings) -> bool {
+    predicates
+        .iter()
+        .all(|index| holds(&query.predicates[*index], bindings))
+}
+
+fn holds(predicate: &Predicate, bindings: &Bindings) -> bool {
+    let (Some(left), Some(right)) = (
+        resolve(&predicate.left, bindings),
+        resolve(&predicate.right, bindings),
+    ) else {
+        return false;
+    };
+
+    match predicate.comparison {
+        Comparison::Equal => left == right,
+        Comparison::NotEqual => left != right,
+        comparison => match (compare(left, right), comparison) {
+            (Some(ordering), Comparison::LessThan) => ordering.is_lt(),
+            (Some(ordering), Comparison::LessThanOrEqual) => ordering.is_le(),
+            (Some(ordering), Comparison::GreaterThan) => ordering.is_gt(),
+            (Some(ordering), Comparison::GreaterThanOrEqual) => ordering.is_ge(),
+            _ => false,
+        },
+    }
+}
+
+/// Order two [Scalar]s of comparable types; integers and floats are
+/// compared numerically.
+fn compare(left: &Scalar, right: &Scalar) -> Option<Ordering> {
+    match (left, right) {
+        (Scalar::Integer(left), Scalar::Integer(right)) => Some(left.cmp(right)),
+        (Scalar::Integer(left), Scalar::Float(right)) => f64::from(*left).partial_cmp(right),
+        (Scalar::Float(left), Scalar::Integer(right)) => left.partial_cmp(&f64::from(*right)),
+        (Scalar::Float(left), Scalar::Float(right)) => left.partial_cmp(right),
+        (Scalar::String(left), Scalar::String(right)) => Some(left.cmp(right)),
+        (Scalar::Boolean(left), Scalar::Boolean(right)) => Some(left.cmp(right)),
+        _ => None,
+    }
+}
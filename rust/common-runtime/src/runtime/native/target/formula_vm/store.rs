@@ -0,0 +1,303 @@
+use crate::CommonRuntimeError;
+use async_stream::try_stream;
+use async_trait::async_trait;
+use common_protos::formula as proto;
+use ct_storage::{CtStorage, Key, MemoryStorage};
+use futures_core::Stream;
+use prost::Message;
+use ranked_prolly_tree::Storage;
+use std::{collections::BTreeMap, ops::RangeInclusive, pin::Pin};
+
+use super::{
+    may_match_query, supporting_datoms, Datom, Entity, Fact, Instruction, RangeQuery, Scalar,
+};
+
+/// A stream of [Datom]s matching a [RangeQuery].
+pub type DatomStream<'a> =
+    Pin<Box<dyn Stream<Item = Result<Datom, CommonRuntimeError>> + Send + 'a>>;
+
+/// A set of asserted and retracted [Fact]s that are committed to a
+/// [DatomStore] together, attributed to a single `cause`.
+#[derive(Clone, Debug)]
+pub struct Transaction {
+    /// The [Entity] recorded as the `cause` of every [Datom] the
+    /// transaction writes
+    pub cause: Entity,
+    /// The [Instruction::Assert]s and [Instruction::Retract]s to apply,
+    /// in order
+    pub instructions: Vec<Instruction>,
+}
+
+impl Transaction {
+    /// Create a [Transaction] applying `instructions` on behalf of `cause`
+    pub fn new(cause: Entity, instructions: Vec<Instruction>) -> Self {
+        Transaction {
+            cause,
+            instructions,
+        }
+    }
+
+    /// Whether the transaction would not change a [DatomStore]
+    pub fn is_empty(&self) -> bool {
+        self.instructions
+            .iter()
+            .all(|instruction| matches!(instruction, Instruction::Import))
+    }
+}
+
+/// A store of [Datom]s that can answer the [RangeQuery]s issued by
+/// `common:formula/virtual-module` modules.
+#[async_trait]
+pub trait DatomStore {
+    /// Stream the [Datom]s currently asserted that match `query`
+    fn query<'a>(&'a self, query: &'a RangeQuery) -> DatomStream<'a>;
+
+    /// Apply a [Transaction] as a single unit: no query observes some,
+    /// but not all, of its instructions
+    async fn commit(&mut self, transaction: Transaction) -> Result<(), CommonRuntimeError>;
+}
+
+/// Whether `datom` falls within `query`.
+///
+/// For [RangeQuery::Query], this is whether `datom` matches any of its
+/// patterns; whether it supports a solution depends on other facts.
+pub fn matches_range_query(query: &RangeQuery, datom: &Datom) -> bool {
+    let (entity, attribute, value) = match query {
+        RangeQuery::Query(query) => return may_match_query(query, datom),
+        RangeQuery::Entity(query) => (
+            Some(&query.entity),
+            query.attribute.as_ref(),
+            query.value.as_ref(),
+        ),
+        RangeQuery::Attribute(query) => (
+            query.entity.as_ref(),
+            Some(&query.attribute),
+            query.value.as_ref(),
+        ),
+        RangeQuery::Value(query) => (
+            query.entity.as_ref(),
+            query.attribute.as_ref(),
+            Some(&query.value),
+        ),
+    };
+    entity.map_or(true, |entity| *entity == datom.entity)
+        && attribute.map_or(true, |attribute| *attribute == datom.attribute)
+        && value.map_or(true, |value| *value == datom.value)
+}
+
+const COMPONENT_LEN: usize = 32;
+const RETRACTED: u8 = 0;
+const ASSERTED: u8 = 1;
+
+/// A [DatomStore] backed by three [CtStorage] trees, each holding every
+/// [Datom] under a different ordering of its components:
+///
+/// * `eav`: entity, attribute, value
+/// * `aev`: attribute, entity, value
+/// * `vae`: value, attribute, entity
+///
+/// Each [RangeQuery] variant is resolved against the ordering whose prefix
+/// it constrains. [CtStorage] does not support removal, so retracted
+/// [Datom]s are kept as tombstones and skipped when queried. A commit
+/// replaces all three orderings at once, so they never diverge.
+pub struct IndexedDatomStore<S> {
+    eav: CtStorage<S>,
+    aev: CtStorage<S>,
+    vae: CtStorage<S>,
+}
+
+impl IndexedDatomStore<MemoryStorage> {
+    /// Create an empty [IndexedDatomStore] held in memory
+    pub fn open_memory() -> Result<Self, CommonRuntimeError> {
+        Ok(IndexedDatomStore::new(
+            open_memory()?,
+            open_memory()?,
+            open_memory()?,
+        ))
+    }
+}
+
+impl<S> IndexedDatomStore<S>
+where
+    S: Storage<Key, Vec<u8>> + Clone + Send + Sync,
+{
+    /// Create an [IndexedDatomStore] from its `eav`, `aev` and `vae`
+    /// orderings, which must hold the same [Datom]s
+    pub fn new(eav: CtStorage<S>, aev: CtStorage<S>, vae: CtStorage<S>) -> Self {
+        IndexedDatomStore { eav, aev, vae }
+    }
+
+    /// The root hashes of the `eav`, `aev` and `vae` orderings, which
+    /// together identify a version of the store
+    pub fn hashes(&self) -> [Option<&[u8]>; 3] {
+        [self.eav.hash(), self.aev.hash(), self.vae.hash()]
+    }
+
+    /// Select the ordering and key range that contain every [Datom]
+    /// matching `query`, unless it is a [RangeQuery::Query], which
+    /// spans several ranges
+    fn resolve(&self, query: &RangeQuery) -> Option<(&CtStorage<S>, RangeInclusive<Key>)> {
+        Some(match query {
+            RangeQuery::Query(_) => return None,
+            RangeQuery::Entity(query) => (
+                &self.eav,
+                prefix_range(
+                    hash(query.entity.id.as_bytes()),
+                    query.attribute.as_ref().map(|a| hash(a.as_bytes())),
+                ),
+            ),
+            RangeQuery::Attribute(query) => (
+                &self.aev,
+                prefix_range(
+                    hash(query.attribute.as_bytes()),
+                    query.entity.as_ref().map(|e| hash(e.id.as_bytes())),
+                ),
+            ),
+            RangeQuery::Value(query) => (
+                &self.vae,
+                prefix_range(
+                    hash_scalar(&query.value),
+                    query.attribute.as_ref().map(|a| hash(a.as_bytes())),
+                ),
+            ),
+        })
+    }
+}
+
+#[async_trait]
+impl<S> DatomStore for IndexedDatomStore<S>
+where
+    S: Storage<Key, Vec<u8>> + Clone + Send + Sync,
+{
+    fn query<'a>(&'a self, query: &'a RangeQuery) -> DatomStream<'a> {
+        let Some((index, range)) = self.resolve(query) else {
+            return Box::pin(try_stream! {
+                if let RangeQuery::Query(query) = query {
+                    for datom in supporting_datoms(self, query).await? {
+                        yield datom;
+                    }
+                }
+            });
+        };
+        Box::pin(try_stream! {
+            let entries = index.stream_range(range).await;
+            for await entry in entries {
+                let entry = entry.map_err(storage_error)?;
+                if let Some(datom) = decode_datom(&entry.value)? {
+                    if matches_range_query(query, &datom) {
+                        yield datom;
+                    }
+                }
+            }
+        })
+    }
+
+    async fn commit(&mut self, transaction: Transaction) -> Result<(), CommonRuntimeError> {
+        // Stage every write before touching the trees so that a malformed
+        // transaction leaves the store unchanged. Later instructions for
+        // the same fact supersede earlier ones.
+        let mut staged = BTreeMap::new();
+        for instruction in transaction.instructions {
+            let (fact, marker) = match instruction {
+                Instruction::Assert(fact) => (fact, ASSERTED),
+                Instruction::Retract(fact) => (fact, RETRACTED),
+                Instruction::Import => {
+                    warn!("Ignoring unsupported import instruction");
+                    continue;
+                }
+            };
+            let components = fact_components(&fact);
+            let record = encode_datom(
+                Datom {
+                    entity: fact.entity,
+                    attribute: fact.attribute,
+                    value: fact.value,
+                    cause: transaction.cause.clone(),
+                },
+                marker,
+            );
+            staged.insert(components, record);
+        }
+
+        // Write to copies of the orderings and publish them together once
+        // every write has succeeded, so that a failed commit leaves all
+        // three unchanged.
+        let mut eav = self.eav.clone();
+        let mut aev = self.aev.clone();
+        let mut vae = self.vae.clone();
+        for ([entity, attribute, value], record) in staged {
+            eav.set(
+                Key::from_components(&entity, &attribute, &value),
+                record.clone(),
+            )
+            .await
+            .map_err(storage_error)?;
+            aev.set(
+                Key::from_components(&attribute, &entity, &value),
+                record.clone(),
+            )
+            .await
+            .map_err(storage_error)?;
+            vae.set(Key::from_components(&value, &attribute, &entity), record)
+                .await
+                .map_err(storage_error)?;
+        }
+
+        self.eav = eav;
+        self.aev = aev;
+        self.vae = vae;
+
+        Ok(())
+    }
+}
+
+fn open_memory() -> Result<CtStorage<MemoryStorage>, CommonRuntimeError> {
+    CtStorage::<MemoryStorage>::open_memory().map_err(storage_error)
+}
+
+fn storage_error(error: ct_storage::Error) -> CommonRuntimeError {
+    CommonRuntimeError::InternalError(format!("Datom storage failed: {error}"))
+}
+
+fn prefix_range(
+    first: [u8; COMPONENT_LEN],
+    second: Option<[u8; COMPONENT_LEN]>,
+) -> RangeInclusive<Key> {
+    match second {
+        Some(second) => Key::ns_range_from_components(&first, &second),
+        None => Key::entity_range_from_components(&first),
+    }
+}
+
+fn fact_components(fact: &Fact) -> [[u8; COMPONENT_LEN]; 3] {
+    [
+        hash(fact.entity.id.as_bytes()),
+        hash(fact.attribute.as_bytes()),
+        hash_scalar(&fact.value),
+    ]
+}
+
+fn hash(bytes: &[u8]) -> [u8; COMPONENT_LEN] {
+    <[u8; COMPONENT_LEN] as From<blake3::Hash>>::from(blake3::hash(bytes))
+}
+
+fn hash_scalar(scalar: &Scalar) -> [u8; COMPONENT_LEN] {
+    hash(&proto::Scalar::from(scalar.clone()).encode_to_vec())
+}
+
+fn encode_datom(datom: Datom, marker: u8) -> Vec<u8> {
+    let mut record = vec![marker];
+    record.extend(proto::Datom::from(datom).encode_to_vec());
+    record
+}
+
+/// Decode a stored record, returning [None] for tombstones.
+fn decode_datom(record: &[u8]) -> Result<Option<Datom>, CommonRuntimeError> {
+    match record.split_first() {
+        Some((&ASSERTED, datom)) => Ok(Some(Datom::try_from(
+            proto::Datom::decode(datom).map_err(|_| CommonRuntimeError::InvalidValue)?,
+        )?)),
+        Some((&RETRACTED, _)) => Ok(None),
+        _ => Err(CommonRuntimeError::InvalidValue),
+    }
+}
@@ -0,0 +1,258 @@
+use crate::{CommonRuntimeError, Module, ModuleInstanceId};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+};
+use tokio::sync::Mutex;
+
+use super::{
+    matches_range_query, Datom, DatomStore, Entity, Fact, FormulaDriver, FormulaRun, Instruction,
+    NativeFormulaVm, RangeQuery, Scalar, State, Transaction,
+};
+
+/// The maximum number of [Transaction]s a single commit may cause to
+/// propagate through subscribed formulas before they are considered to
+/// be cycling.
+pub const MAX_PROPAGATIONS: usize = 1024;
+
+/// A live formula registered with [FormulaSubscriptions].
+struct Subscription {
+    formula: Arc<Mutex<NativeFormulaVm>>,
+    input: Vec<(String, Scalar)>,
+    range_query: RangeQuery,
+    state: State,
+    cause: Entity,
+    /// Every fact the formula has asserted and not since retracted
+    held: Vec<Fact>,
+    /// The facts asserted by the formula's latest `end`
+    ended: Vec<Fact>,
+}
+
+/// How a committed [Transaction] affects a [Subscription].
+enum Delta {
+    /// Nothing matching the subscription's query changed
+    None,
+    /// Only assertions matched; these can be folded into the retained
+    /// [State] with `step`
+    Asserted(Vec<Datom>),
+    /// A matching fact was retracted, which `step` cannot express, or
+    /// the formula's query joins several facts, so the formula must be
+    /// run again from `init`
+    Invalidated,
+}
+
+/// Keeps formulas up to date as the facts they query change.
+///
+/// Each subscribed formula is run once against the [DatomStore], after
+/// which its [RangeQuery] and [State] are retained. Every [Transaction]
+/// committed through [FormulaSubscriptions::commit] is compared against
+/// the retained queries: newly asserted [Datom]s that fall in a formula's
+/// range are fed into `step` (followed by `end`), so only the delta is
+/// evaluated. The [Transaction]s that formulas emit in response are
+/// committed and propagated in turn, until no formula reacts.
+///
+/// The facts a formula derived earlier are retracted once it no longer
+/// derives them: when it is run again from `init`, every fact it held
+/// that it does not assert again is retracted, and when it is updated,
+/// so is every fact asserted by its previous `end` but not its latest.
+///
+/// A formula never reacts to its own [Transaction]s.
+pub struct FormulaSubscriptions<S> {
+    driver: FormulaDriver<S>,
+    subscriptions: BTreeMap<ModuleInstanceId, Subscription>,
+}
+
+impl<S> FormulaSubscriptions<S>
+where
+    S: DatomStore + Send + Sync,
+{
+    /// Create [FormulaSubscriptions] that run formulas with `driver`
+    pub fn new(driver: FormulaDriver<S>) -> Self {
+        FormulaSubscriptions {
+            driver,
+            subscriptions: BTreeMap::new(),
+        }
+    }
+
+    /// The underlying [FormulaDriver]
+    pub fn driver(&self) -> &FormulaDriver<S> {
+        &self.driver
+    }
+
+    /// Whether the formula instance `instance_id` is subscribed
+    pub fn is_subscribed(&self, instance_id: &ModuleInstanceId) -> bool {
+        self.subscriptions.contains_key(instance_id)
+    }
+
+    /// Run `formula` with `input` against the current facts and keep it
+    /// up to date from then on, attributing the facts it asserts and
+    /// retracts to `cause`.
+    ///
+    /// Returns the initial run, along with the [Transaction]s emitted by
+    /// other formulas in reaction to it.
+    pub async fn subscribe(
+        &mut self,
+        formula: Arc<Mutex<NativeFormulaVm>>,
+        input: Vec<(String, Scalar)>,
+        cause: Entity,
+    ) -> Result<(FormulaRun, Vec<Transaction>), CommonRuntimeError> {
+        let (instance_id, run, ended) = {
+            let mut vm = formula.lock().await;
+            let (run, ended) = self
+                .driver
+                .rerun(&mut vm, &input, &[], cause.clone())
+                .await?;
+            (vm.instance_id().clone(), run, ended)
+        };
+        let mut held = vec![];
+        hold(&mut held, &run.transaction);
+
+        self.subscriptions.insert(
+            instance_id,
+            Subscription {
+                formula,
+                input,
+                range_query: run.range_query.clone(),
+                state: run.state.clone(),
+                cause,
+                held,
+                ended,
+            },
+        );
+
+        let reactions = self.propagate(run.transaction.clone()).await?;
+
+        Ok((run, reactions))
+    }
+
+    /// Stop keeping the formula instance `instance_id` up to date,
+    /// returning whether it was subscribed
+    pub fn unsubscribe(&mut self, instance_id: &ModuleInstanceId) -> bool {
+        self.subscriptions.remove(instance_id).is_some()
+    }
+
+    /// Commit `transaction` to the [DatomStore], re-evaluating every
+    /// subscribed formula whose range it touches.
+    ///
+    /// Returns the [Transaction]s that formulas emitted in reaction, in
+    /// the order they were committed.
+    pub async fn commit(
+        &mut self,
+        transaction: Transaction,
+    ) -> Result<Vec<Transaction>, CommonRuntimeError> {
+        self.driver.store_mut().commit(transaction.clone()).await?;
+        self.propagate(transaction).await
+    }
+
+    async fn propagate(
+        &mut self,
+        transaction: Transaction,
+    ) -> Result<Vec<Transaction>, CommonRuntimeError> {
+        let mut pending = VecDeque::from([transaction]);
+        let mut reactions = vec![];
+
+        while let Some(transaction) = pending.pop_front() {
+            for subscription in self.subscriptions.values_mut() {
+                if subscription.cause == transaction.cause {
+                    continue;
+                }
+
+                let mut formula = subscription.formula.lock().await;
+                let reaction = match delta(&subscription.range_query, &transaction) {
+                    Delta::None => continue,
+                    Delta::Asserted(datoms) => {
+                        let (state, reaction, ended) = self
+                            .driver
+                            .update(
+                                &mut formula,
+                                subscription.state.clone(),
+                                datoms,
+                                &subscription.ended,
+                                subscription.cause.clone(),
+                            )
+                            .await?;
+                        subscription.state = state;
+                        subscription.ended = ended;
+                        reaction
+                    }
+                    Delta::Invalidated => {
+                        let (run, ended) = self
+                            .driver
+                            .rerun(
+                                &mut formula,
+                                &subscription.input,
+                                &subscription.held,
+                                subscription.cause.clone(),
+                            )
+                            .await?;
+                        subscription.range_query = run.range_query;
+                        subscription.state = run.state;
+                        subscription.ended = ended;
+                        run.transaction
+                    }
+                };
+                hold(&mut subscription.held, &reaction);
+
+                if !reaction.is_empty() {
+                    if reactions.len() == MAX_PROPAGATIONS {
+                        return Err(CommonRuntimeError::ModuleRunFailed(format!(
+                            "Formulas did not settle after {MAX_PROPAGATIONS} propagations"
+                        )));
+                    }
+                    reactions.push(reaction.clone());
+                    pending.push_back(reaction);
+                }
+            }
+        }
+
+        Ok(reactions)
+    }
+}
+
+/// Determine how `transaction` affects a formula querying `range_query`.
+fn delta(range_query: &RangeQuery, transaction: &Transaction) -> Delta {
+    let mut asserted = vec![];
+
+    for instruction in transaction.instructions.iter() {
+        let (fact, is_assertion) = match instruction {
+            Instruction::Assert(fact) => (fact, true),
+            Instruction::Retract(fact) => (fact, false),
+            Instruction::Import => continue,
+        };
+        let datom = Datom {
+            entity: fact.entity.clone(),
+            attribute: fact.attribute.clone(),
+            value: fact.value.clone(),
+            cause: transaction.cause.clone(),
+        };
+        if !matches_range_query(range_query, &datom) {
+            continue;
+        }
+        if !is_assertion || matches!(range_query, RangeQuery::Query(_)) {
+            return Delta::Invalidated;
+        }
+        asserted.push(datom);
+    }
+
+    if asserted.is_empty() {
+        Delta::None
+    } else {
+        Delta::Asserted(asserted)
+    }
+}
+
+/// Update the facts `held` by a formula with those it asserted and
+/// retracted in `transaction`.
+fn hold(held: &mut Vec<Fact>, transaction: &Transaction) {
+    for instruction in transaction.instructions.iter() {
+        match instruction {
+            Instruction::Assert(fact) => {
+                if !held.contains(fact) {
+                    held.push(fact.clone());
+                }
+            }
+            Instruction::Retract(fact) => held.retain(|held| held != fact),
+            Instruction::Import => {}
+        }
+    }
+}
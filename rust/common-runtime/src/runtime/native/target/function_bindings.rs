@@ -0,0 +1,374 @@
+//! Bindings for the Wasmtime runtime.
+//!
+//! Wasmtime bindings are implemented as traits against
+//! some context that implements [WasiView] in order to
+//! access underlying [WasiCtx] and [ResourceTable].
+//! In order to be generic across context types, we roll
+//! our own [BindingsView] to access Common Tools specific
+//! resources.
+//! A wrapper [BindingsImpl] provides a concrete implementation
+//! to target these implementions, a pattern used by wasmtime's
+//! [WASI bindings][wasi bindings], [WASI HTTP bindings][wasi-http bindings],
+//! and [recommended to others][wasmtime-8764] pursuing similar.
+//!
+//! [wasi bindings]: https://github.com/bytecodealliance/wasmtime/blob/main/crates/wasi-common/src/lib.rs
+//! [wasi-http bindings]: https://github.com/bytecodealliance/wasmtime/blob/main/crates/wasi-http/src/lib.rs
+//! [wasmtime-8764]: https://github.com/bytecodealliance/wasmtime/issues/8764
+
+use crate::{InputOutput, ModuleContext, ModuleContextMut, Value};
+use async_trait::async_trait;
+use wasmtime::component::Resource;
+use wasmtime_wasi::{ResourceTable, WasiCtx, WasiView};
+
+/// Helper function for linking contexts.
+fn type_annotate<T: BindingsView, F>(val: F) -> F
+where
+    F: Fn(&mut T) -> BindingsImpl<&mut T>,
+{
+    val
+}
+
+/// Trait to implement for registering as
+/// a wasmtime context.
+pub trait BindingsView: WasiView + ModuleContext + ModuleContextMut {
+    fn common_table(&self) -> &ResourceTable;
+    fn common_table_mut(&mut self) -> &mut ResourceTable;
+}
+
+struct BindingsImpl<T>(pub T);
+
+impl<T> WasiView for BindingsImpl<T>
+where
+    T: WasiView,
+{
+    fn ctx(&mut self) -> &mut WasiCtx {
+        self.0.ctx()
+    }
+    fn table(&mut self) -> &mut ResourceTable {
+        self.0.table()
+    }
+}
+
+impl<T> BindingsView for BindingsImpl<T>
+where
+    T: BindingsView,
+{
+    fn common_table(&self) -> &ResourceTable {
+        self.0.common_table()
+    }
+    fn common_table_mut(&mut self) -> &mut ResourceTable {
+        self.0.common_table_mut()
+    }
+}
+
+impl<T> BindingsView for &mut T
+where
+    T: BindingsView,
+{
+    fn common_table(&self) -> &ResourceTable {
+        T::common_table(self)
+    }
+    fn common_table_mut(&mut self) -> &mut ResourceTable {
+        T::common_table_mut(self)
+    }
+}
+
+impl<T> ModuleContext for BindingsImpl<T>
+where
+    T: BindingsView,
+{
+    type Io = T::Io;
+
+    fn ifc(&self) -> &common_ifc::Context {
+        self.0.ifc()
+    }
+    fn io(&self) -> &Self::Io {
+        self.0.io()
+    }
+}
+
+impl<T> ModuleContextMut for BindingsImpl<T>
+where
+    T: BindingsView,
+{
+    fn io_mut(&mut self) -> &mut Self::Io {
+        self.0.io_mut()
+    }
+}
+
+/// A reference to the input `key`, or to a member of its
+/// (nested) map value at `path`.
+struct HostReference {
+    key: String,
+    path: Vec<String>,
+}
+
+impl HostReference {
+    /// Read the referenced value from `io`, if present.
+    fn value<Io: InputOutput>(&self, io: &Io) -> Option<Value> {
+        self.path
+            .iter()
+            .try_fold(io.read(&self.key)?, |value, name| match value {
+                Value::Map(mut members) => members.remove(name),
+                _ => None,
+            })
+    }
+}
+
+#[allow(missing_docs)]
+pub mod module {
+    wasmtime::component::bindgen!({
+      world: "module",
+      path: "../../wit/common/function/wit",
+      async: true
+    });
+
+    /// Link resources for `common:function` targets.
+    pub fn add_to_linker<T>(l: &mut wasmtime::component::Linker<T>) -> wasmtime::Result<()>
+    where
+        T: super::BindingsView,
+    {
+        let get = super::type_annotate::<T, _>(|t| super::BindingsImpl(t));
+        // Manually link needed types -- we want to call
+        // `add_to_linker_get_host()` on dependent types,
+        // rather than `Module::add_to_linker()`,
+        // due to bounds on these functions with `BindingsImpl`.
+        common::data::types::add_to_linker_get_host(l, get)?;
+        common::io::state::add_to_linker_get_host(l, get)?;
+        common::function::reflect::add_to_linker_get_host(l, get)?;
+        Ok(())
+    }
+}
+
+#[allow(missing_docs)]
+pub mod vm {
+    wasmtime::component::bindgen!({
+      world: "virtual-module",
+      path: "../../wit/common/function/wit",
+      async: true,
+      with: {
+          "common:function": super::module::common::function,
+          "common:data": super::module::common::data,
+          "common:io": super::module::common::io,
+      }
+    });
+
+    /// Link resources for `common:function/virtual-module` targets.
+    pub fn add_to_linker<T>(l: &mut wasmtime::component::Linker<T>) -> wasmtime::Result<()>
+    where
+        T: super::BindingsView,
+    {
+        let get = super::type_annotate::<T, _>(|t| super::BindingsImpl(t));
+        // Manually link needed types -- we want to call
+        // `add_to_linker_get_host()` on dependent types,
+        // rather than `VirtualModule::add_to_linker()`,
+        // due to bounds on these functions with `BindingsImpl`.
+        common::data::types::add_to_linker_get_host(l, get)?;
+        common::io::state::add_to_linker_get_host(l, get)?;
+        common::function::reflect::add_to_linker_get_host(l, get)?;
+        Ok(())
+    }
+}
+
+use module::common::{
+    self,
+    data::types::{Reference, Value as GuestValue, ValueNode as GuestValueNode},
+};
+
+impl From<GuestValue> for Value {
+    fn from(value: GuestValue) -> Self {
+        match value {
+            GuestValue::String(inner) => Value::String(inner),
+            GuestValue::Number(inner) => Value::Number(inner),
+            GuestValue::Boolean(inner) => Value::Boolean(inner),
+            GuestValue::Buffer(inner) => Value::Buffer(inner),
+            GuestValue::Null => Value::Null,
+            GuestValue::List(tree) | GuestValue::Map(tree) => value_from_tree(&tree, 0),
+        }
+    }
+}
+
+impl From<Value> for GuestValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::String(inner) => GuestValue::String(inner),
+            Value::Boolean(inner) => GuestValue::Boolean(inner),
+            Value::Number(inner) => GuestValue::Number(inner),
+            Value::Buffer(inner) => GuestValue::Buffer(inner),
+            Value::Null => GuestValue::Null,
+            Value::List(_) => {
+                let mut tree = vec![];
+                value_into_tree(value, &mut tree);
+                GuestValue::List(tree)
+            }
+            Value::Map(_) => {
+                let mut tree = vec![];
+                value_into_tree(value, &mut tree);
+                GuestValue::Map(tree)
+            }
+        }
+    }
+}
+
+/// Reassemble the [`Value`] rooted at `index` within a flattened `value-tree`.
+/// Members must appear after their parent in the tree, which rules out cycles;
+/// any member that violates this (or is out of bounds) is read as [`Value::Null`].
+fn value_from_tree(tree: &[GuestValueNode], index: usize) -> Value {
+    let member = |member_index: u32| {
+        let member_index = member_index as usize;
+        if member_index > index && member_index < tree.len() {
+            value_from_tree(tree, member_index)
+        } else {
+            warn!("Ignoring invalid value tree member index {member_index}");
+            Value::Null
+        }
+    };
+
+    match tree.get(index) {
+        Some(GuestValueNode::String(inner)) => Value::String(inner.clone()),
+        Some(GuestValueNode::Number(inner)) => Value::Number(*inner),
+        Some(GuestValueNode::Boolean(inner)) => Value::Boolean(*inner),
+        Some(GuestValueNode::Buffer(inner)) => Value::Buffer(inner.clone()),
+        Some(GuestValueNode::Null) | None => Value::Null,
+        Some(GuestValueNode::List(members)) => {
+            Value::List(members.iter().map(|index| member(*index)).collect())
+        }
+        Some(GuestValueNode::Map(members)) => Value::Map(
+            members
+                .iter()
+                .map(|(key, index)| (key.clone(), member(*index)))
+                .collect(),
+        ),
+    }
+}
+
+/// Flatten a [`Value`] into `tree` in pre-order, returning the index of its node.
+fn value_into_tree(value: Value, tree: &mut Vec<GuestValueNode>) -> u32 {
+    let index = tree.len();
+    tree.push(GuestValueNode::Null);
+
+    let node = match value {
+        Value::String(inner) => GuestValueNode::String(inner),
+        Value::Boolean(inner) => GuestValueNode::Boolean(inner),
+        Value::Number(inner) => GuestValueNode::Number(inner),
+        Value::Buffer(inner) => GuestValueNode::Buffer(inner),
+        Value::Null => GuestValueNode::Null,
+        Value::List(members) => GuestValueNode::List(
+            members
+                .into_iter()
+                .map(|member| value_into_tree(member, tree))
+                .collect(),
+        ),
+        Value::Map(members) => GuestValueNode::Map(
+            members
+                .into_iter()
+                .map(|(key, member)| (key, value_into_tree(member, tree)))
+                .collect(),
+        ),
+    };
+
+    tree[index] = node;
+    index as u32
+}
+
+#[async_trait]
+impl<T> common::io::state::Host for BindingsImpl<T>
+where
+    T: BindingsView,
+{
+    async fn read(&mut self, name: String) -> Option<wasmtime::component::Resource<Reference>> {
+        debug!("common:io/state.read: {name}");
+        self.io().read(&name)?;
+
+        self.common_table_mut()
+            .push(HostReference {
+                key: name,
+                path: vec![],
+            })
+            .map_err(|error| error!("Unable to allocate Reference: {error}"))
+            .ok()
+            .map(|host_reference| Resource::new_own(host_reference.rep()))
+    }
+
+    async fn write(&mut self, name: String, value: GuestValue) -> () {
+        debug!("common:io/state.write: {name}");
+        self.io_mut().write(&name, value.into());
+    }
+}
+
+#[async_trait]
+impl<T> common::data::types::HostReference for BindingsImpl<T>
+where
+    T: BindingsView,
+{
+    /// Dereference a reference to a value
+    /// This call is fallible (for example, if the dereference is not allowed)
+    /// The value may be none (for example, if it is strictly opaque)
+    async fn deref(&mut self, resource: Resource<Reference>) -> Result<Option<GuestValue>, String> {
+        let host_resource = Resource::<HostReference>::new_own(resource.rep());
+
+        let reference = self
+            .common_table()
+            .get(&host_resource)
+            .map_err(|error| format!("{error}"))?;
+        let key = reference.key.clone();
+        let value = reference.value(self.io());
+
+        // Only inputs that are actually dereferenced constrain the labels
+        // of subsequent writes
+        self.io_mut().observe(&key);
+
+        Ok(value.map(|value| value.into()))
+    }
+
+    async fn drop(&mut self, rep: Resource<Reference>) -> wasmtime::Result<()> {
+        let host_resource = Resource::<HostReference>::new_own(rep.rep());
+        self.common_table_mut().delete(host_resource)?;
+        Ok(())
+    }
+
+    async fn read(
+        &mut self,
+        this: wasmtime::component::Resource<Reference>,
+        name: String,
+    ) -> Option<wasmtime::component::Resource<Reference>> {
+        let host_resource = Resource::<HostReference>::new_own(this.rep());
+        let reference = self
+            .common_table()
+            .get(&host_resource)
+            .map_err(|error| error!("Unable to read Reference: {error}"))
+            .ok()?;
+        let mut path = reference.path.clone();
+        path.push(name);
+        let member = HostReference {
+            key: reference.key.clone(),
+            path,
+        };
+
+        // Whether a member exists depends on the input's value
+        self.io_mut().observe(&member.key);
+        member.value(self.io())?;
+
+        self.common_table_mut()
+            .push(member)
+            .map_err(|error| error!("Unable to allocate Reference: {error}"))
+            .ok()
+            .map(|host_reference| Resource::new_own(host_reference.rep()))
+    }
+}
+
+impl<T> common::data::types::Host for BindingsImpl<T> where T: BindingsView {}
+
+#[async_trait]
+impl<T> common::function::reflect::Host for BindingsImpl<T>
+where
+    T: BindingsView,
+{
+    async fn input_keys(&mut self) -> Vec<String> {
+        self.io().input().keys().cloned().collect()
+    }
+
+    async fn output_keys(&mut self) -> Vec<String> {
+        self.io().output_shape().keys().cloned().collect()
+    }
+}
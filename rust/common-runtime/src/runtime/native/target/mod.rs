@@ -0,0 +1,6 @@
+//! Substantive implementations of various Module types for the [crate::NativeRuntime]
+
+pub mod formula_vm;
+pub mod function;
+mod function_bindings;
+pub mod function_vm;
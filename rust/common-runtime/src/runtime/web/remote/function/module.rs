@@ -57,7 +57,7 @@ impl FunctionInterface for WebRemoteFunction {
                 keep_alive: true,
             })
             .await
-            .map_err(|error| CommonRuntimeError::ModuleRunFailed(format!("{error}")))?
+            .map_err(CommonRuntimeError::from_run_status)?
             .into_inner();
 
         Ok(IoData::try_from(output)?)
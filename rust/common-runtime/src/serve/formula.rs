@@ -1,14 +1,18 @@
 use crate::{
-    target::formula_vm::{Datom, NativeFormulaVmContext, ScalarMap},
-    Affinity, CommonRuntimeError, FormulaVmDefinition, LiveModules, ModuleDefinition, ModuleDriver,
-    ModuleFactory, ModuleInstanceId, ModuleManager, NativeRuntime,
+    target::formula_vm::{Datom, NativeFormulaVm, NativeFormulaVmContext, ScalarMap, State},
+    Affinity, BasicIo, CommonRuntimeError, FormulaVmDefinition, LiveModules, ModuleDefinition,
+    ModuleDriver, ModuleFactory, ModuleInstanceId, ModuleManager, NativeRuntime,
 };
+use async_stream::try_stream;
+use common_ifc::{Context as IfcContext, ModuleEnvironment};
 use common_protos::formula::{
     InstantiateFormulaRequest, InstantiateFormulaResponse, RunEndFormulaRequest,
     RunEndFormulaResponse, RunInitFormulaRequest, RunInitFormulaResponse, RunStepFormulaRequest,
-    RunStepFormulaResponse,
+    RunStepFormulaResponse, StreamStepFormulaRequest, StreamStepFormulaResponse,
 };
 use common_wit::Target;
+use futures_core::Stream;
+use futures_util::StreamExt;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
@@ -44,7 +48,10 @@ pub async fn instantiate_formula(
                 .prepare(function_module_definition)
                 .await?;
             let function_module_instance = function_module_factory
-                .instantiate(NativeFormulaVmContext::default())
+                .instantiate(NativeFormulaVmContext::new(
+                    BasicIo::default(),
+                    IfcContext::from((ModuleEnvironment::Server,)),
+                ))
                 .await?;
             live_modules
                 .lock()
@@ -88,6 +95,10 @@ pub async fn run_init_formula(
         }
     };
 
+    live_modules
+        .retain_formula_state(&instance_id, state.clone())
+        .await;
+
     Ok(RunInitFormulaResponse {
         state,
         range_query: Some(range_query.into()),
@@ -104,7 +115,12 @@ pub async fn run_step_formula(
         .get(&instance_id)
         .await
         .ok_or(CommonRuntimeError::UnknownInstanceId(instance_id))?;
-    let state = &request.state;
+    let state = resolve_state(
+        &live_modules,
+        &instance_id,
+        (!request.use_retained_state).then_some(request.state),
+    )
+    .await?;
     let datoms = request
         .datoms
         .into_iter()
@@ -114,7 +130,7 @@ pub async fn run_step_formula(
     let (state, instructions) = match function {
         Function::VirtualFormula(function) => {
             let mut function = function.lock().await;
-            function.step(state, datoms).await?
+            function.step(&state, datoms).await?
         }
         _ => {
             return Err(CommonRuntimeError::InternalError(
@@ -123,6 +139,10 @@ pub async fn run_step_formula(
         }
     };
 
+    live_modules
+        .retain_formula_state(&instance_id, state.clone())
+        .await;
+
     Ok(RunStepFormulaResponse {
         state,
         instructions: instructions.into_iter().map(|i| i.into()).collect(),
@@ -139,11 +159,16 @@ pub async fn run_end_formula(
         .get(&instance_id)
         .await
         .ok_or(CommonRuntimeError::UnknownInstanceId(instance_id))?;
-    let state = &request.state;
+    let state = resolve_state(
+        &live_modules,
+        &instance_id,
+        (!request.use_retained_state).then_some(request.state),
+    )
+    .await?;
     let instructions = match function {
         Function::VirtualFormula(function) => {
             let mut function = function.lock().await;
-            function.end(state).await?
+            function.end(&state).await?
         }
         _ => {
             return Err(CommonRuntimeError::InternalError(
@@ -156,3 +181,97 @@ pub async fn run_end_formula(
         instructions: instructions.into_iter().map(|i| i.into()).collect(),
     })
 }
+
+/// Step a formula through a stream of datom batches, yielding the
+/// instructions emitted for each batch in turn.
+///
+/// The formula's [State] is retained by the server between batches (and
+/// after the stream ends, for a subsequent `RunEndFormula`). Each batch
+/// is only read from `requests` once the instructions for the previous
+/// batch have been consumed, so a slow client applies backpressure.
+pub fn stream_step_formula<R, E>(
+    mut requests: R,
+    live_modules: Arc<Mutex<LiveModules>>,
+) -> impl Stream<Item = Result<StreamStepFormulaResponse, CommonRuntimeError>> + Send
+where
+    R: Stream<Item = Result<StreamStepFormulaRequest, E>> + Unpin + Send,
+    E: std::fmt::Display + Send,
+{
+    try_stream! {
+        let mut session: Option<(ModuleInstanceId, Arc<Mutex<NativeFormulaVm>>, State)> = None;
+
+        while let Some(request) = requests.next().await {
+            let request = request
+                .map_err(|error| CommonRuntimeError::InternalError(format!("{error}")))?;
+
+            let (instance_id, function, state) = match session.take() {
+                Some((instance_id, function, state)) => {
+                    if !request.instance_id.is_empty() && request.instance_id != instance_id.0 {
+                        Err::<(), _>(CommonRuntimeError::InvalidInstantiationParameters(
+                            "A stream may only step a single formula instance".into(),
+                        ))?;
+                    }
+                    (instance_id, function, state)
+                }
+                None => {
+                    let instance_id = ModuleInstanceId(request.instance_id);
+                    let live_modules = live_modules.lock().await;
+                    let function = match live_modules.get(&instance_id).await {
+                        Some(Function::VirtualFormula(function)) => function,
+                        Some(_) => Err(CommonRuntimeError::InternalError(
+                            "Unexpected function type.".into(),
+                        ))?,
+                        None => Err(CommonRuntimeError::UnknownInstanceId(instance_id.clone()))?,
+                    };
+                    let state = resolve_state(&live_modules, &instance_id, request.state).await?;
+                    (instance_id, function, state)
+                }
+            };
+
+            let datoms = request
+                .datoms
+                .into_iter()
+                .map(Datom::try_from)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if datoms.is_empty() {
+                session = Some((instance_id, function, state));
+                continue;
+            }
+
+            let (state, instructions) = function.lock().await.step(&state, datoms).await?;
+
+            live_modules
+                .lock()
+                .await
+                .retain_formula_state(&instance_id, state.clone())
+                .await;
+
+            session = Some((instance_id, function, state));
+
+            yield StreamStepFormulaResponse {
+                instructions: instructions.into_iter().map(|i| i.into()).collect(),
+            };
+        }
+    }
+}
+
+/// Use the [State] provided by a request, falling back to the one
+/// retained for the formula.
+async fn resolve_state(
+    live_modules: &LiveModules,
+    instance_id: &ModuleInstanceId,
+    state: Option<State>,
+) -> Result<State, CommonRuntimeError> {
+    match state {
+        Some(state) => Ok(state),
+        None => live_modules
+            .formula_state(instance_id)
+            .await
+            .ok_or_else(|| {
+                CommonRuntimeError::InvalidInstantiationParameters(format!(
+                    "No state was provided or retained for formula {instance_id}"
+                ))
+            }),
+    }
+}
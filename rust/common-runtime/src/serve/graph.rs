@@ -0,0 +1,80 @@
+use crate::{CommonRuntimeError, IoData, IoShape, ModuleDefinition, Orchestrator};
+use common_graph::{Graph, GraphBuilder};
+use common_protos::runtime::{
+    ModuleGraphEdge, ModuleGraphNode, RunModuleGraphRequest, RunModuleGraphResponse,
+};
+
+/// Run the graph of modules described by the provided [`RunModuleGraphRequest`]
+/// with the provided [`Orchestrator`].
+pub async fn run_module_graph(
+    request: RunModuleGraphRequest,
+    orchestrator: Orchestrator,
+) -> Result<RunModuleGraphResponse, CommonRuntimeError> {
+    let input = IoData::try_from(request.input)?;
+    let graph = module_graph(
+        request.nodes,
+        request.edges,
+        input.keys().cloned().collect(),
+        request.outputs,
+    )?;
+    let output = orchestrator.run(&graph, input).await?;
+
+    Ok(RunModuleGraphResponse {
+        output: output.into(),
+    })
+}
+
+/// Build a [`Graph`] of [`ModuleDefinition`]s from its description.
+fn module_graph(
+    nodes: Vec<ModuleGraphNode>,
+    edges: Vec<ModuleGraphEdge>,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+) -> Result<Graph<ModuleDefinition>, CommonRuntimeError> {
+    let mut builder = GraphBuilder::default()
+        .set_graph_input(inputs)
+        .set_graph_output(outputs);
+
+    for node in nodes {
+        let target = node.target().into();
+        let affinity = node.affinity().into();
+        let module_reference = node.module_reference.ok_or_else(|| {
+            CommonRuntimeError::InvalidModuleGraph(format!(
+                "No module referenced by node '{}'",
+                node.label
+            ))
+        })?;
+        let definition = ModuleDefinition {
+            target,
+            affinity,
+            inputs: IoShape::try_from(node.input_shape)?,
+            outputs: IoShape::try_from(node.output_shape)?,
+            body: module_reference.try_into()?,
+        };
+        let input_ports: Vec<String> = definition.inputs.keys().cloned().collect();
+        let output_ports: Vec<String> = definition.outputs.keys().cloned().collect();
+        builder = builder.node(node.label, definition, input_ports, output_ports);
+    }
+
+    for edge in edges {
+        let (Some(from), Some(to)) = (edge.from, edge.to) else {
+            return Err(CommonRuntimeError::InvalidModuleGraph(
+                "Edges must connect two ports".into(),
+            ));
+        };
+        // An empty node refers to the graph's own inputs and outputs.
+        builder = match (from.node.is_empty(), to.node.is_empty()) {
+            (true, false) => builder.connect_input(from.port, (to.node, to.port))?,
+            (false, true) => builder.connect_output((from.node, from.port), to.port)?,
+            (false, false) => builder.connect((from.node, from.port), (to.node, to.port))?,
+            (true, true) => {
+                return Err(CommonRuntimeError::InvalidModuleGraph(format!(
+                    "Graph input '{}' may not connect directly to graph output '{}'",
+                    from.port, to.port
+                )))
+            }
+        };
+    }
+
+    Ok(builder.build()?)
+}
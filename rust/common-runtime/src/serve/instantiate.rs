@@ -45,9 +45,7 @@ pub async fn instantiate_module(
             let function_module_instance = function_module_factory
                 .instantiate(NativeFunctionContext::new(
                     BasicIo::from_initial_state(default_input, output_shape),
-                    IfcContext {
-                        environment: ModuleEnvironment::Server,
-                    },
+                    IfcContext::from((ModuleEnvironment::Server,)),
                 ))
                 .await?;
             live_modules
@@ -66,9 +64,7 @@ pub async fn instantiate_module(
             let function_module_instance = function_module_factory
                 .instantiate(NativeFunctionVmContext::new(
                     BasicIo::from_initial_state(default_input, output_shape),
-                    IfcContext {
-                        environment: ModuleEnvironment::Server,
-                    },
+                    IfcContext::from((ModuleEnvironment::Server,)),
                 ))
                 .await?;
             live_modules
@@ -5,7 +5,9 @@ use tokio::sync::Mutex;
 
 use crate::{
     target::{
-        formula_vm::NativeFormulaVm, function::NativeFunction, function_vm::NativeFunctionVm,
+        formula_vm::{NativeFormulaVm, State},
+        function::NativeFunction,
+        function_vm::NativeFunctionVm,
     },
     Module, ModuleInstanceId, ModuleManager,
 };
@@ -55,6 +57,20 @@ impl From<NativeFormulaVm> for Function {
 #[derive(Default)]
 pub struct LiveModules {
     functions: Arc<Mutex<BTreeMap<ModuleInstanceId, Function>>>,
+    formula_states: Arc<Mutex<BTreeMap<ModuleInstanceId, State>>>,
+}
+
+impl LiveModules {
+    /// Retain the latest [State] of a live formula, so that clients need
+    /// not send it back with every request
+    pub async fn retain_formula_state(&self, id: &ModuleInstanceId, state: State) {
+        self.formula_states.lock().await.insert(id.clone(), state);
+    }
+
+    /// The [State] most recently retained for a live formula, if any
+    pub async fn formula_state(&self, id: &ModuleInstanceId) -> Option<State> {
+        self.formula_states.lock().await.get(id).cloned()
+    }
 }
 
 #[async_trait]
@@ -73,6 +89,7 @@ impl ModuleManager<Function> for LiveModules {
     }
 
     async fn take(&self, id: &ModuleInstanceId) -> Option<Function> {
+        self.formula_states.lock().await.remove(id);
         self.functions.lock().await.remove(id)
     }
 }
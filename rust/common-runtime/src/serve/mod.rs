@@ -1,9 +1,13 @@
 pub(crate) mod formula;
+pub(crate) mod graph;
 pub(crate) mod instantiate;
 pub(crate) mod run;
 
 mod live_modules;
 pub use live_modules::*;
 
+mod orchestrator;
+pub use orchestrator::*;
+
 mod server;
 pub use server::*;
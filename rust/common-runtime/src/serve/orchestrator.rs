@@ -0,0 +1,245 @@
+use crate::{
+    target::{function::NativeFunctionContext, function_vm::NativeFunctionVmContext},
+    Affinity, BasicIo, CommonRuntimeError, Data, FunctionDefinition, FunctionInterface,
+    FunctionVmDefinition, HasModuleContext, InputOutput, IoData, ModuleContext, ModuleDefinition,
+    ModuleDriver, ModuleFactory, NativeRuntime, RemoteFunctionDefinition, Validated, Value,
+};
+use common_graph::{Graph, ReadyNode};
+use common_ifc::{validate_graph, Context as IfcContext, ModuleEnvironment, Policy};
+use common_protos::{
+    common,
+    runtime::{
+        runtime_client::RuntimeClient, InstantiateModuleRequest, InstantiateModuleResponse,
+        RunModuleRequest, RunModuleResponse,
+    },
+};
+use common_wit::Target;
+use http::Uri;
+use std::{collections::BTreeMap, sync::Arc};
+use tokio::sync::Mutex;
+
+/// The number of modules an [`Orchestrator`] runs at once, by default.
+const DEFAULT_CONCURRENCY: usize = 8;
+
+/// Runs a [`Graph`] of [`ModuleDefinition`]s, routing the [`IoData`]
+/// written by each module to the modules connected to it.
+///
+/// Modules are prepared by the [`NativeRuntime`] when they run locally,
+/// and are instantiated and run on the configured remote Runtime when
+/// their [`Affinity`] is [`Affinity::RemoteOnly`] (or
+/// [`Affinity::PrefersRemote`], if a remote Runtime is configured).
+/// Each module is validated against the [`Policy`] in the
+/// [`ModuleEnvironment`] it is placed in.
+pub struct Orchestrator {
+    runtime: Arc<Mutex<NativeRuntime>>,
+    policy: Arc<Policy>,
+    remote_runtime_address: Option<Uri>,
+    concurrency: usize,
+    environment: ModuleEnvironment,
+}
+
+/// Where an [`Orchestrator`] runs a module.
+enum Placement<'a> {
+    /// On the local [`NativeRuntime`]
+    Local,
+    /// On the remote Runtime at the given address
+    Remote(&'a Uri),
+}
+
+impl Orchestrator {
+    /// Instantiate a new [`Orchestrator`] that validates graphs against
+    /// `policy`; the optional `remote_runtime_address` is used to run
+    /// modules that may not run locally.
+    pub fn new(
+        runtime: Arc<Mutex<NativeRuntime>>,
+        policy: Arc<Policy>,
+        remote_runtime_address: Option<Uri>,
+    ) -> Self {
+        Orchestrator {
+            runtime,
+            policy,
+            remote_runtime_address,
+            concurrency: DEFAULT_CONCURRENCY,
+            environment: ModuleEnvironment::Server,
+        }
+    }
+
+    /// Run at most `concurrency` modules at once.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Validate modules that run locally as running in `environment`
+    /// ([`ModuleEnvironment::Server`] by default). Modules that run on
+    /// the remote Runtime are always validated as running on a
+    /// [`ModuleEnvironment::Server`].
+    pub fn with_environment(mut self, environment: ModuleEnvironment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Run `graph` with `input` as the graph's inputs, returning
+    /// the values written to the graph's outputs.
+    ///
+    /// Every module is first placed according to its [`Affinity`], and
+    /// the labels of `input` are propagated through the graph and
+    /// validated against the [`Policy`] in each module's environment, so
+    /// that no module runs if any module would be rejected.
+    pub async fn run(
+        &self,
+        graph: &Graph<ModuleDefinition>,
+        input: IoData,
+    ) -> Result<IoData, CommonRuntimeError> {
+        for definition in graph.nodes().iter().filter_map(|node| node.inner()) {
+            self.placement(&definition.affinity)?;
+        }
+        let ifc_graph = graph.map(|definition| self.ifc_context(&definition.affinity));
+        validate_graph(
+            &ifc_graph,
+            &self.policy,
+            input
+                .iter()
+                .map(|(key, data)| (key.as_str(), data.label.clone())),
+        )?;
+
+        let orchestrator = self;
+        let output = graph
+            .process_async(
+                input.iter().map(|(key, data)| (key.as_str(), data.clone())),
+                self.concurrency,
+                move |mut ready: ReadyNode<'_, ModuleDefinition, Data<Value>>| async move {
+                    let definition = ready.node().inner().ok_or_else(|| {
+                        CommonRuntimeError::InternalError("Missing module in graph.".into())
+                    })?;
+                    let input = IoData::from(
+                        ready
+                            .inputs()
+                            .iter()
+                            .filter_map(|(port, data)| Some((port.to_string(), data.clone()?)))
+                            .collect::<BTreeMap<_, _>>(),
+                    );
+                    let output = orchestrator.run_module(definition, input).await?;
+                    for (port, data) in ready.outputs_mut().iter_mut() {
+                        *data = output.get(*port).cloned();
+                    }
+                    Ok::<_, CommonRuntimeError>(ready)
+                },
+            )
+            .await??;
+
+        Ok(IoData::from(
+            output.into_inner()[0]
+                .0
+                .iter()
+                .filter_map(|(port, data)| Some((port.to_string(), data.clone()?)))
+                .collect::<BTreeMap<_, _>>(),
+        ))
+    }
+
+    /// Where a module with `affinity` runs.
+    fn placement(&self, affinity: &Affinity) -> Result<Placement<'_>, CommonRuntimeError> {
+        match (affinity, &self.remote_runtime_address) {
+            (Affinity::RemoteOnly | Affinity::PrefersRemote, Some(address)) => {
+                Ok(Placement::Remote(address))
+            }
+            (Affinity::RemoteOnly, None) => Err(CommonRuntimeError::PreparationFailed(
+                "Cannot run remote module; no remote runtime address was configured".to_string(),
+            )),
+            _ => Ok(Placement::Local),
+        }
+    }
+
+    /// The [`IfcContext`] a module with `affinity` runs in.
+    fn ifc_context(&self, affinity: &Affinity) -> IfcContext {
+        match self.placement(affinity) {
+            Ok(Placement::Local) => IfcContext::from((self.environment.clone(),)),
+            Ok(Placement::Remote(_)) | Err(_) => IfcContext::from((ModuleEnvironment::Server,)),
+        }
+    }
+
+    /// Run the module described by `definition` with `input`,
+    /// dispatching it according to its [`Affinity`].
+    async fn run_module(
+        &self,
+        definition: &ModuleDefinition,
+        input: IoData,
+    ) -> Result<IoData, CommonRuntimeError> {
+        let io = BasicIo::new(input, definition.outputs.clone());
+        match self.placement(&definition.affinity)? {
+            Placement::Remote(address) => self.run_remote_module(definition, io, address).await,
+            Placement::Local => self.run_local_module(definition, io).await,
+        }
+    }
+
+    async fn run_local_module(
+        &self,
+        definition: &ModuleDefinition,
+        io: BasicIo,
+    ) -> Result<IoData, CommonRuntimeError> {
+        let ifc = self.ifc_context(&definition.affinity);
+        // Preparing may build the module, so other modules
+        // should not wait on the lock in the meantime
+        let runtime = self.runtime.lock().await.clone();
+        match definition.target {
+            Target::CommonFunction => {
+                let factory = runtime
+                    .prepare(FunctionDefinition::try_from(definition.clone())?)
+                    .await?;
+                let mut function = factory
+                    .instantiate(NativeFunctionContext::new(io.clone(), ifc))
+                    .await?;
+                let validated_io =
+                    Validated::try_from((self.policy.as_ref(), function.context().ifc(), io))?;
+                function.run(validated_io).await
+            }
+            Target::CommonFunctionVm => {
+                let factory = runtime
+                    .prepare(FunctionVmDefinition::try_from(definition.clone())?)
+                    .await?;
+                let mut function = factory
+                    .instantiate(NativeFunctionVmContext::new(io.clone(), ifc))
+                    .await?;
+                let validated_io =
+                    Validated::try_from((self.policy.as_ref(), function.context().ifc(), io))?;
+                function.run(validated_io).await
+            }
+            _ => Err(CommonRuntimeError::InvalidInstantiationParameters(
+                "Unsupported target.".into(),
+            )),
+        }
+    }
+
+    async fn run_remote_module(
+        &self,
+        definition: &ModuleDefinition,
+        io: BasicIo,
+        address: &Uri,
+    ) -> Result<IoData, CommonRuntimeError> {
+        let definition = RemoteFunctionDefinition::try_from(definition.clone())?;
+        let mut client = RuntimeClient::connect(address.to_string()).await?;
+
+        let InstantiateModuleResponse { instance_id } = client
+            .instantiate_module(InstantiateModuleRequest {
+                output_shape: io.output_shape().into(),
+                default_input: io.input().into(),
+                target: common::Target::from(&definition.inner().target).into(),
+                module_reference: Some(definition.inner().body.to_owned().into()),
+            })
+            .await
+            .map_err(|error| CommonRuntimeError::ModuleInstantiationFailed(format!("{error}")))?
+            .into_inner();
+
+        let RunModuleResponse { output } = client
+            .run_module(RunModuleRequest {
+                instance_id,
+                input: io.input().into(),
+                keep_alive: false,
+            })
+            .await
+            .map_err(CommonRuntimeError::from_run_status)?
+            .into_inner();
+
+        IoData::try_from(output)
+    }
+}
@@ -10,6 +10,7 @@ use tokio::sync::Mutex;
 pub async fn run_module(
     request: RunModuleRequest,
     live_modules: Arc<Mutex<LiveModules>>,
+    policy: Arc<Policy>,
 ) -> Result<RunModuleResponse, CommonRuntimeError> {
     let instance_id = ModuleInstanceId(request.instance_id);
     let live_modules = live_modules.lock().await;
@@ -24,7 +25,7 @@ pub async fn run_module(
             .ok_or(CommonRuntimeError::UnknownInstanceId(instance_id))?,
     };
     let input = request.input.try_into()?;
-    let policy = Policy::with_defaults()?;
+    let policy = Policy::clone(&policy);
 
     let output = match function {
         Function::Module(function) => {
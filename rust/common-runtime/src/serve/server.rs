@@ -1,26 +1,35 @@
 use super::LiveModules;
 use crate::NativeRuntime;
 use crate::{
-    formula::{instantiate_formula, run_end_formula, run_init_formula, run_step_formula},
+    formula::{
+        instantiate_formula, run_end_formula, run_init_formula, run_step_formula,
+        stream_step_formula,
+    },
+    graph::run_module_graph,
     run::run_module,
     serve::instantiate::instantiate_module,
-    ArtifactResolver, CommonRuntimeError,
+    ArtifactResolver, CommonRuntimeError, Orchestrator,
 };
 use async_trait::async_trait;
+use common_ifc::Policy;
 use common_protos::{
     formula::{
         InstantiateFormulaRequest, InstantiateFormulaResponse, RunEndFormulaRequest,
         RunEndFormulaResponse, RunInitFormulaRequest, RunInitFormulaResponse,
-        RunStepFormulaRequest, RunStepFormulaResponse,
+        RunStepFormulaRequest, RunStepFormulaResponse, StreamStepFormulaRequest,
+        StreamStepFormulaResponse,
     },
     runtime::{
         runtime_server::{Runtime as RuntimeServerHandlers, RuntimeServer},
-        InstantiateModuleRequest, InstantiateModuleResponse, RunModuleRequest, RunModuleResponse,
+        InstantiateModuleRequest, InstantiateModuleResponse, RunModuleGraphRequest,
+        RunModuleGraphResponse, RunModuleRequest, RunModuleResponse,
     },
     MAX_MESSAGE_SIZE,
 };
+use futures_core::Stream;
+use futures_util::StreamExt;
 use http::{HeaderName, Uri};
-use std::{sync::Arc, time::Duration};
+use std::{pin::Pin, sync::Arc, time::Duration};
 use tokio::{net::TcpListener, sync::Mutex};
 use tonic::{transport::Server as TonicServer, Status};
 use tonic_web::GrpcWebLayer;
@@ -33,6 +42,8 @@ use tower_http::{
 pub struct Server {
     runtime: Arc<Mutex<NativeRuntime>>,
     live_modules: Arc<Mutex<LiveModules>>,
+    policy: Arc<Policy>,
+    remote_runtime_address: Option<Uri>,
 }
 
 impl Server {
@@ -45,8 +56,24 @@ impl Server {
         Ok(Server {
             runtime: Arc::new(Mutex::new(runtime)),
             live_modules: Arc::new(Mutex::new(LiveModules::default())),
+            policy: Arc::new(Policy::with_defaults()?),
+            remote_runtime_address: None,
         })
     }
+
+    /// Validate module runs against `policy` instead
+    /// of [`Policy::with_defaults`].
+    pub fn with_policy(mut self, policy: Policy) -> Self {
+        self.policy = Arc::new(policy);
+        self
+    }
+
+    /// Run the remote-only modules of module graphs on the
+    /// Runtime at `remote_runtime_address`.
+    pub fn with_remote_runtime(mut self, remote_runtime_address: Uri) -> Self {
+        self.remote_runtime_address = Some(remote_runtime_address);
+        self
+    }
 }
 
 #[async_trait]
@@ -70,7 +97,26 @@ impl RuntimeServerHandlers for Server {
         request: tonic::Request<RunModuleRequest>,
     ) -> Result<tonic::Response<RunModuleResponse>, tonic::Status> {
         Ok(tonic::Response::new(
-            run_module(request.into_inner(), self.live_modules.clone()).await?,
+            run_module(
+                request.into_inner(),
+                self.live_modules.clone(),
+                self.policy.clone(),
+            )
+            .await?,
+        ))
+    }
+
+    async fn run_module_graph(
+        &self,
+        request: tonic::Request<RunModuleGraphRequest>,
+    ) -> Result<tonic::Response<RunModuleGraphResponse>, tonic::Status> {
+        let orchestrator = Orchestrator::new(
+            self.runtime.clone(),
+            self.policy.clone(),
+            self.remote_runtime_address.clone(),
+        );
+        Ok(tonic::Response::new(
+            run_module_graph(request.into_inner(), orchestrator).await?,
         ))
     }
 
@@ -106,6 +152,18 @@ impl RuntimeServerHandlers for Server {
         ))
     }
 
+    type StreamStepFormulaStream =
+        Pin<Box<dyn Stream<Item = Result<StreamStepFormulaResponse, tonic::Status>> + Send>>;
+
+    async fn stream_step_formula(
+        &self,
+        request: tonic::Request<tonic::Streaming<StreamStepFormulaRequest>>,
+    ) -> Result<tonic::Response<Self::StreamStepFormulaStream>, tonic::Status> {
+        let responses = stream_step_formula(request.into_inner(), self.live_modules.clone())
+            .map(|response| response.map_err(tonic::Status::from));
+        Ok(tonic::Response::new(Box::pin(responses)))
+    }
+
     async fn run_end_formula(
         &self,
         request: tonic::Request<RunEndFormulaRequest>,
@@ -124,6 +182,9 @@ impl From<CommonRuntimeError> for Status {
             CommonRuntimeError::SandboxCreationFailed(_) => Status::internal(format!("{value}")),
             CommonRuntimeError::ModuleInstantiationFailed(_) => Status::aborted(format!("{value}")),
             CommonRuntimeError::ModuleRunFailed(_) => Status::aborted(format!("{value}")),
+            CommonRuntimeError::ModuleFailure(error) => {
+                ct_protos::common::ModuleError::from(error).into()
+            }
             CommonRuntimeError::InternalError(_) => Status::internal(format!("{value}")),
             CommonRuntimeError::InvalidValue => Status::invalid_argument(format!("{value}")),
             CommonRuntimeError::InvalidModuleId(_) => Status::invalid_argument(format!("{value}")),
@@ -136,6 +197,10 @@ impl From<CommonRuntimeError> for Status {
             CommonRuntimeError::InvalidInstantiationParameters(_) => {
                 Status::invalid_argument(format!("{value}"))
             }
+            CommonRuntimeError::InvalidQuery(_) => Status::invalid_argument(format!("{value}")),
+            CommonRuntimeError::InvalidModuleGraph(_) => {
+                Status::invalid_argument(format!("{value}"))
+            }
             CommonRuntimeError::PolicyRejection(_) => Status::invalid_argument(format!("{value}")),
             CommonRuntimeError::InvalidValueKind(_) => Status::invalid_argument(format!("{value}")),
         }
@@ -154,6 +219,22 @@ pub async fn serve(
     listener: TcpListener,
     builder_address: Option<Uri>,
 ) -> Result<(), CommonRuntimeError> {
+    serve_with_policy(listener, builder_address, Policy::with_defaults()?).await
+}
+
+/// Start the Common Runtime server like [`serve`], validating
+/// module runs against `policy`, e.g. as loaded with [`Policy::from_path`].
+pub async fn serve_with_policy(
+    listener: TcpListener,
+    builder_address: Option<Uri>,
+    policy: Policy,
+) -> Result<(), CommonRuntimeError> {
+    serve_server(listener, Server::new(builder_address)?.with_policy(policy)).await
+}
+
+/// Start the provided Common Runtime [`Server`], listening to incoming
+/// connections on the provided [`TcpListener`]
+pub async fn serve_server(listener: TcpListener, server: Server) -> Result<(), CommonRuntimeError> {
     let incoming_stream = async_stream::stream! {
         loop {
             let (stream, _) = listener.accept().await?;
@@ -161,7 +242,7 @@ pub async fn serve(
         }
     };
 
-    let runtime_server = RuntimeServer::new(Server::new(builder_address)?)
+    let runtime_server = RuntimeServer::new(server)
         .max_encoding_message_size(MAX_MESSAGE_SIZE)
         .max_decoding_message_size(MAX_MESSAGE_SIZE);
 
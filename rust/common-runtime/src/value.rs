@@ -1,5 +1,6 @@
 use crate::CommonRuntimeError;
 use common_protos::common as proto;
+use std::collections::BTreeMap;
 
 /// An intrinsic value type within a Common Runtime
 #[derive(PartialEq, Clone, Debug)]
@@ -12,6 +13,20 @@ pub enum Value {
     Number(f64),
     /// A slab of bytes
     Buffer(Vec<u8>),
+    /// The absence of a value
+    Null,
+    /// An ordered list of values
+    ///
+    /// Note that a [`Data`] wrapping a composite value carries a single
+    /// label that applies to the value as a whole, including all of its
+    /// members.
+    ///
+    /// [`Data`]: crate::Data
+    List(Vec<Value>),
+    /// A mapping of UTF-8 string keys to values
+    ///
+    /// As with [`Value::List`], any label applies to the map as a whole.
+    Map(BTreeMap<String, Value>),
 }
 
 impl Value {
@@ -22,6 +37,9 @@ impl Value {
             Value::Boolean(_) if kind == &ValueKind::Boolean => true,
             Value::Number(_) if kind == &ValueKind::Number => true,
             Value::Buffer(_) if kind == &ValueKind::Buffer => true,
+            Value::Null if kind == &ValueKind::Null => true,
+            Value::List(_) if kind == &ValueKind::List => true,
+            Value::Map(_) if kind == &ValueKind::Map => true,
             _ => false,
         }
     }
@@ -38,6 +56,12 @@ pub enum ValueKind {
     Number,
     /// A slab of bytes
     Buffer,
+    /// The absence of a value
+    Null,
+    /// An ordered list of values
+    List,
+    /// A mapping of UTF-8 string keys to values
+    Map,
 }
 
 impl From<&Value> for ValueKind {
@@ -47,6 +71,9 @@ impl From<&Value> for ValueKind {
             Value::Boolean(_) => ValueKind::Boolean,
             Value::Number(_) => ValueKind::Number,
             Value::Buffer(_) => ValueKind::Buffer,
+            Value::Null => ValueKind::Null,
+            Value::List(_) => ValueKind::List,
+            Value::Map(_) => ValueKind::Map,
         }
     }
 }
@@ -60,6 +87,9 @@ impl TryFrom<&str> for ValueKind {
             "boolean" => ValueKind::Boolean,
             "number" => ValueKind::Number,
             "buffer" => ValueKind::Buffer,
+            "null" => ValueKind::Null,
+            "list" => ValueKind::List,
+            "map" => ValueKind::Map,
             any_other => return Err(CommonRuntimeError::InvalidValueKind(any_other.to_owned())),
         })
     }
@@ -75,6 +105,19 @@ impl TryFrom<proto::Value> for Value {
             proto::value::Variant::Number(number) => Value::Number(number),
             proto::value::Variant::Boolean(boolean) => Value::Boolean(boolean),
             proto::value::Variant::Buffer(buffer) => Value::Buffer(buffer),
+            proto::value::Variant::Null(_) => Value::Null,
+            proto::value::Variant::List(list) => Value::List(
+                list.values
+                    .into_iter()
+                    .map(Value::try_from)
+                    .collect::<Result<_, _>>()?,
+            ),
+            proto::value::Variant::Map(map) => Value::Map(
+                map.entries
+                    .into_iter()
+                    .map(|(key, value)| Ok((key, Value::try_from(value)?)))
+                    .collect::<Result<_, CommonRuntimeError>>()?,
+            ),
         })
     }
 }
@@ -89,6 +132,21 @@ impl std::fmt::Display for Value {
                 Value::Boolean(v) => v.to_string(),
                 Value::Number(v) => v.to_string(),
                 Value::Buffer(v) => format!("Buffer<{}b>", v.len()),
+                Value::Null => "null".into(),
+                Value::List(v) => format!(
+                    "[{}]",
+                    v.iter()
+                        .map(|value| value.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                Value::Map(v) => format!(
+                    "{{{}}}",
+                    v.iter()
+                        .map(|(key, value)| format!("{key}: {value}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
             }
         )
     }
@@ -102,6 +160,16 @@ impl From<Value> for proto::Value {
                 Value::Boolean(number) => proto::value::Variant::Boolean(number),
                 Value::Number(boolean) => proto::value::Variant::Number(boolean),
                 Value::Buffer(buffer) => proto::value::Variant::Buffer(buffer),
+                Value::Null => proto::value::Variant::Null(true),
+                Value::List(list) => proto::value::Variant::List(proto::ValueList {
+                    values: list.into_iter().map(proto::Value::from).collect(),
+                }),
+                Value::Map(map) => proto::value::Variant::Map(proto::ValueMap {
+                    entries: map
+                        .into_iter()
+                        .map(|(key, value)| (key, proto::Value::from(value)))
+                        .collect(),
+                }),
             }),
         }
     }
@@ -114,6 +182,9 @@ impl From<&ValueKind> for proto::ValueKind {
             ValueKind::Boolean => proto::ValueKind::Boolean,
             ValueKind::Number => proto::ValueKind::Number,
             ValueKind::Buffer => proto::ValueKind::Buffer,
+            ValueKind::Null => proto::ValueKind::Null,
+            ValueKind::List => proto::ValueKind::List,
+            ValueKind::Map => proto::ValueKind::Map,
         }
     }
 }
@@ -125,6 +196,9 @@ impl From<proto::ValueKind> for ValueKind {
             proto::ValueKind::Boolean => ValueKind::Boolean,
             proto::ValueKind::Number => ValueKind::Number,
             proto::ValueKind::Buffer => ValueKind::Buffer,
+            proto::ValueKind::Null => ValueKind::Null,
+            proto::ValueKind::List => ValueKind::List,
+            proto::ValueKind::Map => ValueKind::Map,
         }
     }
 }
@@ -158,3 +232,15 @@ impl From<Vec<u8>> for Value {
         Value::Buffer(value)
     }
 }
+
+impl From<Vec<Value>> for Value {
+    fn from(value: Vec<Value>) -> Self {
+        Value::List(value)
+    }
+}
+
+impl From<BTreeMap<String, Value>> for Value {
+    fn from(value: BTreeMap<String, Value>) -> Self {
+        Value::Map(value)
+    }
+}
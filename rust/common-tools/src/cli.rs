@@ -33,5 +33,30 @@ pub enum Command {
         /// Runtime server listens on provided port.
         #[arg(short, long, default_value_t = 8081)]
         port: u16,
+
+        /// Validate module runs against the JSON or TOML
+        /// policy document at this path.
+        #[arg(long)]
+        policy: Option<PathBuf>,
+    },
+
+    /// Work with policy documents.
+    Policy {
+        /// Policy command to execute.
+        #[command(subcommand)]
+        command: PolicyCommand,
+    },
+}
+
+/// `policy` subcommands implementing [clap::Subcommand].
+#[derive(Subcommand)]
+pub enum PolicyCommand {
+    /// Validates a graph document against a policy document.
+    Check {
+        /// Path to a JSON or TOML policy document.
+        policy: PathBuf,
+
+        /// Path to a JSON or TOML graph document.
+        graph: PathBuf,
     },
 }
@@ -1,5 +1,6 @@
-use crate::cli::Cli;
+use crate::cli::{Cli, PolicyCommand};
 use anyhow::{anyhow, Result};
+use common_ifc::{validate_graph, CommonIfcError, GraphDocument};
 use common_protos::{
     common,
     runtime::{self, runtime_client::RuntimeClient},
@@ -21,7 +22,10 @@ pub async fn exec_command(cli: Cli) -> Result<()> {
             port,
             stdin,
         } => run(module_path, port, stdin).await,
-        Serve { port } => serve(port).await,
+        Serve { port, policy } => serve(port, policy).await,
+        Policy {
+            command: PolicyCommand::Check { policy, graph },
+        } => check_policy(policy, graph),
     }
 }
 
@@ -129,15 +133,62 @@ async fn exec_module(
     }
 }
 
+/// Validates the graph document at `graph_path` against the
+/// policy document at `policy_path`, printing any capability use.
+fn check_policy(policy_path: PathBuf, graph_path: PathBuf) -> Result<()> {
+    let policy = common_ifc::Policy::from_path(&policy_path)?;
+    let (graph, inputs) = GraphDocument::from_path(&graph_path)?.into_graph()?;
+
+    match validate_graph(
+        &graph,
+        &policy,
+        inputs
+            .iter()
+            .map(|(name, label)| (name.as_str(), label.clone())),
+    ) {
+        Ok((_, audit_trail)) => {
+            for entry in audit_trail {
+                println!(
+                    "{}: {} lowered {:?} to {:?}",
+                    entry.node, entry.capability, entry.before, entry.after
+                );
+            }
+            println!(
+                "OK: {} satisfies {}",
+                graph_path.display(),
+                policy_path.display()
+            );
+            Ok(())
+        }
+        Err(CommonIfcError::PolicyViolation(source)) => Err(anyhow!(
+            "Input '{}' violates the {:?} policy{}: {}",
+            source.input,
+            source.label_type,
+            source
+                .node
+                .map(|node| format!(" at node '{node}'"))
+                .unwrap_or_default(),
+            source.cause
+        )),
+        Err(error) => Err(error.into()),
+    }
+}
+
 /// Starts a [`common_runtime`] server listening on `runtime_port`,
-/// with a [`common_builder`] server.
-async fn serve(runtime_port: u16) -> Result<()> {
+/// with a [`common_builder`] server, validating module runs
+/// against the policy document at `policy_path`, if any.
+async fn serve(runtime_port: u16, policy_path: Option<PathBuf>) -> Result<()> {
     use common_builder::serve as serve_builder;
-    use common_runtime::serve as serve_runtime;
+    use common_runtime::serve_with_policy as serve_runtime;
     use http::Uri;
     use std::net::SocketAddr;
     use tokio::net::TcpListener;
 
+    let policy = match policy_path {
+        Some(path) => common_ifc::Policy::from_path(path)?,
+        None => common_ifc::Policy::with_defaults()?,
+    };
+
     let builder_listener = TcpListener::bind("127.0.0.1:0").await?;
     let builder_address: Uri = format!("http://{}", builder_listener.local_addr()?).parse()?;
     let builder_task = tokio::task::spawn(serve_builder(builder_listener));
@@ -146,7 +197,11 @@ async fn serve(runtime_port: u16) -> Result<()> {
         .parse()
         .map_err(|_| anyhow::anyhow!("Invalid port: {}", runtime_port))?;
     let runtime_listener = TcpListener::bind(runtime_address).await?;
-    let runtime_task = tokio::task::spawn(serve_runtime(runtime_listener, Some(builder_address)));
+    let runtime_task = tokio::task::spawn(serve_runtime(
+        runtime_listener,
+        Some(builder_address),
+        policy,
+    ));
 
     tokio::select! {
         _ = builder_task => {},
@@ -1,5 +1,10 @@
-use ct_protos::builder::ReadComponentResponse;
+use ct_common::{ContentType, ModuleId};
+use ct_protos::{
+    builder::{ComponentMetadata, ReadComponentResponse},
+    common,
+};
 use deno_emit::BundleEmit;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 pub struct Artifact {
     pub component: String,
@@ -32,3 +37,42 @@ impl From<ReadComponentResponse> for Artifact {
         }
     }
 }
+
+/// Metadata stored alongside an [`Artifact`].
+pub struct ArtifactMetadata {
+    /// When the artifact was built.
+    pub build_time: SystemTime,
+    /// The [`ModuleId`] of the source the artifact was built from.
+    pub source_hash: ModuleId,
+    /// The content type of the source the artifact was built from.
+    pub content_type: ContentType,
+    /// Size of the artifact, including its source map, in bytes.
+    pub size: u64,
+}
+
+impl ArtifactMetadata {
+    /// Milliseconds from the Unix epoch to `build_time`.
+    pub fn build_time_millis(&self) -> u64 {
+        self.build_time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64
+    }
+
+    /// Returns the `SystemTime` `millis` milliseconds after the Unix epoch.
+    pub fn build_time_from_millis(millis: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(millis)
+    }
+}
+
+impl From<(blake3::Hash, ArtifactMetadata)> for ComponentMetadata {
+    fn from((component_id, metadata): (blake3::Hash, ArtifactMetadata)) -> Self {
+        ComponentMetadata {
+            component_id: component_id.to_string(),
+            build_time: metadata.build_time_millis(),
+            source_hash: metadata.source_hash.to_string(),
+            content_type: common::ContentType::from(metadata.content_type).into(),
+            size: metadata.size,
+        }
+    }
+}
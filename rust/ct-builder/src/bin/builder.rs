@@ -11,8 +11,8 @@ pub fn main() {
 #[tokio::main]
 pub async fn main() -> Result<(), ct_builder::Error> {
     use clap::Parser;
-    use ct_builder::serve;
-    use std::net::SocketAddr;
+    use ct_builder::{serve_with_type_checker, PersistedHashStorage, TypeChecker};
+    use std::{net::SocketAddr, path::PathBuf};
     use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
     let subscriber = FmtSubscriber::builder()
@@ -26,16 +26,40 @@ pub async fn main() -> Result<(), ct_builder::Error> {
         /// Set build server to listen on provided port.
         #[arg(short, long, default_value_t = 8082)]
         port: u16,
+
+        /// Persist built components to a database at provided path.
+        /// If not set, components are lost when the server stops.
+        #[arg(long)]
+        storage_path: Option<PathBuf>,
+
+        /// Evict the oldest built components once their total
+        /// size exceeds provided number of bytes.
+        #[arg(long)]
+        max_storage_size: Option<u64>,
+
+        /// Type check modules with the TypeScript compiler at provided path.
+        #[arg(long, default_value = "tsc")]
+        tsc_path: PathBuf,
     }
 
     let cli = Cli::parse();
     let port = cli.port;
+    let mut storage = match cli.storage_path {
+        Some(path) => PersistedHashStorage::open(path)?,
+        None => PersistedHashStorage::temporary()?,
+    };
+    if let Some(max_size) = cli.max_storage_size {
+        storage = storage.with_max_size(max_size);
+    }
+
+    let type_checker = TypeChecker::new(cli.tsc_path)?;
+
     let socket_address: SocketAddr = format!("0.0.0.0:{port}").parse()?;
     let listener = tokio::net::TcpListener::bind(socket_address).await?;
 
     info!("Server listening on {}", socket_address);
 
-    serve(listener).await?;
+    serve_with_type_checker(listener, storage, type_checker).await?;
 
     Ok(())
 }
@@ -2,46 +2,184 @@ use crate::{
     artifact::Artifact,
     error::Error,
     storage::{JsComponentStorage, PersistedHashStorage},
-    JavaScriptBundler,
+    BundleConfig, Diagnostic, JavaScriptBundler, Lockfile, TypeChecker,
 };
 use async_trait::async_trait;
 use blake3::Hash;
-use ct_common::{ContentType, ModuleDefinition};
-use ct_protos::builder::{
-    builder_server::Builder as BuilderProto, BuildComponentRequest, BuildComponentResponse,
-    ReadComponentRequest, ReadComponentResponse,
+use ct_common::{ContentType, ModuleDefinition, ModuleId};
+use ct_protos::{
+    builder::{
+        builder_server::Builder as BuilderProto, BuildComponentRequest, BuildComponentResponse,
+        BundleSourceCodeRequest, BundleSourceCodeResponse, ComponentMetadata,
+        DeleteComponentRequest, DeleteComponentResponse, ListComponentsRequest,
+        ListComponentsResponse, ReadComponentRequest, ReadComponentResponse,
+    },
+    common,
 };
-use std::str::FromStr;
+use std::{collections::HashMap, str::FromStr};
 use tonic::{Request, Response, Status};
 
 pub struct BuildComponentConfig {
     definition: ModuleDefinition,
     bundle_common_imports: bool,
+    lockfile: Lockfile,
+    offline: bool,
+    type_check: bool,
+}
+
+impl BuildComponentConfig {
+    fn from_request(
+        module_definition: Option<common::ModuleDefinition>,
+        bundle_common_imports: bool,
+        lockfile: HashMap<String, String>,
+        offline: bool,
+        type_check: bool,
+    ) -> Result<Self, Error> {
+        Ok(BuildComponentConfig {
+            definition: module_definition
+                .ok_or(Error::BadRequest)?
+                .try_into()
+                .map_err(|_| Error::BadRequest)?,
+            bundle_common_imports,
+            lockfile: lockfile.try_into()?,
+            offline,
+            type_check,
+        })
+    }
+}
+
+impl TryFrom<BuildComponentRequest> for BuildComponentConfig {
+    type Error = Error;
+
+    fn try_from(value: BuildComponentRequest) -> Result<Self, Self::Error> {
+        BuildComponentConfig::from_request(
+            value.module_definition,
+            value.bundle_common_imports,
+            value.lockfile,
+            value.offline,
+            value.type_check,
+        )
+    }
+}
+
+impl TryFrom<BundleSourceCodeRequest> for BuildComponentConfig {
+    type Error = Error;
+
+    fn try_from(value: BundleSourceCodeRequest) -> Result<Self, Self::Error> {
+        BuildComponentConfig::from_request(
+            value.module_definition,
+            value.bundle_common_imports,
+            value.lockfile,
+            value.offline,
+            false,
+        )
+    }
 }
 
 #[derive(Clone)]
 pub struct Builder {
     storage: PersistedHashStorage,
+    type_checker: TypeChecker,
 }
 
 impl Builder {
-    pub fn new(storage: PersistedHashStorage) -> Self {
-        Self { storage }
+    pub fn new(storage: PersistedHashStorage, type_checker: TypeChecker) -> Self {
+        Self {
+            storage,
+            type_checker,
+        }
     }
 
-    pub async fn build(&self, config: BuildComponentConfig) -> Result<Hash, Error> {
-        info!("Building source: {:#?}", config.definition.source);
-        let artifact = match config.definition.content_type {
+    pub async fn bundle(
+        &self,
+        config: &BuildComponentConfig,
+    ) -> Result<(Artifact, Lockfile), Error> {
+        info!("Bundling source: {:#?}", config.definition.source);
+        match config.definition.content_type {
             ContentType::JavaScript => {
-                JavaScriptBundler::bundle_from_bytes_sync(config.definition.source.into()).await?
+                let mut bundle_config = BundleConfig::default()
+                    .with_cache(self.storage.dependency_cache())
+                    .with_lockfile(config.lockfile.clone())
+                    .with_offline(config.offline);
+                if config.bundle_common_imports {
+                    // Shims for `common:` imports are provided
+                    // alongside the module by the host.
+                    bundle_config =
+                        bundle_config.with_common_shims(config.definition.dependencies.clone());
+                }
+                JavaScriptBundler::bundle_from_bytes_sync(
+                    config.definition.source.clone().into(),
+                    bundle_config,
+                )
+                .await
+            }
+            // Python sources are interpreted as-is and are not bundled.
+            ContentType::Python => Ok((
+                Artifact {
+                    component: config.definition.source.clone(),
+                    source_map: None,
+                },
+                Lockfile::default(),
+            )),
+        }
+    }
+
+    pub async fn type_check(
+        &self,
+        config: &BuildComponentConfig,
+    ) -> Result<Vec<Diagnostic>, Error> {
+        match (config.type_check, &config.definition.content_type) {
+            (true, ContentType::JavaScript) => {
+                self.type_checker
+                    .check(&config.definition.source, &config.definition.dependencies)
+                    .await
             }
-        };
-        self.storage.write(artifact).await
+            (true, ContentType::Python) => Err(Error::InvalidModule(
+                "Type checking is only supported for JavaScript modules".into(),
+            )),
+            (false, _) => Ok(vec![]),
+        }
+    }
+
+    pub async fn build(
+        &self,
+        config: BuildComponentConfig,
+    ) -> Result<(Hash, Lockfile, Vec<Diagnostic>), Error> {
+        // Bundling first reports unresolvable modules
+        // without spending a run of the type checker.
+        let (artifact, lockfile) = self.bundle(&config).await?;
+        let diagnostics = self.type_check(&config).await?;
+        let id = self
+            .storage
+            .write(
+                artifact,
+                ModuleId::from(&config.definition),
+                config.definition.content_type,
+            )
+            .await?;
+        Ok((id, lockfile, diagnostics))
     }
 
     pub async fn read(&self, hash: Hash) -> Result<Artifact, Error> {
         self.storage.read(&hash).await?.ok_or(Error::ModuleNotFound)
     }
+
+    pub async fn list(&self) -> Result<Vec<ComponentMetadata>, Error> {
+        Ok(self
+            .storage
+            .list()
+            .await?
+            .into_iter()
+            .map(ComponentMetadata::from)
+            .collect())
+    }
+
+    pub async fn delete(&self, hash: Hash) -> Result<(), Error> {
+        match self.storage.delete(&hash).await? {
+            true => Ok(()),
+            false => Err(Error::ModuleNotFound),
+        }
+    }
 }
 
 #[async_trait]
@@ -50,22 +188,24 @@ impl BuilderProto for Builder {
         &self,
         request: Request<BuildComponentRequest>,
     ) -> Result<Response<BuildComponentResponse>, Status> {
-        let request = request.into_inner();
-        let definition: ModuleDefinition = request
-            .module_definition
-            .ok_or(Error::BadRequest)?
-            .try_into()
-            .map_err(|_| Error::BadRequest)?;
-
-        let id = self
-            .build(BuildComponentConfig {
-                definition,
-                bundle_common_imports: false,
-            })
-            .await?;
+        let (id, lockfile, diagnostics) = self.build(request.into_inner().try_into()?).await?;
 
         Ok(Response::new(BuildComponentResponse {
             component_id: id.to_string(),
+            lockfile: lockfile.into(),
+            diagnostics: diagnostics.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn bundle_source_code(
+        &self,
+        request: Request<BundleSourceCodeRequest>,
+    ) -> Result<Response<BundleSourceCodeResponse>, Status> {
+        let (artifact, lockfile) = self.bundle(&request.into_inner().try_into()?).await?;
+        Ok(Response::new(BundleSourceCodeResponse {
+            bundled_source_code: artifact.component,
+            source_map: artifact.source_map,
+            lockfile: lockfile.into(),
         }))
     }
 
@@ -79,6 +219,26 @@ impl BuilderProto for Builder {
         let artifact = self.read(hash).await?;
         Ok(Response::new(ReadComponentResponse::from(artifact)))
     }
+
+    async fn list_components(
+        &self,
+        _request: Request<ListComponentsRequest>,
+    ) -> Result<Response<ListComponentsResponse>, Status> {
+        Ok(Response::new(ListComponentsResponse {
+            components: self.list().await?,
+        }))
+    }
+
+    async fn delete_component(
+        &self,
+        request: Request<DeleteComponentRequest>,
+    ) -> Result<Response<DeleteComponentResponse>, Status> {
+        let request = request.into_inner();
+        let hash = Hash::from_str(&request.component_id)
+            .map_err(|error| Status::invalid_argument(format!("Could not parse ID: {error}")))?;
+        self.delete(hash).await?;
+        Ok(Response::new(DeleteComponentResponse {}))
+    }
 }
 
 impl From<Error> for Status {
@@ -7,34 +7,97 @@ use deno_emit::{
     ModuleSpecifier, SourceMapOption, TranspileOptions,
 };
 use deno_graph::source::LoadResponse;
-use reqwest::Client;
+use reqwest::{header::CONTENT_TYPE, Client};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
 use url::Url;
 
-use crate::{artifact::Artifact, Error};
+use crate::{artifact::Artifact, CachedModule, DependencyCache, Error, Lockfile};
 
 // Root module must have `.tsx` in order to be
 // interprete as Typescript/JSX.
 const ROOT_MODULE_URL: &str = "bundler:root.tsx";
 const ROOT_MODULE_SCHEME: &str = "bundler";
+// Provided by the VM to every module, so never inlined.
+const HOST_CALLBACK_SPECIFIER: &str = "common:basic/host-callback@0.0.1";
+
+/// Configures how a [`JavaScriptBundler`] resolves
+/// remote (`http(s)`) imports.
+#[derive(Clone, Default)]
+pub struct BundleConfig {
+    cache: Option<DependencyCache>,
+    lockfile: Lockfile,
+    offline: bool,
+    common_shims: Option<BTreeMap<String, String>>,
+}
+
+impl BundleConfig {
+    /// Read and write fetched modules in `cache`.
+    pub fn with_cache(mut self, cache: DependencyCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Verify the content of imported modules against `lockfile`.
+    /// Locked modules are read from the cache when present. Unless
+    /// `lockfile` is empty, remote imports missing from it are rejected.
+    pub fn with_lockfile(mut self, lockfile: Lockfile) -> Self {
+        self.lockfile = lockfile;
+        self
+    }
+
+    /// If `offline` is `true`, never fetch modules,
+    /// and build only from the cache.
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Inline `shims`, sources keyed by their `common:` specifier, in
+    /// place of `common:` imports, which are otherwise left external.
+    /// Importing a `common:` module without a shim then fails, except
+    /// for the host callback provided by the VM.
+    pub fn with_common_shims(mut self, shims: BTreeMap<String, String>) -> Self {
+        self.common_shims = Some(shims);
+        self
+    }
+}
 
 struct JavaScriptLoader {
     root: Option<Vec<u8>>,
     client: Client,
+    config: BundleConfig,
+    resolved: Arc<Mutex<Lockfile>>,
 }
 
 impl JavaScriptLoader {
-    pub fn new(root: Option<Vec<u8>>) -> Self {
+    pub fn new(root: Option<Vec<u8>>, config: BundleConfig) -> Self {
         Self {
             root,
             client: Client::new(),
+            config,
+            resolved: Arc::new(Mutex::new(Lockfile::default())),
         }
     }
+
+    /// Returns a [`Lockfile`] of the remote modules loaded so far.
+    pub fn lockfile(&self) -> Result<Lockfile, Error> {
+        Ok(self
+            .resolved
+            .lock()
+            .map_err(|error| Error::Internal(format!("{error}")))?
+            .clone())
+    }
 }
 
 impl Loader for JavaScriptLoader {
     fn load(&self, specifier: &ModuleSpecifier, _options: LoadOptions) -> LoadFuture {
         let root = self.root.clone();
         let client = self.client.clone();
+        let config = self.config.clone();
+        let resolved = self.resolved.clone();
         let specifier = specifier.clone();
 
         debug!("Attempting to load '{}'", specifier);
@@ -53,32 +116,91 @@ impl Loader for JavaScriptLoader {
                     specifier,
                     maybe_headers: None,
                 })),
-                "common" => Ok(Some(LoadResponse::External {
-                    specifier: specifier.clone(),
-                })),
+                "common" => match &config.common_shims {
+                    Some(shims) if specifier.as_str() != HOST_CALLBACK_SPECIFIER => {
+                        let shim = shims.get(specifier.as_str()).ok_or_else(|| {
+                            anyhow!("Could not import '{specifier}'. No shim was provided.")
+                        })?;
+                        Ok(Some(LoadResponse::Module {
+                            content: shim.as_bytes().to_vec().into(),
+                            specifier,
+                            // `common:` specifiers have no extension to
+                            // infer a media type from.
+                            maybe_headers: Some(
+                                [(
+                                    CONTENT_TYPE.to_string(),
+                                    "application/typescript".to_string(),
+                                )]
+                                .into(),
+                            ),
+                        }))
+                    }
+                    _ => Ok(Some(LoadResponse::External {
+                        specifier: specifier.clone(),
+                    })),
+                },
                 "http" | "https" => {
-                    let response = client.get(specifier.as_str()).send().await?;
-                    let headers = response.headers().to_owned();
-                    let bytes = response.bytes().await?;
-                    let content = bytes.to_vec().into();
-
-                    trace!("Loaded remote module: {}", String::from_utf8_lossy(&bytes));
+                    let locked = config.lockfile.get(specifier.as_str()).copied();
+                    if locked.is_none() && !config.lockfile.is_empty() {
+                        return Err(anyhow!(
+                            "Could not import '{specifier}'. Module is missing from the lockfile."
+                        ));
+                    }
+                    let cached = match &config.cache {
+                        // Unlocked modules are fetched again when
+                        // online, as their content may have changed.
+                        Some(cache) if config.offline || locked.is_some() => {
+                            cache.read(specifier.as_str(), locked.as_ref())?
+                        }
+                        _ => None,
+                    };
+
+                    let module = match cached {
+                        Some(module) => module,
+                        None if config.offline => {
+                            return Err(anyhow!(
+                                "Could not import '{specifier}'. Module is not cached, and bundling is offline."
+                            ))
+                        }
+                        None => {
+                            let module = fetch_module(&client, &specifier).await?;
+                            if let Some(cache) = &config.cache {
+                                cache.write(
+                                    specifier.as_str(),
+                                    &module.content,
+                                    module.content_type.as_deref(),
+                                )?;
+                            }
+                            module
+                        }
+                    };
+
+                    if let Some(expected) = locked {
+                        if expected != module.hash {
+                            return Err(anyhow!(
+                                "Could not import '{specifier}'. Integrity check failed: expected {expected}, found {}.",
+                                module.hash
+                            ));
+                        }
+                    }
+
+                    resolved
+                        .lock()
+                        .map_err(|error| anyhow!("{error}"))?
+                        .insert(specifier.to_string(), module.hash);
+
+                    trace!(
+                        "Loaded remote module: {}",
+                        String::from_utf8_lossy(&module.content)
+                    );
                     Ok(Some(LoadResponse::Module {
-                        content,
+                        content: module.content.into(),
                         specifier,
-                        maybe_headers: Some(
-                            headers
-                                .into_iter()
-                                .filter_map(|(h, v)| {
-                                    h.map(|header| {
-                                        (
-                                            header.to_string(),
-                                            v.to_str().unwrap_or_default().to_string(),
-                                        )
-                                    })
-                                })
-                                .collect(),
-                        ),
+                        // Only the content type is kept, so that cached
+                        // modules load the same as fetched ones.
+                        maybe_headers: module
+                            .content_type
+                            .map(|content_type| [(CONTENT_TYPE.to_string(), content_type)].into()),
                     }))
                 }
                 "node" | "npm" => Err(anyhow!(
@@ -92,6 +214,25 @@ impl Loader for JavaScriptLoader {
     }
 }
 
+/// Fetch the remote module at `specifier`.
+async fn fetch_module(
+    client: &Client,
+    specifier: &ModuleSpecifier,
+) -> Result<CachedModule, anyhow::Error> {
+    let response = client.get(specifier.as_str()).send().await?;
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_owned());
+    let content = response.bytes().await?.to_vec();
+    Ok(CachedModule {
+        hash: blake3::hash(&content),
+        content,
+        content_type,
+    })
+}
+
 /// A namespace for functions that resolves a JavaScript source
 /// file's dependencies and bundles into a single artifact.
 pub struct JavaScriptBundler {}
@@ -105,7 +246,7 @@ impl JavaScriptBundler {
                 source_map: SourceMapOption::Separate,
                 source_map_file: None,
                 source_map_base: None,
-                inline_sources: false,
+                inline_sources: true,
                 remove_comments: true,
             },
             emit_ignore_directives: false,
@@ -115,14 +256,37 @@ impl JavaScriptBundler {
 
     /// Bundle a JavaScript module via URL.
     pub async fn bundle_from_url(url: Url) -> Result<Artifact, Error> {
-        let mut loader = JavaScriptLoader::new(None);
+        let (artifact, _) = Self::bundle_from_url_with_config(url, BundleConfig::default()).await?;
+        Ok(artifact)
+    }
+
+    /// Bundle a JavaScript module via URL, resolving remote imports
+    /// as configured by `config`. Returns the bundle along with a
+    /// [`Lockfile`] of the remote modules it includes.
+    pub async fn bundle_from_url_with_config(
+        url: Url,
+        config: BundleConfig,
+    ) -> Result<(Artifact, Lockfile), Error> {
+        let mut loader = JavaScriptLoader::new(None, config);
         let emit = bundle(url, &mut loader, None, Self::bundle_options()).await?;
-        Ok(emit.into())
+        Ok((emit.into(), loader.lockfile()?))
     }
 
     /// Bundle a JavaScript module from bytes.
     pub async fn bundle_from_bytes(module: Vec<u8>) -> Result<Artifact, Error> {
-        let mut loader = JavaScriptLoader::new(Some(module));
+        let (artifact, _) =
+            Self::bundle_from_bytes_with_config(module, BundleConfig::default()).await?;
+        Ok(artifact)
+    }
+
+    /// Bundle a JavaScript module from bytes, resolving remote imports
+    /// as configured by `config`. Returns the bundle along with a
+    /// [`Lockfile`] of the remote modules it includes.
+    pub async fn bundle_from_bytes_with_config(
+        module: Vec<u8>,
+        config: BundleConfig,
+    ) -> Result<(Artifact, Lockfile), Error> {
+        let mut loader = JavaScriptLoader::new(Some(module), config);
         let emit = bundle(
             Url::parse(ROOT_MODULE_URL).map_err(|error| Error::Internal(format!("{error}")))?,
             &mut loader,
@@ -130,16 +294,20 @@ impl JavaScriptBundler {
             Self::bundle_options(),
         )
         .await?;
-        Ok(emit.into())
+        Ok((emit.into(), loader.lockfile()?))
     }
 
     /// Spawns a blocking bundle operation on a thread dedicated to blocking
     /// operations. This is needed in cases where bundling is taking place e.g.,
     /// within a web server.
-    pub async fn bundle_from_bytes_sync(source_code: Vec<u8>) -> Result<Artifact, Error> {
+    pub async fn bundle_from_bytes_sync(
+        source_code: Vec<u8>,
+        config: BundleConfig,
+    ) -> Result<(Artifact, Lockfile), Error> {
         tokio::task::spawn_blocking(move || {
-            tokio::runtime::Handle::current()
-                .block_on(JavaScriptBundler::bundle_from_bytes(source_code))
+            tokio::runtime::Handle::current().block_on(
+                JavaScriptBundler::bundle_from_bytes_with_config(source_code, config),
+            )
         })
         .await?
     }
@@ -147,10 +315,11 @@ impl JavaScriptBundler {
 
 #[cfg(test)]
 pub mod tests {
-    use crate::JavaScriptBundler;
+    use crate::{BundleConfig, DependencyCache, JavaScriptBundler, Lockfile};
     use anyhow::Result;
     use ct_test_fixtures::EsmTestServer;
     use ct_tracing::ct_tracing;
+    use std::collections::BTreeMap;
     use url::Url;
 
     fn assert_math_bundle(bundle: &str) {
@@ -238,4 +407,156 @@ console.log(read, write);
 
         Ok(())
     }
+
+    #[tokio::test]
+    #[ct_tracing]
+    async fn it_bundles_from_the_dependency_cache_when_offline() -> Result<()> {
+        let cache = DependencyCache::temporary()?;
+        let mut server = EsmTestServer::default();
+        let addr = server.start().await?;
+        let candidate = Url::parse(&format!("http://{}/math/index.js", addr))?;
+        let (online_bundle, lockfile) = JavaScriptBundler::bundle_from_url_with_config(
+            candidate.clone(),
+            BundleConfig::default().with_cache(cache.clone()),
+        )
+        .await?;
+
+        assert!(lockfile.get(candidate.as_str()).is_some());
+
+        server.stop();
+
+        let (offline_bundle, offline_lockfile) = JavaScriptBundler::bundle_from_url_with_config(
+            candidate,
+            BundleConfig::default()
+                .with_cache(cache)
+                .with_lockfile(lockfile.clone())
+                .with_offline(true),
+        )
+        .await?;
+
+        assert_math_bundle(&offline_bundle.component);
+        assert_eq!(offline_bundle.component, online_bundle.component);
+        assert_eq!(offline_lockfile, lockfile);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ct_tracing]
+    async fn it_fails_offline_when_modules_are_not_cached() -> Result<()> {
+        let mut server = EsmTestServer::default();
+        let addr = server.start().await?;
+        let candidate = Url::parse(&format!("http://{}/math/index.js", addr))?;
+
+        assert!(JavaScriptBundler::bundle_from_url_with_config(
+            candidate,
+            BundleConfig::default()
+                .with_cache(DependencyCache::temporary()?)
+                .with_offline(true),
+        )
+        .await
+        .is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ct_tracing]
+    async fn it_rejects_modules_that_fail_the_integrity_check() -> Result<()> {
+        let mut server = EsmTestServer::default();
+        let addr = server.start().await?;
+        let candidate = Url::parse(&format!("http://{}/math/index.js", addr))?;
+        let lockfile = Lockfile::from(
+            [(candidate.to_string(), blake3::hash(b"export {};"))]
+                .into_iter()
+                .collect::<BTreeMap<_, _>>(),
+        );
+
+        assert!(JavaScriptBundler::bundle_from_url_with_config(
+            candidate,
+            BundleConfig::default().with_lockfile(lockfile),
+        )
+        .await
+        .is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ct_tracing]
+    async fn it_rejects_modules_missing_from_the_lockfile() -> Result<()> {
+        let mut server = EsmTestServer::default();
+        let addr = server.start().await?;
+        let candidate = Url::parse(&format!("http://{}/math/index.js", addr))?;
+        let lockfile = Lockfile::from(
+            [(
+                format!("http://{}/math/other.js", addr),
+                blake3::hash(b"export {};"),
+            )]
+            .into_iter()
+            .collect::<BTreeMap<_, _>>(),
+        );
+
+        assert!(JavaScriptBundler::bundle_from_url_with_config(
+            candidate,
+            BundleConfig::default().with_lockfile(lockfile),
+        )
+        .await
+        .is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ct_tracing]
+    async fn it_inlines_common_shims_when_bundling() -> Result<()> {
+        let candidate = r#"
+import { read } from "common:io/state@0.0.1";
+
+export const run = () => read("foo");
+"#
+        .to_string();
+        let shims = BTreeMap::from([(
+            "common:io/state@0.0.1".to_string(),
+            r#"
+import { callback } from "common:basic/host-callback@0.0.1";
+
+export const read = (name: string) => callback(JSON.stringify({ read: name }));
+"#
+            .to_string(),
+        )]);
+
+        let (bundle, _) = JavaScriptBundler::bundle_from_bytes_with_config(
+            candidate.into(),
+            BundleConfig::default().with_common_shims(shims),
+        )
+        .await?;
+
+        assert!(!bundle.component.contains("common:io/state@0.0.1"));
+        assert!(bundle
+            .component
+            .contains("import { callback } from \"common:basic/host-callback@0.0.1\""));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ct_tracing]
+    async fn it_fails_to_inline_common_imports_without_shims() -> Result<()> {
+        let candidate = r#"
+import { read } from "common:io/state@0.0.1";
+
+export const run = () => read("foo");
+"#
+        .to_string();
+
+        assert!(JavaScriptBundler::bundle_from_bytes_with_config(
+            candidate.into(),
+            BundleConfig::default().with_common_shims(BTreeMap::default()),
+        )
+        .await
+        .is_err());
+
+        Ok(())
+    }
 }
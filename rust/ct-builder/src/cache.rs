@@ -0,0 +1,154 @@
+use crate::{storage::Hash, Error};
+use redb::{Database, ReadableTable, TableDefinition};
+use std::sync::Arc;
+use tempfile::NamedTempFile;
+
+const DEPENDENCY_TABLE: TableDefinition<&str, (Vec<u8>, Option<&str>)> =
+    TableDefinition::new("dependencies");
+const DEPENDENCY_URL_TABLE: TableDefinition<&str, &str> = TableDefinition::new("dependency_urls");
+
+/// A remote module read from a [`DependencyCache`].
+#[derive(Clone, Debug)]
+pub struct CachedModule {
+    /// [`Hash`] of the module's content.
+    pub hash: Hash,
+    /// The module's source.
+    pub content: Vec<u8>,
+    /// `Content-Type` header the module was served with, if any.
+    pub content_type: Option<String>,
+}
+
+/// Content-addressed cache of the remote modules imported
+/// by bundled sources, enabling offline builds.
+///
+/// Module content and the `Content-Type` it was served with are
+/// keyed by the content's [`Hash`], and each URL refers to the
+/// content most recently fetched from it.
+#[derive(Clone)]
+pub struct DependencyCache {
+    db: Arc<Database>,
+    _temp_file: Option<Arc<NamedTempFile>>,
+}
+
+impl DependencyCache {
+    /// Create a new [DependencyCache] backed by a temporary directory.
+    pub fn temporary() -> Result<Self, Error> {
+        info!("Initializing temporary dependency cache");
+        let temp_file = Arc::new(NamedTempFile::new()?);
+        let db = Arc::new(Database::create(temp_file.path())?);
+        Self::from_database(db, Some(temp_file))
+    }
+
+    /// Create a [DependencyCache] in `db`, which may be
+    /// shared with other storage.
+    pub(crate) fn from_database(
+        db: Arc<Database>,
+        temp_file: Option<Arc<NamedTempFile>>,
+    ) -> Result<Self, Error> {
+        {
+            let tx = db.begin_write()?;
+            let _ = tx.open_table(DEPENDENCY_TABLE)?;
+            let _ = tx.open_table(DEPENDENCY_URL_TABLE)?;
+            tx.commit()?;
+        }
+
+        Ok(Self {
+            db,
+            _temp_file: temp_file,
+        })
+    }
+
+    /// Total size in bytes of the modules cached in `tx`.
+    pub(crate) fn size(tx: &redb::WriteTransaction) -> Result<u64, Error> {
+        let table = tx.open_table(DEPENDENCY_TABLE)?;
+        let mut size = 0;
+        for entry in table.iter()? {
+            let (_, module) = entry?;
+            size += module.value().0.len() as u64;
+        }
+        Ok(size)
+    }
+
+    /// Read the module cached for `url`. If `hash` is provided, the
+    /// content with that hash is read instead of the content most
+    /// recently fetched from `url`.
+    pub fn read(&self, url: &str, hash: Option<&Hash>) -> Result<Option<CachedModule>, Error> {
+        debug!(url, ?hash, "Read dependency");
+        let tx = self.db.begin_read()?;
+
+        let key = match hash {
+            Some(hash) => hash.to_string(),
+            None => {
+                let table = tx.open_table(DEPENDENCY_URL_TABLE)?;
+                match table.get(url)? {
+                    Some(hash) => hash.value().to_owned(),
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        let table = tx.open_table(DEPENDENCY_TABLE)?;
+        Ok(table.get(key.as_str())?.map(|entry| {
+            let (content, content_type) = entry.value();
+            CachedModule {
+                hash: blake3::hash(&content),
+                content,
+                content_type: content_type.map(|content_type| content_type.to_owned()),
+            }
+        }))
+    }
+
+    /// Write `content` fetched from `url` to the cache,
+    /// returning its [`Hash`].
+    pub fn write(
+        &self,
+        url: &str,
+        content: &[u8],
+        content_type: Option<&str>,
+    ) -> Result<Hash, Error> {
+        let hash = blake3::hash(content);
+        let key = hash.to_string();
+        debug!(url, %key, "Write dependency");
+        let tx = self.db.begin_write()?;
+        {
+            let mut table = tx.open_table(DEPENDENCY_TABLE)?;
+            table.insert(key.as_str(), (content.to_vec(), content_type))?;
+        }
+        {
+            let mut table = tx.open_table(DEPENDENCY_URL_TABLE)?;
+            table.insert(url, key.as_str())?;
+        }
+        tx.commit()?;
+
+        Ok(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn it_reads_the_content_type_of_locked_content() -> Result<()> {
+        let cache = DependencyCache::temporary()?;
+        let url = "https://example.com/module";
+        let locked = cache.write(url, b"export const a = 1;", Some("application/typescript"))?;
+        let latest = cache.write(url, b"export const b = 2;", Some("application/javascript"))?;
+
+        let module = cache.read(url, Some(&locked))?.unwrap();
+        assert_eq!(module.hash, locked);
+        assert_eq!(
+            module.content_type.as_deref(),
+            Some("application/typescript")
+        );
+
+        let module = cache.read(url, None)?.unwrap();
+        assert_eq!(module.hash, latest);
+        assert_eq!(
+            module.content_type.as_deref(),
+            Some("application/javascript")
+        );
+        Ok(())
+    }
+}
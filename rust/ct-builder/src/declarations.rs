@@ -0,0 +1,382 @@
+use crate::Error;
+use std::fmt::Write;
+use wit_parser::{
+    Docs, Function, FunctionKind, Handle, InterfaceId, Resolve, Results, Type, TypeDefKind, TypeId,
+    TypeOwner,
+};
+
+/// WIT definitions of the interfaces available to modules,
+/// in dependency order.
+const COMMON_WIT: &[(&str, &str)] = &[
+    (
+        "common/data/wit/data.wit",
+        include_str!("../../../wit/common/data/wit/data.wit"),
+    ),
+    (
+        "common/io/wit/io.wit",
+        include_str!("../../../wit/common/io/wit/io.wit"),
+    ),
+    (
+        "common/formula/wit/formula.wit",
+        include_str!("../../../wit/common/formula/wit/formula.wit"),
+    ),
+];
+
+/// Generate TypeScript declarations for the `common:data/types`,
+/// `common:io/state` and `common:formula/module` interfaces, with a
+/// `declare module` block per interface.
+///
+/// Types are mapped the way `jco` maps them: variants are
+/// `{ tag, val }` objects, options are `T | undefined`, and functions
+/// returning a `result` return its `ok` type, throwing on error.
+pub fn common_declarations() -> Result<String, Error> {
+    let mut resolve = Resolve::new();
+    let mut packages = vec![];
+    for (path, wit) in COMMON_WIT {
+        packages.push(
+            resolve
+                .push_str(path, wit)
+                .map_err(|error| Error::Internal(format!("Invalid WIT at {path}: {error}")))?,
+        );
+    }
+
+    let mut declarations = String::new();
+    for package in packages {
+        for interface in resolve.packages[package].interfaces.values() {
+            Declarations::new(&resolve, *interface).write(&mut declarations)?;
+        }
+    }
+    Ok(declarations)
+}
+
+/// Writes the declarations of a single WIT interface.
+struct Declarations<'a> {
+    resolve: &'a Resolve,
+    interface: InterfaceId,
+}
+
+impl<'a> Declarations<'a> {
+    fn new(resolve: &'a Resolve, interface: InterfaceId) -> Self {
+        Self { resolve, interface }
+    }
+
+    fn write(&self, out: &mut String) -> Result<(), Error> {
+        self.write_interface(out)
+            .map_err(|error| Error::Internal(error.to_string()))
+    }
+
+    fn write_interface(&self, out: &mut String) -> std::fmt::Result {
+        let interface = &self.resolve.interfaces[self.interface];
+        let Some(module) = self.resolve.id_of(self.interface) else {
+            return Ok(());
+        };
+
+        write_docs(out, "", &interface.docs)?;
+        writeln!(out, "declare module \"{module}\" {{")?;
+        for (name, id) in interface.types.iter() {
+            let type_def = &self.resolve.types[*id];
+            write_docs(out, "  ", &type_def.docs)?;
+            let name = pascal_case(name);
+            match &type_def.kind {
+                TypeDefKind::Record(record) => {
+                    writeln!(out, "  export interface {name} {{")?;
+                    for field in record.fields.iter() {
+                        write_docs(out, "    ", &field.docs)?;
+                        writeln!(
+                            out,
+                            "    {}: {};",
+                            camel_case(&field.name),
+                            self.type_name(&field.ty)
+                        )?;
+                    }
+                    writeln!(out, "  }}")?;
+                }
+                TypeDefKind::Resource => {
+                    writeln!(out, "  export class {name} {{")?;
+                    for function in interface.functions.values() {
+                        self.write_method(out, *id, function)?;
+                    }
+                    writeln!(out, "  }}")?;
+                }
+                kind => writeln!(out, "  export type {name} = {};", self.kind_name(kind))?,
+            }
+        }
+        for function in interface.functions.values() {
+            if let FunctionKind::Freestanding = function.kind {
+                write_docs(out, "  ", &function.docs)?;
+                writeln!(
+                    out,
+                    "  export function {}({}): {};",
+                    camel_case(&function.name),
+                    self.params(function, 0),
+                    self.return_type(&function.results)
+                )?;
+            }
+        }
+        writeln!(out, "}}")
+    }
+
+    /// Writes `function` if it is a method, static method
+    /// or constructor of `resource`.
+    fn write_method(
+        &self,
+        out: &mut String,
+        resource: TypeId,
+        function: &Function,
+    ) -> std::fmt::Result {
+        let name = camel_case(function.item_name());
+        match function.kind {
+            FunctionKind::Method(id) if id == resource => {
+                write_docs(out, "    ", &function.docs)?;
+                writeln!(
+                    out,
+                    "    {name}({}): {};",
+                    self.params(function, 1),
+                    self.return_type(&function.results)
+                )
+            }
+            FunctionKind::Static(id) if id == resource => {
+                write_docs(out, "    ", &function.docs)?;
+                writeln!(
+                    out,
+                    "    static {name}({}): {};",
+                    self.params(function, 0),
+                    self.return_type(&function.results)
+                )
+            }
+            FunctionKind::Constructor(id) if id == resource => {
+                write_docs(out, "    ", &function.docs)?;
+                writeln!(out, "    constructor({});", self.params(function, 0))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Parameters of `function`, skipping the first `skip`
+    /// (e.g. the `self` parameter of methods).
+    fn params(&self, function: &Function, skip: usize) -> String {
+        function
+            .params
+            .iter()
+            .skip(skip)
+            .map(|(name, ty)| format!("{}: {}", camel_case(name), self.type_name(ty)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Functions returning a `result` return its `ok` type,
+    /// and throw its `err` type.
+    fn return_type(&self, results: &Results) -> String {
+        match results {
+            Results::Anon(Type::Id(id)) => match &self.resolve.types[*id] {
+                type_def if type_def.name.is_some() => self.type_name(&Type::Id(*id)),
+                type_def => match &type_def.kind {
+                    TypeDefKind::Result(result) => match &result.ok {
+                        Some(ok) => self.type_name(ok),
+                        None => "void".into(),
+                    },
+                    kind => self.kind_name(kind),
+                },
+            },
+            Results::Anon(ty) => self.type_name(ty),
+            Results::Named(named) if named.is_empty() => "void".into(),
+            Results::Named(named) => format!(
+                "{{ {} }}",
+                named
+                    .iter()
+                    .map(|(name, ty)| format!("{}: {};", camel_case(name), self.type_name(ty)))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+        }
+    }
+
+    fn type_name(&self, ty: &Type) -> String {
+        match ty {
+            Type::Bool => "boolean".into(),
+            Type::U8
+            | Type::U16
+            | Type::U32
+            | Type::S8
+            | Type::S16
+            | Type::S32
+            | Type::F32
+            | Type::F64 => "number".into(),
+            Type::U64 | Type::S64 => "bigint".into(),
+            Type::Char | Type::String => "string".into(),
+            Type::Id(id) => {
+                let type_def = &self.resolve.types[*id];
+                match &type_def.name {
+                    Some(name) => match type_def.owner {
+                        TypeOwner::Interface(owner) if owner != self.interface => {
+                            match self.resolve.id_of(owner) {
+                                Some(module) => {
+                                    format!("import(\"{module}\").{}", pascal_case(name))
+                                }
+                                None => "unknown".into(),
+                            }
+                        }
+                        _ => pascal_case(name),
+                    },
+                    None => self.kind_name(&type_def.kind),
+                }
+            }
+        }
+    }
+
+    fn kind_name(&self, kind: &TypeDefKind) -> String {
+        match kind {
+            TypeDefKind::Record(record) => format!(
+                "{{ {} }}",
+                record
+                    .fields
+                    .iter()
+                    .map(|field| format!(
+                        "{}: {};",
+                        camel_case(&field.name),
+                        self.type_name(&field.ty)
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+            TypeDefKind::Flags(flags) => format!(
+                "{{ {} }}",
+                flags
+                    .flags
+                    .iter()
+                    .map(|flag| format!("{}?: boolean;", camel_case(&flag.name)))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+            TypeDefKind::Tuple(tuple) => format!(
+                "[{}]",
+                tuple
+                    .types
+                    .iter()
+                    .map(|ty| self.type_name(ty))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            TypeDefKind::Variant(variant) => variant
+                .cases
+                .iter()
+                .map(|case| match &case.ty {
+                    Some(ty) => format!("{{ tag: '{}', val: {} }}", case.name, self.type_name(ty)),
+                    None => format!("{{ tag: '{}' }}", case.name),
+                })
+                .collect::<Vec<_>>()
+                .join(" | "),
+            TypeDefKind::Enum(enum_) => enum_
+                .cases
+                .iter()
+                .map(|case| format!("'{}'", case.name))
+                .collect::<Vec<_>>()
+                .join(" | "),
+            TypeDefKind::Option(ty) => format!("{} | undefined", self.type_name(ty)),
+            TypeDefKind::Result(result) => format!(
+                "{{ tag: 'ok', val: {} }} | {{ tag: 'err', val: {} }}",
+                result
+                    .ok
+                    .as_ref()
+                    .map_or("undefined".into(), |ty| self.type_name(ty)),
+                result
+                    .err
+                    .as_ref()
+                    .map_or("undefined".into(), |ty| self.type_name(ty)),
+            ),
+            TypeDefKind::List(ty) => match ty {
+                Type::U8 => "Uint8Array".into(),
+                Type::S8 => "Int8Array".into(),
+                Type::U16 => "Uint16Array".into(),
+                Type::S16 => "Int16Array".into(),
+                Type::U32 => "Uint32Array".into(),
+                Type::S32 => "Int32Array".into(),
+                Type::U64 => "BigUint64Array".into(),
+                Type::S64 => "BigInt64Array".into(),
+                Type::F32 => "Float32Array".into(),
+                Type::F64 => "Float64Array".into(),
+                ty => format!("Array<{}>", self.type_name(ty)),
+            },
+            TypeDefKind::Handle(Handle::Own(id) | Handle::Borrow(id)) => {
+                self.type_name(&Type::Id(*id))
+            }
+            TypeDefKind::Type(ty) => self.type_name(ty),
+            TypeDefKind::Resource
+            | TypeDefKind::Future(_)
+            | TypeDefKind::Stream(_)
+            | TypeDefKind::Unknown => "unknown".into(),
+        }
+    }
+}
+
+fn write_docs(out: &mut String, indent: &str, docs: &Docs) -> std::fmt::Result {
+    let Some(contents) = &docs.contents else {
+        return Ok(());
+    };
+    writeln!(out, "{indent}/**")?;
+    for line in contents.trim().lines() {
+        writeln!(out, "{indent} * {}", line.trim())?;
+    }
+    writeln!(out, "{indent} */")
+}
+
+/// Converts a kebab-case WIT name to PascalCase.
+fn pascal_case(name: &str) -> String {
+    name.split('-')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Converts a kebab-case WIT name to camelCase.
+fn camel_case(name: &str) -> String {
+    let pascal = pascal_case(name);
+    let mut chars = pascal.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => pascal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn it_converts_wit_names() {
+        assert_eq!(pascal_case("value-node"), "ValueNode");
+        assert_eq!(camel_case("entity-range-query"), "entityRangeQuery");
+        assert_eq!(camel_case("read"), "read");
+    }
+
+    #[test]
+    fn it_generates_declarations_for_common_interfaces() -> Result<()> {
+        let declarations = common_declarations()?;
+
+        assert!(declarations.contains("declare module \"common:data/types@0.0.1\" {"));
+        assert!(declarations.contains("  export class Reference {"));
+        assert!(declarations.contains("    deref(): Value | undefined;"));
+        assert!(declarations.contains("  export type Value = { tag: 'string', val: String }"));
+
+        assert!(declarations.contains("declare module \"common:io/state@0.0.1\" {"));
+        assert!(declarations
+            .contains("  export type Reference = import(\"common:data/types@0.0.1\").Reference;"));
+        assert!(
+            declarations.contains("  export function read(name: String): Reference | undefined;")
+        );
+        assert!(declarations.contains("  export function write(name: String, value: Value): void;"));
+
+        assert!(declarations.contains("declare module \"common:formula/module@0.0.1\" {"));
+        assert!(declarations.contains("  export interface Datom {"));
+        assert!(declarations.contains(
+            "  export function init(input: Array<[string, Scalar]>): [State, RangeQuery];"
+        ));
+        Ok(())
+    }
+}
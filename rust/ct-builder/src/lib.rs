@@ -9,10 +9,18 @@ extern crate tracing;
 mod artifact;
 mod builder;
 mod bundle;
+mod cache;
+mod declarations;
 mod error;
+mod lockfile;
 mod serve;
 mod storage;
+mod typecheck;
 
 pub use bundle::*;
+pub use cache::*;
 pub use error::*;
+pub use lockfile::*;
 pub use serve::*;
+pub use storage::PersistedHashStorage;
+pub use typecheck::*;
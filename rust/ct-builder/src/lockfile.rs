@@ -0,0 +1,62 @@
+use crate::{storage::Hash, Error};
+use std::collections::{BTreeMap, HashMap};
+
+/// Maps the URLs of remote modules imported by a bundle
+/// to the [`Hash`] of their expected content.
+///
+/// Bundling with a [`Lockfile`] fails if an imported module's
+/// content does not match its entry, so that bundles are
+/// reproducible across builds.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Lockfile(BTreeMap<String, Hash>);
+
+impl Lockfile {
+    /// Returns the [`Hash`] expected for the module at `url`, if any.
+    pub fn get(&self, url: &str) -> Option<&Hash> {
+        self.0.get(url)
+    }
+
+    /// Set the [`Hash`] expected for the module at `url`.
+    pub fn insert(&mut self, url: String, hash: Hash) {
+        self.0.insert(url, hash);
+    }
+
+    /// Iterate over URLs and their expected [`Hash`].
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Hash)> {
+        self.0.iter()
+    }
+
+    /// Returns `true` if the lockfile has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<BTreeMap<String, Hash>> for Lockfile {
+    fn from(value: BTreeMap<String, Hash>) -> Self {
+        Lockfile(value)
+    }
+}
+
+impl TryFrom<HashMap<String, String>> for Lockfile {
+    type Error = Error;
+
+    fn try_from(value: HashMap<String, String>) -> Result<Self, Self::Error> {
+        Ok(Lockfile(
+            value
+                .into_iter()
+                .map(|(url, hash)| Ok((url, Hash::from_hex(hash)?)))
+                .collect::<Result<_, Error>>()?,
+        ))
+    }
+}
+
+impl From<Lockfile> for HashMap<String, String> {
+    fn from(value: Lockfile) -> Self {
+        value
+            .0
+            .into_iter()
+            .map(|(url, hash)| (url, hash.to_string()))
+            .collect()
+    }
+}
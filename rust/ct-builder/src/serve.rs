@@ -1,12 +1,36 @@
-use crate::{builder::Builder, error::Error, storage::PersistedHashStorage};
+use crate::{builder::Builder, error::Error, storage::PersistedHashStorage, TypeChecker};
 use ct_protos::{builder::builder_server::BuilderServer, MAX_MESSAGE_SIZE};
 use tokio::net::TcpListener;
 use tonic::transport::Server as TonicServer;
 
 /// Start the Common Builder server, serving gRPC on `grpc_listener`.
+///
+/// Built components are stored in a temporary file,
+/// and are lost when the server stops.
 pub async fn serve(grpc_listener: TcpListener) -> Result<(), Error> {
-    let storage = PersistedHashStorage::temporary()?;
-    let builder = Builder::new(storage);
+    serve_with_storage(grpc_listener, PersistedHashStorage::temporary()?).await
+}
+
+/// Start the Common Builder server, serving gRPC on `grpc_listener`
+/// and storing built components in `storage`.
+///
+/// Modules are type checked with `tsc` from `PATH` when requested.
+pub async fn serve_with_storage(
+    grpc_listener: TcpListener,
+    storage: PersistedHashStorage,
+) -> Result<(), Error> {
+    serve_with_type_checker(grpc_listener, storage, TypeChecker::new("tsc")?).await
+}
+
+/// Start the Common Builder server, serving gRPC on `grpc_listener`,
+/// storing built components in `storage` and type checking
+/// modules with `type_checker` when requested.
+pub async fn serve_with_type_checker(
+    grpc_listener: TcpListener,
+    storage: PersistedHashStorage,
+    type_checker: TypeChecker,
+) -> Result<(), Error> {
+    let builder = Builder::new(storage, type_checker);
     serve_grpc(builder, grpc_listener).await?;
     Ok(())
 }
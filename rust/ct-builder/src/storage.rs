@@ -1,7 +1,12 @@
-use crate::{artifact::Artifact, Error};
+use crate::{
+    artifact::{Artifact, ArtifactMetadata},
+    DependencyCache, Error,
+};
 use async_trait::async_trait;
-use redb::{Database, TableDefinition};
-use std::sync::Arc;
+use ct_common::{ContentType, ModuleId};
+use ct_protos::common;
+use redb::{Database, ReadableTable, TableDefinition};
+use std::{path::Path, str::FromStr, sync::Arc, time::SystemTime};
 use tempfile::NamedTempFile;
 
 /// Key-type for [`HashStorage`].
@@ -12,17 +17,36 @@ pub type Hash = blake3::Hash;
 pub trait JsComponentStorage: Send + Sync {
     /// Read value stored at `key`.
     async fn read(&self, key: &Hash) -> Result<Option<Artifact>, Error>;
-    /// Write `value` to `key`.
-    async fn write(&self, value: Artifact) -> Result<Hash, Error>;
+    /// Write `value`, built from a source identified by `source_hash`
+    /// of type `content_type`.
+    async fn write(
+        &self,
+        value: Artifact,
+        source_hash: ModuleId,
+        content_type: ContentType,
+    ) -> Result<Hash, Error>;
+    /// List the keys of stored values, with their [`ArtifactMetadata`].
+    async fn list(&self) -> Result<Vec<(Hash, ArtifactMetadata)>, Error>;
+    /// Delete value stored at `key`, returning whether it was stored.
+    async fn delete(&self, key: &Hash) -> Result<bool, Error>;
 }
 
 const MODULE_TABLE: TableDefinition<&str, Vec<u8>> = TableDefinition::new("modules");
 const SOURCE_MAP_TABLE: TableDefinition<&str, Vec<u8>> = TableDefinition::new("source_maps");
+/// Build time (milliseconds since the Unix epoch), source hash,
+/// content type and size of each module.
+const METADATA_TABLE: TableDefinition<&str, (u64, &str, i32, u64)> =
+    TableDefinition::new("metadata");
 
 /// Simple key-value storage that persists to disk.
+///
+/// A [`DependencyCache`] is persisted alongside
+/// stored artifacts in the same database.
 #[derive(Clone)]
 pub struct PersistedHashStorage {
     db: Arc<Database>,
+    dependency_cache: DependencyCache,
+    max_size: Option<u64>,
     _temp_file: Option<Arc<NamedTempFile>>,
 }
 
@@ -32,6 +56,21 @@ impl PersistedHashStorage {
         info!("Initializing temporary storage");
         let temp_file = Arc::new(NamedTempFile::new()?);
         let db = Arc::new(Database::create(temp_file.path())?);
+        Self::from_database(db, Some(temp_file))
+    }
+
+    /// Open the [PersistedHashStorage] at `path`, creating
+    /// it if it does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        info!("Opening storage at {}", path.as_ref().display());
+        let db = Arc::new(Database::create(path)?);
+        Self::from_database(db, None)
+    }
+
+    fn from_database(
+        db: Arc<Database>,
+        temp_file: Option<Arc<NamedTempFile>>,
+    ) -> Result<Self, Error> {
         {
             // Create tables upfront, as opening a table
             // from a ReadTransaction errors if table
@@ -39,14 +78,77 @@ impl PersistedHashStorage {
             let tx = db.begin_write()?;
             let _ = tx.open_table(MODULE_TABLE)?;
             let _ = tx.open_table(SOURCE_MAP_TABLE)?;
+            let _ = tx.open_table(METADATA_TABLE)?;
             tx.commit()?;
         }
+        let dependency_cache = DependencyCache::from_database(db.clone(), temp_file.clone())?;
 
         Ok(Self {
             db,
-            _temp_file: Some(temp_file),
+            dependency_cache,
+            max_size: None,
+            _temp_file: temp_file,
         })
     }
+
+    /// Evict the earliest built artifacts once the total size of
+    /// stored artifacts and cached dependencies exceeds `max_size` bytes.
+    ///
+    /// Modules in the [`DependencyCache`] count toward `max_size`,
+    /// but are not evicted.
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// The [`DependencyCache`] persisted alongside this storage.
+    pub fn dependency_cache(&self) -> DependencyCache {
+        self.dependency_cache.clone()
+    }
+
+    /// Delete the earliest built artifacts in `tx` other than
+    /// `keep`, until stored artifacts fit within `max_size`.
+    fn evict(tx: &redb::WriteTransaction, max_size: u64, keep: &str) -> Result<(), Error> {
+        let evicted = {
+            let table = tx.open_table(METADATA_TABLE)?;
+            let mut total_size = DependencyCache::size(tx)?;
+            let mut candidates = vec![];
+            for entry in table.iter()? {
+                let (key, metadata) = entry?;
+                let (build_time, _, _, size) = metadata.value();
+                total_size += size;
+                if key.value() != keep {
+                    candidates.push((build_time, key.value().to_owned(), size));
+                }
+            }
+            candidates.sort();
+
+            let mut evicted = vec![];
+            for (_, key, size) in candidates {
+                if total_size <= max_size {
+                    break;
+                }
+                total_size -= size;
+                evicted.push(key);
+            }
+            evicted
+        };
+
+        for key in evicted.iter() {
+            debug!(%key, "Evict");
+            delete_artifact(tx, key)?;
+        }
+        Ok(())
+    }
+}
+
+/// Delete artifact `key` from each table in `tx`,
+/// returning whether it was stored.
+fn delete_artifact(tx: &redb::WriteTransaction, key: &str) -> Result<bool, Error> {
+    let removed = tx.open_table(MODULE_TABLE)?.remove(key)?.is_some();
+    tx.open_table(SOURCE_MAP_TABLE)?.remove(key)?;
+    tx.open_table(METADATA_TABLE)?.remove(key)?;
+    Ok(removed)
 }
 
 #[async_trait]
@@ -78,24 +180,197 @@ impl JsComponentStorage for PersistedHashStorage {
         }))
     }
 
-    async fn write(&self, value: Artifact) -> Result<Hash, Error> {
+    async fn write(
+        &self,
+        value: Artifact,
+        source_hash: ModuleId,
+        content_type: ContentType,
+    ) -> Result<Hash, Error> {
         let component_bytes = value.component.into_bytes();
         let component_id = blake3::hash(&component_bytes);
         let key = component_id.to_string();
+        let source_map_bytes = value.source_map.map(|s| s.into_bytes());
+        let metadata = ArtifactMetadata {
+            build_time: SystemTime::now(),
+            source_hash,
+            content_type,
+            size: (component_bytes.len() + source_map_bytes.as_ref().map_or(0, |s| s.len())) as u64,
+        };
+
         let tx = self.db.begin_write()?;
         {
             let mut table = tx.open_table(MODULE_TABLE)?;
             table.insert(key.as_str(), component_bytes)?;
         }
 
-        let source_map_bytes = value.source_map.map(|s| s.into_bytes());
         if let Some(source_map_bytes) = source_map_bytes {
             let mut table = tx.open_table(SOURCE_MAP_TABLE)?;
             table.insert(key.as_str(), source_map_bytes)?;
         }
 
+        {
+            let mut table = tx.open_table(METADATA_TABLE)?;
+            // Rebuilding an artifact keeps the time it was first built
+            let build_time = match table.get(key.as_str())? {
+                Some(existing) => existing.value().0,
+                None => metadata.build_time_millis(),
+            };
+            table.insert(
+                key.as_str(),
+                (
+                    build_time,
+                    metadata.source_hash.to_string().as_str(),
+                    i32::from(common::ContentType::from(metadata.content_type)),
+                    metadata.size,
+                ),
+            )?;
+        }
+
+        if let Some(max_size) = self.max_size {
+            Self::evict(&tx, max_size, &key)?;
+        }
+
         tx.commit()?;
 
         Ok(component_id)
     }
+
+    async fn list(&self) -> Result<Vec<(Hash, ArtifactMetadata)>, Error> {
+        let tx = self.db.begin_read()?;
+        let table = tx.open_table(METADATA_TABLE)?;
+        let mut artifacts = vec![];
+        for entry in table.iter()? {
+            let (key, metadata) = entry?;
+            let (build_time, source_hash, content_type, size) = metadata.value();
+            artifacts.push((
+                Hash::from_str(key.value())?,
+                ArtifactMetadata {
+                    build_time: ArtifactMetadata::build_time_from_millis(build_time),
+                    source_hash: ModuleId::from_str(source_hash).map_err(Error::Internal)?,
+                    content_type: common::ContentType::try_from(content_type)
+                        .map_err(|error| Error::Internal(error.to_string()))?
+                        .into(),
+                    size,
+                },
+            ));
+        }
+        Ok(artifacts)
+    }
+
+    async fn delete(&self, key: &Hash) -> Result<bool, Error> {
+        info!(?key, "Delete");
+        let tx = self.db.begin_write()?;
+        let deleted = delete_artifact(&tx, &key.to_string())?;
+        tx.commit()?;
+        Ok(deleted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use ct_tracing::ct_tracing;
+
+    fn artifact(component: &str) -> Artifact {
+        Artifact {
+            component: component.into(),
+            source_map: None,
+        }
+    }
+
+    async fn write(storage: &PersistedHashStorage, component: &str) -> Result<Hash> {
+        Ok(storage
+            .write(
+                artifact(component),
+                ModuleId::from(&ct_common::ModuleDefinition::from(component)),
+                ContentType::JavaScript,
+            )
+            .await?)
+    }
+
+    #[tokio::test]
+    #[ct_tracing]
+    async fn it_persists_artifacts_across_restarts() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("builder.redb");
+
+        let key = {
+            let storage = PersistedHashStorage::open(&path)?;
+            write(&storage, "export const foo = 1;").await?
+        };
+
+        let storage = PersistedHashStorage::open(&path)?;
+        let stored = storage.read(&key).await?.unwrap();
+        assert_eq!(stored.component, "export const foo = 1;");
+
+        let listed = storage.list().await?;
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].0, key);
+        assert_eq!(listed[0].1.size, 21);
+        assert_eq!(
+            listed[0].1.source_hash,
+            ModuleId::from(&ct_common::ModuleDefinition::from("export const foo = 1;"))
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ct_tracing]
+    async fn it_evicts_the_oldest_artifacts() -> Result<()> {
+        let storage = PersistedHashStorage::temporary()?.with_max_size(30);
+        let first = write(&storage, "export const foo = 1;").await?;
+        let second = write(&storage, "export const bar = 2;").await?;
+
+        assert!(storage.read(&first).await?.is_none());
+        assert!(storage.read(&second).await?.is_some());
+        assert_eq!(storage.list().await?.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ct_tracing]
+    async fn it_keeps_the_first_build_time_of_rebuilt_artifacts() -> Result<()> {
+        let storage = PersistedHashStorage::temporary()?.with_max_size(50);
+        let first = write(&storage, "export const foo = 1;").await?;
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let second = write(&storage, "export const bar = 2;").await?;
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert_eq!(write(&storage, "export const foo = 1;").await?, first);
+        write(&storage, "export const baz = 3;").await?;
+
+        assert!(storage.read(&first).await?.is_none());
+        assert!(storage.read(&second).await?.is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ct_tracing]
+    async fn it_counts_cached_dependencies_toward_the_max_size() -> Result<()> {
+        let storage = PersistedHashStorage::temporary()?.with_max_size(50);
+        let first = write(&storage, "export const foo = 1;").await?;
+        storage.dependency_cache().write(
+            "https://example.com/module.js",
+            b"export const dependency = 1;",
+            None,
+        )?;
+        let second = write(&storage, "export const bar = 2;").await?;
+
+        assert!(storage.read(&first).await?.is_none());
+        assert!(storage.read(&second).await?.is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ct_tracing]
+    async fn it_deletes_artifacts() -> Result<()> {
+        let storage = PersistedHashStorage::temporary()?;
+        let key = write(&storage, "export const foo = 1;").await?;
+
+        assert!(storage.delete(&key).await?);
+        assert!(!storage.delete(&key).await?);
+        assert!(storage.read(&key).await?.is_none());
+        assert!(storage.list().await?.is_empty());
+        Ok(())
+    }
 }
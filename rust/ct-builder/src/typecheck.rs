@@ -0,0 +1,299 @@
+use crate::{declarations::common_declarations, Error};
+use ct_protos::builder;
+use std::{
+    collections::BTreeMap,
+    path::{Component, Path, PathBuf},
+    process::Stdio,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{process::Command, sync::Semaphore};
+
+/// File name of the checked module, matching the
+/// root module of [`crate::JavaScriptBundler`].
+const ROOT_MODULE: &str = "root.tsx";
+const DECLARATIONS: &str = "common.d.ts";
+
+/// How long a single run of `tsc` may take, by default.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+/// How many runs of `tsc` may happen at once, by default.
+const DEFAULT_MAX_CONCURRENCY: usize = 4;
+
+/// Declarations for modules that are not described by WIT:
+/// the host callback provided by the JavaScript VM, which
+/// passes values as-is, and remote modules, which are not checked.
+const AMBIENT_DECLARATIONS: &str = r#"declare module "common:basic/host-callback@0.0.1" {
+  export function callback(input: any): any;
+}
+declare module "http://*";
+declare module "https://*";
+"#;
+
+const TSCONFIG: &str = r#"{
+  "compilerOptions": {
+    "strict": true,
+    "noEmit": true,
+    "target": "es2022",
+    "lib": ["es2022"],
+    "types": [],
+    "module": "esnext",
+    "moduleResolution": "bundler",
+    "allowImportingTsExtensions": true,
+    "allowJs": true,
+    "jsx": "preserve",
+    "skipLibCheck": true
+  },
+  "files": ["root.tsx", "common.d.ts"]
+}
+"#;
+
+/// A problem found when type checking a module.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    /// File the diagnostic refers to, e.g. `root.tsx` for the module
+    /// itself. Unset for diagnostics about the compiler's configuration.
+    pub file: Option<String>,
+    /// 1-based line of the diagnostic, or 0 if there is no `file`.
+    pub line: u32,
+    /// 1-based column of the diagnostic, or 0 if there is no `file`.
+    pub column: u32,
+    /// TypeScript error code, e.g. `2322` for `TS2322`.
+    pub code: u32,
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl From<Diagnostic> for builder::Diagnostic {
+    fn from(value: Diagnostic) -> Self {
+        builder::Diagnostic {
+            file: value.file,
+            line: value.line,
+            column: value.column,
+            code: value.code,
+            message: value.message,
+        }
+    }
+}
+
+/// Type checks TypeScript modules with `tsc`, against declarations
+/// of the `common:` interfaces generated from their WIT definitions.
+///
+/// At most 4 runs of `tsc` happen at once, each limited to 30 seconds,
+/// unless configured otherwise.
+#[derive(Clone)]
+pub struct TypeChecker {
+    tsc: PathBuf,
+    declarations: Arc<String>,
+    timeout: Duration,
+    permits: Arc<Semaphore>,
+}
+
+impl TypeChecker {
+    /// Create a [`TypeChecker`] running the TypeScript compiler at `tsc`.
+    pub fn new<P: Into<PathBuf>>(tsc: P) -> Result<Self, Error> {
+        Ok(TypeChecker {
+            tsc: tsc.into(),
+            declarations: Arc::new(common_declarations()? + AMBIENT_DECLARATIONS),
+            timeout: DEFAULT_TIMEOUT,
+            permits: Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENCY)),
+        })
+    }
+
+    /// Fail checks that take longer than `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run at most `max_concurrency` checks at once,
+    /// queueing the others.
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.permits = Arc::new(Semaphore::new(max_concurrency));
+        self
+    }
+
+    /// Type check `source` along with its `dependencies`,
+    /// returning the [`Diagnostic`]s found.
+    ///
+    /// Dependencies imported with a relative specifier (e.g.
+    /// `./util.ts`) are checked alongside `source`; JavaScript
+    /// dependencies are typed from their contents but not checked.
+    /// Imports of remote modules are typed as `any`.
+    pub async fn check(
+        &self,
+        source: &str,
+        dependencies: &BTreeMap<String, String>,
+    ) -> Result<Vec<Diagnostic>, Error> {
+        let dir = tempfile::tempdir()?;
+        tokio::fs::write(dir.path().join(ROOT_MODULE), source).await?;
+        tokio::fs::write(dir.path().join(DECLARATIONS), self.declarations.as_str()).await?;
+        tokio::fs::write(dir.path().join("tsconfig.json"), TSCONFIG).await?;
+        for (specifier, source) in dependencies.iter() {
+            let Some(path) = dependency_path(specifier) else {
+                continue;
+            };
+            let path = dir.path().join(path);
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(path, source).await?;
+        }
+
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|error| Error::Internal(format!("{error}")))?;
+        let output = Command::new(&self.tsc)
+            .args(["--project", ".", "--pretty", "false"])
+            .current_dir(dir.path())
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .output();
+        let output = tokio::time::timeout(self.timeout, output)
+            .await
+            .map_err(|_| {
+                Error::Internal(format!(
+                    "Type checking timed out after {}s",
+                    self.timeout.as_secs()
+                ))
+            })?
+            .map_err(|error| match error.kind() {
+                std::io::ErrorKind::NotFound => Error::InvalidConfiguration(format!(
+                    "TypeScript compiler not found at {}",
+                    self.tsc.display()
+                )),
+                _ => error.into(),
+            })?;
+
+        let diagnostics = parse_diagnostics(&String::from_utf8_lossy(&output.stdout));
+        if !output.status.success() && diagnostics.is_empty() {
+            return Err(Error::Internal(format!(
+                "Type checking failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+        Ok(diagnostics)
+    }
+}
+
+/// The path, relative to the checked module, that a dependency
+/// imported as `specifier` is written to. Only dependencies within the
+/// module's directory are written: others, e.g. shims of `common:`
+/// modules, are described by the declarations.
+fn dependency_path(specifier: &str) -> Option<&Path> {
+    let path = Path::new(specifier.strip_prefix("./")?);
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_)))
+        .then_some(path)
+}
+
+/// Parses the output of `tsc --pretty false`, e.g.:
+///
+/// ```text
+/// root.tsx(3,7): error TS2322: Type 'string' is not assignable to type 'number'.
+/// error TS5023: Unknown compiler option 'foo'.
+/// ```
+///
+/// Indented lines continue the message of the preceding diagnostic.
+fn parse_diagnostics(output: &str) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = vec![];
+    for line in output.lines() {
+        if line.starts_with(' ') {
+            if let Some(diagnostic) = diagnostics.last_mut() {
+                diagnostic.message.push('\n');
+                diagnostic.message.push_str(line.trim());
+            }
+            continue;
+        }
+        if let Some(diagnostic) = parse_diagnostic(line) {
+            diagnostics.push(diagnostic);
+        }
+    }
+    diagnostics
+}
+
+fn parse_diagnostic(line: &str) -> Option<Diagnostic> {
+    let (location, rest) = match line.split_once(": error TS") {
+        Some((location, rest)) => (Some(location), rest),
+        None => (None, line.strip_prefix("error TS")?),
+    };
+    let (code, message) = rest.split_once(": ")?;
+
+    let (file, line, column) = match location {
+        Some(location) => {
+            let (file, position) = location.strip_suffix(')')?.rsplit_once('(')?;
+            let (line, column) = position.split_once(',')?;
+            (
+                Some(file.to_owned()),
+                line.parse().ok()?,
+                column.parse().ok()?,
+            )
+        }
+        None => (None, 0, 0),
+    };
+
+    Some(Diagnostic {
+        file,
+        line,
+        column,
+        code: code.parse().ok()?,
+        message: message.to_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_writes_only_dependencies_within_the_module_directory() {
+        assert_eq!(
+            dependency_path("./util/math.ts"),
+            Some(Path::new("util/math.ts"))
+        );
+        assert_eq!(
+            dependency_path("./increment.js"),
+            Some(Path::new("increment.js"))
+        );
+        assert_eq!(dependency_path("../outside.ts"), None);
+        assert_eq!(dependency_path("./util/../../outside.ts"), None);
+        assert_eq!(dependency_path("/etc/passwd"), None);
+        assert_eq!(dependency_path("common:io/state@0.0.1"), None);
+    }
+
+    #[test]
+    fn it_parses_tsc_diagnostics() {
+        let output = "root.tsx(3,7): error TS2322: Type 'string' is not assignable to type 'number'.\n\
+                      root.tsx(5,1): error TS2345: Argument of type '{ tag: \"str\"; }' is not assignable to parameter of type 'Value'.\n  \
+                      Type '\"str\"' is not assignable to type '\"string\"'.\n\
+                      error TS5023: Unknown compiler option 'foo'.\n";
+
+        assert_eq!(
+            parse_diagnostics(output),
+            vec![
+                Diagnostic {
+                    file: Some("root.tsx".into()),
+                    line: 3,
+                    column: 7,
+                    code: 2322,
+                    message: "Type 'string' is not assignable to type 'number'.".into(),
+                },
+                Diagnostic {
+                    file: Some("root.tsx".into()),
+                    line: 5,
+                    column: 1,
+                    code: 2345,
+                    message: "Argument of type '{ tag: \"str\"; }' is not assignable to parameter of type 'Value'.\nType '\"str\"' is not assignable to type '\"string\"'.".into(),
+                },
+                Diagnostic {
+                    file: None,
+                    line: 0,
+                    column: 0,
+                    code: 5023,
+                    message: "Unknown compiler option 'foo'.".into(),
+                },
+            ]
+        );
+    }
+}
@@ -1,10 +1,11 @@
 #![cfg(not(target_arch = "wasm32"))]
 
-use ct_builder::serve;
-use ct_common::ModuleDefinition;
+use ct_builder::{serve, serve_with_type_checker, PersistedHashStorage, TypeChecker};
+use ct_common::{ModuleDefinition, ModuleId};
 use ct_protos::builder::{
     builder_client::BuilderClient, BuildComponentRequest, BuildComponentResponse,
-    ReadComponentRequest, ReadComponentResponse,
+    BundleSourceCodeRequest, BundleSourceCodeResponse, DeleteComponentRequest, Diagnostic,
+    ListComponentsRequest, ListComponentsResponse, ReadComponentRequest, ReadComponentResponse,
 };
 use ct_test_fixtures::sources::common::BASIC_MODULE_TSX;
 use ct_tracing::ct_tracing;
@@ -19,10 +20,13 @@ async fn it_builds_and_returns_components_over_grpc() -> anyhow::Result<()> {
 
     let mut client = BuilderClient::connect(format!("http://{}", addr)).await?;
 
-    let BuildComponentResponse { component_id } = client
+    let BuildComponentResponse { component_id, .. } = client
         .build_component(BuildComponentRequest {
             module_definition: Some(ModuleDefinition::from(BASIC_MODULE_TSX).try_into()?),
             bundle_common_imports: false,
+            lockfile: Default::default(),
+            offline: false,
+            type_check: false,
         })
         .await?
         .into_inner();
@@ -39,3 +43,193 @@ async fn it_builds_and_returns_components_over_grpc() -> anyhow::Result<()> {
     assert!(source_map.unwrap().contains("\"version\":3,"));
     Ok(())
 }
+
+#[tokio::test]
+#[ct_tracing]
+async fn it_lists_and_deletes_components_over_grpc() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let _handler = tokio::spawn(async { serve(listener).await.unwrap() });
+
+    let mut client = BuilderClient::connect(format!("http://{}", addr)).await?;
+
+    let BuildComponentResponse { component_id, .. } = client
+        .build_component(BuildComponentRequest {
+            module_definition: Some(ModuleDefinition::from(BASIC_MODULE_TSX).try_into()?),
+            bundle_common_imports: false,
+            lockfile: Default::default(),
+            offline: false,
+            type_check: false,
+        })
+        .await?
+        .into_inner();
+
+    let ListComponentsResponse { components } = client
+        .list_components(ListComponentsRequest {})
+        .await?
+        .into_inner();
+
+    assert_eq!(components.len(), 1);
+    assert_eq!(components[0].component_id, component_id);
+    assert_eq!(
+        components[0].source_hash,
+        ModuleId::from(&ModuleDefinition::from(BASIC_MODULE_TSX)).to_string()
+    );
+    assert!(components[0].size > 0);
+
+    client
+        .delete_component(DeleteComponentRequest {
+            component_id: component_id.clone(),
+        })
+        .await?;
+
+    assert!(client
+        .read_component(ReadComponentRequest { component_id })
+        .await
+        .is_err());
+    let ListComponentsResponse { components } = client
+        .list_components(ListComponentsRequest {})
+        .await?
+        .into_inner();
+    assert!(components.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+#[ct_tracing]
+async fn it_bundles_source_code_with_common_shims_over_grpc() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let _handler = tokio::spawn(async { serve(listener).await.unwrap() });
+
+    let mut client = BuilderClient::connect(format!("http://{}", addr)).await?;
+
+    let mut definition = ModuleDefinition::from(
+        r#"
+import { read } from "common:io/state@0.0.1";
+
+export const run = () => read("foo");
+"#,
+    );
+    definition.dependencies.insert(
+        "common:io/state@0.0.1".into(),
+        r#"
+import { callback } from "common:basic/host-callback@0.0.1";
+
+export const read = (name: string) => callback(JSON.stringify({ read: name }));
+"#
+        .into(),
+    );
+
+    let BundleSourceCodeResponse {
+        bundled_source_code,
+        source_map,
+        ..
+    } = client
+        .bundle_source_code(BundleSourceCodeRequest {
+            module_definition: Some(definition.into()),
+            bundle_common_imports: true,
+            lockfile: Default::default(),
+            offline: false,
+        })
+        .await?
+        .into_inner();
+
+    assert!(!bundled_source_code.contains("common:io/state@0.0.1"));
+    assert!(bundled_source_code.contains("common:basic/host-callback@0.0.1"));
+    assert!(source_map.unwrap().contains("\"version\":3,"));
+
+    let ListComponentsResponse { components } = client
+        .list_components(ListComponentsRequest {})
+        .await?
+        .into_inner();
+    assert!(components.is_empty());
+    Ok(())
+}
+
+const MISTYPED_MODULE: &str = r#"import { write } from "common:io/state@0.0.1";
+
+export const run = () => {
+  const count: number = "one";
+  write("count", { tag: "number", val: count });
+};
+"#;
+
+#[tokio::test]
+#[ct_tracing]
+async fn it_returns_type_check_diagnostics_over_grpc() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let _handler = tokio::spawn(async {
+        serve_with_type_checker(
+            listener,
+            PersistedHashStorage::temporary().unwrap(),
+            TypeChecker::new("tsc").unwrap(),
+        )
+        .await
+        .unwrap()
+    });
+
+    let mut client = BuilderClient::connect(format!("http://{}", addr)).await?;
+
+    let BuildComponentResponse {
+        component_id,
+        diagnostics,
+        ..
+    } = client
+        .build_component(BuildComponentRequest {
+            module_definition: Some(ModuleDefinition::from(MISTYPED_MODULE).into()),
+            bundle_common_imports: false,
+            lockfile: Default::default(),
+            offline: false,
+            type_check: true,
+        })
+        .await?
+        .into_inner();
+
+    assert!(!component_id.is_empty());
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic {
+            file: Some("root.tsx".into()),
+            line: 4,
+            column: 9,
+            code: 2322,
+            message: "Type 'string' is not assignable to type 'number'.".into(),
+        }]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+#[ct_tracing]
+async fn it_fails_to_type_check_without_tsc_over_grpc() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let _handler = tokio::spawn(async {
+        serve_with_type_checker(
+            listener,
+            PersistedHashStorage::temporary().unwrap(),
+            TypeChecker::new("/nonexistent/tsc").unwrap(),
+        )
+        .await
+        .unwrap()
+    });
+
+    let mut client = BuilderClient::connect(format!("http://{}", addr)).await?;
+
+    let status = client
+        .build_component(BuildComponentRequest {
+            module_definition: Some(ModuleDefinition::from(MISTYPED_MODULE).into()),
+            bundle_common_imports: false,
+            lockfile: Default::default(),
+            offline: false,
+            type_check: true,
+        })
+        .await
+        .expect_err("type checking requires tsc");
+
+    assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+    assert!(status.message().contains("/nonexistent/tsc"));
+    Ok(())
+}
@@ -0,0 +1,68 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use ct_builder::JavaScriptBundler;
+use ct_common::{ContentType, ModuleDefinition, ModuleError, SourceMapper};
+use ct_runtime::{Error, Runtime};
+use ct_tracing::ct_tracing;
+
+const SOURCE: &str = r#"interface Input {
+  foo: number;
+}
+
+export const run = (input: Input): Input => {
+  if (input.foo > 1) {
+    throw new Error("oops");
+  }
+  return input;
+};
+"#;
+
+#[tokio::test]
+#[ct_tracing]
+async fn it_remaps_errors_thrown_by_bundled_typescript() -> anyhow::Result<()> {
+    let artifact = JavaScriptBundler::bundle_from_bytes(SOURCE.into()).await?;
+    let source_map = artifact
+        .source_map
+        .clone()
+        .expect("bundles have a source map");
+
+    let host_callback = |input: String| -> std::result::Result<String, String> { Ok(input) };
+    let runtime = Runtime::new(host_callback)?;
+    let mut module = runtime.module(ModuleDefinition {
+        content_type: ContentType::JavaScript,
+        source: artifact.component.clone(),
+        dependencies: Default::default(),
+        source_map: Some(source_map.clone()),
+    })?;
+    let mut instance = module.instantiate()?;
+
+    assert_eq!(instance.run(r#"{"foo":1}"#.into())?, r#"{"foo":1}"#);
+    let Err(Error::ModuleFailure(error)) = instance.run(r#"{"foo":2}"#.into()) else {
+        panic!("Expected a module failure");
+    };
+    assert!(error.message.contains("oops"));
+
+    // Boa only reports the positions of syntax errors, so remap the
+    // position of the `throw` in the bundle as a VM backtrace reports it.
+    let (line, column) = artifact
+        .component
+        .lines()
+        .enumerate()
+        .find_map(|(index, line)| Some((index + 1, line.find("throw new Error")? + 1)))
+        .expect("the bundle throws");
+    let error = SourceMapper::new(&source_map)
+        .map_err(anyhow::Error::msg)?
+        .remap(ModuleError::parse(&format!(
+            "Error: oops\n    at run (<module>:{line}:{column})"
+        )));
+
+    assert_eq!(error.frames.len(), 1);
+    assert_eq!(error.frames[0].function.as_deref(), Some("run"));
+    assert_eq!(error.frames[0].line, 7);
+    assert_eq!(error.frames[0].column, 5);
+    assert_eq!(
+        error.snippet.as_deref(),
+        Some("    throw new Error(\"oops\");")
+    );
+    Ok(())
+}
@@ -0,0 +1,160 @@
+/// A position in a module's source, within a [`ModuleError`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct StackFrame {
+    /// Name of the function at this frame, if known.
+    pub function: Option<String>,
+    /// File of this frame. `None` refers to the module's own source.
+    pub file: Option<String>,
+    /// 1-based line of this frame.
+    pub line: u32,
+    /// 1-based column of this frame.
+    pub column: u32,
+}
+
+impl std::fmt::Display for StackFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let file = self.file.as_deref().unwrap_or("<module>");
+        match &self.function {
+            Some(function) => write!(f, "{function} ({file}:{}:{})", self.line, self.column),
+            None => write!(f, "{file}:{}:{}", self.line, self.column),
+        }
+    }
+}
+
+/// An error thrown by a module's code, while loading or running.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModuleError {
+    /// The error's message, without its stack frames.
+    pub message: String,
+    /// The error's stack frames, innermost first.
+    pub frames: Vec<StackFrame>,
+    /// The line of source at the innermost frame, if known.
+    pub snippet: Option<String>,
+}
+
+impl ModuleError {
+    /// Parse an error reported by a VM as `error`, extracting
+    /// stack frames (`at fn (file:line:column)` lines, and
+    /// `at line L, col C` positions) from its message.
+    pub fn parse(error: &str) -> Self {
+        let mut message = vec![];
+        let mut frames = vec![];
+        for line in error.lines() {
+            if let Some(frame) = parse_frame(line) {
+                frames.push(frame);
+            } else if let Some((text, frame)) = parse_position(line) {
+                message.push(text);
+                frames.push(frame);
+            } else {
+                message.push(line);
+            }
+        }
+        ModuleError {
+            message: message.join("\n").trim().to_owned(),
+            frames,
+            snippet: None,
+        }
+    }
+}
+
+impl std::fmt::Display for ModuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+        for frame in self.frames.iter() {
+            write!(f, "\n    at {frame}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a stack trace line, e.g. `at run (module.js:3:7)` or `at module.js:3:7`.
+fn parse_frame(line: &str) -> Option<StackFrame> {
+    let frame = line.trim().strip_prefix("at ")?;
+    let (function, location) = match frame.strip_suffix(')') {
+        Some(frame) => {
+            let (function, location) = frame.split_once(" (")?;
+            (Some(function.to_owned()), location)
+        }
+        None => (None, frame),
+    };
+    let mut parts = location.rsplitn(3, ':');
+    let column = parts.next()?.parse().ok()?;
+    let line = parts.next()?.parse().ok()?;
+    let file = parts.next()?;
+    Some(StackFrame {
+        function,
+        file: (!file.is_empty() && file != "<module>").then(|| file.to_owned()),
+        line,
+        column,
+    })
+}
+
+/// Parses a trailing position in the module's own source, e.g.
+/// `SyntaxError: unexpected token at line 3, col 7`, returning the
+/// line without the position.
+fn parse_position(line: &str) -> Option<(&str, StackFrame)> {
+    let (text, position) = line.rsplit_once(" at line ")?;
+    let (line, column) = position.split_once(", col ")?;
+    Some((
+        text,
+        StackFrame {
+            function: None,
+            file: None,
+            line: line.trim().parse().ok()?,
+            column: column
+                .trim()
+                .trim_end_matches(|c: char| !c.is_ascii_digit())
+                .parse()
+                .ok()?,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_stack_frames() {
+        let error = ModuleError::parse(
+            "TypeError: not a function\n    at run (bundle.js:3:7)\n    at bundle.js:10:1",
+        );
+        assert_eq!(error.message, "TypeError: not a function");
+        assert_eq!(
+            error.frames,
+            vec![
+                StackFrame {
+                    function: Some("run".into()),
+                    file: Some("bundle.js".into()),
+                    line: 3,
+                    column: 7,
+                },
+                StackFrame {
+                    function: None,
+                    file: Some("bundle.js".into()),
+                    line: 10,
+                    column: 1,
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn it_parses_source_positions() {
+        let error = ModuleError::parse("SyntaxError: unexpected token ')' at line 2, col 14");
+        assert_eq!(error.message, "SyntaxError: unexpected token ')'");
+        assert_eq!(
+            error.frames,
+            vec![StackFrame {
+                function: None,
+                file: None,
+                line: 2,
+                column: 14,
+            }]
+        );
+        assert_eq!(
+            error.to_string(),
+            "SyntaxError: unexpected token ')'\n    at <module>:2:14"
+        );
+    }
+}
@@ -2,8 +2,12 @@
 
 //! Utilities and definitions used throughout the system.
 
+mod error;
 mod module;
+mod source_map;
 mod sync;
 
+pub use error::*;
 pub use module::*;
+pub use source_map::*;
 pub use sync::*;
@@ -1,7 +1,11 @@
+use std::collections::BTreeMap;
+
 /// The content type of a module.
 pub enum ContentType {
     /// The JavaScript language.
     JavaScript,
+    /// The Python language.
+    Python,
 }
 
 impl std::fmt::Display for ContentType {
@@ -11,6 +15,7 @@ impl std::fmt::Display for ContentType {
             "{}",
             match self {
                 ContentType::JavaScript => "JavaScript",
+                ContentType::Python => "Python",
             }
         )
     }
@@ -22,6 +27,13 @@ pub struct ModuleDefinition {
     pub content_type: ContentType,
     /// Source code to execute in `vm`.
     pub source: String,
+    /// Pre-bundled sources that `source` may import, keyed
+    /// by their import specifier.
+    pub dependencies: BTreeMap<String, String>,
+    /// Source map of `source` when it was bundled, used to report
+    /// errors at their original positions. Does not contribute
+    /// to the module's [`ModuleId`].
+    pub source_map: Option<String>,
 }
 
 impl<T> From<T> for ModuleDefinition
@@ -32,6 +44,8 @@ where
         ModuleDefinition {
             source: value.into(),
             content_type: ContentType::JavaScript,
+            dependencies: BTreeMap::default(),
+            source_map: None,
         }
     }
 }
@@ -57,9 +71,42 @@ impl From<&ModuleDefinition> for ModuleId {
     fn from(value: &ModuleDefinition) -> ModuleId {
         let mut hasher = blake3::Hasher::new();
 
-        hasher.update(value.content_type.to_string().as_bytes());
-        hasher.update(value.source.as_bytes());
+        // Each field is length-prefixed, so that definitions
+        // cannot collide by moving bytes across fields.
+        let mut update = |field: &str| {
+            hasher.update(&(field.len() as u64).to_le_bytes());
+            hasher.update(field.as_bytes());
+        };
+        update(&value.content_type.to_string());
+        update(&value.source);
+        for (specifier, source) in value.dependencies.iter() {
+            update(specifier);
+            update(source);
+        }
 
         ModuleId(hasher.finalize())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_does_not_collide_definitions_across_field_boundaries() {
+        let definition = |source: &str, specifier: &str, dependency: &str| ModuleDefinition {
+            content_type: ContentType::JavaScript,
+            source: source.into(),
+            dependencies: BTreeMap::from([(specifier.into(), dependency.into())]),
+            source_map: None,
+        };
+        assert_ne!(
+            ModuleId::from(&definition("", "a", "bc")),
+            ModuleId::from(&definition("", "ab", "c"))
+        );
+        assert_ne!(
+            ModuleId::from(&definition("a", "b", "c")),
+            ModuleId::from(&definition("ab", "", "c"))
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{ModuleError, StackFrame};
+use sourcemap::SourceMap;
+use std::sync::Arc;
+
+/// Maps positions in a bundled module's source back
+/// to its original sources, via its source map.
+#[derive(Clone)]
+pub struct SourceMapper(Arc<SourceMap>);
+
+impl SourceMapper {
+    /// Parse `source_map`, failing if it is not a valid source map.
+    pub fn new(source_map: &str) -> Result<Self, String> {
+        let source_map = SourceMap::from_slice(source_map.as_bytes())
+            .map_err(|error| format!("Invalid source map: {error}"))?;
+        Ok(SourceMapper(Arc::new(source_map)))
+    }
+
+    /// Remap the frames of `error` in the bundled module's source,
+    /// setting its snippet to the original source at the innermost frame.
+    pub fn remap(&self, mut error: ModuleError) -> ModuleError {
+        let bundle_file = self.0.get_file();
+        for (index, frame) in error.frames.iter_mut().enumerate() {
+            if frame.file.is_some() && frame.file.as_deref() != bundle_file {
+                continue;
+            }
+            let Some(token) = self
+                .0
+                .lookup_token(frame.line.saturating_sub(1), frame.column.saturating_sub(1))
+            else {
+                continue;
+            };
+            if index == 0 {
+                error.snippet = token
+                    .get_source_view()
+                    .and_then(|view| view.get_line(token.get_src_line()))
+                    .map(|line| line.to_owned());
+            }
+            *frame = StackFrame {
+                function: frame.function.take(),
+                file: token.get_source().map(|source| source.to_owned()),
+                line: token.get_src_line() + 1,
+                column: token.get_src_col() + 1,
+            };
+        }
+        error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Maps the start of each line of the bundle to
+    // the start of the next line in "original.ts".
+    const SOURCE_MAP: &str = r#"{
+        "version": 3,
+        "sources": ["original.ts"],
+        "sourcesContent": ["// A comment\nconst x: number = 1;\nthrow new Error(\"oops\");\n"],
+        "names": [],
+        "mappings": "AACA;AACA"
+    }"#;
+
+    #[test]
+    fn it_remaps_module_errors_to_original_sources() -> Result<(), String> {
+        let source_mapper = SourceMapper::new(SOURCE_MAP)?;
+        let error = source_mapper.remap(ModuleError::parse(
+            "Error: oops\n    at run (<module>:2:7)\n    at dependency.js:1:1",
+        ));
+
+        assert_eq!(
+            error,
+            ModuleError {
+                message: "Error: oops".into(),
+                frames: vec![
+                    StackFrame {
+                        function: Some("run".into()),
+                        file: Some("original.ts".into()),
+                        line: 3,
+                        column: 1,
+                    },
+                    StackFrame {
+                        function: None,
+                        file: Some("dependency.js".into()),
+                        line: 1,
+                        column: 1,
+                    },
+                ],
+                snippet: Some("throw new Error(\"oops\");".into()),
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn it_rejects_invalid_source_maps() {
+        assert!(SourceMapper::new("not a source map").is_err());
+    }
+}
@@ -56,8 +56,40 @@ impl From<Error> for String {
 #[cfg(target_arch = "wasm32")]
 impl From<Error> for wasm_bindgen::JsValue {
     fn from(error: Error) -> Self {
-        error.to_string().into()
+        match error {
+            #[cfg(feature = "runtime")]
+            Error::RuntimeError(ct_runtime::Error::ModuleFailure(error)) => {
+                module_error_to_js(error)
+            }
+            error => error.to_string().into(),
+        }
+    }
+}
+
+/// Converts a [`ct_common::ModuleError`] into a JavaScript `Error`,
+/// with its stack frames as `frames` and its source snippet as `snippet`.
+#[cfg(all(target_arch = "wasm32", feature = "runtime"))]
+fn module_error_to_js(error: ct_common::ModuleError) -> wasm_bindgen::JsValue {
+    use js_sys::{Array, Object, Reflect};
+    use wasm_bindgen::JsValue;
+
+    let js_error = js_sys::Error::new(&error.message);
+    let frames = Array::new();
+    for frame in error.frames.iter() {
+        let js_frame = Object::new();
+        let _ = Reflect::set(
+            &js_frame,
+            &"function".into(),
+            &frame.function.clone().into(),
+        );
+        let _ = Reflect::set(&js_frame, &"file".into(), &frame.file.clone().into());
+        let _ = Reflect::set(&js_frame, &"line".into(), &frame.line.into());
+        let _ = Reflect::set(&js_frame, &"column".into(), &frame.column.into());
+        frames.push(&js_frame);
     }
+    let _ = Reflect::set(&js_error, &"frames".into(), &frames);
+    let _ = Reflect::set(&js_error, &"snippet".into(), &error.snippet.into());
+    JsValue::from(js_error)
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -48,9 +48,21 @@ impl CtEngine {
 
     pub fn define(&mut self, js_definition: JsValue) -> Result<JsValue, JsValue> {
         let definition = ModuleDefinition::from(js_to_string(js_definition)?);
-        let module_id = self.inner.borrow_mut().define(definition)?;
-        info!("Defining {:?}", module_id);
-        Ok(JsValue::from_str(&(module_id.to_string())))
+        self.define_module(definition)
+    }
+
+    /// Define a module from `js_definition`, bundled with `source_map`.
+    /// Errors thrown by the module report positions in its original
+    /// sources.
+    #[wasm_bindgen(js_name = "defineWithSourceMap")]
+    pub fn define_with_source_map(
+        &mut self,
+        js_definition: JsValue,
+        source_map: String,
+    ) -> Result<JsValue, JsValue> {
+        let mut definition = ModuleDefinition::from(js_to_string(js_definition)?);
+        definition.source_map = Some(source_map);
+        self.define_module(definition)
     }
 
     pub fn run(&mut self, id: JsValue, input: JsValue) -> Result<JsValue, JsValue> {
@@ -60,3 +72,11 @@ impl CtEngine {
         Ok(deserialize_js(&result)?)
     }
 }
+
+impl CtEngine {
+    fn define_module(&mut self, definition: ModuleDefinition) -> Result<JsValue, JsValue> {
+        let module_id = self.inner.borrow_mut().define(definition)?;
+        info!("Defining {:?}", module_id);
+        Ok(JsValue::from_str(&(module_id.to_string())))
+    }
+}
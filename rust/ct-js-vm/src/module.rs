@@ -1,12 +1,13 @@
 use crate::{bindings::common::basic::host_callback::callback as host_callback, util};
 use blake3::Hash;
-use boa_engine::module::{ModuleLoader, Referrer};
+use boa_engine::module::{ModuleLoader, Referrer, SyntheticModuleInitializer};
+use boa_engine::object::FunctionObjectBuilder;
 use boa_engine::property::Attribute;
 use boa_engine::{
     builtins::promise::PromiseState, js_string, Context, JsObject, Module as JsModule,
     NativeFunction, Source,
 };
-use boa_engine::{JsResult, JsString, JsValue};
+use boa_engine::{JsError, JsNativeError, JsResult, JsString, JsValue};
 use boa_runtime::Console;
 use once_cell::unsync::OnceCell;
 use std::collections::BTreeMap;
@@ -17,61 +18,102 @@ thread_local! {
     pub static MODULE_STATE: RefCell<OnceCell<Rc<RwLock<Module>>>> = const { RefCell::new(OnceCell::new()) };
 }
 
-#[derive(Default)]
+/// Specifier of the synthetic module exposing the host callback.
+const HOST_CALLBACK_SPECIFIER: &str = "common:basic/host-callback@0.0.1";
+
+/// Specifier of the module exposing `common:io/state`.
+const IO_STATE_SPECIFIER: &str = "common:io/state@0.0.1";
+
+/// Implements `common:io/state` on top of the host callback, which
+/// receives `{ op: "read", name }` and `{ op: "write", name, value }`
+/// requests. A `null` response to a read means the state is absent.
+const IO_STATE_SOURCE: &str = r#"
+import { callback } from "common:basic/host-callback@0.0.1";
+
+const reference = (value) => ({
+  deref: () => value,
+  read: (name) =>
+    value !== null && typeof value === "object" && name in value
+      ? reference(value[name])
+      : undefined,
+});
+
+export const read = (name) => {
+  const value = callback({ op: "read", name });
+  return value === null || value === undefined ? undefined : reference(value);
+};
+
+export const write = (name, value) => {
+  callback({ op: "write", name, value });
+};
+"#;
+
+/// Resolves imports to either host-provided synthetic modules
+/// or to the pre-bundled dependencies that were provided
+/// alongside the module source.
 pub struct CommonModuleLoader {
-    builtins: RefCell<BTreeMap<JsString, JsModule>>,
+    dependencies: BTreeMap<String, String>,
+    modules: RefCell<BTreeMap<JsString, JsModule>>,
 }
 
-/*
 impl CommonModuleLoader {
-    pub fn new() -> Self {
+    pub fn new(dependencies: BTreeMap<String, String>) -> Self {
         Self {
-            builtins: RefCell::new(BTreeMap::new()),
+            dependencies,
+            modules: RefCell::new(BTreeMap::new()),
         }
     }
+
+    fn resolve(&self, specifier: &str, context: &mut Context) -> JsResult<JsModule> {
+        if specifier == HOST_CALLBACK_SPECIFIER {
+            return Ok(create_host_callback_module(context));
+        }
+        if specifier == IO_STATE_SPECIFIER {
+            return JsModule::parse(
+                Source::from_bytes(IO_STATE_SOURCE.as_bytes()),
+                None,
+                context,
+            );
+        }
+
+        let Some(source) = self.dependencies.get(specifier) else {
+            return Err(JsNativeError::typ()
+                .with_message(format!("Could not resolve module '{specifier}'"))
+                .into());
+        };
+
+        JsModule::parse(Source::from_bytes(source.as_bytes()), None, context)
+    }
 }
-*/
 
 impl ModuleLoader for CommonModuleLoader {
     fn load_imported_module(
         &self,
         _referrer: Referrer,
-        _specifier: JsString,
-        _finish_load: Box<dyn FnOnce(JsResult<JsModule>, &mut Context)>,
-        _context: &mut Context,
+        specifier: JsString,
+        finish_load: Box<dyn FnOnce(JsResult<JsModule>, &mut Context)>,
+        context: &mut Context,
     ) {
-        /*
-        println!("Loading imported module: {:?}", specifier);
-        let maybe_module = self.builtins.borrow().get(&specifier).cloned();
-        let module = match maybe_module {
-            None => {
-                let specifier_std = specifier.to_std_string().unwrap_or_default();
-                let module: boa_engine::Module = match specifier_std.as_str() {
-                    //"common:io/state@0.0.1" => create_io_state_module(context),
-                    _ => {
-                        finish_load(
-                            Err(JsError::from_opaque(js_string!("Invalid specifier").into())),
-                            context,
-                        );
-                        return;
-                    }
-                };
-
-                self.builtins
-                    .borrow_mut()
-                    .insert(specifier.clone(), module.clone());
-                module
-            }
-            Some(module) => module,
-        };
-        finish_load(Ok(module), context);
-        */
+        if let Some(module) = self.get_module(specifier.clone()) {
+            finish_load(Ok(module), context);
+            return;
+        }
+
+        let result = self.resolve(&specifier.to_std_string_escaped(), context);
+
+        if let Ok(module) = &result {
+            self.register_module(specifier, module.clone());
+        }
+
+        finish_load(result, context);
     }
 
-    fn register_module(&self, _specifier: boa_engine::JsString, _module: JsModule) {}
+    fn register_module(&self, specifier: JsString, module: JsModule) {
+        self.modules.borrow_mut().insert(specifier, module);
+    }
 
     fn get_module(&self, specifier: JsString) -> Option<JsModule> {
-        self.builtins.borrow().get(&specifier).cloned()
+        self.modules.borrow().get(&specifier).cloned()
     }
 
     fn init_import_meta(
@@ -97,24 +139,32 @@ impl Module {
         let input_js = util::str_to_js_object(input, &mut self.context)?;
         let result = init
             .call(&JsValue::undefined(), &[input_js], &mut self.context)
-            .map_err(util::format_error)?;
+            .map_err(|error| util::format_js_error(error, &mut self.context))?;
         util::js_object_to_str(result, &mut self.context)
     }
 
-    pub fn load(maybe_script: Option<String>) -> Result<Rc<RwLock<Module>>, String> {
-        let script_id = Rc::new(blake3::hash(
-            maybe_script.clone().unwrap_or_default().as_bytes(),
+    pub fn load(
+        maybe_script: Option<String>,
+        dependencies: BTreeMap<String, String>,
+    ) -> Result<Rc<RwLock<Module>>, String> {
+        let script_id = Rc::new(script_id(
+            maybe_script.as_deref().unwrap_or_default(),
+            &dependencies,
         ));
         let maybe_script = maybe_script.map(Rc::new);
 
-        let state = Self::get_or_init(maybe_script.clone(), script_id.clone())?;
+        let state = Self::get_or_init(
+            maybe_script.clone(),
+            dependencies.clone(),
+            script_id.clone(),
+        )?;
         let read_state = state.read().map_err(|error| format!("{error}"))?;
 
         if read_state.script_id == script_id {
             Ok(state.clone())
         } else {
             Self::reset();
-            Self::get_or_init(maybe_script, script_id)
+            Self::get_or_init(maybe_script, dependencies, script_id)
         }
     }
 
@@ -130,6 +180,7 @@ impl Module {
 
     fn get_or_init(
         maybe_script: Option<Rc<String>>,
+        dependencies: BTreeMap<String, String>,
         script_id: Rc<Hash>,
     ) -> Result<Rc<RwLock<Module>>, String> {
         Ok(MODULE_STATE
@@ -143,7 +194,7 @@ impl Module {
                             return Err("Must provide a script to load!".to_owned());
                         };
 
-                        let loader = Rc::new(CommonModuleLoader::default());
+                        let loader = Rc::new(CommonModuleLoader::new(dependencies));
 
                         let mut context = Context::builder()
                             .module_loader(loader.clone())
@@ -175,7 +226,11 @@ impl Module {
                                 //println!("Module didn't evaluate!")
                             }
                             PromiseState::Rejected(error) => {
-                                //println!("Module error: {}", error.display())
+                                let error = JsError::from_opaque(error);
+                                return Err(format!(
+                                    "Module error: {}",
+                                    util::format_js_error(error, &mut context)
+                                ));
                             }
                         };
 
@@ -211,22 +266,63 @@ fn get_module_export(
         .ok_or_else(|| format!("No '{export_name}' function was exported!"))
 }
 
+fn call_host_callback(_: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+    let Some(arg) = args.get(0) else {
+        return Err(util::str_to_js_error(
+            "`hostCallback` requires at least one argument.",
+        ));
+    };
+    let payload = util::js_object_to_str(arg.clone(), context).map_err(util::str_to_js_error)?;
+    let response = host_callback(&payload).map_err(util::str_to_js_error)?;
+    Ok(util::str_to_js_object(response, context).map_err(util::str_to_js_error)?)
+}
+
 fn set_host_callback(context: &mut Context) -> Result<(), String> {
     context
         .register_global_builtin_callable(
             js_string!("hostCallback"),
             1,
-            NativeFunction::from_fn_ptr(|_, args, context| {
-                let Some(arg) = args.get(0) else {
-                    return Err(util::str_to_js_error(
-                        "`hostCallback` requires at least one argument.",
-                    ));
-                };
-                let payload =
-                    util::js_object_to_str(arg.clone(), context).map_err(util::str_to_js_error)?;
-                let response = host_callback(&payload).map_err(util::str_to_js_error)?;
-                Ok(util::str_to_js_object(response, context).map_err(util::str_to_js_error)?)
-            }),
+            NativeFunction::from_fn_ptr(call_host_callback),
         )
         .map_err(|e| e.to_string())
 }
+
+/// Creates a synthetic module exporting the host callback
+/// as `callback`, mirroring `common:basic/host-callback`.
+fn create_host_callback_module(context: &mut Context) -> JsModule {
+    let callback = FunctionObjectBuilder::new(
+        context.realm(),
+        NativeFunction::from_fn_ptr(call_host_callback),
+    )
+    .length(1)
+    .build();
+
+    JsModule::synthetic(
+        &[js_string!("callback")],
+        SyntheticModuleInitializer::from_copy_closure_with_captures(
+            |module, callback, _context| {
+                module.set_export(&js_string!("callback"), callback.clone().into())?;
+                Ok(())
+            },
+            callback,
+        ),
+        None,
+        None,
+        context,
+    )
+}
+
+/// Identifies `script` along with its `dependencies`. Every field is
+/// prefixed with its length, so that different scripts cannot share
+/// an ID by moving bytes across field boundaries.
+fn script_id(script: &str, dependencies: &BTreeMap<String, String>) -> Hash {
+    let mut hasher = blake3::Hasher::new();
+    let fields = dependencies
+        .iter()
+        .flat_map(|(specifier, source)| [specifier.as_str(), source.as_str()]);
+    for field in std::iter::once(script).chain(fields) {
+        hasher.update(&(field.len() as u64).to_le_bytes());
+        hasher.update(field.as_bytes());
+    }
+    hasher.finalize()
+}
@@ -7,6 +7,34 @@ pub fn format_error<E: std::fmt::Display>(error: E) -> String {
     format!("{}", error)
 }
 
+/// Converts an error thrown by a module to a string, followed by the
+/// frames of its `stack` (one `at function (file:line:column)` per line)
+/// when the thrown value carries one.
+///
+/// Boa reports the position of syntax errors in their message (as
+/// `at line L, col C`), so only errors thrown at runtime need a `stack`
+/// for the host to locate them.
+pub fn format_js_error(error: JsError, context: &mut Context) -> String {
+    let mut formatted = format_error(&error);
+    let thrown = error.to_opaque(context);
+    let stack = match thrown.as_object() {
+        Some(object) => object.get(js_string!("stack"), context).ok(),
+        None => None,
+    };
+    if let Some(stack) = stack.as_ref().and_then(JsValue::as_string) {
+        for frame in stack
+            .to_std_string_escaped()
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.starts_with("at "))
+        {
+            formatted.push('\n');
+            formatted.push_str(frame);
+        }
+    }
+    formatted
+}
+
 pub fn str_to_js_object(value: String, context: &mut Context) -> Result<JsValue, String> {
     json_parse(JsValue::String(js_string!(value)), context)
 }
@@ -13,6 +13,7 @@ pub mod common {
         fn from(value: ContentType) -> Self {
             match value {
                 ContentType::JavaScript => ct_common::ContentType::JavaScript,
+                ContentType::Python => ct_common::ContentType::Python,
             }
         }
     }
@@ -21,6 +22,7 @@ pub mod common {
         fn from(value: ct_common::ContentType) -> Self {
             match value {
                 ct_common::ContentType::JavaScript => ContentType::JavaScript,
+                ct_common::ContentType::Python => ContentType::Python,
             }
         }
     }
@@ -30,6 +32,8 @@ pub mod common {
             ModuleDefinition {
                 content_type: ContentType::from(value.content_type).into(),
                 source: value.source,
+                dependencies: value.dependencies.into_iter().collect(),
+                source_map: value.source_map,
             }
         }
     }
@@ -42,9 +46,82 @@ pub mod common {
                     .map_err(|e| e.to_string())?
                     .into(),
                 source: value.source,
+                dependencies: value.dependencies.into_iter().collect(),
+                source_map: value.source_map,
             })
         }
     }
+
+    impl From<ct_common::StackFrame> for StackFrame {
+        fn from(value: ct_common::StackFrame) -> Self {
+            StackFrame {
+                function: value.function,
+                file: value.file,
+                line: value.line,
+                column: value.column,
+            }
+        }
+    }
+
+    impl From<StackFrame> for ct_common::StackFrame {
+        fn from(value: StackFrame) -> Self {
+            ct_common::StackFrame {
+                function: value.function,
+                file: value.file,
+                line: value.line,
+                column: value.column,
+            }
+        }
+    }
+
+    impl From<ct_common::ModuleError> for ModuleError {
+        fn from(value: ct_common::ModuleError) -> Self {
+            ModuleError {
+                message: value.message,
+                frames: value.frames.into_iter().map(StackFrame::from).collect(),
+                snippet: value.snippet,
+            }
+        }
+    }
+
+    impl From<ModuleError> for ct_common::ModuleError {
+        fn from(value: ModuleError) -> Self {
+            ct_common::ModuleError {
+                message: value.message,
+                frames: value
+                    .frames
+                    .into_iter()
+                    .map(ct_common::StackFrame::from)
+                    .collect(),
+                snippet: value.snippet,
+            }
+        }
+    }
+
+    impl From<ModuleError> for tonic::Status {
+        /// Returns an [`Aborted`](tonic::Code::Aborted) status for `value`,
+        /// with the encoded [`ModuleError`] as its details.
+        fn from(value: ModuleError) -> Self {
+            use prost::Message;
+            tonic::Status::with_details(
+                tonic::Code::Aborted,
+                ct_common::ModuleError::from(value.clone()).to_string(),
+                value.encode_to_vec().into(),
+            )
+        }
+    }
+
+    impl ModuleError {
+        /// Decode the [`ModuleError`] carried as the details of `status`,
+        /// if `status` was created from one.
+        pub fn from_status(status: &tonic::Status) -> Option<Self> {
+            use prost::Message;
+            if status.code() != tonic::Code::Aborted || status.details().is_empty() {
+                return None;
+            }
+            ModuleError::decode(status.details()).ok()
+        }
+    }
 }
 
 /// Protobufs for the module builder.
@@ -53,3 +130,42 @@ pub mod common {
 pub mod builder {
     tonic::include_proto!("builder");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::common::ModuleError;
+
+    #[test]
+    fn it_decodes_module_errors_from_statuses() {
+        let error = ct_common::ModuleError {
+            message: "Error: oops".into(),
+            frames: vec![ct_common::StackFrame {
+                function: Some("run".into()),
+                file: Some("original.ts".into()),
+                line: 3,
+                column: 1,
+            }],
+            snippet: Some("throw new Error(\"oops\");".into()),
+        };
+        let status = tonic::Status::from(ModuleError::from(error.clone()));
+
+        assert_eq!(status.code(), tonic::Code::Aborted);
+        assert_eq!(status.message(), error.to_string());
+        assert_eq!(
+            ModuleError::from_status(&status).map(ct_common::ModuleError::from),
+            Some(error)
+        );
+    }
+
+    #[test]
+    fn it_does_not_decode_module_errors_from_other_statuses() {
+        assert_eq!(
+            ModuleError::from_status(&tonic::Status::aborted("Module failed")),
+            None
+        );
+        assert_eq!(
+            ModuleError::from_status(&tonic::Status::internal("Internal error")),
+            None
+        );
+    }
+}
@@ -0,0 +1,52 @@
+#![warn(missing_docs)]
+
+//! This crate implements a basic Python VM with
+//! minimal API during development.
+//!
+//! Building for wasm32-wasip1 results in an empty lib.
+
+#[cfg(all(target_arch = "wasm32", target_os = "wasi"))]
+#[allow(warnings)]
+#[rustfmt::skip]
+mod bindings;
+
+#[cfg(all(target_arch = "wasm32", target_os = "wasi"))]
+mod module;
+#[cfg(all(target_arch = "wasm32", target_os = "wasi"))]
+mod util;
+
+#[cfg(all(target_arch = "wasm32", target_os = "wasi"))]
+mod guest {
+    use crate::bindings::exports::common::basic::processor::Guest as ProcessorGuest;
+    use crate::bindings::exports::common::basic::vm::Guest as VmGuest;
+    use crate::module::Module;
+
+    pub struct PythonInterpreter;
+
+    impl ProcessorGuest for PythonInterpreter {
+        fn run(input: String) -> Result<String, String> {
+            let module = Module::get().ok_or("No script source has been set!")?;
+            let module = module.read().map_err(|error| format!("{error}"))?;
+            module.call_run(input)
+        }
+    }
+
+    impl VmGuest for PythonInterpreter {
+        fn set_source(source: String, dependencies: Vec<(String, String)>) -> Result<(), String> {
+            Module::load(Some(source), dependencies.into_iter().collect())?;
+            Ok(())
+        }
+    }
+
+    impl Drop for PythonInterpreter {
+        fn drop(&mut self) {
+            Module::reset();
+        }
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_os = "wasi"))]
+use guest::*;
+
+#[cfg(all(target_arch = "wasm32", target_os = "wasi"))]
+bindings::export!(PythonInterpreter with_types_in bindings);
@@ -0,0 +1,231 @@
+use crate::util;
+use blake3::Hash;
+use once_cell::unsync::OnceCell;
+use rustpython_vm::{
+    compiler::Mode, pymodule, scope::Scope, Interpreter, PyObjectRef, VirtualMachine,
+};
+use std::collections::BTreeMap;
+use std::{cell::RefCell, thread_local};
+use std::{rc::Rc, sync::RwLock};
+
+thread_local! {
+    pub static MODULE_STATE: RefCell<OnceCell<Rc<RwLock<Module>>>> = const { RefCell::new(OnceCell::new()) };
+}
+
+/// Name of the native module exposing the host callback,
+/// mirroring `common:basic/host-callback`.
+const HOST_CALLBACK_MODULE: &str = "host_callback";
+
+/// Exposes the host callback to Python as `host_callback.callback`.
+#[pymodule]
+mod host_callback {
+    use crate::{bindings::common::basic::host_callback::callback as host_callback, util};
+    use rustpython_vm::{PyObjectRef, PyResult, VirtualMachine};
+
+    #[pyfunction]
+    pub fn callback(payload: PyObjectRef, vm: &VirtualMachine) -> PyResult<PyObjectRef> {
+        let payload = util::py_object_to_str(&payload, vm)
+            .map_err(|error| util::str_to_py_error(error, vm))?;
+        let response = host_callback(&payload).map_err(|error| util::str_to_py_error(error, vm))?;
+        util::str_to_py_object(response, vm).map_err(|error| util::str_to_py_error(error, vm))
+    }
+}
+
+pub struct Module {
+    run_fn: PyObjectRef,
+    interpreter: Interpreter,
+    script_id: Rc<Hash>,
+}
+
+impl Module {
+    pub fn call_run(&self, input: String) -> Result<String, String> {
+        self.interpreter.enter(|vm| {
+            let input = util::str_to_py_object(input, vm)?;
+            let result = self
+                .run_fn
+                .call((input,), vm)
+                .map_err(|error| util::format_exception(vm, error))?;
+            util::py_object_to_str(&result, vm)
+        })
+    }
+
+    pub fn load(
+        maybe_script: Option<String>,
+        dependencies: BTreeMap<String, String>,
+    ) -> Result<Rc<RwLock<Module>>, String> {
+        let script_id = Rc::new(script_id(
+            maybe_script.as_deref().unwrap_or_default(),
+            &dependencies,
+        ));
+        let maybe_script = maybe_script.map(Rc::new);
+
+        let state = Self::get_or_init(
+            maybe_script.clone(),
+            dependencies.clone(),
+            script_id.clone(),
+        )?;
+        let read_state = state.read().map_err(|error| format!("{error}"))?;
+
+        if read_state.script_id == script_id {
+            Ok(state.clone())
+        } else {
+            Self::reset();
+            Self::get_or_init(maybe_script, dependencies, script_id)
+        }
+    }
+
+    pub fn get() -> Option<Rc<RwLock<Module>>> {
+        MODULE_STATE.with_borrow(|state| state.get().cloned())
+    }
+
+    pub fn reset() {
+        MODULE_STATE.with_borrow_mut(|state| {
+            state.take();
+        });
+    }
+
+    fn get_or_init(
+        maybe_script: Option<Rc<String>>,
+        dependencies: BTreeMap<String, String>,
+        script_id: Rc<Hash>,
+    ) -> Result<Rc<RwLock<Module>>, String> {
+        Ok(MODULE_STATE
+            .try_with(move |state| {
+                let state = state.borrow_mut();
+                let result = state
+                    .get_or_try_init(move || {
+                        let script = if let Some(script) = maybe_script {
+                            script
+                        } else {
+                            return Err("Must provide a script to load!".to_owned());
+                        };
+
+                        let interpreter = Interpreter::with_init(Default::default(), |vm| {
+                            vm.add_native_module(
+                                HOST_CALLBACK_MODULE.to_owned(),
+                                Box::new(host_callback::make_module),
+                            );
+                        });
+
+                        let run_fn = interpreter.enter(|vm| {
+                            vm.builtins
+                                .set_attr(
+                                    "host_callback",
+                                    vm.new_function("host_callback", host_callback::callback),
+                                    vm,
+                                )
+                                .map_err(|error| util::format_exception(vm, error))?;
+
+                            register_dependencies(&dependencies, vm)?;
+
+                            let scope = vm.new_scope_with_builtins();
+                            exec_source(&script, "<module>", scope.clone(), vm)
+                                .map_err(|error| format!("Module error: {error}"))?;
+
+                            get_module_export("run", &scope, vm)
+                        })?;
+
+                        Ok(Rc::new(RwLock::new(Module {
+                            run_fn,
+                            interpreter,
+                            script_id,
+                        }))) as Result<_, String>
+                    })?
+                    .clone();
+                Ok(result) as Result<Rc<RwLock<Module>>, String>
+            })
+            .map_err(|error| format!("{error}"))??
+            .clone())
+    }
+}
+
+/// Compiles and executes `source` within `scope`.
+fn exec_source(
+    source: &str,
+    source_path: &str,
+    scope: Scope,
+    vm: &VirtualMachine,
+) -> Result<(), String> {
+    let code = vm
+        .compile(source, Mode::Exec, source_path.to_owned())
+        .map_err(|error| format!("{error}"))?;
+    vm.run_code_obj(code, scope)
+        .map_err(|error| util::format_exception(vm, error))?;
+    Ok(())
+}
+
+/// Evaluates each pre-bundled dependency as a module and inserts it
+/// into `sys.modules` under its specifier, so that `source` can
+/// `import` it. Dependencies may import one another: those that fail
+/// are retried until no further progress can be made.
+fn register_dependencies(
+    dependencies: &BTreeMap<String, String>,
+    vm: &VirtualMachine,
+) -> Result<(), String> {
+    let sys_modules = vm
+        .sys_module
+        .get_attr("modules", vm)
+        .map_err(|error| util::format_exception(vm, error))?;
+    let mut pending = dependencies.iter().collect::<Vec<_>>();
+
+    while !pending.is_empty() {
+        let mut last_error = None;
+        let mut remaining = vec![];
+
+        for (specifier, source) in pending.iter().copied() {
+            let dict = vm.ctx.new_dict();
+            let module = vm.new_module(specifier, dict.clone(), None);
+            let scope = Scope::with_builtins(None, dict, vm);
+
+            match exec_source(source, specifier, scope, vm) {
+                Ok(_) => sys_modules
+                    .set_item(specifier.as_str(), module.into(), vm)
+                    .map_err(|error| util::format_exception(vm, error))?,
+                Err(error) => {
+                    last_error = Some(format!("Could not load module '{specifier}': {error}"));
+                    remaining.push((specifier, source));
+                }
+            }
+        }
+
+        if remaining.len() == pending.len() {
+            return Err(last_error.unwrap_or_default());
+        }
+
+        pending = remaining;
+    }
+
+    Ok(())
+}
+
+fn get_module_export(
+    export_name: &str,
+    scope: &Scope,
+    vm: &VirtualMachine,
+) -> Result<PyObjectRef, String> {
+    scope
+        .globals
+        .get_item_opt(export_name, vm)
+        .map_err(|error| {
+            format!(
+                "Failed to get '{export_name}' export: {}",
+                util::format_exception(vm, error)
+            )
+        })?
+        .filter(|export| export.is_callable())
+        .ok_or_else(|| format!("No '{export_name}' function was exported!"))
+}
+
+/// Hashes `script` and its `dependencies`, prefixing each
+/// field with its length so that no two scripts collide.
+fn script_id(script: &str, dependencies: &BTreeMap<String, String>) -> Hash {
+    let mut hasher = blake3::Hasher::new();
+    let fields = dependencies
+        .iter()
+        .flat_map(|(specifier, source)| [specifier.as_str(), source.as_str()]);
+    for field in std::iter::once(script).chain(fields) {
+        hasher.update(&(field.len() as u64).to_le_bytes());
+        hasher.update(field.as_bytes());
+    }
+    hasher.finalize()
+}
@@ -0,0 +1,118 @@
+use rustpython_vm::{
+    builtins::{PyBaseExceptionRef, PyDict, PyFloat, PyInt, PyList, PyStr, PyTuple},
+    PyObjectRef, PyResult, VirtualMachine,
+};
+use serde_json::{Map, Number, Value};
+
+const INVALID_PY_TYPE: &str = "Could not cast Python value.";
+
+/// Converts a Python exception to a string, including its traceback.
+pub fn format_exception(vm: &VirtualMachine, exception: PyBaseExceptionRef) -> String {
+    let mut message = String::new();
+    match vm.write_exception(&mut message, &exception) {
+        Ok(_) => message.trim_end().to_owned(),
+        Err(_) => "Unknown Python exception".to_owned(),
+    }
+}
+
+pub fn str_to_py_object(value: String, vm: &VirtualMachine) -> Result<PyObjectRef, String> {
+    let json: Value = serde_json::from_str(&value).map_err(|error| format!("{error}"))?;
+    Ok(json_to_py_object(json, vm))
+}
+
+pub fn py_object_to_str(value: &PyObjectRef, vm: &VirtualMachine) -> Result<String, String> {
+    let json = py_object_to_json(value, vm).map_err(|error| format_exception(vm, error))?;
+    serde_json::to_string(&json).map_err(|error| format!("{error}"))
+}
+
+pub fn str_to_py_error<S: Into<String>>(value: S, vm: &VirtualMachine) -> PyBaseExceptionRef {
+    vm.new_runtime_error(value.into())
+}
+
+fn json_to_py_object(value: Value, vm: &VirtualMachine) -> PyObjectRef {
+    match value {
+        Value::Null => vm.ctx.none(),
+        Value::Bool(boolean) => vm.ctx.new_bool(boolean).into(),
+        Value::Number(number) => match number.as_i64() {
+            Some(integer) => vm.ctx.new_int(integer).into(),
+            None => vm.ctx.new_float(number.as_f64().unwrap_or(f64::NAN)).into(),
+        },
+        Value::String(string) => vm.ctx.new_str(string).into(),
+        Value::Array(values) => vm
+            .ctx
+            .new_list(
+                values
+                    .into_iter()
+                    .map(|value| json_to_py_object(value, vm))
+                    .collect(),
+            )
+            .into(),
+        Value::Object(entries) => {
+            let dict = vm.ctx.new_dict();
+            for (key, value) in entries {
+                // Setting a `str` key on a fresh dict cannot fail.
+                let _ = dict.set_item(key.as_str(), json_to_py_object(value, vm), vm);
+            }
+            dict.into()
+        }
+    }
+}
+
+fn py_object_to_json(value: &PyObjectRef, vm: &VirtualMachine) -> PyResult<Value> {
+    if vm.is_none(value) {
+        return Ok(Value::Null);
+    }
+
+    // `bool` is a subclass of `int` and must be checked first.
+    if value.class().is(vm.ctx.types.bool_type) {
+        return Ok(Value::Bool(value.clone().try_to_bool(vm)?));
+    }
+
+    if value.downcast_ref::<PyInt>().is_some() {
+        let integer: i64 = value.clone().try_into_value(vm)?;
+        return Ok(Value::Number(integer.into()));
+    }
+
+    if let Some(float) = value.downcast_ref::<PyFloat>() {
+        return Number::from_f64(float.to_f64())
+            .map(Value::Number)
+            .ok_or_else(|| vm.new_value_error("Cannot serialize a non-finite float".to_owned()));
+    }
+
+    if let Some(string) = value.downcast_ref::<PyStr>() {
+        return Ok(Value::String(string.as_str().to_owned()));
+    }
+
+    if let Some(list) = value.downcast_ref::<PyList>() {
+        let members = list.borrow_vec().to_vec();
+        return members
+            .iter()
+            .map(|member| py_object_to_json(member, vm))
+            .collect::<PyResult<Vec<_>>>()
+            .map(Value::Array);
+    }
+
+    if let Some(tuple) = value.downcast_ref::<PyTuple>() {
+        return tuple
+            .as_slice()
+            .iter()
+            .map(|member| py_object_to_json(member, vm))
+            .collect::<PyResult<Vec<_>>>()
+            .map(Value::Array);
+    }
+
+    if let Some(dict) = value.downcast_ref::<PyDict>() {
+        let mut entries = Map::new();
+        for (key, member) in dict {
+            let key = key
+                .downcast_ref::<PyStr>()
+                .ok_or_else(|| vm.new_type_error("Keys must be strings".to_owned()))?
+                .as_str()
+                .to_owned();
+            entries.insert(key, py_object_to_json(&member, vm)?);
+        }
+        return Ok(Value::Object(entries));
+    }
+
+    Err(vm.new_type_error(INVALID_PY_TYPE.to_owned()))
+}
@@ -4,8 +4,8 @@ use std::{
     process::Command,
 };
 
-/// Builds `common-formula-javascript-interpreter` as a
-/// wasm32-wasip1 component for inclusion via
+/// Builds `ct-js-vm` and `ct-py-vm` as wasm32-wasip1
+/// components for inclusion via e.g.
 /// `include_bytes!(env!("CT_JS_VM_WASM_PATH"));`
 fn main() {
     let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
@@ -13,6 +13,7 @@ fn main() {
     let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
 
     build_component(project_root_dir, out_dir.as_path(), "ct-js-vm", "CT_JS_VM");
+    build_component(project_root_dir, out_dir.as_path(), "ct-py-vm", "CT_PY_VM");
 }
 
 fn build_component(project_root_dir: &Path, out_dir: &Path, crate_name: &str, env_name: &str) {
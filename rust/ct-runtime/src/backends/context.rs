@@ -1,11 +1,30 @@
+use crate::{HostCallback, RecordedCallback};
 use rand::Rng;
+use std::collections::VecDeque;
+
+/// How host callback invocations are handled by a [`Context`].
+enum Callbacks {
+    /// Invoke the host callback.
+    Live,
+    /// Invoke the host callback, recording each invocation.
+    Record(Vec<RecordedCallback>),
+    /// Respond with previously recorded invocations, without
+    /// invoking the host callback.
+    Replay(VecDeque<RecordedCallback>),
+}
 
 /// A context providing host functionality for linking
 /// WASI in backends.
 ///
-/// Currently only providng random number generator.
+/// Provides a random number generator and mediates host
+/// callback invocations. VM components are virtualized such
+/// that these are their only imports, so a [`Context`] created
+/// via [`Context::deterministic`] or [`Context::replay`] fully
+/// determines the behavior of an instance.
 pub struct Context {
     random: cap_rand::rngs::StdRng,
+    callbacks: Callbacks,
+    divergence: Option<String>,
 }
 
 impl Context {
@@ -16,13 +35,99 @@ impl Context {
             .take(len as usize)
             .collect()
     }
+
+    /// Implements `common:basic/host-callback@0.0.1` `callback`.
+    pub fn invoke_host_callback(
+        &mut self,
+        host_callback: &HostCallback,
+        request: String,
+    ) -> std::result::Result<String, String> {
+        match &mut self.callbacks {
+            Callbacks::Live => host_callback.invoke(request),
+            Callbacks::Record(recorded) => {
+                let response = host_callback.invoke(request.clone());
+                recorded.push(RecordedCallback {
+                    request,
+                    response: response.clone(),
+                });
+                response
+            }
+            Callbacks::Replay(recorded) => match recorded.pop_front() {
+                Some(callback) if callback.request == request => callback.response,
+                Some(callback) => self.diverge(format!(
+                    "Expected host callback with '{}', got '{request}'",
+                    callback.request
+                )),
+                None => self.diverge(format!("Unexpected host callback with '{request}'")),
+            },
+        }
+    }
 }
 
 impl Context {
     /// Create a new [`Context`].
     pub fn new() -> Self {
-        let random = thread_rng();
-        Self { random }
+        Self {
+            random: thread_rng(),
+            callbacks: Callbacks::Live,
+            divergence: None,
+        }
+    }
+
+    /// Create a new [`Context`] whose randomness is derived from
+    /// `seed`, recording host callback invocations.
+    pub fn deterministic(seed: u64) -> Self {
+        Self {
+            random: seeded_rng(seed),
+            callbacks: Callbacks::Record(vec![]),
+            divergence: None,
+        }
+    }
+
+    /// Create a new [`Context`] whose randomness is derived from
+    /// `seed`, responding to host callback invocations with
+    /// `callbacks` in order.
+    pub fn replay(seed: u64, callbacks: Vec<RecordedCallback>) -> Self {
+        Self {
+            random: seeded_rng(seed),
+            callbacks: Callbacks::Replay(callbacks.into()),
+            divergence: None,
+        }
+    }
+
+    /// Whether this context was created with a seed.
+    pub fn is_deterministic(&self) -> bool {
+        !matches!(self.callbacks, Callbacks::Live)
+    }
+
+    /// Take the host callback invocations recorded since
+    /// the last call.
+    pub fn take_recorded_callbacks(&mut self) -> Vec<RecordedCallback> {
+        match &mut self.callbacks {
+            Callbacks::Record(recorded) => std::mem::take(recorded),
+            _ => vec![],
+        }
+    }
+
+    /// Check that a replay has not diverged from its recording,
+    /// optionally requiring that all recorded host callback
+    /// invocations were consumed.
+    pub fn check_divergence(&self, finished: bool) -> std::result::Result<(), String> {
+        if let Some(divergence) = &self.divergence {
+            return Err(divergence.to_owned());
+        }
+        match &self.callbacks {
+            Callbacks::Replay(remaining) if finished && !remaining.is_empty() => Err(format!(
+                "{} recorded host callbacks were not invoked",
+                remaining.len()
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn diverge(&mut self, message: String) -> std::result::Result<String, String> {
+        self.divergence.get_or_insert(message.clone());
+        Err(message)
     }
 }
 
@@ -37,3 +142,8 @@ fn thread_rng() -> cap_rand::rngs::StdRng {
     let mut rng = cap_rand::thread_rng(cap_rand::ambient_authority());
     cap_rand::rngs::StdRng::from_seed(rng.gen())
 }
+
+fn seeded_rng(seed: u64) -> cap_rand::rngs::StdRng {
+    use cap_rand::SeedableRng;
+    cap_rand::rngs::StdRng::seed_from_u64(seed)
+}
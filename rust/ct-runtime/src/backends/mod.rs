@@ -1,4 +1,4 @@
-mod context;
+pub(crate) mod context;
 mod traits;
 
 pub use traits::*;
@@ -8,6 +8,8 @@ mod wcl;
 #[cfg(target_arch = "wasm32")]
 pub use wcl::{WclEngine as Engine, WclInstance as Instance, WclModule as Module};
 
+#[cfg(not(target_arch = "wasm32"))]
+mod snapshot;
 #[cfg(not(target_arch = "wasm32"))]
 mod wasmtime;
 #[cfg(not(target_arch = "wasm32"))]
@@ -0,0 +1,126 @@
+//! Pre-initialization of VM components.
+//!
+//! A snapshot is a VM component whose linear memory and globals
+//! were captured after the module's source was loaded via
+//! `set-source`, in the spirit of [wizer]. Instances created from
+//! a snapshot skip parsing and evaluating the source: the guest
+//! recognizes that the requested source is already loaded.
+//!
+//! Any guest state established while loading the source, such as
+//! a seeded random number generator, is shared by every instance
+//! created from the same snapshot.
+//!
+//! [wizer]: https://github.com/bytecodealliance/wizer
+
+use crate::backends::{context::Context, wasmtime::create_linker};
+use crate::{Error, HostCallback, Result};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use component_init::Invoker;
+use ct_common::ModuleDefinition;
+use wasmtime::{
+    component::{Component, Instance},
+    Store,
+};
+
+mod virtual_module {
+    wasmtime::component::bindgen!({
+        world: "virtual-module",
+        path: "../../wit/common/basic/wit",
+        async: true,
+    });
+}
+
+/// Create a snapshot of the VM component `vm_bytes` after loading
+/// the source of `definition`, returning the bytes of the
+/// pre-initialized component.
+///
+/// This blocks the calling thread until the snapshot is created.
+pub fn create_snapshot(
+    vm_bytes: &[u8],
+    definition: &ModuleDefinition,
+    host_callback: HostCallback,
+) -> Result<Vec<u8>> {
+    let source = definition.source.clone();
+    let dependencies = definition
+        .dependencies
+        .iter()
+        .map(|(specifier, source)| (specifier.to_owned(), source.to_owned()))
+        .collect::<Vec<_>>();
+
+    // `component_init` is async, and is driven to completion on a
+    // dedicated thread. The caller still waits for it; the thread
+    // only keeps `block_on` from being nested within an executor
+    // the caller may already be running on.
+    let initialize = move || {
+        futures_executor::block_on(component_init::initialize(vm_bytes, move |instrumented| {
+            Box::pin(async move {
+                let engine = {
+                    let mut config = wasmtime::Config::default();
+                    config.async_support(true);
+                    wasmtime::Engine::new(&config)?
+                };
+                let component = Component::new(&engine, &instrumented)?;
+                let linker = create_linker(host_callback, &engine)?;
+                let mut store = Store::new(&engine, Context::new());
+
+                let instance = linker.instantiate_async(&mut store, &component).await?;
+                virtual_module::VirtualModule::new(&mut store, &instance)?
+                    .common_basic_vm()
+                    .call_set_source(&mut store, &source, &dependencies)
+                    .await?
+                    .map_err(|error| anyhow!(error))?;
+
+                Ok(Box::new(SnapshotInvoker { store, instance }) as Box<dyn Invoker>)
+            })
+        }))
+    };
+
+    std::thread::scope(|scope| scope.spawn(initialize).join())
+        .map_err(|_| Error::InternalError("Snapshot creation panicked".into()))?
+        .map_err(|e| Error::InstantiationFailure(format!("Could not create snapshot: {e}")))
+}
+
+/// Reads the state of an initialized instance on behalf
+/// of [`component_init`].
+struct SnapshotInvoker {
+    store: Store<Context>,
+    instance: Instance,
+}
+
+impl SnapshotInvoker {
+    async fn call<R>(&mut self, function: &str) -> anyhow::Result<R>
+    where
+        (R,): wasmtime::component::Lift + Send + Sync + 'static,
+    {
+        let func = self
+            .instance
+            .get_typed_func::<(), (R,)>(&mut self.store, function)?;
+        let (result,) = func.call_async(&mut self.store, ()).await?;
+        func.post_return_async(&mut self.store).await?;
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl Invoker for SnapshotInvoker {
+    async fn call_s32(&mut self, function: &str) -> anyhow::Result<i32> {
+        self.call(function).await
+    }
+
+    async fn call_s64(&mut self, function: &str) -> anyhow::Result<i64> {
+        self.call(function).await
+    }
+
+    async fn call_f32(&mut self, function: &str) -> anyhow::Result<f32> {
+        self.call(function).await
+    }
+
+    async fn call_f64(&mut self, function: &str) -> anyhow::Result<f64> {
+        self.call(function).await
+    }
+
+    async fn call_list_u8(&mut self, function: &str) -> anyhow::Result<Vec<u8>> {
+        self.call(function).await
+    }
+}
@@ -1,4 +1,4 @@
-use crate::Result;
+use crate::{backends::context::Context, Result};
 use ct_common::ModuleDefinition;
 
 /// Interface of backends to provide the of
@@ -19,11 +19,18 @@ pub trait ModuleBackend {
     /// Concrete type of [`InstanceBackend`] produced by this module.
     type Instance: InstanceBackend;
     /// Instantiate a new [`InstanceBackend`].
-    fn instantiate(&mut self) -> Result<Self::Instance>;
+    fn instantiate(&mut self) -> Result<Self::Instance> {
+        self.instantiate_with_context(Context::new())
+    }
+    /// Instantiate a new [`InstanceBackend`] providing host
+    /// functionality via `context`.
+    fn instantiate_with_context(&mut self, context: Context) -> Result<Self::Instance>;
 }
 
 /// An active instance of a [`ModuleBackend`].
 pub trait InstanceBackend {
     /// Run the process in this instance.
     fn run(&mut self, input: String) -> Result<String>;
+    /// The [`Context`] providing host functionality to this instance.
+    fn context_mut(&mut self) -> &mut Context;
 }
@@ -1,7 +1,10 @@
-use crate::backends::{context::Context, EngineBackend, InstanceBackend, ModuleBackend};
-use crate::{Error, HostCallback, HostCallbackFn, Result, VirtualMachine};
-use ct_common::ModuleDefinition;
-use std::collections::HashMap;
+use crate::backends::{
+    context::Context, snapshot::create_snapshot, EngineBackend, InstanceBackend, ModuleBackend,
+};
+use crate::{Error, HostCallback, HostCallbackFn, Result, RuntimeConfig, VirtualMachine};
+use ct_common::{ModuleDefinition, ModuleError, ModuleId};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
 use wasmtime::{
     component::{Component, Linker},
     AsContextMut,
@@ -18,12 +21,18 @@ mod virtual_module {
 pub struct WasmtimeEngine {
     engine: wasmtime::Engine,
     vm_components: HashMap<VirtualMachine, Component>,
+    snapshots: Arc<Mutex<SnapshotCache>>,
     callback: HostCallback,
+    config: RuntimeConfig,
 }
 
 impl WasmtimeEngine {
     /// Create a new [`WasmtimeEngine`].
-    pub fn new(callback: impl HostCallbackFn, vms: Vec<VirtualMachine>) -> Result<Self> {
+    pub fn new(
+        callback: impl HostCallbackFn,
+        vms: Vec<VirtualMachine>,
+        config: RuntimeConfig,
+    ) -> Result<Self> {
         let engine = {
             let mut config = wasmtime::Config::default();
 
@@ -43,9 +52,90 @@ impl WasmtimeEngine {
         Ok(WasmtimeEngine {
             engine,
             vm_components,
+            snapshots: Arc::new(Mutex::new(SnapshotCache::new(config.snapshot_capacity))),
             callback,
+            config,
         })
     }
+
+    /// Get the snapshot of `vm` pre-initialized with the source
+    /// of `definition`, creating it if it does not yet exist.
+    fn snapshot(&self, vm: &VirtualMachine, definition: &ModuleDefinition) -> Result<Component> {
+        let id = ModuleId::from(definition);
+        if let Some(component) = self.lock_snapshots()?.get(&id) {
+            return Ok(component);
+        }
+
+        // The cache is not locked while the snapshot is created, so
+        // that creating one does not hold up modules whose snapshots
+        // are already cached. Should another snapshot of the same
+        // module be cached in the meantime, that one is kept.
+        let snapshot = create_snapshot(vm.as_bytes(), definition, self.callback.clone())?;
+        let component =
+            Component::new(&self.engine, snapshot).map_err(|e| Error::from(e.to_string()))?;
+
+        let mut snapshots = self.lock_snapshots()?;
+        if let Some(component) = snapshots.get(&id) {
+            return Ok(component);
+        }
+        snapshots.insert(id, component.clone());
+        Ok(component)
+    }
+
+    fn lock_snapshots(&self) -> Result<MutexGuard<'_, SnapshotCache>> {
+        self.snapshots
+            .lock()
+            .map_err(|e| Error::InternalError(e.to_string()))
+    }
+}
+
+/// Snapshots by the [`ModuleId`] of the module they were created
+/// for, holding at most `capacity` snapshots. The least recently
+/// used snapshot is dropped to make room for a new one.
+struct SnapshotCache {
+    components: HashMap<ModuleId, Component>,
+    recency: VecDeque<ModuleId>,
+    capacity: usize,
+}
+
+impl SnapshotCache {
+    fn new(capacity: usize) -> Self {
+        SnapshotCache {
+            components: HashMap::default(),
+            recency: VecDeque::default(),
+            capacity,
+        }
+    }
+
+    fn get(&mut self, id: &ModuleId) -> Option<Component> {
+        let component = self.components.get(id)?.to_owned();
+        self.touch(id);
+        Some(component)
+    }
+
+    fn insert(&mut self, id: ModuleId, component: Component) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.components.insert(id.clone(), component).is_some() {
+            self.touch(&id);
+            return;
+        }
+        self.recency.push_back(id);
+        while self.recency.len() > self.capacity {
+            if let Some(evicted) = self.recency.pop_front() {
+                self.components.remove(&evicted);
+            }
+        }
+    }
+
+    fn touch(&mut self, id: &ModuleId) {
+        if let Some(position) = self.recency.iter().position(|other| other == id) {
+            if let Some(id) = self.recency.remove(position) {
+                self.recency.push_back(id);
+            }
+        }
+    }
 }
 
 impl EngineBackend for WasmtimeEngine {
@@ -53,16 +143,21 @@ impl EngineBackend for WasmtimeEngine {
 
     fn module(&self, definition: ModuleDefinition) -> Result<Self::Module> {
         let requested_vm = VirtualMachine::try_from(&definition.content_type)?;
-        let component = self
+        let vm_component = self
             .vm_components
             .get(&requested_vm)
-            .ok_or(Error::UnsupportedVm)?
-            .to_owned();
+            .ok_or(Error::UnsupportedVm)?;
+        let snapshot = if self.config.snapshots {
+            Some(self.snapshot(&requested_vm, &definition)?)
+        } else {
+            None
+        };
         let linker = create_linker(self.callback.clone(), &self.engine)?;
         Ok(WasmtimeModule {
             linker,
             engine: self.engine.clone(),
-            component,
+            component: vm_component.to_owned(),
+            snapshot,
             definition,
         })
     }
@@ -73,24 +168,39 @@ pub struct WasmtimeModule {
     linker: Linker<Context>,
     engine: wasmtime::Engine,
     component: Component,
+    snapshot: Option<Component>,
     definition: ModuleDefinition,
 }
 
 impl ModuleBackend for WasmtimeModule {
     type Instance = WasmtimeInstance;
-    fn instantiate(&mut self) -> Result<Self::Instance> {
-        let context = Context::new();
+    fn instantiate_with_context(&mut self, context: Context) -> Result<Self::Instance> {
+        // A snapshot's state depends on the context it was created
+        // with, so deterministic instances load the source themselves.
+        let component = match &self.snapshot {
+            Some(snapshot) if !context.is_deterministic() => snapshot,
+            _ => &self.component,
+        };
         let mut store = wasmtime::Store::new(&self.engine, context);
 
         let module_instance =
-            virtual_module::VirtualModule::instantiate(&mut store, &self.component, &self.linker)
+            virtual_module::VirtualModule::instantiate(&mut store, component, &self.linker)
                 .map_err(|e| Error::InstantiationFailure(e.to_string()))?;
 
+        let dependencies = self
+            .definition
+            .dependencies
+            .iter()
+            .map(|(specifier, source)| (specifier.to_owned(), source.to_owned()))
+            .collect::<Vec<_>>();
+
+        // When instantiated from a snapshot, the source is
+        // already loaded and this only verifies that it matches.
         module_instance
             .common_basic_vm()
-            .call_set_source(&mut store, &self.definition.source)
+            .call_set_source(&mut store, &self.definition.source, &dependencies)
             .map_err(|e| Error::InstantiationFailure(e.to_string()))?
-            .map_err(|e| Error::InstantiationFailure(e.to_string()))?;
+            .map_err(|e| Error::ModuleFailure(ModuleError::parse(&e)))?;
 
         Ok(WasmtimeInstance {
             module_instance,
@@ -112,12 +222,16 @@ impl InstanceBackend for WasmtimeInstance {
             .common_basic_processor()
             .call_run(self.store.as_context_mut(), &input)
             .map_err(|e| Error::InvocationFailure(e.to_string()))?
-            .map_err(|e| Error::InvocationFailure(e.to_string()))?;
+            .map_err(|e| Error::ModuleFailure(ModuleError::parse(&e)))?;
         Ok(value)
     }
+
+    fn context_mut(&mut self) -> &mut Context {
+        self.store.data_mut()
+    }
 }
 
-fn create_linker(
+pub(crate) fn create_linker(
     host_callback: HostCallback,
     engine: &wasmtime::Engine,
 ) -> Result<Linker<Context>> {
@@ -139,8 +253,44 @@ fn create_linker(
     callback_interface
         .func_wrap::<_, (String,), (std::result::Result<String, String>,)>(
             "callback",
-            move |_ctx, params| Ok((host_callback.invoke(params.0),)),
+            move |mut ctx, params| {
+                let store: &mut Context = ctx.data_mut();
+                Ok((store.invoke_host_callback(&host_callback, params.0),))
+            },
         )
         .map_err(|e| Error::LinkerFailure(e.to_string()))?;
     Ok(linker)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::SnapshotCache;
+    use ct_common::{ContentType, ModuleDefinition, ModuleId};
+    use wasmtime::component::Component;
+
+    fn module_id(source: &str) -> ModuleId {
+        ModuleId::from(&ModuleDefinition {
+            content_type: ContentType::JavaScript,
+            source: source.into(),
+            dependencies: Default::default(),
+            source_map: None,
+        })
+    }
+
+    #[test]
+    fn it_drops_the_least_recently_used_snapshot() {
+        let engine = wasmtime::Engine::default();
+        let component = Component::new(&engine, "(component)").unwrap();
+        let (a, b, c) = (module_id("a"), module_id("b"), module_id("c"));
+
+        let mut cache = SnapshotCache::new(2);
+        cache.insert(a.clone(), component.clone());
+        cache.insert(b.clone(), component.clone());
+        assert!(cache.get(&a).is_some());
+        cache.insert(c.clone(), component);
+
+        assert!(cache.get(&a).is_some());
+        assert!(cache.get(&b).is_none());
+        assert!(cache.get(&c).is_some());
+    }
+}
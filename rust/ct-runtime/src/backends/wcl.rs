@@ -3,9 +3,9 @@
 
 use crate::{
     backends::{context::Context, EngineBackend, InstanceBackend, ModuleBackend},
-    Error, HostCallback, HostCallbackFn, Result, VirtualMachine,
+    Error, HostCallback, HostCallbackFn, Result, RuntimeConfig, VirtualMachine,
 };
-use ct_common::{ConditionalSend, ConditionalSync, ModuleDefinition};
+use ct_common::{ConditionalSend, ConditionalSync, ModuleDefinition, ModuleError};
 use std::collections::HashMap;
 use wasm_component_layer as wcl;
 
@@ -26,7 +26,14 @@ pub struct WclEngine {
 
 impl WclEngine {
     /// Create a new [`WclEngine`].
-    pub fn new(callback: impl HostCallbackFn, vms: Vec<VirtualMachine>) -> Result<Self> {
+    ///
+    /// Snapshots are not supported by this backend, so
+    /// [`RuntimeConfig::snapshots`] is ignored.
+    pub fn new(
+        callback: impl HostCallbackFn,
+        vms: Vec<VirtualMachine>,
+        _config: RuntimeConfig,
+    ) -> Result<Self> {
         let engine = wcl::Engine::new(InnerEngine::default());
         let mut vm_components = HashMap::default();
         for vm in vms {
@@ -76,8 +83,7 @@ pub struct WclModule {
 
 impl ModuleBackend for WclModule {
     type Instance = WclInstance;
-    fn instantiate(&mut self) -> Result<Self::Instance> {
-        let context = Context::default();
+    fn instantiate_with_context(&mut self, context: Context) -> Result<Self::Instance> {
         let mut store = Store::new(&self.engine, context);
 
         Interface::Identifier("wasi:random/random@0.2.0")
@@ -98,7 +104,10 @@ impl ModuleBackend for WclModule {
                 &mut store,
                 &mut self.linker,
                 "callback",
-                move |_ctx, params| Ok((host_callback.invoke(params.0),)),
+                move |mut ctx, params| {
+                    let store: &mut Context = ctx.data_mut();
+                    Ok((store.invoke_host_callback(&host_callback, params.0),))
+                },
             )
             .map_err(|e| Error::LinkerFailure(e.to_string()))?;
 
@@ -124,11 +133,21 @@ impl WclInstance {
         module_instance: wcl::Instance,
         definition: &ModuleDefinition,
     ) -> Result<Self> {
-        let set_source_fn = Interface::Identifier("common:basic/vm@0.0.1")
-            .get_fn::<String, std::result::Result<(), String>>(&module_instance, "set-source")?;
-        let _ = set_source_fn
-            .call(&mut store, definition.source.clone())
-            .map_err(|e| Error::InstantiationFailure(e.to_string()))?;
+        let set_source_fn =
+            Interface::Identifier("common:basic/vm@0.0.1")
+                .get_fn::<(String, Vec<(String, String)>), std::result::Result<(), String>>(
+                    &module_instance,
+                    "set-source",
+                )?;
+        let dependencies = definition
+            .dependencies
+            .iter()
+            .map(|(specifier, source)| (specifier.to_owned(), source.to_owned()))
+            .collect();
+        set_source_fn
+            .call(&mut store, (definition.source.clone(), dependencies))
+            .map_err(|e| Error::InstantiationFailure(e.to_string()))?
+            .map_err(|e| Error::ModuleFailure(ModuleError::parse(&e)))?;
 
         let run_fn = Interface::Identifier("common:basic/processor@0.0.1")
             .get_fn::<String, std::result::Result<String, String>>(&module_instance, "run")?;
@@ -146,7 +165,11 @@ impl InstanceBackend for WclInstance {
         self.run_fn
             .call(&mut self.store, input)
             .map_err(|e| Error::InvocationFailure(e.to_string()))?
-            .map_err(|e| Error::InvocationFailure(e.to_string()))
+            .map_err(|e| Error::ModuleFailure(ModuleError::parse(&e)))
+    }
+
+    fn context_mut(&mut self) -> &mut Context {
+        self.store.data_mut()
     }
 }
 
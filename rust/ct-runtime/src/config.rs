@@ -0,0 +1,28 @@
+#[cfg(doc)]
+use crate::Runtime;
+#[cfg(doc)]
+use ct_common::ModuleId;
+
+/// Configuration of a [`Runtime`].
+#[derive(Clone, Debug)]
+pub struct RuntimeConfig {
+    /// Whether instances start from a snapshot of a VM that has
+    /// already loaded the module's source, rather than loading
+    /// it on every instantiation. Snapshots are created once per
+    /// [`ModuleId`] and are only supported by the native backend.
+    /// When enabled, [`Runtime::module`] fails if a snapshot
+    /// cannot be created. Disabled by default.
+    pub snapshots: bool,
+    /// The maximum number of snapshots kept at once. The least
+    /// recently used snapshot is dropped to make room for a new one.
+    pub snapshot_capacity: usize,
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        RuntimeConfig {
+            snapshots: false,
+            snapshot_capacity: 64,
+        }
+    }
+}
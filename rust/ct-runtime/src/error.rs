@@ -1,3 +1,4 @@
+use ct_common::ModuleError;
 use std::fmt::Debug;
 use thiserror::Error;
 
@@ -24,6 +25,16 @@ pub enum Error {
     #[error("Failed to invoke sandbox: {0}")]
     InvocationFailure(String),
 
+    /// The module's code threw an error while loading or running.
+    /// Its frames refer to the module's original sources when
+    /// the module was defined with a source map.
+    #[error("Module failed: {0}")]
+    ModuleFailure(ModuleError),
+
+    /// A replayed run did not match its recording.
+    #[error("Replay diverged from its recording: {0}")]
+    ReplayDivergence(String),
+
     /// An unexpected internal error occurred
     #[error("Internal error: {0}")]
     InternalError(String),
@@ -6,12 +6,17 @@
 //! Supports native and `wasm32-unknown-unknown` build targets.
 
 mod backends;
+mod config;
 mod error;
 mod host;
+mod replay;
 mod runtime;
+mod source_map;
 mod vm;
 
+pub use config::*;
 pub use error::*;
 pub use host::*;
+pub use replay::*;
 pub use runtime::*;
 pub use vm::*;
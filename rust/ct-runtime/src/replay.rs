@@ -0,0 +1,52 @@
+#[cfg(doc)]
+use crate::{Instance, Module};
+
+/// A host callback invocation observed during a deterministic run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecordedCallback {
+    /// The payload the guest passed to the host callback.
+    pub request: String,
+    /// The host callback's response.
+    pub response: std::result::Result<String, String>,
+}
+
+/// A single [`Instance::run`] observed during a deterministic run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecordedRun {
+    /// Input provided to the run.
+    pub input: String,
+    /// Output of the run, with errors in their display form.
+    pub output: std::result::Result<String, String>,
+}
+
+/// Everything needed to re-execute a deterministic [`Instance`]
+/// and compare its outputs bit-for-bit via [`Module::replay`].
+/// With the `serde` feature, a [`ReplayLog`] can be serialized
+/// to replay a run in a different process.
+///
+/// The host callback is the only source of nondeterminism left
+/// once randomness is seeded, so its responses are recorded
+/// (in invocation order, including those made while the module
+/// source was loaded) and served back during replay.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReplayLog {
+    /// Seed of the instance's random number generator.
+    pub seed: u64,
+    /// Host callback invocations, in order.
+    pub callbacks: Vec<RecordedCallback>,
+    /// Runs of the instance, in order.
+    pub runs: Vec<RecordedRun>,
+}
+
+impl ReplayLog {
+    /// Create an empty [`ReplayLog`] for an instance seeded with `seed`.
+    pub fn new(seed: u64) -> Self {
+        ReplayLog {
+            seed,
+            ..Default::default()
+        }
+    }
+}
@@ -1,9 +1,10 @@
 use crate::{
-    backends::{self, EngineBackend, InstanceBackend, ModuleBackend},
+    backends::{self, context::Context, EngineBackend, InstanceBackend, ModuleBackend},
+    source_map::{parse_source_map, remap_error},
     vm::VirtualMachine,
-    HostCallbackFn, Result,
+    Error, HostCallbackFn, RecordedRun, ReplayLog, Result, RuntimeConfig,
 };
-use ct_common::{ModuleDefinition, ModuleId};
+use ct_common::{ModuleDefinition, ModuleId, SourceMapper};
 
 /// A [`Runtime`] creates [`Module`]s.
 pub struct Runtime {
@@ -11,16 +12,37 @@ pub struct Runtime {
 }
 
 impl Runtime {
-    /// Create a new [`Runtime`].
+    /// Create a new [`Runtime`] with the default [`RuntimeConfig`].
     pub fn new(callback: impl HostCallbackFn) -> Result<Self> {
-        let inner = backends::Engine::new(callback, vec![VirtualMachine::JavaScript])?;
+        Self::with_config(callback, RuntimeConfig::default())
+    }
+
+    /// Create a new [`Runtime`] configured by `config`.
+    pub fn with_config(callback: impl HostCallbackFn, config: RuntimeConfig) -> Result<Self> {
+        let inner = backends::Engine::new(
+            callback,
+            vec![VirtualMachine::JavaScript, VirtualMachine::Python],
+            config,
+        )?;
         Ok(Runtime { inner })
     }
 
     /// Construct a new [`Module`] factory given a `definition`.
+    ///
+    /// If `definition` has a source map, [`Error::ModuleFailure`]
+    /// errors are remapped to positions in the original sources.
     pub fn module(&self, definition: ModuleDefinition) -> Result<Module> {
         let id: ModuleId = (&definition).into();
-        Ok(Module::new(id, self.inner.module(definition)?))
+        let source_mapper = definition
+            .source_map
+            .as_deref()
+            .map(parse_source_map)
+            .transpose()?;
+        Ok(Module::new(
+            id,
+            self.inner.module(definition)?,
+            source_mapper,
+        ))
     }
 }
 
@@ -28,11 +50,16 @@ impl Runtime {
 pub struct Module {
     inner: backends::Module,
     id: ModuleId,
+    source_mapper: Option<SourceMapper>,
 }
 
 impl Module {
-    fn new(id: ModuleId, inner: backends::Module) -> Self {
-        Self { id, inner }
+    fn new(id: ModuleId, inner: backends::Module, source_mapper: Option<SourceMapper>) -> Self {
+        Self {
+            id,
+            inner,
+            source_mapper,
+        }
     }
 
     /// Get the [`ModuleId`] for this module.
@@ -42,22 +69,110 @@ impl Module {
 
     /// Create a new [`Instance`] of this module.
     pub fn instantiate(&mut self) -> Result<Instance> {
-        Ok(Instance::new(self.inner.instantiate()?))
+        let inner = self
+            .inner
+            .instantiate()
+            .map_err(|error| remap_error(self.source_mapper.as_ref(), error))?;
+        Ok(Instance::new(inner, None, self.source_mapper.clone()))
+    }
+
+    /// Create a new deterministic [`Instance`] of this module.
+    ///
+    /// Randomness is derived from `seed` and host callback
+    /// invocations are recorded, such that the instance's runs
+    /// can be re-executed via [`Module::replay`] with its
+    /// [`Instance::replay_log`].
+    pub fn instantiate_deterministic(&mut self, seed: u64) -> Result<Instance> {
+        let mut inner = self
+            .inner
+            .instantiate_with_context(Context::deterministic(seed))
+            .map_err(|error| remap_error(self.source_mapper.as_ref(), error))?;
+        let mut log = ReplayLog::new(seed);
+        log.callbacks = inner.context_mut().take_recorded_callbacks();
+        Ok(Instance::new(inner, Some(log), self.source_mapper.clone()))
+    }
+
+    /// Re-execute the runs recorded in `log`, serving host callback
+    /// invocations from the recording. Fails with
+    /// [`Error::ReplayDivergence`] unless every run produces the
+    /// recorded output and makes the recorded host callbacks.
+    pub fn replay(&mut self, log: &ReplayLog) -> Result<()> {
+        let mut inner = self
+            .inner
+            .instantiate_with_context(Context::replay(log.seed, log.callbacks.clone()))?;
+        inner
+            .context_mut()
+            .check_divergence(false)
+            .map_err(Error::ReplayDivergence)?;
+
+        for (index, recorded) in log.runs.iter().enumerate() {
+            let output = inner
+                .run(recorded.input.clone())
+                .map_err(|error| remap_error(self.source_mapper.as_ref(), error).to_string());
+            inner
+                .context_mut()
+                .check_divergence(false)
+                .map_err(Error::ReplayDivergence)?;
+            if output != recorded.output {
+                return Err(Error::ReplayDivergence(format!(
+                    "Run {index} produced {output:?}, expected {:?}",
+                    recorded.output
+                )));
+            }
+        }
+
+        inner
+            .context_mut()
+            .check_divergence(true)
+            .map_err(Error::ReplayDivergence)
     }
 }
 
 /// An [`Instance`] can execute sandboxed code.
 pub struct Instance {
     inner: backends::Instance,
+    log: Option<ReplayLog>,
+    source_mapper: Option<SourceMapper>,
 }
 
 impl Instance {
-    fn new(inner: backends::Instance) -> Self {
-        Self { inner }
+    fn new(
+        inner: backends::Instance,
+        log: Option<ReplayLog>,
+        source_mapper: Option<SourceMapper>,
+    ) -> Self {
+        Self {
+            inner,
+            log,
+            source_mapper,
+        }
     }
 
     /// Invoke this instance.
     pub fn run(&mut self, input: String) -> Result<String> {
-        self.inner.run(input)
+        let output = self
+            .inner
+            .run(input.clone())
+            .map_err(|error| remap_error(self.source_mapper.as_ref(), error));
+        let Some(log) = &mut self.log else {
+            return output;
+        };
+
+        log.callbacks
+            .extend(self.inner.context_mut().take_recorded_callbacks());
+        log.runs.push(RecordedRun {
+            input,
+            output: output
+                .as_ref()
+                .map(String::to_owned)
+                .map_err(|error| error.to_string()),
+        });
+        output
+    }
+
+    /// The [`ReplayLog`] of this instance's runs, if it was
+    /// created via [`Module::instantiate_deterministic`].
+    pub fn replay_log(&self) -> Option<&ReplayLog> {
+        self.log.as_ref()
     }
 }
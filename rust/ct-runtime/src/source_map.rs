@@ -0,0 +1,31 @@
+use crate::{Error, Result};
+use ct_common::SourceMapper;
+
+/// Parse `source_map` into a [`SourceMapper`].
+pub(crate) fn parse_source_map(source_map: &str) -> Result<SourceMapper> {
+    SourceMapper::new(source_map).map_err(Error::InstantiationFailure)
+}
+
+/// Remap the frames of [`Error::ModuleFailure`] errors with
+/// `source_mapper`, if any. Other errors are returned as-is.
+pub(crate) fn remap_error(source_mapper: Option<&SourceMapper>, error: Error) -> Error {
+    match (source_mapper, error) {
+        (Some(source_mapper), Error::ModuleFailure(error)) => {
+            Error::ModuleFailure(source_mapper.remap(error))
+        }
+        (_, error) => error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_rejects_invalid_source_maps() {
+        assert!(matches!(
+            parse_source_map("not a source map"),
+            Err(Error::InstantiationFailure(_))
+        ));
+    }
+}
@@ -2,12 +2,15 @@ use crate::Error;
 use ct_common::ContentType;
 
 static JAVASCRIPT_VM: &[u8] = include_bytes!(env!("CT_JS_VM_WASM_PATH"));
+static PYTHON_VM: &[u8] = include_bytes!(env!("CT_PY_VM_WASM_PATH"));
 
 /// Virtual Machines for various languages supported.
 #[derive(PartialEq, Eq, Hash)]
 pub enum VirtualMachine {
     /// JavaScript virtual machine.
     JavaScript,
+    /// Python virtual machine.
+    Python,
 }
 
 impl VirtualMachine {
@@ -15,6 +18,7 @@ impl VirtualMachine {
     pub fn as_bytes(&self) -> &[u8] {
         match self {
             VirtualMachine::JavaScript => JAVASCRIPT_VM,
+            VirtualMachine::Python => PYTHON_VM,
         }
     }
 }
@@ -24,6 +28,7 @@ impl TryFrom<&ContentType> for VirtualMachine {
     fn try_from(value: &ContentType) -> Result<Self, Self::Error> {
         match value {
             ContentType::JavaScript => Ok(VirtualMachine::JavaScript),
+            ContentType::Python => Ok(VirtualMachine::Python),
         }
     }
 }
@@ -1,5 +1,6 @@
 use ct_common::{ContentType, ModuleDefinition};
-use ct_runtime::{Result, Runtime};
+use ct_runtime::{Error, ReplayLog, Result, Runtime, RuntimeConfig};
+use std::sync::{Arc, Mutex};
 
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen_test::wasm_bindgen_test;
@@ -22,6 +23,8 @@ async fn it_runs_a_js_vm() -> Result<()> {
     let definition = ModuleDefinition {
         content_type: ContentType::JavaScript,
         source: source.into(),
+        dependencies: Default::default(),
+        source_map: None,
     };
 
     let host_callback = |input: String| -> std::result::Result<String, String> { Ok(input) };
@@ -34,3 +37,313 @@ async fn it_runs_a_js_vm() -> Result<()> {
     assert_eq!(output, r#"{"foo":10,"reflect":{"test":123}}"#);
     Ok(())
 }
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+async fn it_resolves_imports_in_a_js_vm() -> Result<()> {
+    let source = r#"
+    import { increment } from "./increment.js";
+    import { callback } from "common:basic/host-callback@0.0.1";
+
+    export const run = (input) => {
+      input.foo = increment(input.foo);
+      input.reflect = callback({
+        test: 123,
+      });
+      return input;
+    }
+    "#;
+    let definition = ModuleDefinition {
+        content_type: ContentType::JavaScript,
+        source: source.into(),
+        dependencies: [(
+            "./increment.js".into(),
+            "export const increment = (value) => value + 1;".into(),
+        )]
+        .into(),
+        source_map: None,
+    };
+
+    let host_callback = |input: String| -> std::result::Result<String, String> { Ok(input) };
+    let runtime = Runtime::new(host_callback)?;
+    let mut module = runtime.module(definition)?;
+    let mut instance = module.instantiate()?;
+
+    let input = r#"{"foo":9}"#;
+    let output = instance.run(input.into())?;
+    assert_eq!(output, r#"{"foo":10,"reflect":{"test":123}}"#);
+    Ok(())
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+async fn it_provides_io_state_in_a_js_vm() -> Result<()> {
+    let source = r#"
+    import { read, write } from "common:io/state@0.0.1";
+
+    export const run = (input) => {
+      write("bar", read("foo").deref() + 1);
+      input.missing = read("missing") === undefined;
+      return input;
+    }
+    "#;
+    let definition = ModuleDefinition {
+        content_type: ContentType::JavaScript,
+        source: source.into(),
+        dependencies: Default::default(),
+        source_map: None,
+    };
+
+    let writes = Arc::new(Mutex::new(vec![]));
+    let host_callback = {
+        let writes = writes.clone();
+        move |input: String| -> std::result::Result<String, String> {
+            if input == r#"{"op":"read","name":"foo"}"# {
+                return Ok("41".into());
+            }
+            if input.starts_with(r#"{"op":"write""#) {
+                writes.lock().unwrap().push(input);
+            }
+            Ok("null".into())
+        }
+    };
+    let runtime = Runtime::new(host_callback)?;
+    let mut module = runtime.module(definition)?;
+    let mut instance = module.instantiate()?;
+
+    let output = instance.run("{}".into())?;
+    assert_eq!(output, r#"{"missing":true}"#);
+    assert_eq!(
+        *writes.lock().unwrap(),
+        vec![r#"{"op":"write","name":"bar","value":42}"#.to_owned()]
+    );
+    Ok(())
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+async fn it_fails_to_resolve_unknown_imports_in_a_js_vm() -> Result<()> {
+    let source = r#"
+    import { increment } from "./increment.js";
+
+    export const run = (input) => increment(input);
+    "#;
+    let definition = ModuleDefinition {
+        content_type: ContentType::JavaScript,
+        source: source.into(),
+        dependencies: Default::default(),
+        source_map: None,
+    };
+
+    let host_callback = |input: String| -> std::result::Result<String, String> { Ok(input) };
+    let runtime = Runtime::new(host_callback)?;
+    let mut module = runtime.module(definition)?;
+    assert!(module.instantiate().is_err());
+    Ok(())
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+async fn it_reports_errors_thrown_by_a_js_vm() -> Result<()> {
+    let source = r#"
+    export const run = (input) => {
+      throw new Error("oops");
+    }
+    "#;
+    let definition = ModuleDefinition {
+        content_type: ContentType::JavaScript,
+        source: source.into(),
+        dependencies: Default::default(),
+        source_map: None,
+    };
+
+    let host_callback = |input: String| -> std::result::Result<String, String> { Ok(input) };
+    let runtime = Runtime::new(host_callback)?;
+    let mut module = runtime.module(definition)?;
+    let mut instance = module.instantiate()?;
+
+    let Err(Error::ModuleFailure(error)) = instance.run("{}".into()) else {
+        panic!("Expected a module failure");
+    };
+    assert!(error.message.contains("oops"));
+    Ok(())
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+async fn it_runs_a_py_vm() -> Result<()> {
+    let source = r#"
+def run(input):
+    input["foo"] = input["foo"] + 1
+    input["reflect"] = host_callback({"test": 123})
+    return input
+"#;
+    let definition = ModuleDefinition {
+        content_type: ContentType::Python,
+        source: source.into(),
+        dependencies: Default::default(),
+        source_map: None,
+    };
+
+    let host_callback = |input: String| -> std::result::Result<String, String> { Ok(input) };
+    let runtime = Runtime::new(host_callback)?;
+    let mut module = runtime.module(definition)?;
+    let mut instance = module.instantiate()?;
+
+    let input = r#"{"foo":9}"#;
+    let output = instance.run(input.into())?;
+    assert_eq!(output, r#"{"foo":10,"reflect":{"test":123}}"#);
+    Ok(())
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+async fn it_resolves_imports_in_a_py_vm() -> Result<()> {
+    let source = r#"
+from increment import increment
+from host_callback import callback
+
+def run(input):
+    input["foo"] = increment(input["foo"])
+    input["reflect"] = callback({"test": 123})
+    return input
+"#;
+    let definition = ModuleDefinition {
+        content_type: ContentType::Python,
+        source: source.into(),
+        dependencies: [(
+            "increment".into(),
+            "def increment(value):\n    return value + 1\n".into(),
+        )]
+        .into(),
+        source_map: None,
+    };
+
+    let host_callback = |input: String| -> std::result::Result<String, String> { Ok(input) };
+    let runtime = Runtime::new(host_callback)?;
+    let mut module = runtime.module(definition)?;
+    let mut instance = module.instantiate()?;
+
+    let input = r#"{"foo":9}"#;
+    let output = instance.run(input.into())?;
+    assert_eq!(output, r#"{"foo":10,"reflect":{"test":123}}"#);
+    Ok(())
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+async fn it_fails_to_load_a_py_vm_without_run() -> Result<()> {
+    let definition = ModuleDefinition {
+        content_type: ContentType::Python,
+        source: "value = 1".into(),
+        dependencies: Default::default(),
+        source_map: None,
+    };
+
+    let host_callback = |input: String| -> std::result::Result<String, String> { Ok(input) };
+    let runtime = Runtime::new(host_callback)?;
+    let mut module = runtime.module(definition)?;
+    assert!(module.instantiate().is_err());
+    Ok(())
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+async fn it_isolates_instances_created_from_a_snapshot() -> Result<()> {
+    let source = r#"
+    let count = 0;
+    export const run = (input) => {
+      count += 1;
+      input.count = count;
+      return input;
+    }
+    "#;
+
+    for snapshots in [true, false] {
+        let definition = ModuleDefinition {
+            content_type: ContentType::JavaScript,
+            source: source.into(),
+            dependencies: Default::default(),
+            source_map: None,
+        };
+
+        let host_callback = |input: String| -> std::result::Result<String, String> { Ok(input) };
+        let runtime = Runtime::with_config(
+            host_callback,
+            RuntimeConfig {
+                snapshots,
+                ..Default::default()
+            },
+        )?;
+        let mut module = runtime.module(definition)?;
+
+        for _ in 0..2 {
+            let mut instance = module.instantiate()?;
+            assert_eq!(instance.run("{}".into())?, r#"{"count":1}"#);
+            assert_eq!(instance.run("{}".into())?, r#"{"count":2}"#);
+        }
+    }
+    Ok(())
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+async fn it_replays_deterministic_runs() -> Result<()> {
+    let source = r#"
+    export const run = (input) => {
+      input.random = Math.random();
+      input.reflect = globalThis.hostCallback({
+        test: input.foo,
+      });
+      return input;
+    }
+    "#;
+    let definition = ModuleDefinition {
+        content_type: ContentType::JavaScript,
+        source: source.into(),
+        dependencies: Default::default(),
+        source_map: None,
+    };
+
+    let host_callback = |input: String| -> std::result::Result<String, String> { Ok(input) };
+    let runtime = Runtime::new(host_callback)?;
+    let mut module = runtime.module(definition)?;
+
+    let mut outputs = vec![];
+    let mut log = None;
+    for _ in 0..2 {
+        let mut instance = module.instantiate_deterministic(42)?;
+        outputs.push(instance.run(r#"{"foo":1}"#.into())?);
+        outputs.push(instance.run(r#"{"foo":2}"#.into())?);
+        log = instance.replay_log().cloned();
+    }
+    assert_eq!(outputs[0], outputs[2]);
+    assert_eq!(outputs[1], outputs[3]);
+    assert_ne!(outputs[0], outputs[1]);
+
+    let log = log.expect("deterministic instances have a replay log");
+    assert_eq!(log.runs.len(), 2);
+    assert_eq!(log.callbacks.len(), 2);
+    module.replay(&log)?;
+
+    let serialized = serde_json::to_string(&log).map_err(|e| Error::from(e.to_string()))?;
+    let deserialized: ReplayLog =
+        serde_json::from_str(&serialized).map_err(|e| Error::from(e.to_string()))?;
+    assert_eq!(deserialized, log);
+    module.replay(&deserialized)?;
+
+    let mut reseeded = log.clone();
+    reseeded.seed = 43;
+    assert!(matches!(
+        module.replay(&reseeded),
+        Err(Error::ReplayDivergence(_))
+    ));
+
+    let mut tampered = log.clone();
+    tampered.callbacks[1].response = Ok(r#"{"test":3}"#.into());
+    assert!(matches!(
+        module.replay(&tampered),
+        Err(Error::ReplayDivergence(_))
+    ));
+    Ok(())
+}
@@ -17,6 +17,10 @@ use std::path::PathBuf;
 const BRANCHING_FACTOR: u8 = 64;
 
 /// Passive database.
+///
+/// Cloning a [`CtStorage`] is a copy-on-write snapshot of the tree:
+/// writes to the clone do not change the root of the original.
+#[derive(Clone)]
 pub struct CtStorage<S> {
     tree: Tree<BRANCHING_FACTOR, S, Key>,
 }
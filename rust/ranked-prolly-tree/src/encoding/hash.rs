@@ -5,7 +5,7 @@ pub struct HashDisplay(Vec<u8>);
 impl std::fmt::Display for HashDisplay {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for byte in self.0.iter() {
-            write!(f, "{:x}", byte)?;
+            write!(f, "{:02x}", byte)?;
         }
         Ok(())
     }
@@ -21,3 +21,14 @@ impl From<Vec<u8>> for HashDisplay {
 pub type Hash = Vec<u8>;
 /// Reference to a [`Hash`].
 pub type HashRef = <Hash as std::ops::Deref>::Target;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_pads_each_byte_to_two_hex_digits() {
+        assert_eq!(HashDisplay::from(vec![0x01, 0x23]).to_string(), "0123");
+        assert_eq!(HashDisplay::from(vec![0x12, 0x03]).to_string(), "1203");
+    }
+}
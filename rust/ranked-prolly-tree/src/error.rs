@@ -52,6 +52,13 @@ impl From<std::num::TryFromIntError> for Error {
     }
 }
 
+#[cfg(feature = "render")]
+impl From<serde_json::Error> for Error {
+    fn from(value: serde_json::Error) -> Self {
+        Error::Encoding(value.to_string())
+    }
+}
+
 impl From<std::string::FromUtf8Error> for Error {
     fn from(value: std::string::FromUtf8Error) -> Self {
         Error::Io(value.to_string())
@@ -1,5 +1,6 @@
-use crate::{Entry, Error, HashDisplay, Key, Node, NodeExt, Result, Storage, Tree};
+use crate::{Entry, Error, HashDisplay, Key, Node, NodeExt, Rank, Result, Storage, Tree};
 use ct_common::ConditionalSync;
+use serde::Serialize;
 use std::io::Write;
 
 trait Renderable<const P: u8> {
@@ -144,6 +145,117 @@ where
     Ok(out_nodes)
 }
 
+/// A node of a [`JsonTree`].
+#[derive(Serialize, Debug)]
+struct JsonNode {
+    hash: String,
+    rank: Rank,
+    boundary: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    children: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entries: Option<Vec<JsonEntry>>,
+}
+
+/// An entry of a segment [`JsonNode`].
+#[derive(Serialize, Debug)]
+struct JsonEntry {
+    key: String,
+    rank: Rank,
+}
+
+/// A structured dump of a [Tree], written by [`render_json`].
+#[derive(Serialize, Debug)]
+struct JsonTree {
+    root: String,
+    nodes: Vec<JsonNode>,
+}
+
+/// Generates a structured JSON dump of a [Tree].
+///
+/// Traverses the provided [Tree] and writes an object into the provided
+/// writer, containing the `root` node's hash, and every node's `hash`,
+/// `rank` and `boundary`, along with the hashes of a branch's `children`,
+/// or the keys and ranks of a segment's `entries`. Hashes and keys are
+/// hex-encoded.
+pub async fn render_json<const P: u8, S, K, V, W>(tree: &Tree<P, S, K, V>, w: W) -> Result<()>
+where
+    S: Storage<K, V>,
+    K: Key + 'static,
+    W: Write,
+    V: Clone + ConditionalSync,
+{
+    let Some(root) = tree.root() else {
+        return Err(Error::Internal("Empty tree.".into()));
+    };
+
+    render_node_json::<P, S, K, V, W>(root, tree.storage(), w).await
+}
+
+/// Renders a JSON dump where `node` is the root node.
+///
+/// See [`render_json`].
+pub async fn render_node_json<const P: u8, S, K, V, W>(
+    node: &Node<P, K, V>,
+    storage: &S,
+    mut w: W,
+) -> Result<()>
+where
+    S: Storage<K, V>,
+    W: Write,
+    K: Key + 'static,
+    V: Clone + ConditionalSync,
+{
+    let mut json_nodes = vec![];
+    let mut nodes = vec![node.to_owned()];
+    while !nodes.is_empty() {
+        let mut out_nodes = vec![];
+        for node in nodes {
+            let mut json_node = JsonNode {
+                hash: HashDisplay::from(node.hash().to_owned()).to_string(),
+                rank: node.rank(),
+                boundary: HashDisplay::from(node.block.boundary().as_ref().to_vec()).to_string(),
+                children: None,
+                entries: None,
+            };
+            match node.is_branch() {
+                true => {
+                    let children = node.into_children(storage).await?;
+                    json_node.children = Some(
+                        children
+                            .iter()
+                            .map(|child| HashDisplay::from(child.hash().to_owned()).to_string())
+                            .collect(),
+                    );
+                    out_nodes.extend(children);
+                }
+                false => {
+                    json_node.entries = Some(
+                        node.into_entries()?
+                            .iter()
+                            .map(|entry| JsonEntry {
+                                key: HashDisplay::from(entry.key.as_ref().to_vec()).to_string(),
+                                rank: entry.rank(P as u32),
+                            })
+                            .collect(),
+                    );
+                }
+            }
+            json_nodes.push(json_node);
+        }
+        nodes = out_nodes;
+    }
+
+    let tree = JsonTree {
+        root: HashDisplay::from(node.hash().to_owned()).to_string(),
+        nodes: json_nodes,
+    };
+    serde_json::to_writer(&mut w, &tree)?;
+    writeln!(w)?;
+
+    Ok(())
+}
+
 #[cfg(all(not(target_arch = "wasm32"), test))]
 mod tests {
     use super::*;
@@ -167,4 +279,32 @@ mod tests {
         //std::fs::write("tree.dot", &dot)?;
         Ok(())
     }
+
+    #[tokio::test]
+    async fn it_renders_to_json() -> Result<()> {
+        let mut set = BTreeMap::default();
+        for i in 0..1024u32 {
+            let key = i.to_be_bytes().to_vec();
+            let value = <[u8; 32] as From<blake3::Hash>>::from(blake3::hash(&key)).to_vec();
+            set.insert(key, value);
+        }
+        let storage = NodeStorage::new(BasicEncoder::default(), SyncMemoryStore::default());
+        let tree = Tree::<32, _>::from_set(set, storage.clone()).await?;
+
+        let mut out = std::io::Cursor::new(vec![]);
+        render_json(&tree, &mut out).await?;
+        let json = String::from_utf8(out.into_inner())?;
+        serde_json::from_str::<serde_json::Value>(&json)?;
+
+        let root = tree.root().unwrap();
+        let root_hash = HashDisplay::from(root.hash().to_owned()).to_string();
+        assert!(json.starts_with(&format!(
+            r#"{{"root":"{root_hash}","nodes":[{{"hash":"{root_hash}","rank":{},"#,
+            root.rank()
+        )));
+        assert!(json.contains(r#""children":["#));
+        // Every key is listed in exactly one segment.
+        assert_eq!(json.matches(r#"{"key":""#).count(), 1024);
+        Ok(())
+    }
 }